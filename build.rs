@@ -1,9 +1,23 @@
 use autocfg;
 
+const PATHS: [&str; 5] = [
+    "linux_media_sys::MEDIA_LNK_FL_ANCILLARY_LINK",
+    "linux_media_sys::MEDIA_ENT_F_CONN_RF",
+    "linux_media_sys::MEDIA_ENT_F_CONN_SVIDEO",
+    "linux_media_sys::MEDIA_ENT_F_CONN_COMPOSITE",
+    "linux_media_sys::MEDIA_ENT_F_PROC_VIDEO_ISP",
+];
+
 fn main() {
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("linux") {
+        // src/raw_stub.rs declares all of them in place of linux-media-sys
+        for path in PATHS {
+            println!("cargo:rustc-cfg=has_{}", path.replace("::", "__"));
+        }
+        return;
+    }
     let cfg = autocfg::new();
-    cfg.emit_has_path("linux_media_sys::MEDIA_LNK_FL_ANCILLARY_LINK");
-    cfg.emit_has_path("linux_media_sys::MEDIA_ENT_F_CONN_RF");
-    cfg.emit_has_path("linux_media_sys::MEDIA_ENT_F_CONN_SVIDEO");
-    cfg.emit_has_path("linux_media_sys::MEDIA_ENT_F_CONN_COMPOSITE");
+    for path in PATHS {
+        cfg.emit_has_path(path);
+    }
 }
@@ -0,0 +1,50 @@
+/*
+ * The C API of linux-media, built with
+ *   cargo rustc --lib --release --features capi --crate-type cdylib
+ *
+ * Functions returning int return 0 on success and a negated errno on failure.
+ * Functions returning pointers return NULL on failure.
+ * The message of the last failure on the calling thread is read with linux_media_last_error().
+ * Strings returned are released with linux_media_string_free().
+ * A panic in the library is caught and reported as a failure, -EIO or NULL.
+ */
+#ifndef LINUX_MEDIA_H
+#define LINUX_MEDIA_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A media device opened by linux_media_open(). */
+typedef struct LinuxMediaDevice LinuxMediaDevice;
+
+/* The media devices of the system in a JSON array of {"index", "path", "sysfs_path", "model"}. */
+char *linux_media_devices_json(void);
+
+/* Open the media device file such like "/dev/media0". */
+LinuxMediaDevice *linux_media_open(const char *path);
+
+/* Close a device. Nothing is done for NULL. */
+void linux_media_close(LinuxMediaDevice *device);
+
+/* The topology of the device in JSON. */
+char *linux_media_topology_json(const LinuxMediaDevice *device);
+
+/*
+ * Set up links given in the syntax of `media-ctl --links`, e.g. "\"imx219 10-0010\":0->\"csi\":0[1]".
+ * On failure the links already changed are restored as far as possible, but setting up links is not atomic:
+ * if some of them can not be restored either, the message of linux_media_last_error() tells how many links are left changed.
+ */
+int linux_media_setup_links(const LinuxMediaDevice *device, const char *links);
+
+/* The message of the last failure on the calling thread, or NULL. Valid until the next failure on the thread. */
+const char *linux_media_last_error(void);
+
+/* Release a string returned by this library. Nothing is done for NULL. */
+void linux_media_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* LINUX_MEDIA_H */
@@ -0,0 +1,151 @@
+//! Resolution of ALSA interfaces into the indices of sound cards and devices.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::media_interface::MediaInterface;
+use crate::media_interface_type::MediaInterfaceType;
+
+/// An ALSA device, addressed by the index of the card and the index of the device on the card.
+///
+/// # Details
+/// The indices are read from the name of the device file, e.g. `/dev/snd/pcmC1D0c` is the device `0` of the card `1`.
+/// The device is absent for the interfaces of a whole card, such as the control interface `/dev/snd/controlC1`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct AlsaDevice {
+    #[serde(rename = "card")]
+    pub card: u32,
+    #[serde(rename = "device")]
+    pub device: Option<u32>,
+}
+
+impl AlsaDevice {
+    /// Parse the name of a device file of ALSA such like `pcmC0D0p` or `controlC0`.
+    ///
+    /// # Returns
+    /// `None` if `name` is not a device file of a card, e.g. `timer` and `seq`, which are shared by all cards.
+    pub fn from_devname(name: &str) -> Option<Self> {
+        let name = name.rsplit('/').next()?;
+        let prefix = ["pcm", "control", "hw", "midi", "compr"]
+            .into_iter()
+            .find(|prefix| name.starts_with(prefix))?;
+        let rest = name[prefix.len()..].strip_prefix('C')?;
+        let (card, device) = match rest.split_once('D') {
+            Some((card, device)) => {
+                // PCM devices are suffixed with `p` for playback or `c` for capture
+                let device = device.trim_end_matches(['p', 'c']);
+                (card, Some(device.parse().ok()?))
+            }
+            None => (rest, None),
+        };
+        Some(Self {
+            card: card.parse().ok()?,
+            device,
+        })
+    }
+
+    /// The name of the device for ALSA libraries, `hw:CARD,DEVICE` or `hw:CARD` for a whole card.
+    ///
+    /// # Details
+    /// The name is passed as it is to `snd_pcm_open`, or `alsa::pcm::PCM::new` of the `alsa` crate.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    ///
+    /// let device = AlsaDevice::from_devname("/dev/snd/pcmC1D0c").unwrap();
+    /// assert_eq!(device.hw_name(), "hw:1,0");
+    /// ```
+    pub fn hw_name(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for AlsaDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.device {
+            Some(device) => write!(f, "hw:{},{}", self.card, device),
+            None => write!(f, "hw:{}", self.card),
+        }
+    }
+}
+
+impl MediaInterfaceType {
+    /// Whether the interface is a device node of ALSA.
+    pub fn is_alsa(&self) -> bool {
+        use MediaInterfaceType::*;
+        matches!(
+            self,
+            ALSAPCMCapture
+                | ALSAPCMPlayback
+                | ALSAControl
+                | ALSACompress
+                | ALSARawMIDI
+                | ALSAHardwareDependent
+                | ALSASequencer
+                | ALSATimer
+        )
+    }
+}
+
+impl MediaInterface {
+    /// Resolve the ALSA interface into the indices of the card and the device.
+    ///
+    /// # Details
+    /// The device file is resolved through sysfs by [`MediaIntfDevnode::dev_path`][crate::MediaIntfDevnode::dev_path].
+    ///
+    /// # Returns
+    /// `None` if the interface is not of ALSA, the device file is not resolved, or it is shared by all cards.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = media.new_topology()?;
+    ///     for intf in topology.interfaces_slice() {
+    ///         if intf.r#type() == MediaInterfaceType::ALSAPCMCapture {
+    ///             if let Some(device) = intf.alsa_device() {
+    ///                 // e.g. alsa::pcm::PCM::new(&device.hw_name(), alsa::Direction::Capture, false)
+    ///                 println!("{}", device.hw_name());
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn alsa_device(&self) -> Option<AlsaDevice> {
+        if !self.r#type().is_alsa() {
+            return None;
+        }
+        let path = self.devnode().dev_path()?;
+        AlsaDevice::from_devname(path.to_str()?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_device_names() {
+        let device = |card, device| Some(AlsaDevice { card, device });
+        assert_eq!(AlsaDevice::from_devname("pcmC0D0p"), device(0, Some(0)));
+        assert_eq!(
+            AlsaDevice::from_devname("/dev/snd/pcmC12D3c"),
+            device(12, Some(3))
+        );
+        assert_eq!(AlsaDevice::from_devname("controlC2"), device(2, None));
+        assert_eq!(AlsaDevice::from_devname("hwC1D0"), device(1, Some(0)));
+        assert_eq!(AlsaDevice::from_devname("midiC1D2"), device(1, Some(2)));
+        assert_eq!(AlsaDevice::from_devname("timer"), None);
+        assert_eq!(AlsaDevice::from_devname("seq"), None);
+        assert_eq!(AlsaDevice::from_devname("pcmCxD0p"), None);
+
+        assert_eq!(device(1, Some(0)).unwrap().hw_name(), "hw:1,0");
+        assert_eq!(device(2, None).unwrap().hw_name(), "hw:2");
+    }
+}
@@ -0,0 +1,175 @@
+//! Selecting the media device to operate on.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use linux_media as media;
+use media::{
+    Media, MediaDeviceEnumerator, MediaDeviceFilter, MediaDeviceNode, MediaTopologyCounts, Version,
+};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+/// How the user selected a media device.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    /// A device file such like `/dev/media1`
+    Path(PathBuf),
+    /// `N` of `/dev/mediaN`
+    Index(u32),
+    /// The model name of the device
+    Model(String),
+    /// The bus info of the device such like `platform:1000880000.pisp_be`
+    BusInfo(String),
+}
+
+impl Selector {
+    /// Build a selector from the command line options.
+    /// `device` is taken as an index if it consists of digits only, otherwise as a path.
+    /// Defaults to `/dev/media0` if nothing is specified.
+    pub fn new(device: Option<&str>, model: Option<&str>, bus_info: Option<&str>) -> Self {
+        if let Some(model) = model {
+            Selector::Model(model.to_string())
+        } else if let Some(bus_info) = bus_info {
+            Selector::BusInfo(bus_info.to_string())
+        } else {
+            match device {
+                Some(device) => match device.parse() {
+                    Ok(index) => Selector::Index(index),
+                    Err(_) => Selector::Path(PathBuf::from(device)),
+                },
+                None => Selector::Path(PathBuf::from("/dev/media0")),
+            }
+        }
+    }
+
+    /// Whether `node` is selected. Opens the device only if the bus info is needed.
+    fn matches(&self, node: &MediaDeviceNode) -> bool {
+        match self {
+            Selector::Path(path) => node.path() == path,
+            Selector::Index(index) => node.index() == *index,
+            Selector::Model(model) => MediaDeviceFilter::new().model(model).matches(node),
+            Selector::BusInfo(bus_info) => {
+                MediaDeviceFilter::new().bus_info(bus_info).matches(node)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Selector::Path(path) => write!(f, "{}", path.display()),
+            Selector::Index(index) => write!(f, "index {}", index),
+            Selector::Model(model) => write!(f, "model {:?}", model),
+            Selector::BusInfo(bus_info) => write!(f, "bus info {:?}", bus_info),
+        }
+    }
+}
+
+/// A device listed when the selector matches nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Available {
+    pub path: PathBuf,
+    pub model: Option<String>,
+    pub bus_info: Option<String>,
+}
+
+impl Available {
+    fn new(node: &MediaDeviceNode) -> Self {
+        let info = node.open().ok().map(|media| media.info().clone());
+        Self {
+            path: node.path().to_path_buf(),
+            model: info
+                .as_ref()
+                .map(|info| info.model().to_string())
+                .or_else(|| node.model().map(str::to_string)),
+            bus_info: info.map(|info| info.bus_info().to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Available {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<12} {:<16} {}",
+            self.path.display(),
+            self.model.as_deref().unwrap_or("?"),
+            self.bus_info.as_deref().unwrap_or("?")
+        )
+    }
+}
+
+/// A row of `list`.
+///
+/// Fields other than the path are `None` if the device can not be opened.
+#[derive(Debug, Clone, Serialize)]
+pub struct Listed {
+    #[serde(rename = "path")]
+    pub path: PathBuf,
+    #[serde(rename = "driver")]
+    pub driver: Option<String>,
+    #[serde(rename = "model")]
+    pub model: Option<String>,
+    #[serde(rename = "bus_info")]
+    pub bus_info: Option<String>,
+    #[serde(rename = "media_version")]
+    pub media_version: Option<Version>,
+    #[serde(rename = "counts")]
+    pub counts: Option<MediaTopologyCounts>,
+}
+
+impl Listed {
+    fn new(node: &MediaDeviceNode) -> Self {
+        let media = node.open().ok();
+        let info = media.as_ref().map(|media| media.info());
+        Self {
+            path: node.path().to_path_buf(),
+            driver: info.map(|info| info.driver().to_string()),
+            model: info
+                .map(|info| info.model().to_string())
+                .or_else(|| node.model().map(str::to_string)),
+            bus_info: info.map(|info| info.bus_info().to_string()),
+            media_version: info.map(|info| info.media_version()),
+            // only the numbers are fetched, which is much cheaper than the whole topology
+            counts: media.and_then(|media| media.topology_counts().ok()),
+        }
+    }
+}
+
+/// List all media devices.
+pub fn list() -> Result<Vec<Listed>> {
+    Ok(MediaDeviceEnumerator::new()?
+        .map(|node| Listed::new(&node))
+        .collect())
+}
+
+fn available() -> Vec<Available> {
+    MediaDeviceEnumerator::new()
+        .map(|devices| devices.map(|node| Available::new(&node)).collect())
+        .unwrap_or_default()
+}
+
+/// Open the media device selected by `selector`.
+///
+/// # Details
+/// A path is opened directly, other selectors are resolved through [`MediaDeviceEnumerator`].
+/// If nothing matches, the returned error lists the available devices.
+pub fn open(selector: &Selector) -> Result<Media> {
+    let no_device = || Error::NoDevice {
+        selector: selector.clone(),
+        available: available(),
+    };
+    if let Selector::Path(path) = selector {
+        return match Media::from_path(path) {
+            Err(media::error::Error::FileNotFound { .. }) => Err(no_device()),
+            result => Ok(result?),
+        };
+    }
+    let node = MediaDeviceEnumerator::new()?
+        .find(|node| selector.matches(node))
+        .ok_or_else(no_device)?;
+    Ok(node.open()?)
+}
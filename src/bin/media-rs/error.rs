@@ -0,0 +1,162 @@
+//! Errors reported by `media-rs`.
+
+use std::fmt;
+use std::io;
+
+use linux_media as media;
+use serde::Serialize;
+
+use crate::device::{Available, Selector};
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// An error from the library
+    Media(media::error::Error),
+    /// Failed to set up links
+    LinkSetup(media::error::Error),
+    /// Failed to serialize the output into JSON
+    Json(serde_json::Error),
+    /// Failed to serialize a configuration into TOML
+    TomlSer(toml::ser::Error),
+    /// Failed to parse a configuration in TOML
+    TomlDe(toml::de::Error),
+    /// No entity has the name or the id
+    NoEntity { entity: String },
+    /// No media device matches the selector
+    NoDevice {
+        selector: Selector,
+        available: Vec<Available>,
+    },
+}
+
+/// Exit codes of `media-rs`, documented in the README.
+pub mod exit {
+    pub const SUCCESS: u8 = 0;
+    /// Errors not classified below
+    pub const FAILURE: u8 = 1;
+    /// Invalid command line arguments, including malformed link descriptions
+    pub const USAGE: u8 = 2;
+    /// No media device matches the selection
+    pub const DEVICE_NOT_FOUND: u8 = 3;
+    /// The device, or a file, can not be accessed for lack of permission
+    pub const PERMISSION_DENIED: u8 = 4;
+    /// No link was changed since a link could not be found or set up
+    pub const LINK_SETUP_FAILED: u8 = 5;
+    /// Some links were left changed after a link failed to be set up
+    pub const PARTIAL_APPLY: u8 = 6;
+}
+
+fn is_permission_denied(err: &media::error::Error) -> bool {
+    use media::error::Error::*;
+    match err {
+        Io { source, .. } | FileNotFound { source, .. } => {
+            source.kind() == io::ErrorKind::PermissionDenied
+        }
+        Ioctl { code, .. } => matches!(code.raw_os_error(), Some(libc::EACCES | libc::EPERM)),
+        PermissionDenied { .. } => true,
+        _ => false,
+    }
+}
+
+impl Error {
+    /// The exit code of the process reporting this error.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::NoDevice { .. } => exit::DEVICE_NOT_FOUND,
+            Error::Media(media::error::Error::LinkSpecParseError { .. }) => exit::USAGE,
+            Error::LinkSetup(media::error::Error::PartialLinkSetup { .. }) => exit::PARTIAL_APPLY,
+            Error::Media(err) | Error::LinkSetup(err) if is_permission_denied(err) => {
+                exit::PERMISSION_DENIED
+            }
+            Error::LinkSetup(_) => exit::LINK_SETUP_FAILED,
+            _ => exit::FAILURE,
+        }
+    }
+
+    /// The name of the cause reported in JSON errors.
+    pub fn kind(&self) -> &'static str {
+        match self.exit_code() {
+            exit::USAGE => "usage",
+            exit::DEVICE_NOT_FOUND => "device_not_found",
+            exit::PERMISSION_DENIED => "permission_denied",
+            exit::LINK_SETUP_FAILED => "link_setup_failed",
+            exit::PARTIAL_APPLY => "partial_apply",
+            _ => "failure",
+        }
+    }
+}
+
+/// An error printed to stderr by `--json`.
+#[derive(Debug, Serialize)]
+pub struct JsonError {
+    #[serde(rename = "kind")]
+    pub kind: &'static str,
+    #[serde(rename = "exit_code")]
+    pub exit_code: u8,
+    #[serde(rename = "message")]
+    pub message: String,
+}
+
+impl From<&Error> for JsonError {
+    fn from(err: &Error) -> Self {
+        Self {
+            kind: err.kind(),
+            exit_code: err.exit_code(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<media::error::Error> for Error {
+    fn from(err: media::error::Error) -> Self {
+        Error::Media(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl From<toml::ser::Error> for Error {
+    fn from(err: toml::ser::Error) -> Self {
+        Error::TomlSer(err)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(err: toml::de::Error) -> Self {
+        Error::TomlDe(err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Media(err) => write!(f, "{}", err),
+            Error::LinkSetup(err) => write!(f, "link setup failed: {}", err),
+            Error::Json(err) => write!(f, "json error: {}", err),
+            Error::TomlSer(err) => write!(f, "toml error: {}", err),
+            Error::TomlDe(err) => write!(f, "toml error: {}", err),
+            Error::NoEntity { entity } => write!(f, "no entity named or numbered {:?}", entity),
+            Error::NoDevice {
+                selector,
+                available,
+            } => {
+                write!(f, "no media device matches {}", selector)?;
+                if available.is_empty() {
+                    write!(f, "; no media devices found")
+                } else {
+                    write!(f, "; available devices:")?;
+                    for device in available {
+                        write!(f, "\n  {}", device)?;
+                    }
+                    Ok(())
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Details of a single entity.
+
+use linux_media as media;
+use media::{
+    DeviceDriver, EntityId, LinkType, MediaEntity, MediaInterface, MediaLink, MediaPad,
+    MediaTopology,
+};
+use serde::Serialize;
+
+use crate::error::{Error, Result};
+
+/// An entity with the objects related to it, serialized by `entity --json`.
+#[derive(Debug, Serialize)]
+pub struct EntityDetails<'a> {
+    #[serde(rename = "entity")]
+    pub entity: &'a MediaEntity,
+    #[serde(rename = "pads")]
+    pub pads: Vec<&'a MediaPad>,
+    /// Data links and ancillary links from or to the entity.
+    #[serde(rename = "links")]
+    pub links: Vec<&'a MediaLink>,
+    /// Interfaces linked to the entity.
+    #[serde(rename = "interfaces")]
+    pub interfaces: Vec<&'a MediaInterface>,
+    /// The kernel driver of the device behind the device nodes, found in sysfs.
+    #[serde(rename = "driver")]
+    pub driver: Option<DeviceDriver>,
+}
+
+/// Find the entity named `entity`, or having the id if `entity` consists of digits.
+pub fn find<'a>(topology: &'a MediaTopology, entity: &str) -> Result<&'a MediaEntity> {
+    let id: Option<EntityId> = entity.parse::<u32>().ok().map(Into::into);
+    topology
+        .entities_slice()
+        .iter()
+        .find(|e| e.name() == entity)
+        .or_else(|| {
+            topology
+                .entities_slice()
+                .iter()
+                .find(|e| Some(e.id()) == id)
+        })
+        .ok_or_else(|| Error::NoEntity {
+            entity: entity.to_string(),
+        })
+}
+
+impl<'a> EntityDetails<'a> {
+    pub fn new(topology: &'a MediaTopology, entity: &'a MediaEntity) -> Self {
+        let pads: Vec<&MediaPad> = topology
+            .pads_slice()
+            .iter()
+            .filter(|pad| pad.entity_id == entity.id())
+            .collect();
+        let has_pad = |id| pads.iter().any(|pad| pad.id == id);
+        let mut links = vec![];
+        let mut interfaces = vec![];
+        for link in topology.links_slice() {
+            match link.r#type() {
+                LinkType::DataLink { source_id, sink_id } => {
+                    if has_pad(*source_id) || has_pad(*sink_id) {
+                        links.push(link);
+                    }
+                }
+                LinkType::AncillaryLink { source_id, sink_id } => {
+                    if [source_id.raw(), sink_id.raw()].contains(&entity.id().into()) {
+                        links.push(link);
+                    }
+                }
+                LinkType::InterfaceLink { source_id, sink_id } => {
+                    if *sink_id == entity.id() {
+                        interfaces.extend(
+                            topology
+                                .interfaces_slice()
+                                .iter()
+                                .filter(|intf| intf.id() == *source_id),
+                        );
+                    }
+                }
+            }
+        }
+        Self {
+            entity,
+            pads,
+            links,
+            interfaces,
+            driver: topology.entity_driver(entity.id()),
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! `media-rs`: inspect and configure media controller devices.
+//!
+//! Built with the `cli` feature:
+//! ```sh
+//! cargo run --features cli -- list
+//! cargo run --features cli -- --device /dev/media0 topology
+//! cargo run --features cli -- --model unicam entities
+//! cargo run --features cli -- entity "imx219 10-0010"
+//! cargo run --features cli -- watch --json
+//! cargo run --features cli -- diff before.json after.json
+//! cargo run --features cli -- save-config -o pipeline.toml
+//! cargo run --features cli -- topology --dot | dot -Tsvg > topology.svg
+//! cargo run --features cli -- set-link '"imx219 10-0010":0 -> "unicam-image":0 [1]'
+//! ```
+
+mod device;
+mod error;
+mod inspect;
+mod print;
+
+use std::env;
+use std::fmt::{self, Write as _};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use clap::{Parser, Subcommand};
+use linux_media as media;
+use serde::Serialize;
+
+/// Inspect and configure Linux media controller devices
+#[derive(Debug, Parser)]
+#[command(name = "media-rs", version, arg_required_else_help = true)]
+struct Cli {
+    /// The media device to open, given as a device file or `N` of `/dev/mediaN` [default: /dev/media0]
+    #[arg(short, long, global = true, value_name = "PATH|INDEX")]
+    device: Option<String>,
+    /// Open the media device with the model name
+    #[arg(long, global = true, conflicts_with_all = ["device", "bus_info"])]
+    model: Option<String>,
+    /// Open the media device with the bus info such like `platform:1000880000.pisp_be`
+    #[arg(long, global = true, conflicts_with = "device")]
+    bus_info: Option<String>,
+    /// Print the result in JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+    /// Set up links before running the command, in the syntax of `media-ctl --links`
+    #[arg(short = 'l', long, value_name = "LINKS")]
+    links: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// List all media devices with their drivers and numbers of entities and interfaces
+    ///
+    /// The device selection is ignored.
+    List,
+    /// Show the media device information
+    Info,
+    /// Show entities with their pads and links, interfaces and device nodes
+    Topology {
+        /// Print the topology as a Graphviz DOT graph, as `media-ctl --print-dot` does
+        #[arg(long, conflicts_with_all = ["mermaid", "json"])]
+        dot: bool,
+        /// Print the topology as a Mermaid flowchart
+        #[arg(long, conflicts_with = "json")]
+        mermaid: bool,
+        /// Write the output to the file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// List entities
+    Entities,
+    /// Show an entity with its pads, links and device nodes
+    Entity {
+        /// The name or the id of the entity
+        entity: String,
+    },
+    /// List data links between pads
+    Links,
+    /// Print devices being added or removed and topologies being changed, until interrupted
+    ///
+    /// All media devices are watched regardless of the device selection.
+    /// With `--json`, each event is printed as a JSON object in a line.
+    Watch {
+        /// Interval between polls in milliseconds
+        #[arg(long, default_value_t = 500, value_name = "MS")]
+        interval: u64,
+    },
+    /// Save the current link configuration in TOML, or in JSON with `--json`
+    SaveConfig {
+        /// Write the configuration to the file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Set up the links saved by `save-config`
+    ApplyConfig {
+        /// The configuration in TOML, or in JSON if the file name ends with `.json`
+        config: PathBuf,
+    },
+    /// Compare topology snapshots saved by `topology --json`
+    ///
+    /// If only one snapshot is given, it is compared with the current topology of the device.
+    Diff {
+        /// The old snapshot
+        before: PathBuf,
+        /// The new snapshot
+        after: Option<PathBuf>,
+    },
+    /// Disable all links except immutable ones, as `media-ctl --reset` does
+    Reset,
+    /// Set up links, e.g. `'"a":0 -> "b":0 [1], "c":1 -> "d":0 [0]'`
+    ///
+    /// Links are described as `media-ctl --links` does. Either all links are set up,
+    /// or none of them are changed.
+    SetLink {
+        /// Comma separated links: `"entity":pad -> "entity":pad [flags]`
+        links: String,
+    },
+}
+
+/// Where and how the result of a command is written.
+struct Output {
+    json: bool,
+    file: Option<PathBuf>,
+}
+
+impl Output {
+    /// Write `value` in JSON if `--json` is given, otherwise the text formatted by `text`.
+    fn write<T, F>(&self, value: &T, text: F) -> error::Result<()>
+    where
+        T: Serialize + ?Sized,
+        F: FnOnce(&mut String) -> fmt::Result,
+    {
+        let out = if self.json {
+            serde_json::to_string_pretty(value)? + "\n"
+        } else {
+            let mut out = String::new();
+            text(&mut out).expect("writing to a String never fails");
+            out
+        };
+        self.write_str(&out)
+    }
+
+    /// Write `out` as it is.
+    fn write_str(&self, out: &str) -> error::Result<()> {
+        match &self.file {
+            Some(path) => fs::write(path, out)
+                .map_err(|err| media::error::trap_io_error(err, path.clone()))?,
+            None => print!("{}", out),
+        }
+        Ok(())
+    }
+}
+
+/// An event with the time it was observed, printed by `watch --json`.
+#[derive(Serialize)]
+struct Timestamped {
+    /// Seconds since the Unix epoch
+    #[serde(rename = "time")]
+    time: f64,
+    #[serde(rename = "event")]
+    event: media::MediaEvent,
+}
+
+fn watch(json: bool, interval: Duration) -> error::Result<()> {
+    let watcher = media::MediaWatcher::new()?.interval(interval);
+    let mut stdout = io::stdout();
+    for event in watcher {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |time| time.as_secs_f64());
+        let event = event?;
+        let text = if json {
+            serde_json::to_string(&Timestamped { time, event })? + "\n"
+        } else {
+            let mut text = String::new();
+            print::event(&mut text, time, &event).expect("writing to a String never fails");
+            text
+        };
+        // stop quietly when the reader of the pipe has gone
+        if stdout.write_all(text.as_bytes()).is_err() || stdout.flush().is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Load a topology snapshot saved by `topology --json`.
+fn load(path: &Path) -> error::Result<media::MediaTopology> {
+    let json = fs::read_to_string(path)
+        .map_err(|err| media::error::trap_io_error(err, path.to_path_buf()))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+/// Load a link configuration saved by `save-config`.
+fn load_config(path: &Path) -> error::Result<media::PipelineConfig> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| media::error::trap_io_error(err, path.to_path_buf()))?;
+    if path.extension().map_or(false, |ext| ext == "json") {
+        Ok(serde_json::from_str(&text)?)
+    } else {
+        Ok(toml::from_str(&text)?)
+    }
+}
+
+fn diff(
+    output: &Output,
+    (before_path, before): (&Path, &media::MediaTopology),
+    (after_path, after): (&Path, &media::MediaTopology),
+) -> error::Result<()> {
+    let diff = before.diff(after);
+    output.write(&diff, |out| {
+        out.write_str(&diff.to_unified(
+            &before_path.display().to_string(),
+            &after_path.display().to_string(),
+        ))
+    })
+}
+
+fn run(cli: Cli) -> error::Result<()> {
+    let selector = device::Selector::new(
+        cli.device.as_deref(),
+        cli.model.as_deref(),
+        cli.bus_info.as_deref(),
+    );
+    let mut output = Output {
+        json: cli.json,
+        file: None,
+    };
+    match &cli.command {
+        Some(Command::List) => {
+            let devices = device::list()?;
+            return output.write(&devices, |out| print::list(out, &devices));
+        }
+        Some(Command::Watch { interval }) => {
+            return watch(cli.json, Duration::from_millis(*interval));
+        }
+        Some(Command::Diff {
+            before,
+            after: Some(after),
+        }) => {
+            return diff(&output, (before, &load(before)?), (after, &load(after)?));
+        }
+        _ => {}
+    }
+    // report syntax errors before opening the device
+    let mut links = vec![];
+    if let Some(spec) = &cli.links {
+        links.extend(media::LinkSpec::parse_list(spec)?);
+    }
+    if let Some(Command::SetLink { links: spec }) = &cli.command {
+        links.extend(media::LinkSpec::parse_list(spec)?);
+    }
+    let media = device::open(&selector)?;
+    // links are reset before set up as `media-ctl -r -l ...` does
+    if let Some(Command::Reset) = &cli.command {
+        let reset = media.reset_links().map_err(error::Error::LinkSetup)?;
+        let topology = media.new_topology()?;
+        output.write(&reset, |out| print::reset(out, &topology, &reset))?;
+    }
+    let applied = if links.is_empty() {
+        vec![]
+    } else {
+        media.setup_links(&links).map_err(error::Error::LinkSetup)?
+    };
+    let Some(command) = cli.command else {
+        return Ok(());
+    };
+    match command {
+        Command::Info => output.write(media.info(), |out| print::info(out, media.info()))?,
+        Command::Topology {
+            dot,
+            mermaid,
+            output: file,
+        } => {
+            let topology = media.new_topology()?;
+            output.file = file;
+            if dot || mermaid {
+                let graph = if dot {
+                    topology.to_dot(&media::DotStyle::default())
+                } else {
+                    topology.to_mermaid()
+                };
+                output.write_str(&graph)?;
+            } else {
+                output.write(&topology, |out| {
+                    print::topology(out, media.info(), &topology)
+                })?;
+            }
+        }
+        Command::Entities => {
+            let topology = media.new_topology()?;
+            output.write(topology.entities_slice(), |out| {
+                print::entities(out, &topology)
+            })?;
+        }
+        Command::Entity { entity } => {
+            let topology = media.new_topology()?;
+            let entity = inspect::find(&topology, &entity)?;
+            let details = inspect::EntityDetails::new(&topology, entity);
+            output.write(&details, |out| {
+                print::entity(out, &print::Names::new(&topology), entity)?;
+                print::driver(out, details.driver.as_ref())
+            })?;
+        }
+        Command::Links => {
+            let topology = media.new_topology()?;
+            let links: Vec<&media::MediaLink> = topology.data_links().collect();
+            output.write(&links, |out| print::links(out, &topology))?;
+        }
+        Command::SetLink { .. } => output.write(&applied, |_| Ok(()))?,
+        Command::SaveConfig { output: file } => {
+            let config = media::PipelineConfig::from_media(&media)?;
+            output.file = file;
+            if output.json {
+                output.write(&config, |_| Ok(()))?;
+            } else {
+                output.write_str(&toml::to_string(&config)?)?;
+            }
+        }
+        Command::ApplyConfig { config } => {
+            let applied = load_config(&config)?
+                .apply(&media)
+                .map_err(error::Error::LinkSetup)?;
+            output.write(&applied, |_| Ok(()))?;
+        }
+        Command::Diff { before, .. } => {
+            let current = media.new_topology()?;
+            diff(
+                &output,
+                (&before, &load(&before)?),
+                (media.path(), &current),
+            )?;
+        }
+        Command::Reset | Command::List | Command::Watch { .. } => {}
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        // `--json` can not be parsed from invalid arguments, so look for it by hand
+        Err(err) if err.use_stderr() && env::args().any(|arg| arg == "--json") => {
+            let err = error::JsonError {
+                kind: "usage",
+                exit_code: error::exit::USAGE,
+                message: err
+                    .render()
+                    .to_string()
+                    .lines()
+                    .next()
+                    .unwrap_or_default()
+                    .trim_start_matches("error: ")
+                    .to_string(),
+            };
+            eprintln!(
+                "{}",
+                serde_json::to_string(&err).expect("serializing strings never fails")
+            );
+            return ExitCode::from(error::exit::USAGE);
+        }
+        Err(err) => err.exit(),
+    };
+    let json = cli.json;
+    match run(cli) {
+        Ok(()) => ExitCode::from(error::exit::SUCCESS),
+        Err(err) => {
+            match serde_json::to_string(&error::JsonError::from(&err)) {
+                Ok(message) if json => eprintln!("{}", message),
+                _ => eprintln!("media-rs: {}", err),
+            }
+            ExitCode::from(err.exit_code())
+        }
+    }
+}
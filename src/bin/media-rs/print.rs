@@ -0,0 +1,373 @@
+//! Human readable output.
+
+use std::fmt::{self, Write};
+
+use linux_media as media;
+use media::{
+    DeviceDriver, EntityId, LinkReset, LinkType, MediaDeviceInfo, MediaEntity, MediaEvent,
+    MediaLink, MediaLinkDesc, MediaPad, MediaPadFlags, MediaTopology, PadId,
+};
+
+use crate::device::Listed;
+
+/// Resolves ids in a topology into names.
+pub struct Names<'a> {
+    topology: &'a MediaTopology,
+}
+
+impl<'a> Names<'a> {
+    pub fn new(topology: &'a MediaTopology) -> Self {
+        Self { topology }
+    }
+
+    pub fn entity(&self, id: EntityId) -> Option<&'a MediaEntity> {
+        self.topology
+            .entities_slice()
+            .iter()
+            .find(|entity| entity.id() == id)
+    }
+
+    pub fn entity_name(&self, id: EntityId) -> String {
+        self.entity(id)
+            .map(|entity| format!("\"{}\"", entity.name()))
+            .unwrap_or_else(|| format!("#{}", u32::from(id)))
+    }
+
+    pub fn pad(&self, id: PadId) -> Option<&'a MediaPad> {
+        self.topology.pads_slice().iter().find(|pad| pad.id == id)
+    }
+
+    /// Format a pad as `"entity":index` as media-ctl does.
+    pub fn pad_name(&self, id: PadId) -> String {
+        match self.pad(id) {
+            Some(pad) => format!(
+                "{}:{}",
+                self.entity_name(pad.entity_id),
+                pad.index.map_or_else(|| "?".to_string(), |i| i.to_string())
+            ),
+            None => format!("#{}", u32::from(id)),
+        }
+    }
+
+    /// Format a link as `"entity":index -> "entity":index`.
+    pub fn link_desc(&self, desc: &MediaLinkDesc) -> String {
+        format!(
+            "{}:{} -> {}:{}",
+            self.entity_name(desc.source().id()),
+            desc.source().index(),
+            self.entity_name(desc.sink().id()),
+            desc.sink().index()
+        )
+    }
+}
+
+pub fn flags<B>(flags: &B) -> String
+where
+    B: bitflags::Flags,
+    B::Bits: bitflags::parser::WriteHex,
+{
+    let mut names = String::new();
+    bitflags::parser::to_writer(flags, &mut names).expect("writing to a String never fails");
+    names
+}
+
+fn pad_direction(pad: &MediaPad) -> String {
+    let flags = [
+        (MediaPadFlags::Sink, "Sink"),
+        (MediaPadFlags::Source, "Source"),
+        (MediaPadFlags::MustConnect, "Must connect"),
+        (MediaPadFlags::Internal, "Internal"),
+    ];
+    let names: Vec<&str> = flags
+        .iter()
+        .filter(|(flag, _)| pad.flags.contains(*flag))
+        .map(|(_, name)| *name)
+        .collect();
+    names.join(", ")
+}
+
+/// Write media devices as a table.
+pub fn list<W: Write>(out: &mut W, devices: &[Listed]) -> fmt::Result {
+    let unknown = || "?".to_string();
+    let rows: Vec<[String; 7]> = devices
+        .iter()
+        .map(|device| {
+            [
+                device.path.display().to_string(),
+                device.driver.clone().unwrap_or_else(unknown),
+                device.model.clone().unwrap_or_else(unknown),
+                device.bus_info.clone().unwrap_or_else(unknown),
+                device
+                    .media_version
+                    .as_ref()
+                    .map_or_else(unknown, |version| version.to_string()),
+                device
+                    .counts
+                    .map_or_else(unknown, |counts| counts.entities.to_string()),
+                device
+                    .counts
+                    .map_or_else(unknown, |counts| counts.interfaces.to_string()),
+            ]
+        })
+        .collect();
+    let header = [
+        "PATH",
+        "DRIVER",
+        "MODEL",
+        "BUS INFO",
+        "VERSION",
+        "ENTITIES",
+        "INTERFACES",
+    ];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut line = |cells: Vec<&str>| {
+        let mut text = String::new();
+        for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+            if i > 0 {
+                text.push_str("  ");
+            }
+            write!(text, "{:<width$}", cell, width = width)?;
+        }
+        writeln!(out, "{}", text.trim_end())
+    };
+    line(header.to_vec())?;
+    for row in &rows {
+        line(row.iter().map(String::as_str).collect())?;
+    }
+    Ok(())
+}
+
+pub fn info<W: Write>(out: &mut W, info: &MediaDeviceInfo) -> fmt::Result {
+    writeln!(out, "{}", info)
+}
+
+fn link_line(names: &Names, link: &MediaLink, from: PadId) -> Option<String> {
+    if let LinkType::DataLink { source_id, sink_id } = link.r#type() {
+        let (arrow, remote) = if *source_id == from {
+            ("->", sink_id)
+        } else if *sink_id == from {
+            ("<-", source_id)
+        } else {
+            return None;
+        };
+        Some(format!(
+            "{} {} [{}]",
+            arrow,
+            names.pad_name(*remote),
+            flags(&link.flags())
+        ))
+    } else {
+        None
+    }
+}
+
+fn device_topology<W: Write>(out: &mut W, topology: &MediaTopology) -> fmt::Result {
+    let names = Names::new(topology);
+    writeln!(out, "Device topology")?;
+    for entity in topology.entities_slice() {
+        self::entity(out, &names, entity)?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Write the kernel driver of an entity found in sysfs, if any.
+pub fn driver<W: Write>(out: &mut W, driver: Option<&DeviceDriver>) -> fmt::Result {
+    let Some(driver) = driver else {
+        return Ok(());
+    };
+    write!(out, "driver {}", driver.driver())?;
+    if let Some(module) = driver.module() {
+        write!(out, " (module {})", module)?;
+    }
+    writeln!(out, " bound to {}", driver.device().display())?;
+    if let Some(node) = driver.firmware_node() {
+        writeln!(out, "firmware node {}", node)?;
+    }
+    Ok(())
+}
+
+/// Write an entity with its pads, links and device nodes as `media-ctl --print-topology` does.
+pub fn entity<W: Write>(out: &mut W, names: &Names, entity: &MediaEntity) -> fmt::Result {
+    let topology = names.topology;
+    let pads: Vec<&MediaPad> = topology
+        .pads_slice()
+        .iter()
+        .filter(|pad| pad.entity_id == entity.id())
+        .collect();
+    writeln!(
+        out,
+        "- entity {}: {} ({} pad{})",
+        u32::from(entity.id()),
+        entity.name(),
+        pads.len(),
+        if pads.len() == 1 { "" } else { "s" }
+    )?;
+    writeln!(out, "            function {:?}", entity.function())?;
+    if let Some(entity_flags) = entity.flags() {
+        if !entity_flags.is_empty() {
+            writeln!(out, "            flags {}", flags(&entity_flags))?;
+        }
+    }
+    for link in topology.links_slice() {
+        if let LinkType::InterfaceLink { source_id, sink_id } = link.r#type() {
+            if *sink_id != entity.id() {
+                continue;
+            }
+            if let Some(intf) = topology
+                .interfaces_slice()
+                .iter()
+                .find(|intf| intf.id() == *source_id)
+            {
+                let devnode = intf.devnode();
+                match devnode.dev_path() {
+                    Some(path) => writeln!(
+                        out,
+                        "            device node {} ({:?})",
+                        path.display(),
+                        intf.r#type()
+                    )?,
+                    None => writeln!(
+                        out,
+                        "            device node {}:{} ({:?})",
+                        devnode.major,
+                        devnode.minor,
+                        intf.r#type()
+                    )?,
+                }
+            }
+        }
+    }
+    for link in topology.links_slice() {
+        if let LinkType::AncillaryLink { source_id, sink_id } = link.r#type() {
+            let (source, sink) = (
+                EntityId::from(source_id.raw()),
+                EntityId::from(sink_id.raw()),
+            );
+            if source == entity.id() {
+                writeln!(out, "            ancillary => {}", names.entity_name(sink))?;
+            } else if sink == entity.id() {
+                writeln!(
+                    out,
+                    "            ancillary <= {}",
+                    names.entity_name(source)
+                )?;
+            }
+        }
+    }
+    for pad in pads {
+        writeln!(
+            out,
+            "\tpad{}: {}",
+            pad.index.map_or_else(|| "?".to_string(), |i| i.to_string()),
+            pad_direction(pad)
+        )?;
+        for link in topology.links_slice() {
+            if let Some(line) = link_line(names, link, pad.id) {
+                writeln!(out, "\t\t{}", line)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the device information and the topology as `media-ctl --print-topology` does.
+pub fn topology<W: Write>(
+    out: &mut W,
+    info: &MediaDeviceInfo,
+    topology: &MediaTopology,
+) -> fmt::Result {
+    writeln!(out, "Media device information")?;
+    writeln!(out, "------------------------")?;
+    self::info(out, info)?;
+    writeln!(out)?;
+    device_topology(out, topology)
+}
+
+pub fn entities<W: Write>(out: &mut W, topology: &MediaTopology) -> fmt::Result {
+    for entity in topology.entities_slice() {
+        writeln!(
+            out,
+            "{:>4} {:<32} {:?}",
+            u32::from(entity.id()),
+            entity.name(),
+            entity.function()
+        )?;
+    }
+    Ok(())
+}
+
+pub fn links<W: Write>(out: &mut W, topology: &MediaTopology) -> fmt::Result {
+    let names = Names::new(topology);
+    for link in topology.links_slice() {
+        if let LinkType::DataLink { source_id, sink_id } = link.r#type() {
+            writeln!(
+                out,
+                "{} -> {} [{}]",
+                names.pad_name(*source_id),
+                names.pad_name(*sink_id),
+                flags(&link.flags())
+            )?;
+        }
+    }
+    Ok(())
+}
+
+pub fn reset<W: Write>(out: &mut W, topology: &MediaTopology, reset: &LinkReset) -> fmt::Result {
+    let names = Names::new(topology);
+    for desc in &reset.disabled {
+        writeln!(out, "disabled  {}", names.link_desc(desc))?;
+    }
+    for desc in &reset.immutable {
+        writeln!(out, "immutable {}", names.link_desc(desc))?;
+    }
+    Ok(())
+}
+
+/// Write an event observed by `watch` in lines prefixed by the time and the device path.
+pub fn event<W: Write>(out: &mut W, time: f64, event: &MediaEvent) -> fmt::Result {
+    match event {
+        MediaEvent::DeviceAdded { device } => writeln!(
+            out,
+            "{:.3} {} added {}",
+            time,
+            device.path().display(),
+            device.model().unwrap_or("?")
+        ),
+        MediaEvent::DeviceRemoved { device } => {
+            writeln!(out, "{:.3} {} removed", time, device.path().display())
+        }
+        MediaEvent::TopologyChanged {
+            device,
+            old_version,
+            new_version,
+            diff,
+        } => {
+            let path = device.path().display();
+            writeln!(
+                out,
+                "{:.3} {} topology {} -> {}",
+                time, path, old_version, new_version
+            )?;
+            for change in &diff.changes {
+                writeln!(
+                    out,
+                    "{:.3} {} {} {} {} {} -> {}",
+                    time,
+                    path,
+                    format!("{:?}", change.change).to_lowercase(),
+                    change.object,
+                    change.key,
+                    change.before.as_deref().unwrap_or("-"),
+                    change.after.as_deref().unwrap_or("-")
+                )?;
+            }
+            Ok(())
+        }
+    }
+}
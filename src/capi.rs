@@ -0,0 +1,267 @@
+//! A C API for enumerating media devices, reading their topologies and setting up links.
+//!
+//! # Details
+//! The functions are declared in `include/linux_media.h`.
+//! The library is built as a shared library with `cargo rustc --lib --release --features capi --crate-type cdylib`.
+//!
+//! Functions returning `int` return `0` on success and a negated `errno` on failure,
+//! and strings returned are allocated by this library and released with `linux_media_string_free`.
+//! The message of the last failure on the calling thread is read with `linux_media_last_error`.
+//! A panic in this library is caught before it unwinds into C, and reported as a failure, `-EIO` or `NULL`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::fs::File;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use serde::Serialize;
+
+use crate::error::{self, Error};
+use crate::{enumerate, LinkSpec, Media, MediaBackend, MediaDeviceNode};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn fail_with(errno: c_int, message: String) -> c_int {
+    set_last_error(message);
+    -errno
+}
+
+/// The `errno` of `err`, including the errors of files and of links set up partially.
+fn errno_of(err: &Error) -> Option<c_int> {
+    err.errno().or_else(|| match err {
+        Error::Io { source, .. }
+        | Error::FileNotFound { source, .. }
+        | Error::PermissionDenied { source, .. } => source.raw_os_error(),
+        Error::PartialLinkSetup { source, .. } => errno_of(source),
+        _ => None,
+    })
+}
+
+/// Record the message of `err` as the last error, and convert it into a negated `errno`.
+fn fail(err: Error) -> c_int {
+    fail_with(errno_of(&err).unwrap_or(libc::EINVAL), err.to_string())
+}
+
+/// Run `f`, returning `on_panic` with the message of the panic recorded as the last error if it panics,
+/// since unwinding across `extern "C"` functions into C is undefined behavior.
+fn catch<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        set_last_error(format!("panicked: {}", message));
+        on_panic
+    })
+}
+
+/// Serialize `value` into a JSON string released by [`linux_media_string_free`].
+fn json_string<T: Serialize>(value: &T) -> std::result::Result<*mut c_char, c_int> {
+    let json =
+        serde_json::to_string(value).map_err(|err| fail_with(libc::EINVAL, err.to_string()))?;
+    let json = CString::new(json).map_err(|err| fail_with(libc::EINVAL, err.to_string()))?;
+    Ok(json.into_raw())
+}
+
+/// Read a string passed from C.
+///
+/// # Safety
+/// `s` is `NULL` or a pointer to a nul terminated string.
+unsafe fn str_arg<'a>(s: *const c_char, name: &str) -> std::result::Result<&'a str, c_int> {
+    if s.is_null() {
+        return Err(fail_with(libc::EINVAL, format!("{} is NULL", name)));
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map_err(|err| fail_with(libc::EINVAL, format!("{} is not UTF-8: {}", name, err)))
+}
+
+/// A media device opened by [`linux_media_open`], which is opaque to C.
+pub struct LinuxMediaDevice {
+    media: Media<Box<dyn MediaBackend>>,
+}
+
+/// The media devices of the system in a JSON array, whose elements are of the form of [`MediaDeviceNode`].
+///
+/// # Returns
+/// `NULL` on failure.
+#[no_mangle]
+pub extern "C" fn linux_media_devices_json() -> *mut c_char {
+    catch(ptr::null_mut(), || {
+        enumerate()
+            .map_err(fail)
+            .and_then(|devices| json_string(&devices.collect::<Vec<MediaDeviceNode>>()))
+            .unwrap_or(ptr::null_mut())
+    })
+}
+
+/// Open the media device file `path` such like `/dev/media0`.
+///
+/// # Returns
+/// `NULL` on failure.
+///
+/// # Safety
+/// `path` is `NULL` or a pointer to a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn linux_media_open(path: *const c_char) -> *mut LinuxMediaDevice {
+    catch(ptr::null_mut(), || {
+        let Ok(path) = str_arg(path, "path") else {
+            return ptr::null_mut();
+        };
+        let media = File::options()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|err| error::trap_io_error(err, path.into()))
+            .and_then(|file| Media::with_backend(path, Box::new(file) as Box<dyn MediaBackend>));
+        match media {
+            Ok(media) => Box::into_raw(Box::new(LinuxMediaDevice { media })),
+            Err(err) => {
+                fail(err);
+                ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Close `device`. Nothing is done if `device` is `NULL`.
+///
+/// # Safety
+/// `device` is `NULL` or a pointer returned by [`linux_media_open`] and not closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn linux_media_close(device: *mut LinuxMediaDevice) {
+    catch((), || {
+        if !device.is_null() {
+            drop(Box::from_raw(device));
+        }
+    })
+}
+
+/// The topology of `device` in JSON, of the form of [`MediaTopology`][crate::MediaTopology].
+///
+/// # Returns
+/// `NULL` on failure.
+///
+/// # Safety
+/// `device` is a pointer returned by [`linux_media_open`] and not closed yet.
+#[no_mangle]
+pub unsafe extern "C" fn linux_media_topology_json(device: *const LinuxMediaDevice) -> *mut c_char {
+    catch(ptr::null_mut(), || {
+        (*device)
+            .media
+            .new_topology()
+            .map_err(fail)
+            .and_then(|topology| json_string(&topology))
+            .unwrap_or(ptr::null_mut())
+    })
+}
+
+/// Set up the links `links` of `device`, given in the syntax of `media-ctl --links`.
+///
+/// # Details
+/// The links are set up by [`Media::setup_links`], which restores the links already changed on failure as far as it can.
+/// Setting up links is not atomic: if some of them can not be restored either,
+/// the failure is [`Error::PartialLinkSetup`] whose message tells how many links are left changed, and the `errno` is that of the link which failed.
+///
+/// # Safety
+/// `device` is a pointer returned by [`linux_media_open`] and not closed yet,
+/// and `links` is `NULL` or a pointer to a nul terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn linux_media_setup_links(
+    device: *const LinuxMediaDevice,
+    links: *const c_char,
+) -> c_int {
+    catch(-libc::EIO, || {
+        let links = match str_arg(links, "links") {
+            Ok(links) => links,
+            Err(errno) => return errno,
+        };
+        let result =
+            LinkSpec::parse_list(links).and_then(|specs| (*device).media.setup_links(&specs));
+        match result {
+            Ok(_) => 0,
+            Err(err) => fail(err),
+        }
+    })
+}
+
+/// The message of the last failure on the calling thread, or `NULL` if nothing has failed.
+///
+/// # Details
+/// The message is valid until the next failure on the thread.
+#[no_mangle]
+pub extern "C" fn linux_media_last_error() -> *const c_char {
+    catch(ptr::null(), || {
+        LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+    })
+}
+
+/// Release a string returned by this library. Nothing is done if `s` is `NULL`.
+///
+/// # Safety
+/// `s` is `NULL` or a pointer returned by this library and not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn linux_media_string_free(s: *mut c_char) {
+    catch((), || {
+        if !s.is_null() {
+            drop(CString::from_raw(s));
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::MediaTopology;
+
+    fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let string = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { linux_media_string_free(s) };
+        string
+    }
+
+    #[test]
+    fn topology_and_links_through_handles() {
+        let backend: Box<dyn MediaBackend> = Box::new(mock());
+        let device = Box::into_raw(Box::new(LinuxMediaDevice {
+            media: Media::with_backend("/dev/media0", backend).unwrap(),
+        }));
+
+        let links = CString::new("\"sensor\":0->\"csi\":0[1]").unwrap();
+        assert_eq!(
+            unsafe { linux_media_setup_links(device, links.as_ptr()) },
+            0
+        );
+        let json = take_string(unsafe { linux_media_topology_json(device) });
+        let topology: MediaTopology = serde_json::from_str(&json).unwrap();
+        let desc = topology.link_desc(&topology.links_slice()[0]).unwrap();
+        assert!(desc.flags().contains(crate::MediaLinkFlags::Enabled));
+
+        let links = CString::new("\"isp\":0->\"csi\":0[1]").unwrap();
+        let ret = unsafe { linux_media_setup_links(device, links.as_ptr()) };
+        assert_eq!(ret, -libc::EINVAL);
+        let message = unsafe { CStr::from_ptr(linux_media_last_error()) };
+        assert!(message.to_str().unwrap().contains("isp"), "{:?}", message);
+
+        unsafe { linux_media_close(device) };
+    }
+
+    #[test]
+    fn panics_are_reported_as_failures() {
+        let ret = catch(-libc::EIO, || -> c_int { panic!("broken backend") });
+        assert_eq!(ret, -libc::EIO);
+        let message = unsafe { CStr::from_ptr(linux_media_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "panicked: broken backend");
+    }
+}
@@ -0,0 +1,38 @@
+//! Collections holding topology objects.
+//!
+//! With the `smallvec` feature, they keep a few objects inline
+//! so that small topologies such as those of USB webcams are built without heap allocation.
+
+use crate::media_entity::MediaEntity;
+use crate::media_interface::MediaInterface;
+use crate::media_link::MediaLink;
+use crate::media_link_desc::MediaLinkDesc;
+use crate::media_pad::MediaPad;
+use crate::media_pad_desc::MediaPadDesc;
+use crate::raw as media;
+
+macro_rules! items {
+    ($(#[$meta:meta])* $name:ident = [$t:ty; $n:literal]) => {
+        $(#[$meta])*
+        #[cfg(feature = "smallvec")]
+        pub(crate) type $name = smallvec::SmallVec<[$t; $n]>;
+        $(#[$meta])*
+        #[cfg(not(feature = "smallvec"))]
+        pub(crate) type $name = Vec<$t>;
+    };
+}
+
+items!(Entities = [MediaEntity; 8]);
+items!(Interfaces = [MediaInterface; 8]);
+items!(Pads = [MediaPad; 16]);
+items!(Links = [MediaLink; 16]);
+items!(PadDescs = [MediaPadDesc; 8]);
+items!(LinkDescs = [MediaLinkDesc; 8]);
+items!(
+    /// Filled by `MEDIA_IOC_ENUM_LINKS`
+    RawPadDescs = [media::media_pad_desc; 8]
+);
+items!(
+    /// Filled by `MEDIA_IOC_ENUM_LINKS`
+    RawLinkDescs = [media::media_link_desc; 8]
+);
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::firmware_node::FirmwareNode;
+use crate::media_entity::EntityId;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::media_link::LinkType;
+use crate::media_topology::MediaTopology;
+
+/// The kernel driver bound to the device behind a device node, found by walking sysfs.
+///
+/// # Details
+/// The device is the one `/sys/dev/char/{major}:{minor}/device` points to, such like the I2C client of a sensor
+/// or the USB interface of a webcam. If no driver is bound to it, the nearest ancestor bound to a driver is reported instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DeviceDriver {
+    /// The sysfs directory of the device bound to the driver.
+    #[serde(rename = "device")]
+    device: PathBuf,
+    /// The name of the driver such like `imx219` or `uvcvideo`.
+    #[serde(rename = "driver")]
+    driver: String,
+    /// The kernel module of the driver, `None` if the driver is built into the kernel.
+    #[serde(rename = "module")]
+    module: Option<String>,
+    /// The device tree node or the ACPI device describing the device or its ancestors, if any.
+    #[serde(rename = "firmware_node")]
+    firmware_node: Option<FirmwareNode>,
+}
+
+/// The name of the directory a sysfs symlink points to.
+fn link_name(link: &Path) -> Option<String> {
+    let target = fs::read_link(link).ok()?;
+    Some(target.file_name()?.to_string_lossy().into_owned())
+}
+
+impl DeviceDriver {
+    /// Find the driver of the character device `devnode` in `/sys/dev/char`.
+    ///
+    /// # Returns
+    /// `None` if sysfs is not available, the device node is not known to it, or no driver is bound to the device or its ancestors.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// // /dev/media0 is usually 239:0 or so, but there is nothing to find for an unknown device number
+    /// assert_eq!(DeviceDriver::from_devnode(MediaIntfDevnode { major: 0, minor: 0 }), None);
+    /// ```
+    pub fn from_devnode(devnode: MediaIntfDevnode) -> Option<Self> {
+        Self::read(&PathBuf::from(devnode).join("device"))
+    }
+
+    /// Find the driver of the device the sysfs symlink `device` points to.
+    fn read(device: &Path) -> Option<Self> {
+        let device = fs::canonicalize(device).ok()?;
+        device.ancestors().find_map(|dir| {
+            let driver = link_name(&dir.join("driver"))?;
+            Some(Self {
+                device: dir.to_path_buf(),
+                driver,
+                module: link_name(&dir.join("driver/module")),
+                firmware_node: FirmwareNode::read(dir),
+            })
+        })
+    }
+
+    pub fn device(&self) -> &Path {
+        &self.device
+    }
+
+    pub fn driver(&self) -> &str {
+        &self.driver
+    }
+
+    pub fn module(&self) -> Option<&str> {
+        self.module.as_deref()
+    }
+
+    pub fn firmware_node(&self) -> Option<&FirmwareNode> {
+        self.firmware_node.as_ref()
+    }
+}
+
+impl MediaTopology {
+    /// The device nodes of the interfaces linked to `entity`.
+    pub(crate) fn entity_devnodes(
+        &self,
+        entity: EntityId,
+    ) -> impl Iterator<Item = MediaIntfDevnode> + '_ {
+        self.links_slice()
+            .iter()
+            .filter_map(move |link| match link.r#type() {
+                LinkType::InterfaceLink { source_id, sink_id } if *sink_id == entity => self
+                    .interfaces_slice()
+                    .iter()
+                    .find(|intf| intf.id() == *source_id)
+                    .map(|intf| intf.devnode()),
+                _ => None,
+            })
+    }
+
+    /// Find the kernel driver of `entity` by walking sysfs, as [`drivers`][Self::drivers] does.
+    pub fn entity_driver(&self, entity: EntityId) -> Option<DeviceDriver> {
+        self.entity_devnodes(entity)
+            .find_map(DeviceDriver::from_devnode)
+    }
+
+    fn drivers_with<F>(&self, driver: F) -> BTreeMap<EntityId, DeviceDriver>
+    where
+        F: Fn(MediaIntfDevnode) -> Option<DeviceDriver>,
+    {
+        self.entities_slice()
+            .iter()
+            .filter_map(|entity| {
+                let driver = self.entity_devnodes(entity.id()).find_map(&driver)?;
+                Some((entity.id(), driver))
+            })
+            .collect()
+    }
+
+    /// Find the kernel drivers of the entities having device nodes, by walking sysfs.
+    ///
+    /// # Details
+    /// The drivers are found by [`DeviceDriver::from_devnode`] for the device nodes of the interfaces linked to each entity.
+    /// Entities without device nodes, or whose drivers are not found, are left out.
+    /// The topology has to include interfaces and links.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = media.new_topology()?;
+    ///     let drivers = topology.drivers();
+    ///     for entity in topology.entities_slice() {
+    ///         if let Some(driver) = drivers.get(&entity.id()) {
+    ///             println!("{}: {} ({:?})", entity.name(), driver.driver(), driver.module());
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn drivers(&self) -> BTreeMap<EntityId, DeviceDriver> {
+        self.drivers_with(DeviceDriver::from_devnode)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+    use crate::{MediaEntityFunctions, MediaInterfaceType, MediaPadFlags, MockTopologyBuilder};
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn finds_drivers_of_entities() {
+        let tmp = TempDir::new("driver");
+        let root = tmp.path();
+        let i2c = root.join("devices/platform/soc/fe205000.i2c/i2c-10");
+        let sensor = i2c.join("10-0010");
+        let subdev = sensor.join("video4linux/v4l-subdev0");
+        let of_node = root.join("firmware/devicetree/base/soc/i2c@7e205000/imx219@10");
+        fs::create_dir_all(&subdev).unwrap();
+        fs::create_dir_all(&of_node).unwrap();
+        fs::create_dir_all(root.join("bus/i2c/drivers/imx219")).unwrap();
+        fs::create_dir_all(root.join("module/imx219")).unwrap();
+        fs::create_dir_all(root.join("dev/char")).unwrap();
+        symlink(&sensor, subdev.join("device")).unwrap();
+        symlink(root.join("bus/i2c/drivers/imx219"), sensor.join("driver")).unwrap();
+        symlink(
+            root.join("module/imx219"),
+            root.join("bus/i2c/drivers/imx219/module"),
+        )
+        .unwrap();
+        symlink(&of_node, sensor.join("of_node")).unwrap();
+        symlink(&subdev, root.join("dev/char/81:1")).unwrap();
+
+        let mut builder = MockTopologyBuilder::new();
+        let (imx219, _) = builder.entity(
+            "imx219 10-0010",
+            MediaEntityFunctions::CAMSensor,
+            &[MediaPadFlags::Source],
+        );
+        let (csi, _) = builder.entity("csi", MediaEntityFunctions::VIDIFBridge, &[]);
+        let devnode = |minor| MediaIntfDevnode { major: 81, minor };
+        builder.interface(MediaInterfaceType::V4LSubdev, devnode(1), imx219);
+        builder.interface(MediaInterfaceType::V4LSubdev, devnode(2), csi);
+        let topology = builder.build();
+
+        let drivers = topology.drivers_with(|devnode| {
+            let dev_char = root.join(format!("dev/char/{}:{}", devnode.major, devnode.minor));
+            DeviceDriver::read(&dev_char.join("device"))
+        });
+
+        assert_eq!(drivers.len(), 1);
+        let driver = &drivers[&imx219];
+        assert!(driver.device().ends_with("i2c-10/10-0010"));
+        assert_eq!(driver.driver(), "imx219");
+        assert_eq!(driver.module(), Some("imx219"));
+        assert_eq!(
+            driver.firmware_node().unwrap().path(),
+            "/soc/i2c@7e205000/imx219@10"
+        );
+    }
+}
@@ -0,0 +1,336 @@
+//! The capabilities of DVB frontends, read from the device files such like `/dev/dvb/adapter0/frontend0`.
+//!
+//! # Details
+//! `linux/dvb/frontend.h` is not covered by [`linux_media_sys`], so the struct passed to `FE_GET_INFO` is defined here.
+
+use std::ffi::CStr;
+use std::fs::OpenOptions;
+use std::mem::size_of;
+use std::os::fd::{AsRawFd, OwnedFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::ioctl::ioc;
+use crate::ioctl::ioctl;
+use crate::media_interface::InterfaceId;
+use crate::media_interface_type::MediaInterfaceType;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::media_link::LinkType;
+use crate::media_topology::MediaTopology;
+use crate::raw as media;
+
+/// Mirrors of the structs of `linux/dvb/frontend.h`.
+pub mod raw {
+    #![allow(non_camel_case_types)]
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct dvb_frontend_info {
+        pub name: [libc::c_char; 128],
+        /// `enum fe_type`, deprecated in favor of `DTV_ENUM_DELSYS`.
+        pub r#type: u32,
+        pub frequency_min: u32,
+        pub frequency_max: u32,
+        pub frequency_stepsize: u32,
+        pub frequency_tolerance: u32,
+        pub symbol_rate_min: u32,
+        pub symbol_rate_max: u32,
+        pub symbol_rate_tolerance: u32,
+        pub notifier_delay: u32,
+        /// `enum fe_caps`
+        pub caps: u32,
+    }
+}
+
+/// `_IOR('o', 61, struct dvb_frontend_info)`
+pub const FE_GET_INFO: libc::c_ulong = ioc(
+    media::_IOC_READ,
+    b'o',
+    61,
+    size_of::<raw::dvb_frontend_info>(),
+);
+
+/// The type of a frontend, `enum fe_type`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum DvbFrontendType {
+    /// DVB-S, QPSK modulated satellite.
+    Qpsk,
+    /// DVB-C, QAM modulated cable.
+    Qam,
+    /// DVB-T, OFDM modulated terrestrial.
+    Ofdm,
+    /// ATSC terrestrial or cable.
+    Atsc,
+}
+
+impl TryFrom<u32> for DvbFrontendType {
+    type Error = u32;
+    fn try_from(v: u32) -> std::result::Result<Self, u32> {
+        use DvbFrontendType::*;
+        match v {
+            0 => Ok(Qpsk),
+            1 => Ok(Qam),
+            2 => Ok(Ofdm),
+            3 => Ok(Atsc),
+            other => Err(other),
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// The capabilities of a frontend, `enum fe_caps`.
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+    pub struct DvbFrontendCaps: u32 {
+        const CanInversionAuto = 0x1;
+        const CanFec1_2 = 0x2;
+        const CanFec2_3 = 0x4;
+        const CanFec3_4 = 0x8;
+        const CanFec4_5 = 0x10;
+        const CanFec5_6 = 0x20;
+        const CanFec6_7 = 0x40;
+        const CanFec7_8 = 0x80;
+        const CanFec8_9 = 0x100;
+        const CanFecAuto = 0x200;
+        const CanQpsk = 0x400;
+        const CanQam16 = 0x800;
+        const CanQam32 = 0x1000;
+        const CanQam64 = 0x2000;
+        const CanQam128 = 0x4000;
+        const CanQam256 = 0x8000;
+        const CanQamAuto = 0x10000;
+        const CanTransmissionModeAuto = 0x20000;
+        const CanBandwidthAuto = 0x40000;
+        const CanGuardIntervalAuto = 0x80000;
+        const CanHierarchyAuto = 0x100000;
+        const Can8Vsb = 0x200000;
+        const Can16Vsb = 0x400000;
+        /// The delivery systems are enumerated with `DTV_ENUM_DELSYS` instead of the type.
+        const HasExtendedCaps = 0x800000;
+        const CanMultistream = 0x4000000;
+        const CanTurboFec = 0x8000000;
+        /// DVB-S2 and other second generation modulations.
+        const Can2gModulation = 0x10000000;
+        const NeedsBending = 0x20000000;
+        const CanRecover = 0x40000000;
+        const CanMuteTs = 0x80000000;
+    }
+}
+
+#[cfg(feature = "schemars")]
+crate::schema::bitflags_schema!(DvbFrontendCaps);
+
+/// The information of a frontend read by `FE_GET_INFO`.
+///
+/// # Details
+/// The frequencies are in kHz for satellite frontends and in Hz for the others.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DvbFrontendInfo {
+    #[serde(rename = "name")]
+    pub name: String,
+    /// `None` if the type is unknown to this crate.
+    #[serde(rename = "type")]
+    pub r#type: Option<DvbFrontendType>,
+    #[serde(rename = "frequency_min")]
+    pub frequency_min: u32,
+    #[serde(rename = "frequency_max")]
+    pub frequency_max: u32,
+    #[serde(rename = "frequency_stepsize")]
+    pub frequency_stepsize: u32,
+    #[serde(rename = "frequency_tolerance")]
+    pub frequency_tolerance: u32,
+    #[serde(rename = "symbol_rate_min")]
+    pub symbol_rate_min: u32,
+    #[serde(rename = "symbol_rate_max")]
+    pub symbol_rate_max: u32,
+    #[serde(rename = "symbol_rate_tolerance")]
+    pub symbol_rate_tolerance: u32,
+    #[serde(rename = "caps")]
+    pub caps: DvbFrontendCaps,
+}
+
+impl From<raw::dvb_frontend_info> for DvbFrontendInfo {
+    fn from(info: raw::dvb_frontend_info) -> Self {
+        // safety: `c_char` and `u8` have the same layout
+        let name: &[u8] =
+            unsafe { std::slice::from_raw_parts(info.name.as_ptr() as *const u8, info.name.len()) };
+        // a name filling the whole array is taken as is, as drivers may leave it unterminated
+        let name = match CStr::from_bytes_until_nul(name) {
+            Ok(name) => name.to_string_lossy(),
+            Err(_) => String::from_utf8_lossy(name),
+        };
+        Self {
+            name: name.into_owned(),
+            r#type: info.r#type.try_into().ok(),
+            frequency_min: info.frequency_min,
+            frequency_max: info.frequency_max,
+            frequency_stepsize: info.frequency_stepsize,
+            frequency_tolerance: info.frequency_tolerance,
+            symbol_rate_min: info.symbol_rate_min,
+            symbol_rate_max: info.symbol_rate_max,
+            symbol_rate_tolerance: info.symbol_rate_tolerance,
+            caps: DvbFrontendCaps::from_bits_retain(info.caps),
+        }
+    }
+}
+
+impl DvbFrontendInfo {
+    /// Read the information of the frontend of the device file `path`.
+    ///
+    /// # Details
+    /// The device file is opened read-only, which is allowed even while another process tunes the frontend.
+    pub fn from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let fd: OwnedFd = OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_CLOEXEC | libc::O_NONBLOCK)
+            .open(&path)
+            .map_err(|err| error::trap_io_error(err, path.clone()))?
+            .into();
+        let mut info: raw::dvb_frontend_info = unsafe { std::mem::zeroed() };
+        unsafe { ioctl!(fd, FE_GET_INFO, &mut info)? };
+        Ok(info.into())
+    }
+}
+
+/// A DVB frontend interface of a topology along with the capabilities of the tuner.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DvbFrontend {
+    #[serde(rename = "interface")]
+    interface: InterfaceId,
+    #[serde(rename = "devnode")]
+    devnode: MediaIntfDevnode,
+    /// The device file such like `/dev/dvb/adapter0/frontend0`.
+    #[serde(rename = "path")]
+    path: PathBuf,
+    /// The names of the entities controlled through the frontend, such as the tuner and the demodulator.
+    #[serde(rename = "entities")]
+    entities: Vec<String>,
+    #[serde(rename = "info")]
+    info: DvbFrontendInfo,
+}
+
+impl DvbFrontend {
+    pub fn interface(&self) -> InterfaceId {
+        self.interface
+    }
+
+    pub fn devnode(&self) -> MediaIntfDevnode {
+        self.devnode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn entities(&self) -> &[String] {
+        &self.entities
+    }
+
+    pub fn info(&self) -> &DvbFrontendInfo {
+        &self.info
+    }
+}
+
+impl MediaTopology {
+    /// Read the information of every DVB frontend interface of the topology with `FE_GET_INFO`.
+    ///
+    /// # Errors
+    /// [`FileNotFound`][error::Error::FileNotFound] if the device file of a frontend is not resolved through sysfs,
+    /// or the errors of [`DvbFrontendInfo::from_path`].
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     for frontend in media.new_topology()?.dvb_frontends()? {
+    ///         let info = frontend.info();
+    ///         println!("{}: {} - {}", info.name, info.frequency_min, info.frequency_max);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dvb_frontends(&self) -> Result<Vec<DvbFrontend>> {
+        self.interfaces_slice()
+            .iter()
+            .filter(|intf| intf.r#type() == MediaInterfaceType::DigitalTVFrontEnd)
+            .map(|intf| {
+                let path = intf
+                    .devnode()
+                    .dev_path()
+                    .ok_or_else(|| error::Error::FileNotFound {
+                        path: intf.path(),
+                        source: std::io::ErrorKind::NotFound.into(),
+                    })?;
+                let entities = self
+                    .links_slice()
+                    .iter()
+                    .filter_map(|link| match link.r#type() {
+                        LinkType::InterfaceLink { source_id, sink_id }
+                            if *source_id == intf.id() =>
+                        {
+                            self.entities_slice().iter().find(|e| e.id() == *sink_id)
+                        }
+                        _ => None,
+                    })
+                    .map(|entity| entity.name().to_string())
+                    .collect();
+                let info = DvbFrontendInfo::from_path(&path)?;
+                Ok(DvbFrontend {
+                    interface: intf.id(),
+                    devnode: intf.devnode(),
+                    path,
+                    entities,
+                    info,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn frontend_info_from_raw() {
+        assert_eq!(size_of::<raw::dvb_frontend_info>(), 168);
+        assert_eq!(FE_GET_INFO, 0x80a8_6f3d);
+
+        let mut info: raw::dvb_frontend_info = unsafe { std::mem::zeroed() };
+        for (dst, src) in info.name.iter_mut().zip(b"Silicon Labs Si2168\0") {
+            *dst = *src as libc::c_char;
+        }
+        info.r#type = 2;
+        info.frequency_min = 42_000_000;
+        info.frequency_max = 870_000_000;
+        info.caps = 0x1_0000 | 0x1000_0000;
+        let info = DvbFrontendInfo::from(info);
+        assert_eq!(info.name, "Silicon Labs Si2168");
+        assert_eq!(info.r#type, Some(DvbFrontendType::Ofdm));
+        assert_eq!(
+            info.caps,
+            DvbFrontendCaps::CanQamAuto | DvbFrontendCaps::Can2gModulation
+        );
+
+        let topology = crate::mock_backend::test::mock().topology();
+        assert!(topology.dvb_frontends().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unterminated_name() {
+        let mut info: raw::dvb_frontend_info = unsafe { std::mem::zeroed() };
+        info.name.fill(b'A' as libc::c_char);
+        assert_eq!(DvbFrontendInfo::from(info).name, "A".repeat(128));
+    }
+}
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::media_bus_info::BusInfo;
+use crate::media_entity::MediaEntity;
+
+/// The parts of an entity name following the conventions of drivers, parsed by [`EntityName::parse`].
+///
+/// # Details
+/// Drivers name entities freely, but many follow one of the conventions:
+/// - `<driver> <adapter>-<address>` for I2C clients such like sensors, e.g. `imx219 10-0010`
+/// - `<driver> <unit address>.<name>` for platform devices, e.g. `imx8-mipi-csi2 32e30000.mipi-csi`
+/// - `<driver>_<block>` for blocks of a device, e.g. `rkisp1_isp` or `rkisp1_resizer_mainpath`
+///
+/// Names following none of them, such like `Integrated Camera: Integrated C` of UVC devices,
+/// are kept without a driver. The original name is formatted back by `Display`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct EntityName {
+    #[serde(rename = "name")]
+    name: String,
+    /// The name of the driver, such like `imx219` or `rkisp1`.
+    #[serde(rename = "driver")]
+    driver: Option<String>,
+    /// The device on its bus, [`BusInfo::I2c`] or [`BusInfo::Platform`].
+    #[serde(rename = "bus")]
+    bus: Option<BusInfo>,
+    /// The block of the device following the driver name, such like `isp` of `rkisp1_isp`.
+    #[serde(rename = "block")]
+    block: Option<String>,
+}
+
+/// Whether `word` may be the name of a driver, which has neither spaces nor punctuation other than `-` and `_`.
+fn is_driver_name(word: &str) -> bool {
+    !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Parse the name of an I2C client such like `10-0010`.
+fn i2c_client(word: &str) -> Option<BusInfo> {
+    let (adapter, address) = word.split_once('-')?;
+    if address.len() != 4 || !adapter.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some(BusInfo::I2c {
+        adapter: adapter.parse().ok()?,
+        address: u16::from_str_radix(address, 16).ok()?,
+    })
+}
+
+/// Parse the name of a platform device named by its unit address such like `32e30000.mipi-csi`.
+fn platform_device(word: &str) -> Option<BusInfo> {
+    let (address, name) = word.split_once('.')?;
+    let is_address = !address.is_empty() && address.bytes().all(|b| b.is_ascii_hexdigit());
+    (is_address && is_driver_name(name)).then(|| BusInfo::Platform {
+        device: word.to_string(),
+    })
+}
+
+impl EntityName {
+    /// Parse the name of an entity by the conventions of drivers, as far as it follows one of them.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// let sensor = EntityName::parse("imx219 10-0010");
+    /// assert_eq!(sensor.driver(), Some("imx219"));
+    /// assert_eq!(sensor.bus(), Some(&BusInfo::I2c { adapter: 10, address: 0x10 }));
+    /// assert_eq!(sensor.device_name().as_deref(), Some("10-0010"));
+    ///
+    /// let isp = EntityName::parse("rkisp1_isp");
+    /// assert_eq!((isp.driver(), isp.block()), (Some("rkisp1"), Some("isp")));
+    ///
+    /// let uvc = EntityName::parse("Integrated Camera: Integrated C");
+    /// assert_eq!(uvc.driver(), None);
+    /// ```
+    pub fn parse(name: &str) -> Self {
+        let mut parsed = Self {
+            name: name.to_string(),
+            driver: None,
+            bus: None,
+            block: None,
+        };
+        if let Some((driver, device)) = name.split_once(' ') {
+            let bus = i2c_client(device).or_else(|| platform_device(device));
+            if bus.is_some() && is_driver_name(driver) {
+                parsed.driver = Some(driver.to_string());
+                parsed.bus = bus;
+            }
+        } else if let Some((driver, block)) = name.split_once('_') {
+            if is_driver_name(driver) && is_driver_name(block) {
+                parsed.driver = Some(driver.to_string());
+                parsed.block = Some(block.to_string());
+            }
+        }
+        parsed
+    }
+
+    /// The original name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn driver(&self) -> Option<&str> {
+        self.driver.as_deref()
+    }
+
+    pub fn bus(&self) -> Option<&BusInfo> {
+        self.bus.as_ref()
+    }
+
+    pub fn block(&self) -> Option<&str> {
+        self.block.as_deref()
+    }
+
+    /// The name of the device in `/sys/bus/{i2c,platform}/devices`, such like `10-0010`.
+    pub fn device_name(&self) -> Option<String> {
+        self.bus.as_ref()?.device_name()
+    }
+}
+
+impl fmt::Display for EntityName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl MediaEntity {
+    /// Parse the name of the entity by the conventions of drivers, see [`EntityName::parse`].
+    pub fn parse_name(&self) -> EntityName {
+        EntityName::parse(self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_conventional_names() {
+        let csi = EntityName::parse("imx8-mipi-csi2 32e30000.mipi-csi");
+        assert_eq!(csi.driver(), Some("imx8-mipi-csi2"));
+        assert_eq!(csi.bus().map(BusInfo::bus), Some("platform"));
+        assert_eq!(csi.device_name().as_deref(), Some("32e30000.mipi-csi"));
+
+        let resizer = EntityName::parse("rkisp1_resizer_mainpath");
+        assert_eq!(resizer.driver(), Some("rkisp1"));
+        assert_eq!(resizer.block(), Some("resizer_mainpath"));
+        assert_eq!(resizer.bus(), None);
+
+        // neither an I2C client nor a platform device
+        for name in ["Sensor A", "ipu3-imgu 0", "ov5640 1-3c", "sensor"] {
+            let parsed = EntityName::parse(name);
+            assert_eq!(parsed.driver(), None, "{}", name);
+            assert_eq!(parsed.to_string(), name);
+        }
+    }
+}
@@ -3,6 +3,8 @@ use std::io;
 use std::os::fd::{AsRawFd, RawFd};
 use std::path::PathBuf;
 
+use crate::permission::PermissionDiagnostics;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
@@ -11,6 +13,17 @@ pub enum Error {
     Io { source: io::Error, path: PathBuf },
     /// File not found
     FileNotFound { path: PathBuf, source: io::Error },
+    /// Opening a file or an ioctl of the media controller API failed with `EACCES` or `EPERM`.
+    /// The errors of other ioctls are [`Ioctl`][Error::Ioctl], since V4L2 ioctls such like `VIDIOC_S_EXT_CTRLS` fail with `EACCES` for other reasons,
+    /// e.g. a control being read-only.
+    /// `api` is the ioctl which failed, or `None` if the file could not be opened.
+    /// `path` of an ioctl is resolved from the file descriptor, and is empty if it could not be.
+    PermissionDenied {
+        path: PathBuf,
+        source: io::Error,
+        api: Option<libc::c_ulong>,
+        diagnostics: Option<PermissionDiagnostics>,
+    },
     /// Generic ioctl error
     /// `code` is constructed from [`std::io::Error::from_raw_os_error`].
     Ioctl {
@@ -70,6 +83,56 @@ pub enum Error {
     PadFlagsParseError { from: u32 },
     /// parse error as [`crate::MediaLinkFlags`]
     LinkFlagsParseError { from: u32 },
+    /// parse error of the link type in the flags of a link as [`crate::LinkType`]
+    LinkTypeParseError { from: u32 },
+    /// parse error as [`crate::Version`]
+    VersionParseError { from: String },
+    /// parse error of a link description as [`crate::LinkSpec`]
+    LinkSpecParseError {
+        spec: String,
+        position: usize,
+        reason: &'static str,
+    },
+    /// The link described by `spec` does not exist in the topology
+    LinkNotFound { spec: String },
+    /// Setting up a link failed and some of the links already changed could not be restored
+    PartialLinkSetup {
+        /// The error of the link which failed to be set up
+        source: Box<Error>,
+        /// The links left changed, in the state updated by the driver
+        unrestored: Vec<crate::MediaLinkDesc>,
+    },
+    /// A line of an ioctl tape could not be parsed
+    TapeParseError {
+        path: PathBuf,
+        line: usize,
+        reason: String,
+    },
+    /// An ioctl replayed from a tape differs from the one recorded at `position`
+    TapeMismatch {
+        position: usize,
+        expected: String,
+        found: String,
+    },
+    /// The topology kept changing while it was fetched
+    TopologyUnstable { attempts: usize },
+    /// More items of a section of the topology than the limit given to [`crate::MediaTopologyBuilder::with_limits`]
+    TopologyTooLarge {
+        section: &'static str,
+        count: u32,
+        limit: u32,
+    },
+    /// No entity of the name is found in any topology
+    EntityNotFound { entity: String },
+    /// Failed to lay out or render a topology graph, with the `render` feature
+    RenderError { reason: String },
+    /// The entity has no V4L2 subdevice whose device file is found, with the `subdev` feature
+    SubdevNotFound { entity: String },
+    /// A route of streams does not match the pads of the entity of the subdevice, with the `subdev` feature
+    InvalidRoute {
+        entity: String,
+        reason: &'static str,
+    },
 }
 
 impl Error {
@@ -91,6 +154,15 @@ impl Error {
         match code {
             libc::EBUSY => DeviceIsBusy { fd, code, api },
             libc::ENOTTY => NotSupportedIoctl { fd, code, api },
+            libc::EACCES | libc::EPERM if is_media_ioctl(api) => {
+                let path = fd_path(fd).unwrap_or_default();
+                PermissionDenied {
+                    diagnostics: PermissionDiagnostics::inspect(&path),
+                    path,
+                    source: io::Error::from_raw_os_error(code),
+                    api: Some(api),
+                }
+            }
             _ => Ioctl {
                 fd,
                 code: io::Error::from_raw_os_error(code),
@@ -98,6 +170,27 @@ impl Error {
             },
         }
     }
+
+    /// The errno of a failed ioctl, or `None` if the error is not from an ioctl.
+    pub fn errno(&self) -> Option<libc::c_int> {
+        use Error::*;
+        match self {
+            Ioctl { code, .. } => code.raw_os_error(),
+            PermissionDenied {
+                source,
+                api: Some(_),
+                ..
+            } => source.raw_os_error(),
+            NotSupportedIoctl { code, .. }
+            | DeviceIsBusy { code, .. }
+            | RequestIsAlreadyQueued { code, .. }
+            | RequestNotContainBuffers { code, .. }
+            | OutOfMemory { code, .. }
+            | RequestHasInvalidData { code, .. }
+            | HardwareBadState { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Error {
@@ -106,6 +199,21 @@ impl fmt::Display for Error {
         match self {
             Io { path, .. } => write!(f, "io error: {}", path.display()),
             FileNotFound { path, .. } => write!(f, "file not found: {}", path.display()),
+            PermissionDenied {
+                path,
+                api,
+                diagnostics,
+                ..
+            } => {
+                write!(f, "permission denied: {}", path.display())?;
+                if let Some(api) = api {
+                    write!(f, ": 0x{:02X}", api)?;
+                }
+                if let Some(diagnostics) = diagnostics {
+                    write!(f, ": {}", diagnostics)?;
+                }
+                Ok(())
+            }
             Ioctl { fd, code, api } => {
                 write!(f, "generic ioctl error {}: 0x{:02X}: {}", fd, api, code)
             }
@@ -159,14 +267,104 @@ impl fmt::Display for Error {
             LinkFlagsParseError { from, .. } => {
                 write!(f, "link flags parse error: {}", from)
             }
+            LinkTypeParseError { from, .. } => {
+                write!(f, "link type parse error: {:#x}", from)
+            }
+            VersionParseError { from, .. } => {
+                write!(f, "version parse error: {}", from)
+            }
+            LinkSpecParseError {
+                spec,
+                position,
+                reason,
+            } => {
+                write!(
+                    f,
+                    "link description parse error at {}: {}: {}",
+                    position, reason, spec
+                )
+            }
+            LinkNotFound { spec } => {
+                write!(f, "link not found: {}", spec)
+            }
+            PartialLinkSetup { source, unrestored } => {
+                write!(
+                    f,
+                    "link setup failed and {} link(s) could not be restored: {}",
+                    unrestored.len(),
+                    source
+                )
+            }
+            TapeParseError { path, line, reason } => {
+                write!(
+                    f,
+                    "tape parse error at {}:{}: {}",
+                    path.display(),
+                    line,
+                    reason
+                )
+            }
+            TapeMismatch {
+                position,
+                expected,
+                found,
+            } => {
+                write!(
+                    f,
+                    "the ioctl #{} differs from the tape: expected {}, found {}",
+                    position, expected, found
+                )
+            }
+            TopologyUnstable { attempts } => write!(
+                f,
+                "the topology kept changing over {} attempts to fetch it",
+                attempts
+            ),
+            TopologyTooLarge {
+                section,
+                count,
+                limit,
+            } => write!(
+                f,
+                "the topology has {} {}, more than the limit of {}",
+                count, section, limit
+            ),
+            EntityNotFound { entity } => {
+                write!(f, "entity not found: {}", entity)
+            }
+            RenderError { reason } => {
+                write!(f, "render error: {}", reason)
+            }
+            SubdevNotFound { entity } => {
+                write!(f, "subdevice not found: {}", entity)
+            }
+            InvalidRoute { entity, reason } => {
+                write!(f, "invalid route of {}: {}", entity, reason)
+            }
         }
     }
 }
 
+/// Whether `api` is an ioctl of the media controller API, whose type is `'|'`.
+fn is_media_ioctl(api: libc::c_ulong) -> bool {
+    (api >> crate::raw::_IOC_TYPESHIFT) & 0xff == b'|' as libc::c_ulong
+}
+
+/// The path of the file the file descriptor `fd` of this process refers to.
+fn fd_path(fd: RawFd) -> Option<PathBuf> {
+    std::fs::read_link(format!("/proc/self/fd/{}", fd)).ok()
+}
+
 pub fn trap_io_error(err: io::Error, path: PathBuf) -> Error {
     use io::ErrorKind::*;
     match err.kind() {
         NotFound => Error::FileNotFound { path, source: err },
+        PermissionDenied => Error::PermissionDenied {
+            diagnostics: PermissionDiagnostics::inspect(&path),
+            path,
+            source: err,
+            api: None,
+        },
         _ => Error::Io { source: err, path },
     }
 }
@@ -174,6 +372,8 @@ pub fn trap_io_error(err: io::Error, path: PathBuf) -> Error {
 #[cfg(test)]
 pub mod test {
     use super::*;
+    use crate::raw as media;
+    use crate::test_util::TempDir;
 
     // https://www.kernel.org/doc/html/v6.9/userspace-api/media/gen-errors.html
     #[test]
@@ -192,4 +392,36 @@ pub mod test {
             }
         ));
     }
+
+    #[test]
+    fn eacces_is_diagnosed() {
+        let tmp = TempDir::new("eacces");
+        let path = tmp.path().join("media0");
+        let file = std::fs::File::create(&path).unwrap();
+        let err = Error::ioctl_error(file.as_raw_fd(), libc::EACCES, media::MEDIA_IOC_DEVICE_INFO);
+        // not a media ioctl but VIDIOC_S_EXT_CTRLS, which fails with EACCES for read-only controls
+        let v4l2 = Error::ioctl_error(file.as_raw_fd(), libc::EACCES, 0xc020_5648);
+
+        assert!(matches!(v4l2, Error::Ioctl { .. }), "{:?}", v4l2);
+        assert_eq!(v4l2.errno(), Some(libc::EACCES));
+
+        assert_eq!(err.errno(), Some(libc::EACCES));
+        let Error::PermissionDenied {
+            path: found,
+            api,
+            diagnostics,
+            ..
+        } = err
+        else {
+            panic!("not a permission error: {:?}", err);
+        };
+        assert_eq!(api, Some(media::MEDIA_IOC_DEVICE_INFO));
+        // the path is resolved through procfs
+        if cfg!(target_os = "linux") {
+            assert_eq!(found, path);
+            let diagnostics = diagnostics.unwrap();
+            assert_eq!(diagnostics.owner(), diagnostics.uid());
+            assert_eq!(diagnostics.hint(), None);
+        }
+    }
 }
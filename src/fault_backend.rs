@@ -0,0 +1,262 @@
+use std::collections::BTreeSet;
+use std::os::fd::OwnedFd;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::error::{Error, Result};
+use crate::media_backend::{MediaBackend, TopologyArrays};
+use crate::raw as media;
+use crate::{MediaTopologyCounts, MockOp};
+
+/// The faults injected by [`FaultBackend`].
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+///
+/// let mut plan = FaultPlan::new();
+/// // every third setup of a link fails with EBUSY, and the device is gone after 100 calls
+/// plan.fail_every(3, libc::EBUSY)
+///     .only(MockOp::SetupLink)
+///     .disconnect_after(100);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FaultPlan {
+    fail_every: Option<(usize, libc::c_int)>,
+    ops: BTreeSet<MockOp>,
+    bump_version: Option<(usize, u64)>,
+    disconnect_after: Option<usize>,
+}
+
+impl FaultPlan {
+    /// A plan injecting no fault.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail every `n`th call with `errno`.
+    ///
+    /// # Details
+    /// Only the calls of the operations selected by [`only`][Self::only] are counted, if any.
+    pub fn fail_every(&mut self, n: usize, errno: libc::c_int) -> &mut Self {
+        assert!(n > 0, "fail_every needs a positive period");
+        self.fail_every = Some((n, errno));
+        self
+    }
+
+    /// Restrict [`fail_every`][Self::fail_every] to `op`, in addition to the operations selected so far.
+    pub fn only(&mut self, op: MockOp) -> &mut Self {
+        self.ops.insert(op);
+        self
+    }
+
+    /// Advance the topology version reported by `MEDIA_IOC_G_TOPOLOGY` by `step` every `n` calls,
+    /// as if the topology changed between them.
+    pub fn bump_version(&mut self, n: usize, step: u64) -> &mut Self {
+        assert!(n > 0, "bump_version needs a positive period");
+        self.bump_version = Some((n, step));
+        self
+    }
+
+    /// Fail every call after the first `calls` with `ENODEV`, as a device unplugged.
+    pub fn disconnect_after(&mut self, calls: usize) -> &mut Self {
+        self.disconnect_after = Some(calls);
+        self
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// The calls since connected
+    calls: usize,
+    /// The calls counted by `fail_every`
+    counted: usize,
+    /// The calls of `MEDIA_IOC_G_TOPOLOGY`
+    topologies: usize,
+}
+
+/// A [`MediaBackend`] forwarding every operation to another backend, failing some of them as planned.
+///
+/// # Details
+/// Injects the faults a real device shows now and then, to test the paths of applications handling them,
+/// such as retrying on `EBUSY`, reopening a device unplugged, and rolling back links partially set up.
+/// Injected errors report the file descriptor `-1`, and failed calls are not forwarded.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn run(mock: MockBackend) -> error::Result<()> {
+/// let mut plan = FaultPlan::new();
+/// plan.disconnect_after(3);
+/// let media = Media::with_backend("/dev/media0", FaultBackend::new(mock, plan))?;
+/// // the device info and the two calls fetching the topology are served
+/// let _ = media.new_topology()?;
+/// assert!(media.topology_counts().is_err());
+/// media.backend().reconnect();
+/// let _ = media.topology_counts()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FaultBackend<B> {
+    backend: B,
+    plan: FaultPlan,
+    state: Mutex<State>,
+}
+
+impl<B> FaultBackend<B> {
+    pub fn new(backend: B, plan: FaultPlan) -> Self {
+        Self {
+            backend,
+            plan,
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+
+    pub fn plan(&self) -> &FaultPlan {
+        &self.plan
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// The number of the calls since connected, including the failed ones.
+    pub fn calls(&self) -> usize {
+        self.state().calls
+    }
+
+    /// Connect the device again, which serves [`disconnect_after`][FaultPlan::disconnect_after] calls more.
+    pub fn reconnect(&self) {
+        self.state().calls = 0;
+    }
+
+    /// Count a call of `op` and fail it if planned.
+    fn call(&self, op: MockOp) -> Result<()> {
+        let mut state = self.state();
+        state.calls += 1;
+        if let Some(limit) = self.plan.disconnect_after {
+            if state.calls > limit {
+                return Err(Error::ioctl_error(-1, libc::ENODEV, op.api()));
+            }
+        }
+        if let Some((n, errno)) = self.plan.fail_every {
+            if self.plan.ops.is_empty() || self.plan.ops.contains(&op) {
+                state.counted += 1;
+                if state.counted % n == 0 {
+                    return Err(Error::ioctl_error(-1, errno, op.api()));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<B: MediaBackend> MediaBackend for FaultBackend<B> {
+    fn device_info(&self) -> Result<media::media_device_info> {
+        self.call(MockOp::DeviceInfo)?;
+        self.backend.device_info()
+    }
+
+    fn g_topology(&self, arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts> {
+        self.call(MockOp::GTopology)?;
+        let mut counts = self.backend.g_topology(arrays)?;
+        if let Some((n, step)) = self.plan.bump_version {
+            let mut state = self.state();
+            counts.version += (state.topologies / n) as u64 * step;
+            state.topologies += 1;
+        }
+        Ok(counts)
+    }
+
+    fn enum_entities(&self, desc: &mut media::media_entity_desc) -> Result<()> {
+        self.call(MockOp::EnumEntities)?;
+        self.backend.enum_entities(desc)
+    }
+
+    unsafe fn enum_links(
+        &self,
+        entity: u32,
+        pads: &mut [media::media_pad_desc],
+        links: &mut [media::media_link_desc],
+    ) -> Result<()> {
+        self.call(MockOp::EnumLinks)?;
+        self.backend.enum_links(entity, pads, links)
+    }
+
+    fn setup_link(&self, link: &mut media::media_link_desc) -> Result<()> {
+        self.call(MockOp::SetupLink)?;
+        self.backend.setup_link(link)
+    }
+
+    fn request_alloc(&self) -> Result<OwnedFd> {
+        self.call(MockOp::RequestAlloc)?;
+        self.backend.request_alloc()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::{LinkSpec, Media};
+
+    #[test]
+    fn fails_every_nth_setup() {
+        let mut plan = FaultPlan::new();
+        plan.fail_every(2, libc::EBUSY).only(MockOp::SetupLink);
+        let media =
+            Media::with_backend("/dev/media-mock", FaultBackend::new(mock(), plan)).unwrap();
+        let specs =
+            LinkSpec::parse_list("\"sensor\":0->\"csi\":0[1], \"csi\":1->\"dma\":0[1]").unwrap();
+
+        let err = media.setup_links(&specs).unwrap_err();
+        assert!(matches!(err, Error::DeviceIsBusy { .. }));
+        // the first link was rolled back
+        let topology = media.backend().backend().topology();
+        assert!(topology
+            .links_slice()
+            .iter()
+            .all(|link| link.flags().is_empty()));
+    }
+
+    #[test]
+    fn disconnects_and_reconnects() {
+        let mut plan = FaultPlan::new();
+        plan.disconnect_after(3);
+        let media =
+            Media::with_backend("/dev/media-mock", FaultBackend::new(mock(), plan)).unwrap();
+        media.new_topology().unwrap();
+        let err = media.topology_counts().unwrap_err();
+        assert_eq!(err.errno(), Some(libc::ENODEV));
+        media.backend().reconnect();
+        assert_eq!(media.topology_counts().unwrap().entities, 3);
+    }
+
+    #[test]
+    fn topology_changing_between_calls_is_refetched() {
+        let mut plan = FaultPlan::new();
+        plan.bump_version(3, 1);
+        let media =
+            Media::with_backend("/dev/media-mock", FaultBackend::new(mock(), plan)).unwrap();
+        // the version changes between the third and the fourth call of MEDIA_IOC_G_TOPOLOGY
+        assert_eq!(media.new_topology().unwrap().version(), 1);
+        assert_eq!(media.new_topology().unwrap().version(), 2);
+
+        let mut plan = FaultPlan::new();
+        plan.bump_version(1, 1);
+        let media =
+            Media::with_backend("/dev/media-mock", FaultBackend::new(mock(), plan)).unwrap();
+        assert!(matches!(
+            media.new_topology(),
+            Err(Error::TopologyUnstable { .. })
+        ));
+    }
+}
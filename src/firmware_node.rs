@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::media_entity::EntityId;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::media_topology::MediaTopology;
+use crate::{Media, MediaBackend};
+
+/// The node of the firmware describing a device, in the device tree or in the ACPI namespace.
+///
+/// # Details
+/// Found in sysfs by the `of_node` and the `firmware_node` links of the device or its nearest ancestor having one,
+/// so that a topology can be correlated with the device tree source of a board or the ACPI tables of a laptop.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum FirmwareNode {
+    /// The full path of a device tree node, such like `/soc/i2c@7e205000/imx219@10`.
+    DeviceTree {
+        #[serde(rename = "path")]
+        path: String,
+    },
+    /// The full path of an ACPI device, such like `\_SB_.PCI0.I2C2.CAM0`.
+    Acpi {
+        #[serde(rename = "path")]
+        path: String,
+    },
+}
+
+/// The path of the device tree node the sysfs directory `node` under `/sys/firmware/devicetree/base` is for.
+fn device_tree_path(node: &Path) -> Option<String> {
+    let base = node.ancestors().find(|dir| {
+        let mut components = dir.components().rev();
+        components.next() == Some(Component::Normal("base".as_ref()))
+            && components.next() == Some(Component::Normal("devicetree".as_ref()))
+    })?;
+    let path = node.strip_prefix(base).ok()?;
+    Some(format!("/{}", path.to_string_lossy()))
+}
+
+impl FirmwareNode {
+    /// Find the firmware node of the device in the sysfs directory `device` or its ancestors.
+    pub(crate) fn read(device: &Path) -> Option<Self> {
+        device.ancestors().find_map(|dir| {
+            if let Ok(node) = fs::canonicalize(dir.join("of_node")) {
+                let path = device_tree_path(&node)?;
+                return Some(FirmwareNode::DeviceTree { path });
+            }
+            let path = fs::read_to_string(dir.join("firmware_node/path")).ok()?;
+            Some(FirmwareNode::Acpi {
+                path: path.trim_end().to_string(),
+            })
+        })
+    }
+
+    /// Find the firmware node of the device behind the character device `devnode`, walking `/sys/dev/char`.
+    ///
+    /// # Returns
+    /// `None` if sysfs is not available, the device node is not known to it,
+    /// or neither the device nor its ancestors are described by the firmware, e.g. USB devices.
+    pub fn from_devnode(devnode: MediaIntfDevnode) -> Option<Self> {
+        Self::read(&fs::canonicalize(PathBuf::from(devnode).join("device")).ok()?)
+    }
+
+    /// The path of the node, in the notation of the device tree or ACPI.
+    pub fn path(&self) -> &str {
+        match self {
+            FirmwareNode::DeviceTree { path } | FirmwareNode::Acpi { path } => path,
+        }
+    }
+}
+
+impl fmt::Display for FirmwareNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FirmwareNode::DeviceTree { path } => write!(f, "of:{}", path),
+            FirmwareNode::Acpi { path } => write!(f, "acpi:{}", path),
+        }
+    }
+}
+
+impl MediaTopology {
+    /// Find the firmware node of `entity` from the device nodes of the interfaces linked to it.
+    pub fn entity_firmware_node(&self, entity: EntityId) -> Option<FirmwareNode> {
+        self.entity_devnodes(entity)
+            .find_map(FirmwareNode::from_devnode)
+    }
+
+    /// Find the firmware nodes of the entities having device nodes, by walking sysfs.
+    ///
+    /// # Details
+    /// Entities without device nodes, or whose devices are not described by the firmware, are left out.
+    /// The topology has to include interfaces and links.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = media.new_topology()?;
+    ///     for (id, node) in topology.firmware_nodes() {
+    ///         println!("{}: {}", u32::from(id), node);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn firmware_nodes(&self) -> BTreeMap<EntityId, FirmwareNode> {
+        self.entities_slice()
+            .iter()
+            .filter_map(|entity| Some((entity.id(), self.entity_firmware_node(entity.id())?)))
+            .collect()
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Find the firmware node of the device the media controller belongs to, such like the node of a CSI-2 receiver or an ISP.
+    ///
+    /// # Returns
+    /// `Ok(None)` if neither the device nor its ancestors are described by the firmware.
+    ///
+    /// # Errors
+    /// The [`path`][Media::path] of the device is not a character device, e.g. if the device was opened with a mock backend.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     if let Some(node) = media.firmware_node()? {
+    ///         println!("{}", node);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn firmware_node(&self) -> Result<Option<FirmwareNode>> {
+        let devnode = MediaIntfDevnode::from_char_device(self.path())?;
+        Ok(FirmwareNode::from_devnode(devnode))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn finds_device_tree_and_acpi_nodes() {
+        let tmp = TempDir::new("fwnode");
+        let root = tmp.path();
+        let sensor = root.join("devices/platform/soc/fe205000.i2c/i2c-10/10-0010");
+        let subdev = sensor.join("video4linux/v4l-subdev0");
+        let of_node = root.join("firmware/devicetree/base/soc/i2c@7e205000/imx219@10");
+        let camera = root.join("devices/pci0000:00/0000:00:15.2/i2c_designware.2/i2c-INT347A:00");
+        let adev = root.join("devices/LNXSYSTM:00/LNXSYBUS:00/INT347A:00");
+        fs::create_dir_all(&subdev).unwrap();
+        fs::create_dir_all(&of_node).unwrap();
+        fs::create_dir_all(&camera).unwrap();
+        fs::create_dir_all(&adev).unwrap();
+        symlink(&of_node, sensor.join("of_node")).unwrap();
+        symlink(&adev, camera.join("firmware_node")).unwrap();
+        fs::write(adev.join("path"), "\\_SB_.PCI0.I2C2.CAM0\n").unwrap();
+
+        let dt = FirmwareNode::read(&subdev);
+        let acpi = FirmwareNode::read(&camera);
+        let usb = FirmwareNode::read(&root.join("devices/pci0000:00"));
+
+        let dt = dt.unwrap();
+        assert_eq!(dt.path(), "/soc/i2c@7e205000/imx219@10");
+        assert_eq!(dt.to_string(), "of:/soc/i2c@7e205000/imx219@10");
+        assert_eq!(
+            acpi,
+            Some(FirmwareNode::Acpi {
+                path: "\\_SB_.PCI0.I2C2.CAM0".to_string()
+            })
+        );
+        assert_eq!(usb, None);
+    }
+}
@@ -0,0 +1,406 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{MediaDeviceInfo, MediaTopology};
+
+/// A synthetic topology modeled on common hardware, for tests and examples running without the hardware.
+///
+/// # Details
+/// The fixtures are not captured from devices, but written after the drivers with [`MockTopologyBuilder`][crate::MockTopologyBuilder],
+/// which numbers the objects as the kernel does.
+/// The names, the functions and the links of the objects follow the drivers,
+/// while the ids and the order of the objects may differ from those of a real device,
+/// and so may objects which only some versions of a driver create.
+/// Each fixture is shipped in `fixtures/` of the crate as JSON in the serialized form of [`MediaDeviceInfo`] and [`MediaTopology`],
+/// which a test checks to be the same as generated by the builder.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+///
+/// let topology = Fixture::RpiUnicam.topology();
+/// let sensor = topology
+///     .entities_slice()
+///     .iter()
+///     .find(|entity| entity.function() == MediaEntityFunctions::CAMSensor)
+///     .unwrap();
+/// assert_eq!(sensor.name(), "imx219 10-0010");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum Fixture {
+    /// A Logitech C920 webcam driven by `uvcvideo`
+    Uvc,
+    /// The CSI-2 receiver `unicam` of a Raspberry Pi 4 with an imx219 sensor
+    RpiUnicam,
+    /// The ISP `bcm2835-isp` of a Raspberry Pi 4
+    RpiIsp,
+    /// The ISP `rkisp1` of a Rockchip RK3399 with an imx219 sensor
+    Rkisp1,
+    /// The virtual media controller driver `vimc` in its default configuration
+    Vimc,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    #[serde(rename = "info")]
+    info: MediaDeviceInfo,
+    #[serde(rename = "topology")]
+    topology: MediaTopology,
+}
+
+impl Fixture {
+    pub const ALL: [Fixture; 5] = [
+        Fixture::Uvc,
+        Fixture::RpiUnicam,
+        Fixture::RpiIsp,
+        Fixture::Rkisp1,
+        Fixture::Vimc,
+    ];
+
+    /// The name of the fixture, which is the stem of its file.
+    pub fn name(&self) -> &'static str {
+        use Fixture::*;
+        match self {
+            Uvc => "uvc",
+            RpiUnicam => "rpi-unicam",
+            RpiIsp => "rpi-isp",
+            Rkisp1 => "rkisp1",
+            Vimc => "vimc",
+        }
+    }
+
+    /// The snapshot in JSON, an object of `info` and `topology`.
+    pub fn json(&self) -> &'static str {
+        use Fixture::*;
+        match self {
+            Uvc => include_str!("../fixtures/uvc.json"),
+            RpiUnicam => include_str!("../fixtures/rpi-unicam.json"),
+            RpiIsp => include_str!("../fixtures/rpi-isp.json"),
+            Rkisp1 => include_str!("../fixtures/rkisp1.json"),
+            Vimc => include_str!("../fixtures/vimc.json"),
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        serde_json::from_str(self.json())
+            .unwrap_or_else(|err| panic!("the fixture {} is broken: {}", self.name(), err))
+    }
+
+    pub fn info(&self) -> MediaDeviceInfo {
+        self.snapshot().info
+    }
+
+    pub fn topology(&self) -> MediaTopology {
+        self.snapshot().topology
+    }
+
+    /// A [`MockBackend`][crate::MockBackend] serving the fixture.
+    #[cfg(feature = "mock")]
+    pub fn backend(&self) -> crate::MockBackend {
+        let Snapshot { info, topology } = self.snapshot();
+        crate::MockBackend::new(info, topology)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology_golden::UPDATE_GOLDEN_ENV;
+    use crate::{
+        EntityId, LinkType, MediaEntityFlags, MediaEntityFunctions, MediaInterfaceType,
+        MediaIntfDevnode, MediaLinkFlags, MediaPadFlags, MockTopologyBuilder, PadId, Version,
+    };
+
+    fn info(
+        driver: &str,
+        model: &str,
+        serial: &str,
+        bus_info: &str,
+        version: Version,
+    ) -> MediaDeviceInfo {
+        MediaDeviceInfo {
+            driver: driver.to_string(),
+            model: model.to_string(),
+            serial: serial.to_string(),
+            bus_info: bus_info.to_string(),
+            media_version: version,
+            hw_revision: 0.into(),
+            driver_version: version,
+        }
+    }
+
+    /// The device node `/dev/video*` or `/dev/v4l-subdev*` numbered `minor`.
+    fn v4l(minor: u32) -> MediaIntfDevnode {
+        MediaIntfDevnode { major: 81, minor }
+    }
+
+    const SINK: MediaPadFlags = MediaPadFlags::Sink;
+    const SOURCE: MediaPadFlags = MediaPadFlags::Source;
+    const MUST_CONNECT: MediaPadFlags = MediaPadFlags::Sink.union(MediaPadFlags::MustConnect);
+    const FIXED: MediaLinkFlags = MediaLinkFlags::Enabled.union(MediaLinkFlags::Immutable);
+
+    /// Register a subdevice with its device node.
+    fn subdev(
+        builder: &mut MockTopologyBuilder,
+        name: &str,
+        function: MediaEntityFunctions,
+        pads: &[MediaPadFlags],
+        minor: u32,
+    ) -> (EntityId, Vec<PadId>) {
+        let (entity, pads) = builder.entity(name, function, pads);
+        builder.interface(MediaInterfaceType::V4LSubdev, v4l(minor), entity);
+        (entity, pads)
+    }
+
+    /// Register a video device with its device node, which has a pad of `flags`.
+    fn video(
+        builder: &mut MockTopologyBuilder,
+        name: &str,
+        flags: MediaPadFlags,
+        minor: u32,
+    ) -> (EntityId, PadId) {
+        let (entity, pads) = builder.entity(name, MediaEntityFunctions::IoV4L, &[flags]);
+        builder.interface(MediaInterfaceType::V4LVideo, v4l(minor), entity);
+        (entity, pads[0])
+    }
+
+    /// `uvcvideo` registers the units of the camera chained from the input terminal to the video device.
+    fn uvc() -> Snapshot {
+        use MediaEntityFunctions::*;
+        let mut builder = MockTopologyBuilder::new();
+        let (webcam, input) = video(&mut builder, "HD Pro Webcam C920", SINK, 0);
+        builder.entity_flags(webcam, MediaEntityFlags::Default);
+        let (_, extension) = builder.entity("Extension 6", V4L2SubdevUnknown, &[SINK, SOURCE]);
+        let (_, processing) =
+            builder.entity("Processing 3", ProcVideoPixelEncConv, &[SINK, SOURCE]);
+        let (_, camera) = builder.entity("Camera 1", CAMSensor, &[SOURCE]);
+        builder.link(camera[0], processing[0], FIXED);
+        builder.link(processing[1], extension[0], FIXED);
+        builder.link(extension[1], input, FIXED);
+        let mut info = info(
+            "uvcvideo",
+            "HD Pro Webcam C920",
+            "2B6D4F1F",
+            "usb-0000:00:14.0-2",
+            Version::new(6, 1, 0),
+        );
+        info.hw_revision = 0x11.into();
+        Snapshot {
+            info,
+            topology: builder.version(16).build(),
+        }
+    }
+
+    /// `unicam` with an imx219 sensor, whose second pad carries the embedded data.
+    fn rpi_unicam() -> Snapshot {
+        let mut builder = MockTopologyBuilder::new();
+        let (_, sensor) = subdev(
+            &mut builder,
+            "imx219 10-0010",
+            MediaEntityFunctions::CAMSensor,
+            &[SOURCE, SOURCE],
+            2,
+        );
+        let (image, image_pad) = video(&mut builder, "unicam-image", MUST_CONNECT, 0);
+        builder.entity_flags(image, MediaEntityFlags::Default);
+        let (_, embedded_pad) = video(&mut builder, "unicam-embedded", MUST_CONNECT, 1);
+        builder.link(sensor[0], image_pad, FIXED);
+        builder.link(sensor[1], embedded_pad, FIXED);
+        Snapshot {
+            info: info(
+                "unicam",
+                "unicam",
+                "",
+                "platform:fe801000.csi",
+                Version::new(6, 1, 21),
+            ),
+            topology: builder.version(16).build(),
+        }
+    }
+
+    /// `bcm2835-isp` reading from one output and writing to three captures.
+    fn rpi_isp() -> Snapshot {
+        let mut builder = MockTopologyBuilder::new();
+        let (_, isp) = builder.entity(
+            "bcm2835_isp0",
+            MediaEntityFunctions::ProcVideoScaler,
+            &[SINK, SOURCE, SOURCE, SOURCE],
+        );
+        let (_, output) = video(&mut builder, "bcm2835-isp0-output0", SOURCE, 13);
+        builder.link(output, isp[0], FIXED);
+        for (index, minor) in [(1, 14), (2, 15), (3, 16)] {
+            let name = format!("bcm2835-isp0-capture{}", index);
+            let (_, capture) = video(&mut builder, &name, SINK, minor);
+            builder.link(isp[index], capture, FIXED);
+        }
+        Snapshot {
+            info: info(
+                "bcm2835-isp",
+                "bcm2835-isp",
+                "",
+                "platform:bcm2835-isp",
+                Version::new(6, 1, 21),
+            ),
+            topology: builder.version(26).build(),
+        }
+    }
+
+    /// `rkisp1` with its CSI-2 receiver `rkisp1_csi` and an imx219 sensor.
+    fn rkisp1() -> Snapshot {
+        use MediaEntityFunctions::*;
+        let mut builder = MockTopologyBuilder::new();
+        let (_, isp) = subdev(
+            &mut builder,
+            "rkisp1_isp",
+            ProcVideoPixelEncConv,
+            &[MUST_CONNECT, SINK, SOURCE, SOURCE],
+            0,
+        );
+        let (_, main) = subdev(
+            &mut builder,
+            "rkisp1_resizer_mainpath",
+            ProcVideoScaler,
+            &[MUST_CONNECT, SOURCE],
+            1,
+        );
+        let (_, self_) = subdev(
+            &mut builder,
+            "rkisp1_resizer_selfpath",
+            ProcVideoScaler,
+            &[MUST_CONNECT, SOURCE],
+            2,
+        );
+        let (_, mainpath) = video(&mut builder, "rkisp1_mainpath", MUST_CONNECT, 4);
+        let (_, selfpath) = video(&mut builder, "rkisp1_selfpath", MUST_CONNECT, 5);
+        let (_, stats) = video(&mut builder, "rkisp1_stats", MUST_CONNECT, 6);
+        let (_, params) = video(&mut builder, "rkisp1_params", SOURCE, 7);
+        let (_, csi) = subdev(
+            &mut builder,
+            "rkisp1_csi",
+            VIDIFBridge,
+            &[MUST_CONNECT, SOURCE],
+            3,
+        );
+        let (_, sensor) = subdev(&mut builder, "imx219 4-0010", CAMSensor, &[SOURCE], 8);
+        builder.link(csi[1], isp[0], FIXED);
+        builder.link(params, isp[1], FIXED);
+        builder.link(isp[2], main[0], MediaLinkFlags::Enabled);
+        builder.link(isp[2], self_[0], MediaLinkFlags::Enabled);
+        builder.link(isp[3], stats, FIXED);
+        builder.link(main[1], mainpath, FIXED);
+        builder.link(self_[1], selfpath, FIXED);
+        builder.link(sensor[0], csi[0], MediaLinkFlags::Enabled);
+        Snapshot {
+            info: info(
+                "rkisp1",
+                "rkisp1",
+                "",
+                "platform:ff910000.isp",
+                Version::new(6, 6, 0),
+            ),
+            topology: builder.version(45).build(),
+        }
+    }
+
+    /// `vimc` in its default configuration, in which the scaler reads from `Debayer A`.
+    fn vimc() -> Snapshot {
+        use MediaEntityFunctions::*;
+        let mut builder = MockTopologyBuilder::new();
+        let (_, sensor_a) = subdev(&mut builder, "Sensor A", CAMSensor, &[SOURCE], 0);
+        let (_, sensor_b) = subdev(&mut builder, "Sensor B", CAMSensor, &[SOURCE], 1);
+        let (_, debayer_a) = subdev(
+            &mut builder,
+            "Debayer A",
+            ProcVideoPixelEncConv,
+            &[SINK, SOURCE],
+            2,
+        );
+        let (_, debayer_b) = subdev(
+            &mut builder,
+            "Debayer B",
+            ProcVideoPixelEncConv,
+            &[SINK, SOURCE],
+            3,
+        );
+        let (_, raw_0) = video(&mut builder, "Raw Capture 0", MUST_CONNECT, 5);
+        let (_, raw_1) = video(&mut builder, "Raw Capture 1", MUST_CONNECT, 6);
+        let (_, input) = subdev(&mut builder, "RGB/YUV Input", CAMSensor, &[SOURCE], 4);
+        let (_, scaler) = subdev(&mut builder, "Scaler", ProcVideoScaler, &[SINK, SOURCE], 8);
+        let (_, capture) = video(&mut builder, "RGB/YUV Capture", MUST_CONNECT, 7);
+        builder.link(sensor_a[0], debayer_a[0], FIXED);
+        builder.link(sensor_a[0], raw_0, FIXED);
+        builder.link(sensor_b[0], debayer_b[0], FIXED);
+        builder.link(sensor_b[0], raw_1, FIXED);
+        builder.link(debayer_a[1], scaler[0], MediaLinkFlags::Enabled);
+        builder.link(debayer_b[1], scaler[0], MediaLinkFlags::empty());
+        builder.link(input[0], scaler[0], MediaLinkFlags::empty());
+        builder.link(scaler[1], capture, FIXED);
+        Snapshot {
+            info: info(
+                "vimc",
+                "VIMC MDEV",
+                "",
+                "platform:vimc.0",
+                Version::new(6, 6, 0),
+            ),
+            topology: builder.version(46).build(),
+        }
+    }
+
+    /// Build the snapshot of `fixture`, from which its file is generated.
+    fn generate(fixture: Fixture) -> Snapshot {
+        use Fixture::*;
+        match fixture {
+            Uvc => uvc(),
+            RpiUnicam => rpi_unicam(),
+            RpiIsp => rpi_isp(),
+            Rkisp1 => rkisp1(),
+            Vimc => vimc(),
+        }
+    }
+
+    /// Regenerate the files of the fixtures with `LINUX_MEDIA_UPDATE_GOLDEN=1` after changing them.
+    #[test]
+    fn fixtures_are_generated() {
+        let update = std::env::var(UPDATE_GOLDEN_ENV).map_or(false, |value| value.trim() == "1");
+        for fixture in Fixture::ALL {
+            let generated = generate(fixture);
+            if update {
+                let path = format!(
+                    "{}/fixtures/{}.json",
+                    env!("CARGO_MANIFEST_DIR"),
+                    fixture.name()
+                );
+                let mut json = serde_json::to_string_pretty(&generated).unwrap();
+                json.push('\n');
+                std::fs::write(path, json).unwrap();
+                continue;
+            }
+            let shipped = fixture.snapshot();
+            assert_eq!(shipped.info, generated.info, "{}", fixture.name());
+            assert!(
+                shipped.topology == generated.topology,
+                "{} differs from the generated one, set {}=1 to update it",
+                fixture.name(),
+                UPDATE_GOLDEN_ENV
+            );
+        }
+    }
+
+    #[test]
+    fn fixtures_are_consistent() {
+        for fixture in Fixture::ALL {
+            let topology = fixture.topology();
+            assert!(!topology.entities_slice().is_empty(), "{}", fixture.name());
+            for link in topology.links_slice() {
+                if let LinkType::DataLink { .. } = link.r#type() {
+                    assert!(
+                        topology.link_desc(link).is_some(),
+                        "{}: {:?}",
+                        fixture.name(),
+                        link
+                    );
+                }
+            }
+        }
+    }
+}
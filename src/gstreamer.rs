@@ -0,0 +1,225 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    EntityId, MediaEntity, MediaInterfaceType, MediaIntfDevnode, MediaLinkFlags, MediaTopology,
+};
+
+/// A subdevice of a capture pipeline, in the properties of the GStreamer element `v4l2subdev`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GstV4l2Subdev {
+    #[serde(rename = "entity")]
+    entity: String,
+    /// The device file such like `/dev/v4l-subdev0`.
+    #[serde(rename = "device")]
+    device: PathBuf,
+}
+
+impl GstV4l2Subdev {
+    /// The name of the entity of the subdevice.
+    pub fn entity(&self) -> &str {
+        &self.entity
+    }
+
+    pub fn device(&self) -> &std::path::Path {
+        &self.device
+    }
+
+    /// The properties to be set on `v4l2subdev`.
+    pub fn properties(&self) -> Vec<(&'static str, String)> {
+        vec![("device", self.device.display().to_string())]
+    }
+}
+
+/// A capture pipeline ending at a video device, in the properties of the GStreamer elements `v4l2src` and `v4l2subdev`.
+///
+/// # Details
+/// Once the links of a pipeline are set up with this crate, capturing is handed off to GStreamer,
+/// which opens the device files instead of the entities of the topology.
+/// The subdevices are those connected to the video device by enabled links, which are configured along with it.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(media) = Media::from_path("/dev/media0") {
+///     for pipeline in media.new_topology()?.gst_v4l2_pipelines() {
+///         // e.g. gst-launch-1.0 v4l2src device=/dev/video0 ! videoconvert ! autovideosink
+///         println!("{} ! videoconvert ! autovideosink", pipeline.launch());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct GstV4l2Pipeline {
+    #[serde(rename = "entity")]
+    entity: String,
+    /// The video device file such like `/dev/video0`.
+    #[serde(rename = "device")]
+    device: PathBuf,
+    /// The subdevices upstream of the video device, from the farthest one such as a sensor.
+    #[serde(rename = "subdevs")]
+    subdevs: Vec<GstV4l2Subdev>,
+}
+
+impl GstV4l2Pipeline {
+    /// The name of the entity of the video device.
+    pub fn entity(&self) -> &str {
+        &self.entity
+    }
+
+    pub fn device(&self) -> &std::path::Path {
+        &self.device
+    }
+
+    pub fn subdevs(&self) -> &[GstV4l2Subdev] {
+        &self.subdevs
+    }
+
+    /// The properties to be set on `v4l2src`.
+    pub fn properties(&self) -> Vec<(&'static str, String)> {
+        vec![("device", self.device.display().to_string())]
+    }
+
+    /// The `v4l2src` element in the syntax of `gst-launch-1.0`.
+    pub fn launch(&self) -> String {
+        format!("v4l2src device={}", self.device.display())
+    }
+}
+
+impl MediaTopology {
+    /// The entities feeding `entity` through enabled links, from the nearest ones.
+    fn upstream(&self, entity: EntityId) -> Vec<&MediaEntity> {
+        let mut visited = BTreeSet::from([entity]);
+        let mut queue = VecDeque::from([entity]);
+        let mut upstream = vec![];
+        while let Some(sink) = queue.pop_front() {
+            let sources = self
+                .links_slice()
+                .iter()
+                .filter_map(|link| self.link_desc(link))
+                .filter(|desc| {
+                    desc.sink().id() == sink && desc.flags().contains(MediaLinkFlags::Enabled)
+                })
+                .map(|desc| desc.source().id());
+            for source in sources {
+                if visited.insert(source) {
+                    queue.push_back(source);
+                    upstream.extend(self.entities_slice().iter().find(|e| e.id() == source));
+                }
+            }
+        }
+        upstream
+    }
+
+    fn gst_v4l2_pipeline_with<F>(
+        &self,
+        entity: &MediaEntity,
+        dev_path: F,
+    ) -> Option<GstV4l2Pipeline>
+    where
+        F: Fn(MediaIntfDevnode) -> Option<PathBuf>,
+    {
+        let device = dev_path(self.interface_devnode(entity.id(), MediaInterfaceType::V4LVideo)?)?;
+        let mut subdevs: Vec<GstV4l2Subdev> = self
+            .upstream(entity.id())
+            .into_iter()
+            .filter_map(|upstream| {
+                let devnode =
+                    self.interface_devnode(upstream.id(), MediaInterfaceType::V4LSubdev)?;
+                Some(GstV4l2Subdev {
+                    entity: upstream.name().to_string(),
+                    device: dev_path(devnode)?,
+                })
+            })
+            .collect();
+        subdevs.reverse();
+        Some(GstV4l2Pipeline {
+            entity: entity.name().to_string(),
+            device,
+            subdevs,
+        })
+    }
+
+    /// Map the video device of the entity `name` and the subdevices feeding it to the properties of GStreamer elements.
+    ///
+    /// # Details
+    /// The device files are resolved through sysfs by [`MediaIntfDevnode::dev_path`],
+    /// and subdevices whose device files are not resolved are left out.
+    ///
+    /// # Returns
+    /// `None` if there is no such entity, or it has no video device resolved.
+    pub fn gst_v4l2_pipeline(&self, name: &str) -> Option<GstV4l2Pipeline> {
+        let entity = self.entities_slice().iter().find(|e| e.name() == name)?;
+        self.gst_v4l2_pipeline_with(entity, |devnode| devnode.dev_path())
+    }
+
+    /// Map every video device to the properties of GStreamer elements, as [`gst_v4l2_pipeline`][Self::gst_v4l2_pipeline].
+    pub fn gst_v4l2_pipelines(&self) -> Vec<GstV4l2Pipeline> {
+        self.entities_slice()
+            .iter()
+            .filter_map(|entity| self.gst_v4l2_pipeline_with(entity, |devnode| devnode.dev_path()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MediaEntityFunctions, MediaPadFlags, MockTopologyBuilder};
+
+    #[test]
+    fn maps_video_device_and_upstream_subdevs() {
+        let mut builder = MockTopologyBuilder::new();
+        let (sensor, sensor_pads) = builder.entity(
+            "imx219 10-0010",
+            MediaEntityFunctions::CAMSensor,
+            &[MediaPadFlags::Source],
+        );
+        let (csi, csi_pads) = builder.entity(
+            "csi",
+            MediaEntityFunctions::VIDIFBridge,
+            &[MediaPadFlags::Sink, MediaPadFlags::Source],
+        );
+        let (video, video_pads) =
+            builder.entity("video", MediaEntityFunctions::IoV4L, &[MediaPadFlags::Sink]);
+        builder.link(sensor_pads[0], csi_pads[0], MediaLinkFlags::Enabled);
+        builder.link(csi_pads[1], video_pads[0], MediaLinkFlags::Enabled);
+        let subdev = |minor| MediaIntfDevnode { major: 81, minor };
+        builder.interface(MediaInterfaceType::V4LSubdev, subdev(1), sensor);
+        builder.interface(MediaInterfaceType::V4LSubdev, subdev(2), csi);
+        builder.interface(MediaInterfaceType::V4LVideo, subdev(0), video);
+        let topology = builder.build();
+
+        let dev_path = |devnode: MediaIntfDevnode| match devnode.minor {
+            0 => Some(PathBuf::from("/dev/video0")),
+            minor => Some(PathBuf::from(format!("/dev/v4l-subdev{}", minor - 1))),
+        };
+        let video = &topology.entities_slice()[2];
+        let pipeline = topology.gst_v4l2_pipeline_with(video, dev_path).unwrap();
+        assert_eq!(pipeline.launch(), "v4l2src device=/dev/video0");
+        let subdevs: Vec<_> = pipeline
+            .subdevs()
+            .iter()
+            .map(|subdev| (subdev.entity(), subdev.properties()))
+            .collect();
+        assert_eq!(
+            subdevs,
+            [
+                (
+                    "imx219 10-0010",
+                    vec![("device", "/dev/v4l-subdev0".to_string())]
+                ),
+                ("csi", vec![("device", "/dev/v4l-subdev1".to_string())]),
+            ]
+        );
+
+        let sensor = &topology.entities_slice()[0];
+        assert!(topology.gst_v4l2_pipeline_with(sensor, dev_path).is_none());
+    }
+}
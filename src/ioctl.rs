@@ -1,29 +1,139 @@
+/// Build an ioctl request as `_IOC` of the kernel does, for the ioctls of the headers not covered by [`linux_media_sys`].
+#[cfg(any(feature = "subdev", feature = "dvb"))]
+pub(crate) const fn ioc(dir: u32, r#type: u8, nr: u32, size: usize) -> libc::c_ulong {
+    use crate::raw as media;
+    ((dir as libc::c_ulong) << media::_IOC_DIRSHIFT)
+        | ((r#type as libc::c_ulong) << media::_IOC_TYPESHIFT)
+        | ((nr as libc::c_ulong) << media::_IOC_NRSHIFT)
+        | ((size as libc::c_ulong) << media::_IOC_SIZESHIFT)
+}
+
+/// The type of the request parameter of `libc::ioctl`, which is `c_int` on musl and `c_ulong` on the other libcs.
+#[cfg(all(target_os = "linux", not(feature = "rustix"), target_env = "musl"))]
+pub(crate) type IoctlRequest = libc::c_int;
+/// The type of the request parameter of `libc::ioctl`, which is `c_int` on musl and `c_ulong` on the other libcs.
+#[cfg(all(target_os = "linux", not(feature = "rustix"), not(target_env = "musl")))]
+pub(crate) type IoctlRequest = libc::c_ulong;
+
+/// Convert a request built as `c_ulong`, such as the ones of [`linux_media_sys`], to the type `libc::ioctl` takes.
+///
+/// # Details
+/// Requests are 32 bits long on every architecture, so the bits are kept as they are even where the type is `c_int`,
+/// for which requests with the direction bits set become negative as the C headers define them.
+/// A request not fitting in 32 bits is rejected instead of being truncated silently, at compile time for constants.
+#[cfg(all(target_os = "linux", not(feature = "rustix")))]
+pub(crate) const fn request(kind: libc::c_ulong) -> IoctlRequest {
+    assert!(
+        kind <= u32::MAX as libc::c_ulong,
+        "ioctl requests must fit in 32 bits"
+    );
+    kind as u32 as IoctlRequest
+}
+
 /// A wrapper macro of ioctl.
 /// If the calling ioctl returned -1, it returns [`crate::error::Error`] corresponding to the errno.
-#[macro_export]
+///
+/// # Details
+/// The request is converted with `request` of this module to the type taken by `libc::ioctl` of the target.
+#[cfg(all(target_os = "linux", not(feature = "rustix")))]
 macro_rules! ioctl {
     ($fd:expr, $kind:expr) => {{
-        let ret = libc::ioctl($fd.as_raw_fd(), $kind);
-        if ret != 0 {
-            Err(crate::error::Error::ioctl_error(
+        const REQUEST: $crate::ioctl::IoctlRequest = $crate::ioctl::request($kind);
+        let ret = libc::ioctl($fd.as_raw_fd(), REQUEST);
+        let result = if ret != 0 {
+            Err($crate::error::Error::ioctl_error(
                 $fd.as_raw_fd(),
                 std::io::Error::last_os_error().raw_os_error().unwrap(),
                 $kind,
             ))
         } else {
             Ok(())
-        }
+        };
+        $crate::logging::trace!("ioctl {:#x} on fd {}: {:?}", $kind, $fd.as_raw_fd(), result);
+        result
     }};
     ($fd:expr, $kind:expr, $arg:expr) => {{
-        let ret = libc::ioctl($fd.as_raw_fd(), $kind, $arg);
-        if ret != 0 {
-            Err(crate::error::Error::ioctl_error(
+        const REQUEST: $crate::ioctl::IoctlRequest = $crate::ioctl::request($kind);
+        let ret = libc::ioctl($fd.as_raw_fd(), REQUEST, $arg);
+        let result = if ret != 0 {
+            Err($crate::error::Error::ioctl_error(
                 $fd.as_raw_fd(),
                 std::io::Error::last_os_error().raw_os_error().unwrap(),
                 $kind,
             ))
         } else {
             Ok(())
-        }
+        };
+        $crate::logging::trace!("ioctl {:#x} on fd {}: {:?}", $kind, $fd.as_raw_fd(), result);
+        result
+    }};
+}
+
+/// A wrapper macro of ioctl, issuing it via rustix.
+/// If the calling ioctl failed, it returns [`crate::error::Error`] corresponding to the errno.
+///
+/// # Details
+/// The request must be a constant, since rustix takes opcodes as const generic parameters.
+#[cfg(all(target_os = "linux", feature = "rustix"))]
+macro_rules! ioctl {
+    ($fd:expr, $kind:expr) => {{
+        use rustix::ioctl::{BadOpcode, NoArg, RawOpcode};
+        let errno = |errno: rustix::io::Errno| errno.raw_os_error();
+        let result = rustix::ioctl::ioctl(&$fd, NoArg::<BadOpcode<{ $kind as RawOpcode }>>::new())
+            .map_err(|err| $crate::error::Error::ioctl_error($fd.as_raw_fd(), errno(err), $kind));
+        $crate::logging::trace!("ioctl {:#x} on fd {}: {:?}", $kind, $fd.as_raw_fd(), result);
+        result
+    }};
+    ($fd:expr, $kind:expr, $arg:expr) => {{
+        use rustix::ioctl::{BadOpcode, RawOpcode, Updater};
+        let errno = |errno: rustix::io::Errno| errno.raw_os_error();
+        let result = rustix::ioctl::ioctl(
+            &$fd,
+            Updater::<BadOpcode<{ $kind as RawOpcode }>, _>::new($arg),
+        )
+        .map_err(|err| $crate::error::Error::ioctl_error($fd.as_raw_fd(), errno(err), $kind));
+        $crate::logging::trace!("ioctl {:#x} on fd {}: {:?}", $kind, $fd.as_raw_fd(), result);
+        result
     }};
 }
+
+/// Fail an ioctl which is not issued on targets other than Linux.
+///
+/// # Safety
+/// Nothing is unsafe here. It is only marked to be called in the `unsafe` blocks written for the real ioctls.
+#[cfg(not(target_os = "linux"))]
+pub(crate) unsafe fn unsupported(
+    fd: std::os::fd::RawFd,
+    kind: libc::c_ulong,
+) -> crate::error::Result<()> {
+    Err(crate::error::Error::ioctl_error(fd, libc::ENOTTY, kind))
+}
+
+/// A stand-in of the ioctl macro on targets other than Linux, where the media controller API does not exist.
+/// It fails with [`NotSupportedIoctl`][crate::error::Error::NotSupportedIoctl] without issuing anything.
+#[cfg(not(target_os = "linux"))]
+macro_rules! ioctl {
+    ($fd:expr, $kind:expr) => {{
+        $crate::ioctl::unsupported($fd.as_raw_fd(), $kind)
+    }};
+    ($fd:expr, $kind:expr, $arg:expr) => {{
+        let _ = $arg;
+        $crate::ioctl::unsupported($fd.as_raw_fd(), $kind)
+    }};
+}
+
+pub(crate) use ioctl;
+
+#[cfg(all(test, target_os = "linux", not(feature = "rustix")))]
+mod test {
+    use super::*;
+    use crate::raw as media;
+
+    #[test]
+    fn requests_keep_their_bits() {
+        // _IOWR, whose direction bits make it negative as c_int
+        let kind = media::MEDIA_IOC_G_TOPOLOGY;
+        assert_eq!(request(kind) as u32, kind as u32);
+        assert_eq!(request(media::MEDIA_REQUEST_IOC_QUEUE) as u32, 0x7c80);
+    }
+}
@@ -0,0 +1,156 @@
+//! The ioctls of the media controller API as typed functions on file descriptors.
+//!
+//! Each function issues its ioctl through the [`ioctl!`][crate::ioctl::ioctl!] macro, retries it while it is interrupted by a signal,
+//! and maps the errno to [`Error`], so that the unsafe code and the error handling of the ioctls are in one place.
+//! [`MediaBackend`][crate::MediaBackend] for file descriptors and [`Request`][crate::Request] are built on them.
+
+use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+
+use crate::error::{Error, Result};
+use crate::ioctl::ioctl;
+use crate::media_backend::TopologyArrays;
+use crate::raw as media;
+use crate::MediaTopologyCounts;
+
+/// Call `ioctl` again as long as it fails with `EINTR`.
+fn retry<T, F>(mut ioctl: F) -> Result<T>
+where
+    F: FnMut() -> Result<T>,
+{
+    loop {
+        match ioctl() {
+            Err(err) if err.errno() == Some(libc::EINTR) => continue,
+            result => return result,
+        }
+    }
+}
+
+/// The number and the pointer of an array to be filled by `MEDIA_IOC_G_TOPOLOGY`.
+fn array<T>(array: Option<&mut [T]>) -> (u32, media::__u64) {
+    match array {
+        Some(array) => (array.len() as u32, array.as_mut_ptr() as media::__u64),
+        None => (0, 0),
+    }
+}
+
+/// `MEDIA_IOC_DEVICE_INFO`
+pub(crate) fn device_info(fd: BorrowedFd<'_>) -> Result<media::media_device_info> {
+    let mut info: media::media_device_info = unsafe { std::mem::zeroed() };
+    retry(|| unsafe { ioctl!(fd, media::MEDIA_IOC_DEVICE_INFO, &mut info) })?;
+    Ok(info)
+}
+
+/// `MEDIA_IOC_G_TOPOLOGY`, filling the arrays requested in `arrays`.
+pub(crate) fn g_topology(
+    fd: BorrowedFd<'_>,
+    arrays: TopologyArrays<'_>,
+) -> Result<MediaTopologyCounts> {
+    let mut topology: media::media_v2_topology = unsafe { std::mem::zeroed() };
+    (topology.num_entities, topology.ptr_entities) = array(arrays.entities);
+    (topology.num_interfaces, topology.ptr_interfaces) = array(arrays.interfaces);
+    (topology.num_pads, topology.ptr_pads) = array(arrays.pads);
+    (topology.num_links, topology.ptr_links) = array(arrays.links);
+    retry(|| unsafe { ioctl!(fd, media::MEDIA_IOC_G_TOPOLOGY, &mut topology) })?;
+    Ok(MediaTopologyCounts {
+        version: topology.topology_version,
+        entities: topology.num_entities,
+        interfaces: topology.num_interfaces,
+        pads: topology.num_pads,
+        links: topology.num_links,
+    })
+}
+
+/// `MEDIA_IOC_ENUM_ENTITIES`
+pub(crate) fn enum_entities(fd: BorrowedFd<'_>, desc: &mut media::media_entity_desc) -> Result<()> {
+    retry(|| unsafe { ioctl!(fd, media::MEDIA_IOC_ENUM_ENTITIES, &mut *desc) })
+}
+
+/// `MEDIA_IOC_ENUM_LINKS`, filling `pads` and `links` of `entity`.
+///
+/// # Safety
+/// `pads` and `links` must be long enough for the pads and the outbound links of `entity`,
+/// see [`MediaBackend::enum_links`][crate::MediaBackend::enum_links].
+pub(crate) unsafe fn enum_links(
+    fd: BorrowedFd<'_>,
+    entity: u32,
+    pads: &mut [media::media_pad_desc],
+    links: &mut [media::media_link_desc],
+) -> Result<()> {
+    let mut enum_links: media::media_links_enum = unsafe { std::mem::zeroed() };
+    enum_links.entity = entity;
+    enum_links.pads = pads.as_mut_ptr();
+    enum_links.links = links.as_mut_ptr();
+    retry(|| ioctl!(fd, media::MEDIA_IOC_ENUM_LINKS, &mut enum_links))
+}
+
+/// `MEDIA_IOC_SETUP_LINK`
+pub(crate) fn setup_link(fd: BorrowedFd<'_>, link: &mut media::media_link_desc) -> Result<()> {
+    retry(|| unsafe { ioctl!(fd, media::MEDIA_IOC_SETUP_LINK, &mut *link) })
+}
+
+/// `MEDIA_IOC_REQUEST_ALLOC`
+pub(crate) fn request_alloc(fd: BorrowedFd<'_>) -> Result<OwnedFd> {
+    let mut request_fd: libc::c_int = -1;
+    retry(|| unsafe { ioctl!(fd, media::MEDIA_IOC_REQUEST_ALLOC, &mut request_fd) })?;
+    Ok(unsafe { OwnedFd::from_raw_fd(request_fd) })
+}
+
+/// `MEDIA_REQUEST_IOC_QUEUE` on the request `fd`, with the errors of queueing mapped to their own variants.
+pub(crate) fn request_queue(fd: BorrowedFd<'_>) -> Result<()> {
+    use Error::*;
+    let api = media::MEDIA_REQUEST_IOC_QUEUE;
+    retry(|| unsafe { ioctl!(fd, media::MEDIA_REQUEST_IOC_QUEUE) }).map_err(|err| {
+        let fd = fd.as_raw_fd();
+        if let Ioctl { ref code, .. } = err {
+            match code.raw_os_error() {
+                Some(code @ libc::EBUSY) => RequestIsAlreadyQueued { fd, code, api },
+                Some(code @ libc::ENOENT) => RequestNotContainBuffers { fd, code, api },
+                Some(code @ libc::ENOMEM) => OutOfMemory { fd, code, api },
+                Some(code @ libc::EINVAL) => RequestHasInvalidData { fd, code, api },
+                Some(code @ libc::EIO) => HardwareBadState { fd, code, api },
+                _ => err,
+            }
+        } else {
+            err
+        }
+    })
+}
+
+/// `MEDIA_REQUEST_IOC_REINIT` on the request `fd`.
+pub(crate) fn request_reinit(fd: BorrowedFd<'_>) -> Result<()> {
+    retry(|| unsafe { ioctl!(fd, media::MEDIA_REQUEST_IOC_REINIT) })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs::File;
+    use std::os::fd::AsFd;
+
+    #[test]
+    fn retries_while_interrupted() {
+        let mut calls = 0;
+        let result = retry(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(Error::ioctl_error(
+                    -1,
+                    libc::EINTR,
+                    media::MEDIA_IOC_DEVICE_INFO,
+                ))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+    }
+
+    #[test]
+    fn fails_on_files_other_than_media_devices() {
+        let file = File::open("/dev/null").unwrap();
+        let err = device_info(file.as_fd()).unwrap_err();
+        assert!(matches!(err, Error::NotSupportedIoctl { .. }), "{:?}", err);
+        let err = request_queue(file.as_fd()).unwrap_err();
+        assert_eq!(err.errno(), Some(libc::ENOTTY));
+    }
+}
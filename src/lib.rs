@@ -1,35 +1,187 @@
+#[cfg(feature = "alsa")]
+pub mod alsa;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod collections;
+pub mod device_driver;
+#[cfg(feature = "dvb")]
+pub mod dvb;
+pub mod entity_name;
 pub mod error;
+#[cfg(any(test, feature = "mock"))]
+pub mod fault_backend;
+pub mod firmware_node;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+#[cfg(feature = "gstreamer")]
+pub mod gstreamer;
 mod ioctl;
+mod ioctls;
+mod logging;
 pub mod media;
+pub mod media_api_capabilities;
+pub mod media_backend;
+pub mod media_bus_info;
+pub mod media_device_enumerator;
 pub mod media_device_info;
 pub mod media_entity;
 pub mod media_entity_desc;
+pub mod media_entity_desc_cache;
+pub mod media_hw_revision;
+pub mod media_info_handle;
 pub mod media_interface;
 pub mod media_interface_type;
 pub mod media_intf_devnode;
+pub mod media_ioctl_support;
 pub mod media_link;
 pub mod media_link_desc;
 pub mod media_link_enum;
+pub mod media_link_spec;
+pub mod media_link_transaction;
 pub mod media_pad;
 pub mod media_pad_desc;
+pub mod media_pipeline_config;
+pub mod media_pipeline_description;
+pub mod media_raw_topology;
 pub mod media_topology;
 pub mod media_topology_builder;
+pub mod media_topology_counts;
+pub mod media_topology_diff;
+pub mod media_topology_dot;
+pub mod media_topology_golden;
+mod media_topology_graph;
+pub mod media_topology_handle;
+mod media_topology_legacy;
+mod media_topology_mermaid;
+pub mod media_topology_stats;
+#[cfg(feature = "render")]
+mod media_topology_svg;
+pub mod media_watcher;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_backend;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_devices;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock_topology_builder;
+pub mod name_interner;
+pub mod parent_device;
+pub mod permission;
+#[cfg(feature = "arbitrary")]
+pub mod raw_arbitrary;
 pub mod request;
+#[cfg(feature = "schemars")]
+mod schema;
+#[cfg(feature = "subdev")]
+pub mod subdev;
+#[cfg(feature = "subdev")]
+pub mod subdev_control;
+pub mod syscall_policy;
+pub mod system_media_graph;
+pub mod tape_backend;
+#[cfg(feature = "test-harness")]
+pub mod test_harness;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+#[cfg(feature = "udev")]
+pub mod udev;
 pub mod version;
 
+#[cfg(feature = "alsa")]
+pub use alsa::*;
+pub use device_driver::*;
+#[cfg(feature = "dvb")]
+pub use dvb::{DvbFrontend, DvbFrontendCaps, DvbFrontendInfo, DvbFrontendType};
+pub use entity_name::*;
+#[cfg(any(test, feature = "mock"))]
+pub use fault_backend::*;
+pub use firmware_node::*;
+#[cfg(feature = "fixtures")]
+pub use fixtures::*;
+#[cfg(feature = "gstreamer")]
+pub use gstreamer::*;
 pub use media::*;
+pub use media_api_capabilities::*;
+pub use media_backend::*;
+pub use media_bus_info::*;
+pub use media_device_enumerator::*;
 pub use media_device_info::*;
 pub use media_entity::*;
 pub use media_entity_desc::*;
+pub use media_entity_desc_cache::*;
+pub use media_hw_revision::*;
+pub use media_info_handle::*;
 pub use media_interface::*;
 pub use media_interface_type::*;
 pub use media_intf_devnode::*;
+pub use media_ioctl_support::*;
 pub use media_link::*;
 pub use media_link_desc::*;
 pub use media_link_enum::*;
+pub use media_link_spec::*;
+pub use media_link_transaction::*;
 pub use media_pad::*;
 pub use media_pad_desc::*;
+pub use media_pipeline_config::*;
+pub use media_pipeline_description::*;
+pub use media_raw_topology::*;
 pub use media_topology::*;
 pub use media_topology_builder::*;
+pub use media_topology_counts::*;
+pub use media_topology_diff::*;
+pub use media_topology_dot::*;
+pub use media_topology_golden::*;
+pub use media_topology_handle::*;
+pub use media_topology_stats::*;
+pub use media_watcher::*;
+#[cfg(feature = "metrics")]
+pub use metrics::{MetricsSnapshot, RequestMetrics};
+#[cfg(any(test, feature = "mock"))]
+pub use mock_backend::*;
+#[cfg(any(test, feature = "mock"))]
+pub use mock_devices::*;
+#[cfg(any(test, feature = "mock"))]
+pub use mock_topology_builder::*;
+pub use name_interner::*;
+pub use parent_device::*;
+pub use permission::*;
+#[cfg(feature = "arbitrary")]
+pub use raw_arbitrary::{Raw, RawStruct};
 pub use request::*;
+#[cfg(feature = "subdev")]
+pub use subdev::{
+    MbusFrameFormat, Rect, SelectionFlags, SelectionTarget, Subdev, SubdevRoute, SubdevWhich,
+};
+#[cfg(feature = "subdev")]
+pub use subdev_control::{cid, ControlValue, SubdevControl};
+pub use syscall_policy::*;
+pub use system_media_graph::*;
+pub use tape_backend::*;
+#[cfg(feature = "test-harness")]
+pub use test_harness::*;
+#[cfg(feature = "udev")]
+pub use udev::*;
 pub use version::*;
+
+/// The raw bindings of the Linux Media API this crate is built on.
+///
+/// Use them together with the `to_raw`/`from_raw` conversions of the wrapper types to issue ioctls
+/// not wrapped by this crate, without depending on a matching version of `linux-media-sys` separately.
+#[cfg(target_os = "linux")]
+pub use linux_media_sys as raw;
+
+/// The raw structs and constants of the Linux Media API, declared without `linux-media-sys` on targets other than Linux.
+///
+/// # Details
+/// The types and the serialized topologies can be handled on any target, e.g. by tools rendering or comparing topologies
+/// captured on devices. Every ioctl fails with [`NotSupportedIoctl`][error::Error::NotSupportedIoctl] there.
+#[cfg(not(target_os = "linux"))]
+#[path = "raw_stub.rs"]
+pub mod raw;
+
+/// The stub compiled on Linux as well, for testing it is the same as `linux-media-sys`.
+#[cfg(all(test, target_os = "linux"))]
+#[allow(dead_code)]
+#[path = "raw_stub.rs"]
+mod raw_stub;
@@ -0,0 +1,38 @@
+//! Log records of ioctl calls, link changes and topology refreshes, emitted through the `log` crate with the `log` feature.
+//!
+//! Without the feature, the macros compile to nothing but still check their arguments.
+
+#[cfg(feature = "log")]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        ::log::debug!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+#[cfg(feature = "log")]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        ::log::trace!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! trace {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+pub(crate) use debug;
+pub(crate) use trace;
@@ -1,19 +1,26 @@
 use std::fs::OpenOptions;
-use std::os::fd::{AsFd, BorrowedFd, OwnedFd};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
 use crate::error;
+use crate::MediaApiCapabilities;
+use crate::MediaBackend;
 use crate::MediaDeviceInfo;
+use crate::MediaLinksEnum;
 use crate::MediaTopology;
 use crate::Request;
 use crate::Version;
 
+/// A media device, accessed through a [`MediaBackend`] which is the file descriptor of the device by default.
 #[derive(Debug)]
-pub struct Media {
+pub struct Media<B = OwnedFd> {
     info: MediaDeviceInfo,
     path: PathBuf,
-    fd: OwnedFd,
+    backend: B,
+    /// The topology returned by [`cached_topology`][Self::cached_topology] last time.
+    topology: Mutex<Option<Arc<MediaTopology>>>,
 }
 
 impl Media {
@@ -29,8 +36,70 @@ impl Media {
             .open(&path)
             .map_err(|err| error::trap_io_error(err, path.clone()))?
             .into();
-        let info = MediaDeviceInfo::from_fd(fd.as_fd())?;
-        Ok(Self { info, path, fd })
+        Self::with_backend(path, fd)
+    }
+}
+
+impl<B> Media<B>
+where
+    B: AsFd,
+{
+    pub fn device_fd(&self) -> BorrowedFd<'_> {
+        self.backend.as_fd()
+    }
+}
+
+impl<B> AsFd for Media<B>
+where
+    B: AsFd,
+{
+    /// The file descriptor of the device, e.g. to wait for events with `poll` or `epoll`.
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.device_fd()
+    }
+}
+
+impl<B> AsRawFd for Media<B>
+where
+    B: AsFd,
+{
+    fn as_raw_fd(&self) -> RawFd {
+        self.device_fd().as_raw_fd()
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Access a media device through `backend` instead of opening a device file.
+    ///
+    /// # Details
+    /// The device information is fetched from `backend`, and `path` is only recorded as the [`path`][Self::path] of the device.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::fs::File;
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(file) = File::options().read(true).write(true).open("/dev/media0") {
+    ///     let media = Media::with_backend("/dev/media0", file)?;
+    ///     println!("{}", media.info().driver());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_backend<P>(path: P, backend: B) -> error::Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let info = MediaDeviceInfo::from_fd(&backend)?;
+        Ok(Self {
+            info,
+            path: path.as_ref().to_path_buf(),
+            backend,
+            topology: Mutex::new(None),
+        })
     }
 
     pub fn info(&self) -> &MediaDeviceInfo {
@@ -45,15 +114,76 @@ impl Media {
         &self.path
     }
 
-    pub fn device_fd(&self) -> BorrowedFd<'_> {
-        self.fd.as_fd()
+    /// The fields and features of the media controller API available on the device, including the ones [probed][MediaApiCapabilities::probe].
+    pub fn capabilities(&self) -> MediaApiCapabilities {
+        self.info.capabilities().probe(&self.backend)
+    }
+
+    /// The backend through which the device is accessed.
+    pub fn backend(&self) -> &B {
+        &self.backend
     }
 
-    pub fn new_request(&self) -> error::Result<Request<'_>> {
-        Request::new(self.fd.as_fd())
+    pub fn new_request(&self) -> error::Result<Request<&B>> {
+        Request::new(&self.backend)
     }
 
     pub fn new_topology(&self) -> error::Result<MediaTopology> {
-        MediaTopology::from_fd(self.info(), self.device_fd())
+        MediaTopology::from_fd(self.info(), &self.backend)
+    }
+
+    /// Get the topology, reusing the one fetched last time if the topology has not changed since.
+    ///
+    /// # Details
+    /// The topology version is probed with a single `MEDIA_IOC_G_TOPOLOGY` without any array,
+    /// and the whole topology is fetched only if the version differs from the cached one.
+    /// Nothing is cached until this method is called.
+    ///
+    /// The topology is fetched every time without being cached if `MEDIA_IOC_G_TOPOLOGY` is not known to be available
+    /// by [`has_topology`][MediaApiCapabilities::has_topology] or turns out to be unsupported,
+    /// as the topologies synthesized by [`MediaTopology::from_legacy_fd`] have no version to compare.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let first = media.cached_topology()?;
+    ///     let second = media.cached_topology()?;
+    ///     assert_eq!(first, second);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn cached_topology(&self) -> error::Result<Arc<MediaTopology>> {
+        if !self.info.capabilities().has_topology() {
+            return self.new_topology().map(Arc::new);
+        }
+        let version = match self.topology_counts() {
+            Ok(counts) => counts.version,
+            Err(error::Error::NotSupportedIoctl { .. }) => {
+                return self.new_topology().map(Arc::new);
+            }
+            Err(err) => return Err(err),
+        };
+        let mut cache = self.topology.lock().unwrap_or_else(|err| err.into_inner());
+        match &*cache {
+            Some(topology) if topology.version() == version => Ok(topology.clone()),
+            _ => {
+                let topology = Arc::new(self.new_topology()?);
+                *cache = Some(topology.clone());
+                Ok(topology)
+            }
+        }
+    }
+
+    /// Drop the topology cached by [`cached_topology`][Self::cached_topology].
+    pub fn clear_topology_cache(&self) {
+        *self.topology.lock().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+
+    /// Enumerate pads and links of every entity with the legacy `MEDIA_IOC_ENUM_LINKS` API.
+    pub fn links_enums(&self) -> error::Result<Vec<MediaLinksEnum>> {
+        MediaLinksEnum::all(&self.backend)
     }
 }
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error;
+use crate::raw as media;
+use crate::version::Version;
+use crate::MediaBackend;
+use crate::MediaDeviceInfo;
+
+/// Fields of the media controller API available on a device, which depend on its media API version.
+///
+/// # Details
+/// Computed once from [`MediaDeviceInfo::media_version`][crate::MediaDeviceInfo::media_version]
+/// instead of testing the version for every object converted.
+/// Conversions taking a capabilities also accept a [`Version`], which is converted with [`MediaApiCapabilities::new`].
+/// Support for requests is not implied by the version, and is only known once [`probe`][MediaApiCapabilities::probe]d on the device.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MediaApiCapabilities {
+    #[serde(rename = "entity_flags")]
+    entity_flags: bool,
+    #[serde(rename = "pad_index")]
+    pad_index: bool,
+    #[serde(rename = "ancillary_links")]
+    ancillary_links: bool,
+    #[serde(rename = "topology")]
+    topology: bool,
+    #[serde(rename = "requests")]
+    requests: Option<bool>,
+}
+
+impl MediaApiCapabilities {
+    pub fn new(media_version: Version) -> Self {
+        let version: u32 = media_version.into();
+        Self {
+            entity_flags: media::MEDIA_V2_ENTITY_HAS_FLAGS(version.into()),
+            pad_index: media::MEDIA_V2_PAD_HAS_INDEX(version.into()),
+            // MEDIA_LNK_FL_ANCILLARY_LINK was added in Linux 5.17
+            ancillary_links: media_version.at_least(5, 17, 0),
+            // older kernels report 0.1.0 whether they have MEDIA_IOC_G_TOPOLOGY or not
+            topology: media_version.at_least(4, 19, 0),
+            requests: None,
+        }
+    }
+
+    /// Probe the features of the device which are not implied by its media API version.
+    ///
+    /// # Details
+    /// Support for requests is probed by allocating a request with `MEDIA_IOC_REQUEST_ALLOC`, which is freed immediately.
+    /// It is left unknown if the allocation fails for a reason other than the ioctl being unsupported.
+    pub fn probe<B>(self, fd: B) -> Self
+    where
+        B: MediaBackend,
+    {
+        let requests = match fd.request_alloc() {
+            Ok(_) => Some(true),
+            Err(error::Error::NotSupportedIoctl { .. }) => Some(false),
+            Err(_) => self.requests,
+        };
+        Self { requests, ..self }
+    }
+
+    /// Whether `media_v2_entity` reports the entity flags.
+    pub fn has_entity_flags(&self) -> bool {
+        self.entity_flags
+    }
+
+    /// Whether `media_v2_pad` reports the pad index.
+    pub fn has_pad_index(&self) -> bool {
+        self.pad_index
+    }
+
+    /// Whether links may be ancillary links between entities, such as a lens controller and its camera sensor.
+    pub fn supports_ancillary_links(&self) -> bool {
+        self.ancillary_links
+    }
+
+    /// Whether `MEDIA_IOC_G_TOPOLOGY` is known to be available on the device.
+    ///
+    /// # Details
+    /// False does not mean the ioctl is missing, as kernels before Linux 4.19 report 0.1.0 as the media API version.
+    pub fn has_topology(&self) -> bool {
+        self.topology
+    }
+
+    /// Whether the device supports requests, or `None` if it has not been [`probe`][MediaApiCapabilities::probe]d.
+    pub fn supports_requests(&self) -> Option<bool> {
+        self.requests
+    }
+}
+
+impl From<Version> for MediaApiCapabilities {
+    fn from(media_version: Version) -> Self {
+        Self::new(media_version)
+    }
+}
+
+impl From<&MediaDeviceInfo> for MediaApiCapabilities {
+    fn from(info: &MediaDeviceInfo) -> Self {
+        Self::new(info.media_version())
+    }
+}
@@ -0,0 +1,250 @@
+use std::fmt;
+use std::fs::File;
+use std::os::fd::{AsFd, BorrowedFd, OwnedFd};
+
+use crate::error::Result;
+use crate::ioctls;
+use crate::raw as media;
+use crate::MediaTopologyCounts;
+
+/// Arrays to be filled by [`MediaBackend::g_topology`].
+///
+/// # Details
+/// Arrays left `None` are not requested, and only the numbers of their objects are reported.
+/// An array shorter than the number of the objects makes the call fail with `ENOSPC`.
+#[derive(Default)]
+pub struct TopologyArrays<'a> {
+    pub entities: Option<&'a mut [media::media_v2_entity]>,
+    pub interfaces: Option<&'a mut [media::media_v2_interface]>,
+    pub pads: Option<&'a mut [media::media_v2_pad]>,
+    pub links: Option<&'a mut [media::media_v2_link]>,
+}
+
+impl fmt::Debug for TopologyArrays<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TopologyArrays")
+            .field("entities", &self.entities.as_ref().map(|xs| xs.len()))
+            .field("interfaces", &self.interfaces.as_ref().map(|xs| xs.len()))
+            .field("pads", &self.pads.as_ref().map(|xs| xs.len()))
+            .field("links", &self.links.as_ref().map(|xs| xs.len()))
+            .finish()
+    }
+}
+
+/// The operations on a media device, each of which corresponds to an ioctl of the media controller API.
+///
+/// # Details
+/// Everything in this crate talks to a device through this trait,
+/// which is implemented for file descriptors of media devices by calling ioctl.
+/// Implementing it for another type lets the code built on this crate run without hardware,
+/// by serving canned topologies or errors from [`Media::with_backend`][crate::Media::with_backend].
+///
+/// Errors are reported as the ioctl would, e.g. [`Error::DeviceIsBusy`][crate::error::Error::DeviceIsBusy] for `EBUSY`,
+/// which [`Error::ioctl_error`][crate::error::Error::ioctl_error] constructs from an errno.
+pub trait MediaBackend {
+    /// Get the information of the device, as `MEDIA_IOC_DEVICE_INFO`.
+    fn device_info(&self) -> Result<media::media_device_info>;
+
+    /// Fill `arrays` with the topology, as `MEDIA_IOC_G_TOPOLOGY`.
+    ///
+    /// # Returns
+    /// The version of the topology and the numbers of the objects in it, regardless of the arrays requested.
+    fn g_topology(&self, arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts>;
+
+    /// Fill `desc` with the entity of `desc.id`, as `MEDIA_IOC_ENUM_ENTITIES`.
+    ///
+    /// # Details
+    /// If the id is or'ed with [`MEDIA_ENT_ID_FLAG_NEXT`][media::MEDIA_ENT_ID_FLAG_NEXT],
+    /// the entity having the smallest id larger than it is filled.
+    fn enum_entities(&self, desc: &mut media::media_entity_desc) -> Result<()>;
+
+    /// Fill `pads` and `links` with the pads and the outbound links of `entity`, as `MEDIA_IOC_ENUM_LINKS`.
+    ///
+    /// # Safety
+    /// The kernel writes as many pads and links as the entity has, without knowing the lengths of the slices.
+    /// `pads` and `links` must be at least as long as the numbers of the pads and the outbound links of `entity`
+    /// reported by [`enum_entities`][Self::enum_entities], and the entity must not gain links in the meantime.
+    unsafe fn enum_links(
+        &self,
+        entity: u32,
+        pads: &mut [media::media_pad_desc],
+        links: &mut [media::media_link_desc],
+    ) -> Result<()>;
+
+    /// Change the flags of `link` to `link.flags`, as `MEDIA_IOC_SETUP_LINK`.
+    ///
+    /// # Details
+    /// `link` is updated to the state reported by the driver.
+    fn setup_link(&self, link: &mut media::media_link_desc) -> Result<()>;
+
+    /// Allocate a request, as `MEDIA_IOC_REQUEST_ALLOC`.
+    ///
+    /// # Returns
+    /// The file descriptor of the request.
+    fn request_alloc(&self) -> Result<OwnedFd>;
+}
+
+impl MediaBackend for BorrowedFd<'_> {
+    fn device_info(&self) -> Result<media::media_device_info> {
+        ioctls::device_info(*self)
+    }
+
+    fn g_topology(&self, arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts> {
+        ioctls::g_topology(*self, arrays)
+    }
+
+    fn enum_entities(&self, desc: &mut media::media_entity_desc) -> Result<()> {
+        ioctls::enum_entities(*self, desc)
+    }
+
+    unsafe fn enum_links(
+        &self,
+        entity: u32,
+        pads: &mut [media::media_pad_desc],
+        links: &mut [media::media_link_desc],
+    ) -> Result<()> {
+        ioctls::enum_links(*self, entity, pads, links)
+    }
+
+    fn setup_link(&self, link: &mut media::media_link_desc) -> Result<()> {
+        ioctls::setup_link(*self, link)
+    }
+
+    fn request_alloc(&self) -> Result<OwnedFd> {
+        ioctls::request_alloc(*self)
+    }
+}
+
+/// Implement [`MediaBackend`] by forwarding every operation to `$backend`, a backend borrowed from `$this`.
+macro_rules! forward_backend {
+    ([$($generics:tt)*] $ty:ty, |$this:ident| $backend:expr) => {
+        impl<$($generics)*> MediaBackend for $ty {
+            fn device_info(&self) -> Result<media::media_device_info> {
+                let $this = self;
+                $backend.device_info()
+            }
+
+            fn g_topology(&self, arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts> {
+                let $this = self;
+                $backend.g_topology(arrays)
+            }
+
+            fn enum_entities(&self, desc: &mut media::media_entity_desc) -> Result<()> {
+                let $this = self;
+                $backend.enum_entities(desc)
+            }
+
+            unsafe fn enum_links(
+                &self,
+                entity: u32,
+                pads: &mut [media::media_pad_desc],
+                links: &mut [media::media_link_desc],
+            ) -> Result<()> {
+                let $this = self;
+                $backend.enum_links(entity, pads, links)
+            }
+
+            fn setup_link(&self, link: &mut media::media_link_desc) -> Result<()> {
+                let $this = self;
+                $backend.setup_link(link)
+            }
+
+            fn request_alloc(&self) -> Result<OwnedFd> {
+                let $this = self;
+                $backend.request_alloc()
+            }
+        }
+    };
+}
+
+forward_backend!([] OwnedFd, |fd| fd.as_fd());
+forward_backend!([] File, |file| file.as_fd());
+forward_backend!([B: MediaBackend + ?Sized] &B, |backend| (**backend));
+forward_backend!([B: MediaBackend + ?Sized] std::sync::Arc<B>, |backend| (**backend));
+forward_backend!([B: MediaBackend + ?Sized] Box<B>, |backend| (**backend));
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Error;
+    use crate::{Media, Version};
+
+    /// A device having a single entity, which supports nothing but the topology.
+    struct SingleEntity;
+
+    impl MediaBackend for SingleEntity {
+        fn device_info(&self) -> Result<media::media_device_info> {
+            let mut info: media::media_device_info = unsafe { std::mem::zeroed() };
+            info.media_version = Version::new(6, 1, 0).into();
+            Ok(info)
+        }
+
+        fn g_topology(&self, arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts> {
+            if let Some(entities) = arrays.entities {
+                entities[0].id = 1;
+                entities[0].function = media::MEDIA_ENT_F_CAM_SENSOR;
+            }
+            Ok(MediaTopologyCounts {
+                version: 7,
+                entities: 1,
+                interfaces: 0,
+                pads: 0,
+                links: 0,
+            })
+        }
+
+        fn enum_entities(&self, _desc: &mut media::media_entity_desc) -> Result<()> {
+            Err(Error::ioctl_error(
+                -1,
+                libc::ENOTTY,
+                media::MEDIA_IOC_ENUM_ENTITIES,
+            ))
+        }
+
+        unsafe fn enum_links(
+            &self,
+            _entity: u32,
+            _pads: &mut [media::media_pad_desc],
+            _links: &mut [media::media_link_desc],
+        ) -> Result<()> {
+            Err(Error::ioctl_error(
+                -1,
+                libc::ENOTTY,
+                media::MEDIA_IOC_ENUM_LINKS,
+            ))
+        }
+
+        fn setup_link(&self, _link: &mut media::media_link_desc) -> Result<()> {
+            Err(Error::ioctl_error(
+                -1,
+                libc::ENOTTY,
+                media::MEDIA_IOC_SETUP_LINK,
+            ))
+        }
+
+        fn request_alloc(&self) -> Result<OwnedFd> {
+            Err(Error::ioctl_error(
+                -1,
+                libc::ENOTTY,
+                media::MEDIA_IOC_REQUEST_ALLOC,
+            ))
+        }
+    }
+
+    #[test]
+    fn media_runs_on_backend() {
+        let media = Media::with_backend("/dev/media-test", SingleEntity).unwrap();
+        assert_eq!(media.media_version(), Version::new(6, 1, 0));
+
+        let topology = media.new_topology().unwrap();
+        assert_eq!(topology.version(), 7);
+        assert_eq!(topology.entities_slice().len(), 1);
+        assert_eq!(topology.entities_slice()[0].id(), 1.into());
+        assert_eq!(media.topology_counts().unwrap().entities, 1);
+
+        assert!(matches!(
+            media.new_request(),
+            Err(Error::NotSupportedIoctl { .. })
+        ));
+    }
+}
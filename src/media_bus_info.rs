@@ -0,0 +1,199 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// The location of a media device, parsed from [`MediaDeviceInfo::bus_info`][crate::MediaDeviceInfo::bus_info].
+///
+/// # Details
+/// Drivers format `bus_info` by the bus their device is attached to, e.g. `platform:fe801000.csi`,
+/// `PCI:0000:02:00.0`, `usb-0000:00:14.0-8` or `I2C:4-001a`.
+/// Strings in none of those forms are kept as they are as [`BusInfo::Other`].
+/// The original string is formatted back by `Display`.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum BusInfo {
+    /// A platform device such like `platform:fe801000.csi`.
+    Platform {
+        #[serde(rename = "device")]
+        device: String,
+    },
+    /// A PCI device such like `PCI:0000:02:00.0`.
+    Pci {
+        #[serde(rename = "domain")]
+        domain: u16,
+        #[serde(rename = "bus")]
+        bus: u8,
+        #[serde(rename = "device")]
+        device: u8,
+        #[serde(rename = "function")]
+        function: u8,
+    },
+    /// A USB device such like `usb-0000:00:14.0-8`, formatted by `usb_make_path`.
+    Usb {
+        /// The name of the host controller, such like `0000:00:14.0` or `xhci-hcd.0.auto`.
+        #[serde(rename = "host")]
+        host: String,
+        /// The ports from the root hub to the device, such like `1.2`.
+        #[serde(rename = "port")]
+        port: String,
+    },
+    /// An I2C device such like `I2C:4-001a`.
+    I2c {
+        #[serde(rename = "adapter")]
+        adapter: u32,
+        #[serde(rename = "address")]
+        address: u16,
+    },
+    /// Any other format.
+    Other(String),
+}
+
+impl BusInfo {
+    /// Parse `bus_info` reported by a driver.
+    pub fn parse(bus_info: &str) -> Self {
+        Self::parse_known(bus_info).unwrap_or_else(|| BusInfo::Other(bus_info.to_string()))
+    }
+
+    fn parse_known(bus_info: &str) -> Option<Self> {
+        if let Some(device) = bus_info.strip_prefix("platform:") {
+            return Some(BusInfo::Platform {
+                device: device.to_string(),
+            });
+        }
+        if let Some(address) = bus_info.strip_prefix("PCI:") {
+            let (domain, rest) = address.split_once(':')?;
+            let (bus, rest) = rest.split_once(':')?;
+            let (device, function) = rest.split_once('.')?;
+            return Some(BusInfo::Pci {
+                domain: u16::from_str_radix(domain, 16).ok()?,
+                bus: u8::from_str_radix(bus, 16).ok()?,
+                device: u8::from_str_radix(device, 16).ok()?,
+                function: u8::from_str_radix(function, 16).ok()?,
+            });
+        }
+        if let Some(path) = bus_info.strip_prefix("usb-") {
+            // the name of the host controller may contain '-'
+            let (host, port) = path.rsplit_once('-')?;
+            return (!host.is_empty() && !port.is_empty()).then(|| BusInfo::Usb {
+                host: host.to_string(),
+                port: port.to_string(),
+            });
+        }
+        if let Some(client) = bus_info.strip_prefix("I2C:") {
+            let (adapter, address) = client.split_once('-')?;
+            return Some(BusInfo::I2c {
+                adapter: adapter.parse().ok()?,
+                address: u16::from_str_radix(address, 16).ok()?,
+            });
+        }
+        None
+    }
+
+    /// The name of the bus, `"platform"`, `"pci"`, `"usb"`, `"i2c"` or `"other"`.
+    pub fn bus(&self) -> &'static str {
+        match self {
+            BusInfo::Platform { .. } => "platform",
+            BusInfo::Pci { .. } => "pci",
+            BusInfo::Usb { .. } => "usb",
+            BusInfo::I2c { .. } => "i2c",
+            BusInfo::Other(_) => "other",
+        }
+    }
+
+    /// The name of the device on its bus as found in `/sys/bus/{bus}/devices`, e.g. `fe801000.csi`, `0000:02:00.0` or `4-001a`.
+    ///
+    /// # Details
+    /// `None` for USB devices, whose names in sysfs are numbered by the bus instead of the host controller, and for other formats.
+    pub fn device_name(&self) -> Option<String> {
+        match self {
+            BusInfo::Platform { device } => Some(device.clone()),
+            BusInfo::Pci {
+                domain,
+                bus,
+                device,
+                function,
+            } => Some(format!(
+                "{:04x}:{:02x}:{:02x}.{:x}",
+                domain, bus, device, function
+            )),
+            BusInfo::I2c { adapter, address } => Some(format!("{}-{:04x}", adapter, address)),
+            BusInfo::Usb { .. } | BusInfo::Other(_) => None,
+        }
+    }
+}
+
+impl From<&str> for BusInfo {
+    fn from(bus_info: &str) -> Self {
+        Self::parse(bus_info)
+    }
+}
+
+impl fmt::Display for BusInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BusInfo::Platform { device } => write!(f, "platform:{}", device),
+            BusInfo::Pci {
+                domain,
+                bus,
+                device,
+                function,
+            } => write!(
+                f,
+                "PCI:{:04x}:{:02x}:{:02x}.{:x}",
+                domain, bus, device, function
+            ),
+            BusInfo::Usb { host, port } => write!(f, "usb-{}-{}", host, port),
+            BusInfo::I2c { adapter, address } => write!(f, "I2C:{}-{:04x}", adapter, address),
+            BusInfo::Other(bus_info) => write!(f, "{}", bus_info),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bus_infos_are_parsed_and_formatted_back() {
+        let cases = [
+            (
+                "platform:fe801000.csi",
+                BusInfo::Platform {
+                    device: "fe801000.csi".to_string(),
+                },
+            ),
+            (
+                "PCI:0000:02:00.0",
+                BusInfo::Pci {
+                    domain: 0,
+                    bus: 2,
+                    device: 0,
+                    function: 0,
+                },
+            ),
+            (
+                "usb-xhci-hcd.0.auto-1.2",
+                BusInfo::Usb {
+                    host: "xhci-hcd.0.auto".to_string(),
+                    port: "1.2".to_string(),
+                },
+            ),
+            (
+                "I2C:4-001a",
+                BusInfo::I2c {
+                    adapter: 4,
+                    address: 0x1a,
+                },
+            ),
+            ("PCI:bogus", BusInfo::Other("PCI:bogus".to_string())),
+        ];
+        for (bus_info, parsed) in cases {
+            assert_eq!(BusInfo::parse(bus_info), parsed);
+            assert_eq!(parsed.to_string(), bus_info);
+        }
+        assert_eq!(
+            BusInfo::parse("PCI:0000:02:00.0").device_name().as_deref(),
+            Some("0000:02:00.0")
+        );
+    }
+}
@@ -0,0 +1,459 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::Media;
+use crate::MediaDeviceInfo;
+
+/// The directory where the kernel lists media devices.
+pub const SYSFS_MEDIA_DEVICES: &str = "/sys/bus/media/devices";
+
+/// Read a small sysfs attribute with a single `read`.
+///
+/// # Details
+/// [`fs::read_to_string`] queries the file size before reading, which is meaningless for sysfs attributes
+/// reporting a page size. Attributes are at most a page, so a page-sized buffer is read at once instead.
+fn read_small(path: &Path) -> io::Result<String> {
+    let mut buf = [0; 4096];
+    let len = fs::File::open(path)?.read(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf[..len]).into_owned())
+}
+
+/// A media device found by [`MediaDeviceEnumerator`].
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MediaDeviceNode {
+    /// `N` of `mediaN`.
+    #[serde(rename = "index")]
+    index: u32,
+    /// The device file such like `/dev/media0`.
+    #[serde(rename = "path")]
+    path: PathBuf,
+    /// The sysfs directory of the device such like `/sys/bus/media/devices/media0`.
+    #[serde(rename = "sysfs_path")]
+    sysfs_path: PathBuf,
+    /// The model name read from sysfs.
+    #[serde(rename = "model")]
+    model: Option<String>,
+}
+
+impl MediaDeviceNode {
+    /// Read the media device described in the sysfs directory `sysfs_path`.
+    ///
+    /// # Details
+    /// The device file name is taken from `DEVNAME` of the `uevent` file, falling back to the directory name.
+    pub fn from_sysfs<P>(sysfs_path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let sysfs_path = sysfs_path.as_ref().to_path_buf();
+        let name = sysfs_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let uevent = sysfs_path.join("uevent");
+        let devname = read_small(&uevent)
+            .map_err(|err| error::trap_io_error(err, uevent))?
+            .lines()
+            .find_map(|line| line.strip_prefix("DEVNAME="))
+            .map(str::to_string)
+            .unwrap_or(name);
+        let index = devname
+            .trim_start_matches(|c: char| !c.is_ascii_digit())
+            .parse()
+            .unwrap_or(u32::MAX);
+        let model = read_small(&sysfs_path.join("model"))
+            .ok()
+            .map(|model| model.trim_end().to_string());
+        Ok(Self {
+            index,
+            path: Path::new("/dev").join(devname),
+            sysfs_path,
+            model,
+        })
+    }
+
+    /// The device `/dev/mediaN` of the index `N`, as listed in [`/sys/bus/media/devices`][SYSFS_MEDIA_DEVICES].
+    #[cfg(any(test, feature = "mock"))]
+    pub(crate) fn with_index(index: u32, model: Option<String>) -> Self {
+        let name = format!("media{}", index);
+        Self {
+            index,
+            path: Path::new("/dev").join(&name),
+            sysfs_path: Path::new(SYSFS_MEDIA_DEVICES).join(name),
+            model,
+        }
+    }
+
+    /// `N` of `/dev/mediaN`.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The device file such like `/dev/media0`.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The sysfs directory of the device.
+    pub fn sysfs_path(&self) -> &Path {
+        &self.sysfs_path
+    }
+
+    /// The model name of the device, which equals to [`MediaDeviceInfo::model`][crate::MediaDeviceInfo::model].
+    /// Available without opening the device.
+    pub fn model(&self) -> Option<&str> {
+        self.model.as_deref()
+    }
+
+    /// Open the device.
+    pub fn open(&self) -> Result<Media> {
+        Media::from_path(&self.path)
+    }
+}
+
+/// Enumerates media devices registered in sysfs.
+///
+/// # Details
+/// Walks [`/sys/bus/media/devices`][SYSFS_MEDIA_DEVICES] and yields the devices in ascending order of their indices.
+/// Devices are not opened while enumerating.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(devices) = MediaDeviceEnumerator::new() {
+///     for device in devices {
+///         println!("{}: {:?}", device.path().display(), device.model());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MediaDeviceEnumerator {
+    devices: std::vec::IntoIter<MediaDeviceNode>,
+}
+
+impl MediaDeviceEnumerator {
+    /// Enumerate media devices listed in [`/sys/bus/media/devices`][SYSFS_MEDIA_DEVICES].
+    ///
+    /// # Details
+    /// Yields nothing if the directory does not exist, which is the case when the media controller is not loaded.
+    pub fn new() -> Result<Self> {
+        match Self::with_sysfs(SYSFS_MEDIA_DEVICES) {
+            Err(error::Error::FileNotFound { .. }) => Ok(Self {
+                devices: vec![].into_iter(),
+            }),
+            result => result,
+        }
+    }
+
+    /// Enumerate media devices listed in the directory `sysfs`.
+    pub fn with_sysfs<P>(sysfs: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let sysfs = sysfs.as_ref();
+        let mut devices: Vec<MediaDeviceNode> = sysfs
+            .read_dir()
+            .map_err(|err| error::trap_io_error(err, sysfs.to_path_buf()))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with("media"))
+            .filter_map(|entry| MediaDeviceNode::from_sysfs(entry.path()).ok())
+            .collect();
+        devices.sort();
+        Ok(Self {
+            devices: devices.into_iter(),
+        })
+    }
+}
+
+impl MediaDeviceEnumerator {
+    /// Yield only the devices selected by `filter`.
+    ///
+    /// # Details
+    /// See [`MediaDeviceFilter::matches`] for when devices are opened.
+    pub fn filter_by(self, filter: MediaDeviceFilter) -> impl Iterator<Item = MediaDeviceNode> {
+        self.filter(move |node| filter.matches(node))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl MediaDeviceEnumerator {
+    /// Apply `f` to every device in parallel.
+    ///
+    /// # Details
+    /// Opening devices and fetching their information or topologies are blocking ioctls,
+    /// which add up on systems with many DVB or UVC adapters.
+    /// `f` is run on the rayon thread pool, and the results are returned in the order of the devices.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// let topologies = MediaDeviceEnumerator::new()?.par_map(|device| {
+    ///     let media = device.open()?;
+    ///     media.new_topology()
+    /// });
+    /// for topology in topologies.into_iter().flatten() {
+    ///     println!("{} entities", topology.entities_slice().len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn par_map<T, F>(self, f: F) -> Vec<T>
+    where
+        T: Send,
+        F: Fn(MediaDeviceNode) -> T + Sync + Send,
+    {
+        use rayon::prelude::*;
+        self.devices
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(f)
+            .collect()
+    }
+
+    /// Select the devices by `filter` in parallel, keeping the order of the devices.
+    ///
+    /// # Details
+    /// Worth it only if the filter [needs the device info][MediaDeviceFilter::needs_device_info],
+    /// since other conditions are decided without opening devices.
+    pub fn par_filter_by(self, filter: MediaDeviceFilter) -> Vec<MediaDeviceNode> {
+        self.par_map(|node| filter.matches(&node).then_some(node))
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+impl Iterator for MediaDeviceEnumerator {
+    type Item = MediaDeviceNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.devices.next()
+    }
+}
+
+/// Conditions selecting media devices found by [`MediaDeviceEnumerator`].
+///
+/// # Details
+/// The model is compared with the one read from sysfs, so filtering by it does not open any device
+/// unless sysfs lacks the model of the device.
+/// Devices are opened for `MEDIA_IOC_DEVICE_INFO` only if the driver or the bus info is specified,
+/// and only if the device passed the other conditions.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// let mut filter = MediaDeviceFilter::new();
+/// filter.model("unicam");
+/// for device in MediaDeviceEnumerator::new()?.filter_by(filter) {
+///     assert_eq!(device.model(), Some("unicam"));
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaDeviceFilter {
+    model: Option<String>,
+    driver: Option<String>,
+    bus_info: Option<String>,
+}
+
+impl MediaDeviceFilter {
+    /// A filter selecting every device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Select devices with the model name.
+    pub fn model(&mut self, model: &str) -> &mut Self {
+        self.model = Some(model.to_string());
+        self
+    }
+
+    /// Select devices with the driver name.
+    pub fn driver(&mut self, driver: &str) -> &mut Self {
+        self.driver = Some(driver.to_string());
+        self
+    }
+
+    /// Select devices with the bus info such like `platform:1000880000.pisp_be`.
+    pub fn bus_info(&mut self, bus_info: &str) -> &mut Self {
+        self.bus_info = Some(bus_info.to_string());
+        self
+    }
+
+    /// Whether the device has to be opened to decide if it is selected.
+    pub fn needs_device_info(&self) -> bool {
+        self.driver.is_some() || self.bus_info.is_some()
+    }
+
+    /// Whether `node` is selected.
+    ///
+    /// # Details
+    /// Conditions known from sysfs are checked first, and the device is opened only if needed.
+    /// The model is compared with the device info instead if sysfs does not have it.
+    /// Devices which can not be opened are not selected by conditions needing the device info.
+    pub fn matches(&self, node: &MediaDeviceNode) -> bool {
+        let model_unknown = match (&self.model, node.model()) {
+            (Some(model), Some(node_model)) if model != node_model => return false,
+            (Some(_), None) => true,
+            _ => false,
+        };
+        if !self.needs_device_info() && !model_unknown {
+            return true;
+        }
+        let Ok(media) = node.open() else {
+            return false;
+        };
+        self.matches_info(media.info())
+    }
+
+    /// Whether the device info satisfies the conditions needing it.
+    pub fn matches_info(&self, info: &MediaDeviceInfo) -> bool {
+        self.model
+            .as_deref()
+            .map_or(true, |model| info.model() == model)
+            && self
+                .driver
+                .as_deref()
+                .map_or(true, |driver| info.driver() == driver)
+            && self
+                .bus_info
+                .as_deref()
+                .map_or(true, |bus_info| info.bus_info() == bus_info)
+    }
+}
+
+/// Enumerate media devices registered in sysfs.
+/// Equivalent to [`MediaDeviceEnumerator::new`].
+pub fn enumerate() -> Result<MediaDeviceEnumerator> {
+    MediaDeviceEnumerator::new()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn enumerate_fake_sysfs() {
+        let tmp = TempDir::new("enum");
+        let root = tmp.path();
+        for (name, model) in [("media1", "rkisp1"), ("media0", "unicam")] {
+            let dir = root.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("uevent"),
+                format!("MAJOR=238\nMINOR=0\nDEVNAME={}\n", name),
+            )
+            .unwrap();
+            fs::write(dir.join("model"), format!("{}\n", model)).unwrap();
+        }
+        let devices: Vec<MediaDeviceNode> =
+            MediaDeviceEnumerator::with_sysfs(root).unwrap().collect();
+
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].path(), Path::new("/dev/media0"));
+        assert_eq!(devices[0].model(), Some("unicam"));
+        assert_eq!(devices[1].index(), 1);
+    }
+
+    #[test]
+    fn filter_by_model_opens_no_device() {
+        let tmp = TempDir::new("filter");
+        let root = tmp.path();
+        for (name, model) in [("media0", "unicam"), ("media1", "rkisp1")] {
+            let dir = root.join(name);
+            fs::create_dir_all(&dir).unwrap();
+            // the device files do not exist, so opening them would fail
+            fs::write(
+                dir.join("uevent"),
+                format!("DEVNAME=nonexistent/{}\n", name),
+            )
+            .unwrap();
+            fs::write(dir.join("model"), format!("{}\n", model)).unwrap();
+        }
+        let mut by_model = MediaDeviceFilter::new();
+        by_model.model("rkisp1");
+        let mut by_bus_info = by_model.clone();
+        by_bus_info.bus_info("platform:rkisp1");
+        let selected: Vec<MediaDeviceNode> = MediaDeviceEnumerator::with_sysfs(root)
+            .unwrap()
+            .filter_by(by_model)
+            .collect();
+        let opened = MediaDeviceEnumerator::with_sysfs(root)
+            .unwrap()
+            .filter_by(by_bus_info)
+            .count();
+
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].index(), 1);
+        assert_eq!(opened, 0);
+    }
+
+    /// The number of read syscalls issued by the current thread.
+    fn syscr() -> u64 {
+        let io = fs::read_to_string("/proc/thread-self/io").unwrap();
+        let syscr = io.lines().find_map(|line| line.strip_prefix("syscr: "));
+        syscr.unwrap().parse().unwrap()
+    }
+
+    #[test]
+    fn enumeration_reads_each_file_once() {
+        let tmp = TempDir::new("syscalls");
+        let root = tmp.path();
+        for index in 0..20 {
+            let dir = root.join(format!("media{}", index));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("uevent"),
+                format!("DEVNAME=nonexistent/media{}\n", index),
+            )
+            .unwrap();
+            fs::write(dir.join("model"), "unicam\n").unwrap();
+        }
+        let mut filter = MediaDeviceFilter::new();
+        filter.model("unicam");
+        let overhead = {
+            let before = syscr();
+            syscr() - before
+        };
+
+        let before = syscr();
+        let selected = MediaDeviceEnumerator::with_sysfs(root)
+            .unwrap()
+            .filter_by(filter)
+            .count();
+        let reads = syscr() - before - overhead;
+
+        assert_eq!(selected, 20);
+        // uevent and model of each device, without reading anything of the devices
+        assert_eq!(reads, 2 * 20);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn par_map_keeps_order() {
+        let tmp = TempDir::new("par");
+        let root = tmp.path();
+        for index in 0..8 {
+            let dir = root.join(format!("media{}", index));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("uevent"), format!("DEVNAME=media{}\n", index)).unwrap();
+        }
+        let indices = MediaDeviceEnumerator::with_sysfs(root)
+            .unwrap()
+            .par_map(|node| node.index());
+
+        assert_eq!(indices, (0..8).collect::<Vec<u32>>());
+    }
+}
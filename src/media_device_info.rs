@@ -1,25 +1,35 @@
-use std::ffi::CStr;
 use std::fmt;
-use std::fs::OpenOptions;
-use std::os::fd::{AsFd, AsRawFd, OwnedFd};
-use std::os::unix::fs::OpenOptionsExt;
+use std::os::fd::OwnedFd;
 use std::path::Path;
 
-use linux_media_sys as media;
 use serde::{Deserialize, Serialize};
 
 use crate::error;
-use crate::ioctl;
+use crate::media_api_capabilities::MediaApiCapabilities;
+use crate::media_bus_info::BusInfo;
+use crate::media_hw_revision::HwRevision;
+use crate::media_raw_topology::{c_str, set_c_str};
+use crate::raw as media;
 use crate::version::*;
+use crate::MediaBackend;
+use crate::MediaInfoHandle;
 
 #[derive(Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaDeviceInfo {
+    #[serde(rename = "driver")]
     pub driver: String,
+    #[serde(rename = "model")]
     pub model: String,
+    #[serde(rename = "serial")]
     pub serial: String,
+    #[serde(rename = "bus_info")]
     pub bus_info: String,
+    #[serde(rename = "media_version")]
     pub media_version: Version,
-    pub hw_revision: u32,
+    #[serde(rename = "hw_revision")]
+    pub hw_revision: HwRevision,
+    #[serde(rename = "driver_version")]
     pub driver_version: Version,
 }
 
@@ -39,33 +49,55 @@ impl fmt::Debug for MediaDeviceInfo {
     }
 }
 
+/// Formatted as the header printed by `media-ctl --print-topology`, e.g.
+///
+/// ```text
+/// Media controller API version 6.1.0
+///
+/// Media device information
+/// ------------------------
+/// driver          unicam
+/// model           unicam
+/// serial
+/// bus info        platform:fe801000.csi
+/// hw revision     0x0
+/// driver version  6.1.0
+/// ```
+impl fmt::Display for MediaDeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Media controller API version {}", self.media_version)?;
+        writeln!(f)?;
+        writeln!(f, "Media device information")?;
+        writeln!(f, "------------------------")?;
+        writeln!(f, "driver          {}", self.driver)?;
+        writeln!(f, "model           {}", self.model)?;
+        writeln!(f, "serial          {}", self.serial)?;
+        writeln!(f, "bus info        {}", self.bus_info)?;
+        writeln!(f, "hw revision     {}", self.hw_revision)?;
+        write!(f, "driver version  {}", self.driver_version)
+    }
+}
+
 impl MediaDeviceInfo {
+    /// Open the device file such like `/dev/media0` and fetch its device information.
+    ///
+    /// # Returns
+    /// The file descriptor of the device paired with the information.
+    #[deprecated(
+        note = "use `MediaInfoHandle::from_path`, which keeps the file descriptor with the information"
+    )]
     pub fn from_path<P>(path: P) -> error::Result<(OwnedFd, Self)>
     where
         P: AsRef<Path>,
     {
-        let path = path.as_ref();
-        let fd: OwnedFd = OpenOptions::new()
-            .read(true)
-            .write(true)
-            .custom_flags(libc::O_CLOEXEC)
-            .open(path)
-            .map_err(|err| error::trap_io_error(err, path.to_path_buf()))?
-            .into();
-        let info = Self::from_fd(fd.as_fd())?;
-        Ok((fd, info))
-    }
-
-    pub fn from_fd<F>(fd: F) -> error::Result<Self>
+        MediaInfoHandle::from_path(path).map(MediaInfoHandle::into_parts)
+    }
+
+    pub fn from_fd<B>(fd: B) -> error::Result<Self>
     where
-        F: AsFd,
+        B: MediaBackend,
     {
-        let info = unsafe {
-            let mut info: media::media_device_info = std::mem::zeroed();
-            ioctl!(fd.as_fd(), media::MEDIA_IOC_DEVICE_INFO, &mut info)?;
-            info
-        };
-        Ok(info.into())
+        Ok(fd.device_info()?.into())
     }
 
     pub fn driver(&self) -> &str {
@@ -84,35 +116,60 @@ impl MediaDeviceInfo {
         &self.bus_info
     }
 
+    /// The location of the device parsed from [`bus_info`][MediaDeviceInfo::bus_info].
+    pub fn bus(&self) -> BusInfo {
+        BusInfo::parse(&self.bus_info)
+    }
+
     pub fn media_version(&self) -> Version {
-        self.media_version.clone()
+        self.media_version
+    }
+
+    /// The fields of the media controller API available on the device.
+    pub fn capabilities(&self) -> MediaApiCapabilities {
+        MediaApiCapabilities::from(self)
     }
 
-    pub fn hw_revision(&self) -> u32 {
+    pub fn hw_revision(&self) -> HwRevision {
         self.hw_revision
     }
 
+    /// The hardware revision [decoded][HwRevision::decode] by the format of the driver.
+    pub fn hw_revision_string(&self) -> String {
+        self.hw_revision.decode(&self.driver)
+    }
+
     pub fn driver_version(&self) -> Version {
-        self.driver_version.clone()
+        self.driver_version
+    }
+
+    /// Convert from the raw struct filled by `MEDIA_IOC_DEVICE_INFO`.
+    pub fn from_raw(info: media::media_device_info) -> Self {
+        info.into()
+    }
+
+    /// Convert into the raw struct to pass to ioctls not wrapped by this crate.
+    pub fn to_raw(&self) -> media::media_device_info {
+        let mut raw: media::media_device_info = unsafe { std::mem::zeroed() };
+        set_c_str(&mut raw.driver, &self.driver);
+        set_c_str(&mut raw.model, &self.model);
+        set_c_str(&mut raw.serial, &self.serial);
+        set_c_str(&mut raw.bus_info, &self.bus_info);
+        raw.media_version = self.media_version.into();
+        raw.hw_revision = self.hw_revision.into();
+        raw.driver_version = self.driver_version.into();
+        raw
     }
 }
 
 impl From<media::media_device_info> for MediaDeviceInfo {
     fn from(info: media::media_device_info) -> Self {
-        let driver = unsafe { CStr::from_ptr(info.driver.as_ptr()) }
-            .to_string_lossy()
-            .to_string();
-        let model = unsafe { CStr::from_ptr(info.model.as_ptr()) }
-            .to_string_lossy()
-            .to_string();
-        let serial = unsafe { CStr::from_ptr(info.serial.as_ptr()) }
-            .to_string_lossy()
-            .to_string();
-        let bus_info = unsafe { CStr::from_ptr(info.bus_info.as_ptr()) }
-            .to_string_lossy()
-            .to_string();
+        let driver = c_str(&info.driver).to_string_lossy().into_owned();
+        let model = c_str(&info.model).to_string_lossy().into_owned();
+        let serial = c_str(&info.serial).to_string_lossy().into_owned();
+        let bus_info = c_str(&info.bus_info).to_string_lossy().into_owned();
         let media_version = info.media_version.into();
-        let hw_revision = info.hw_revision;
+        let hw_revision = info.hw_revision.into();
         let driver_version = info.driver_version.into();
         Self {
             driver,
@@ -125,3 +182,43 @@ impl From<media::media_device_info> for MediaDeviceInfo {
         }
     }
 }
+
+impl From<MediaDeviceInfo> for media::media_device_info {
+    fn from(info: MediaDeviceInfo) -> media::media_device_info {
+        info.to_raw()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn displayed_as_media_ctl_header() {
+        let info = MediaDeviceInfo {
+            driver: "unicam".to_string(),
+            model: "unicam".to_string(),
+            serial: String::new(),
+            bus_info: "platform:fe801000.csi".to_string(),
+            media_version: Version::new(6, 1, 0),
+            hw_revision: 0x10.into(),
+            driver_version: Version::new(6, 1, 21),
+        };
+        let lines: Vec<_> = info.to_string().lines().map(str::to_string).collect();
+        assert_eq!(lines[0], "Media controller API version 6.1.0");
+        assert_eq!(lines[1], "");
+        assert_eq!(lines[6], "serial          ");
+        assert_eq!(lines[8], "hw revision     0x10");
+        assert_eq!(lines.last().unwrap(), "driver version  6.1.21");
+    }
+
+    #[test]
+    fn unterminated_strings_are_read_as_empty() {
+        let mut raw: media::media_device_info = unsafe { std::mem::zeroed() };
+        set_c_str(&mut raw.driver, "unicam");
+        raw.model.fill(b'x' as _);
+        let info = MediaDeviceInfo::from_raw(raw);
+        assert_eq!(info.driver, "unicam");
+        assert_eq!(info.model, "");
+    }
+}
@@ -1,16 +1,20 @@
-use std::ffi::CStr;
 use std::ops::{BitAnd, BitOr};
+use std::sync::Arc;
 
 use bitflags;
-use derive_more::{From, Into};
-use linux_media_sys as media;
+use derive_more::{Display, From, Into};
 use serde::{Deserialize, Serialize};
 
 use crate::error;
+use crate::media_raw_topology::{c_str, set_c_str};
+use crate::raw as media;
+use crate::MediaApiCapabilities;
 use crate::MediaEntityDesc;
+use crate::NameInterner;
 use crate::Version;
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum MediaEntityFunctions {
     /// Unknown entity. That generally indicates that a driver didn’t initialize properly the entity, which is a Kernel bug
     Unknown,
@@ -77,6 +81,9 @@ pub enum MediaEntityFunctions {
     ProcVideoEncoder,
     /// Video (MPEG, HEVC, VPx, etc.) decoder. An entity capable of decompressing a compressed video stream into uncompressed video frames. Must have one sink pad and at least one source pad.
     ProcVideoDecoder,
+    /// Image Signal Processor (ISP) device. ISPs generally are one of a kind devices that have their specific control interfaces using a combination of custom V4L2 controls and IOCTLs, and parameters supplied in a metadata buffer. Added in Linux 5.12.
+    #[cfg(has_linux_media_sys__MEDIA_ENT_F_PROC_VIDEO_ISP)]
+    ProcVideoISP,
     /// Video multiplexer. An entity capable of multiplexing must have at least two sink pads and one source pad, and must pass the video frame(s) received from the active sink pad to the source pad.
     VIDMux,
     /// Video interface bridge. A video interface bridge entity must have at least one sink pad and at least one source pad. It receives video frames on its sink pad from an input video bus of one type (HDMI, eDP, MIPI CSI-2, etc.), and outputs them on its source pad to an output video bus of another type (eDP, MIPI CSI-2, parallel, etc.).
@@ -105,7 +112,7 @@ impl TryFrom<u32> for MediaEntityFunctions {
             #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_RF)]
             media::MEDIA_ENT_F_CONN_RF => Ok(ConnRF),
             #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_SVIDEO)]
-            media::MEDIA_ENT_F_CONN_SVIDEO => Ok(ConnSvideo),
+            media::MEDIA_ENT_F_CONN_SVIDEO => Ok(ConnSVideo),
             #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_COMPOSITE)]
             media::MEDIA_ENT_F_CONN_COMPOSITE => Ok(ConnComposite),
             media::MEDIA_ENT_F_CAM_SENSOR => Ok(CAMSensor),
@@ -126,6 +133,8 @@ impl TryFrom<u32> for MediaEntityFunctions {
             media::MEDIA_ENT_F_PROC_VIDEO_STATISTICS => Ok(ProcVideoStatistics),
             media::MEDIA_ENT_F_PROC_VIDEO_ENCODER => Ok(ProcVideoEncoder),
             media::MEDIA_ENT_F_PROC_VIDEO_DECODER => Ok(ProcVideoDecoder),
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_PROC_VIDEO_ISP)]
+            media::MEDIA_ENT_F_PROC_VIDEO_ISP => Ok(ProcVideoISP),
             media::MEDIA_ENT_F_VID_MUX => Ok(VIDMux),
             media::MEDIA_ENT_F_VID_IF_BRIDGE => Ok(VIDIFBridge),
             media::MEDIA_ENT_F_DV_DECODER => Ok(DVDecoder),
@@ -135,6 +144,140 @@ impl TryFrom<u32> for MediaEntityFunctions {
     }
 }
 
+impl From<MediaEntityFunctions> for u32 {
+    fn from(function: MediaEntityFunctions) -> u32 {
+        use MediaEntityFunctions::*;
+        match function {
+            Unknown => media::MEDIA_ENT_F_UNKNOWN,
+            V4L2SubdevUnknown => media::MEDIA_ENT_F_V4L2_SUBDEV_UNKNOWN,
+            IoV4L => media::MEDIA_ENT_F_IO_V4L,
+            IoVBI => media::MEDIA_ENT_F_IO_VBI,
+            IoSWRadio => media::MEDIA_ENT_F_IO_SWRADIO,
+            IoDTV => media::MEDIA_ENT_F_IO_DTV,
+            DTVDemod => media::MEDIA_ENT_F_DTV_DEMOD,
+            TSDemux => media::MEDIA_ENT_F_TS_DEMUX,
+            DTVCondAccess => media::MEDIA_ENT_F_DTV_CA,
+            DTVNetDecap => media::MEDIA_ENT_F_DTV_NET_DECAP,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_RF)]
+            ConnRF => media::MEDIA_ENT_F_CONN_RF,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_SVIDEO)]
+            ConnSVideo => media::MEDIA_ENT_F_CONN_SVIDEO,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_COMPOSITE)]
+            ConnComposite => media::MEDIA_ENT_F_CONN_COMPOSITE,
+            CAMSensor => media::MEDIA_ENT_F_CAM_SENSOR,
+            Flash => media::MEDIA_ENT_F_FLASH,
+            Lens => media::MEDIA_ENT_F_LENS,
+            ATVDecoder => media::MEDIA_ENT_F_ATV_DECODER,
+            Tuner => media::MEDIA_ENT_F_TUNER,
+            IFVIDDecoder => media::MEDIA_ENT_F_IF_VID_DECODER,
+            IFAUDDecoder => media::MEDIA_ENT_F_IF_AUD_DECODER,
+            AudioCapture => media::MEDIA_ENT_F_AUDIO_CAPTURE,
+            AudioPlayback => media::MEDIA_ENT_F_AUDIO_PLAYBACK,
+            AudioMixer => media::MEDIA_ENT_F_AUDIO_MIXER,
+            ProcVideoComposer => media::MEDIA_ENT_F_PROC_VIDEO_COMPOSER,
+            ProcVideoPixelFormatter => media::MEDIA_ENT_F_PROC_VIDEO_PIXEL_FORMATTER,
+            ProcVideoPixelEncConv => media::MEDIA_ENT_F_PROC_VIDEO_PIXEL_ENC_CONV,
+            ProcVideoLUT => media::MEDIA_ENT_F_PROC_VIDEO_LUT,
+            ProcVideoScaler => media::MEDIA_ENT_F_PROC_VIDEO_SCALER,
+            ProcVideoStatistics => media::MEDIA_ENT_F_PROC_VIDEO_STATISTICS,
+            ProcVideoEncoder => media::MEDIA_ENT_F_PROC_VIDEO_ENCODER,
+            ProcVideoDecoder => media::MEDIA_ENT_F_PROC_VIDEO_DECODER,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_PROC_VIDEO_ISP)]
+            ProcVideoISP => media::MEDIA_ENT_F_PROC_VIDEO_ISP,
+            VIDMux => media::MEDIA_ENT_F_VID_MUX,
+            VIDIFBridge => media::MEDIA_ENT_F_VID_IF_BRIDGE,
+            DVDecoder => media::MEDIA_ENT_F_DV_DECODER,
+            DVEncoder => media::MEDIA_ENT_F_DV_ENCODER,
+        }
+    }
+}
+
+/// A broad category of [`MediaEntityFunctions`], following the groups of the kernel documentation.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MediaEntityCategory {
+    /// Entities of unknown functions.
+    Unknown,
+    /// Data streaming input and/or output entities such as V4L video devices.
+    Io,
+    /// Digital TV demodulators, demuxes, conditional access modules and network decapsulators.
+    DigitalTv,
+    /// Connectors for RF, S-Video and composite signals.
+    Connector,
+    /// Camera sensors, flash and lens controllers.
+    Camera,
+    /// Tuners and their IF-PLL decoder stages.
+    Tuner,
+    /// Audio capture, playback and mixer functions.
+    Audio,
+    /// Video processing such as scalers, pixel formatters and codecs.
+    Processing,
+    /// Analog and digital video decoders and encoders, multiplexers and interface bridges.
+    Video,
+}
+
+impl MediaEntityFunctions {
+    /// The broad category of this function.
+    pub fn category(&self) -> MediaEntityCategory {
+        use MediaEntityFunctions::*;
+        match self {
+            Unknown | V4L2SubdevUnknown => MediaEntityCategory::Unknown,
+            IoV4L | IoVBI | IoSWRadio | IoDTV => MediaEntityCategory::Io,
+            DTVDemod | TSDemux | DTVCondAccess | DTVNetDecap => MediaEntityCategory::DigitalTv,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_RF)]
+            ConnRF => MediaEntityCategory::Connector,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_SVIDEO)]
+            ConnSVideo => MediaEntityCategory::Connector,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_CONN_COMPOSITE)]
+            ConnComposite => MediaEntityCategory::Connector,
+            CAMSensor | Flash | Lens => MediaEntityCategory::Camera,
+            Tuner | IFVIDDecoder | IFAUDDecoder => MediaEntityCategory::Tuner,
+            AudioCapture | AudioPlayback | AudioMixer => MediaEntityCategory::Audio,
+            ProcVideoComposer
+            | ProcVideoPixelFormatter
+            | ProcVideoPixelEncConv
+            | ProcVideoLUT
+            | ProcVideoScaler
+            | ProcVideoStatistics
+            | ProcVideoEncoder
+            | ProcVideoDecoder => MediaEntityCategory::Processing,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_PROC_VIDEO_ISP)]
+            ProcVideoISP => MediaEntityCategory::Processing,
+            ATVDecoder | VIDMux | VIDIFBridge | DVDecoder | DVEncoder => MediaEntityCategory::Video,
+        }
+    }
+
+    /// Whether this is a camera sensor.
+    pub fn is_sensor(&self) -> bool {
+        matches!(self, MediaEntityFunctions::CAMSensor)
+    }
+
+    /// Whether this is a data streaming input and/or output entity.
+    pub fn is_io(&self) -> bool {
+        self.category() == MediaEntityCategory::Io
+    }
+
+    /// Whether this is a video processing entity.
+    pub fn is_processing(&self) -> bool {
+        self.category() == MediaEntityCategory::Processing
+    }
+
+    /// Whether this is a connector.
+    pub fn is_connector(&self) -> bool {
+        self.category() == MediaEntityCategory::Connector
+    }
+
+    /// Whether this is an audio function.
+    pub fn is_audio(&self) -> bool {
+        self.category() == MediaEntityCategory::Audio
+    }
+
+    /// Whether this is a tuner or one of its IF-PLL decoder stages.
+    pub fn is_tuner(&self) -> bool {
+        self.category() == MediaEntityCategory::Tuner
+    }
+}
+
 bitflags::bitflags! {
     /// Media entity flags
     #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
@@ -146,6 +289,9 @@ bitflags::bitflags! {
     }
 }
 
+#[cfg(feature = "schemars")]
+crate::schema::bitflags_schema!(MediaEntityFlags);
+
 impl TryFrom<u32> for MediaEntityFlags {
     type Error = error::Error;
     fn try_from(v: u32) -> error::Result<Self> {
@@ -155,8 +301,21 @@ impl TryFrom<u32> for MediaEntityFlags {
 }
 
 #[derive(
-    Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, From, Into, Serialize, Deserialize,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Hash,
+    From,
+    Into,
+    Display,
+    Serialize,
+    Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct EntityId(u32);
 
 /// for or'ing with linux_media_sys::MEDIA_ENT_ID_FLAG_NEXT.
@@ -178,18 +337,38 @@ impl BitAnd for EntityId {
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaEntity {
+    #[serde(rename = "id")]
     id: EntityId,
-    name: String,
+    /// Shared with other entities of the same name if interned by a [`NameInterner`][crate::NameInterner].
+    #[serde(rename = "name")]
+    name: Arc<str>,
+    #[serde(rename = "function")]
     function: MediaEntityFunctions,
     /// media entity flags.
     /// Only `Some` if `has_flags` return true.
+    #[serde(rename = "flags")]
     flags: Option<MediaEntityFlags>,
 }
 
 impl MediaEntity {
+    pub fn new(
+        id: EntityId,
+        name: &str,
+        function: MediaEntityFunctions,
+        flags: Option<MediaEntityFlags>,
+    ) -> Self {
+        Self {
+            id,
+            name: name.into(),
+            function,
+            flags,
+        }
+    }
+
     pub fn has_flags(version: Version) -> bool {
-        media::MEDIA_V2_ENTITY_HAS_FLAGS(<Version as Into<u32>>::into(version).into())
+        MediaApiCapabilities::new(version).has_entity_flags()
     }
 
     pub fn id(&self) -> EntityId {
@@ -200,6 +379,16 @@ impl MediaEntity {
         &self.name
     }
 
+    /// The shared storage of the name, which is cloned without copying the string.
+    pub fn name_arc(&self) -> &Arc<str> {
+        &self.name
+    }
+
+    /// Replace the storage of the name with the one pooled in `interner`.
+    pub fn intern_name(&mut self, interner: &mut NameInterner) {
+        self.name = interner.intern(&self.name);
+    }
+
     pub fn function(&self) -> MediaEntityFunctions {
         self.function
     }
@@ -208,31 +397,99 @@ impl MediaEntity {
         self.flags
     }
 
-    pub fn from_raw_entity(version: Version, entity: media::media_v2_entity) -> Self {
+    /// Whether all of `flags` are set on this entity.
+    ///
+    /// # Details
+    /// Always false for entities of devices not reporting entity flags,
+    /// see [`has_flags`][MediaEntity::has_flags].
+    pub fn has_flag(&self, flags: MediaEntityFlags) -> bool {
+        self.flags.is_some_and(|set| set.contains(flags))
+    }
+
+    /// Convert from the raw struct filled by `MEDIA_IOC_G_TOPOLOGY`.
+    ///
+    /// # Details
+    /// The flags are only read if `capabilities` reports them, as they are not filled by older kernels.
+    ///
+    /// # Errors
+    /// [`EntityFunctionsParseError`][error::Error::EntityFunctionsParseError] or [`EntityFlagsParseError`][error::Error::EntityFlagsParseError]
+    /// if the function or the flags of the entity are not known to this crate, as a newer kernel may report.
+    pub fn from_raw<C>(capabilities: C, entity: media::media_v2_entity) -> error::Result<Self>
+    where
+        C: Into<MediaApiCapabilities>,
+    {
+        Self::from_raw_entity(capabilities, entity)
+    }
+
+    /// Convert into the raw struct to pass to ioctls not wrapped by this crate.
+    pub fn to_raw(&self) -> media::media_v2_entity {
+        let mut raw: media::media_v2_entity = unsafe { std::mem::zeroed() };
+        raw.id = self.id.into();
+        set_c_str(&mut raw.name, &self.name);
+        raw.function = self.function.into();
+        raw.flags = self.flags.map_or(0, |flags| flags.bits());
+        raw
+    }
+
+    pub fn from_raw_entity<C>(
+        capabilities: C,
+        entity: media::media_v2_entity,
+    ) -> error::Result<Self>
+    where
+        C: Into<MediaApiCapabilities>,
+    {
+        Self::from_raw_entity_with(capabilities, entity, |name| Arc::from(name))
+    }
+
+    /// Convert from the raw struct, taking the name from `interner` instead of allocating it.
+    ///
+    /// # Errors
+    /// The same as [`from_raw`][MediaEntity::from_raw].
+    pub fn from_raw_interned<C>(
+        capabilities: C,
+        entity: media::media_v2_entity,
+        interner: &mut NameInterner,
+    ) -> error::Result<Self>
+    where
+        C: Into<MediaApiCapabilities>,
+    {
+        Self::from_raw_entity_with(capabilities, entity, |name| interner.intern(name))
+    }
+
+    fn from_raw_entity_with<C, F>(
+        capabilities: C,
+        entity: media::media_v2_entity,
+        name: F,
+    ) -> error::Result<Self>
+    where
+        C: Into<MediaApiCapabilities>,
+        F: FnOnce(&str) -> Arc<str>,
+    {
         let id = EntityId::from(entity.id);
-        let name = unsafe { CStr::from_ptr(entity.name.as_ptr()) }
-            .to_string_lossy()
-            .to_string();
-        let function: MediaEntityFunctions = entity.function.try_into().unwrap();
-        let flags: Option<MediaEntityFlags> = if Self::has_flags(version) {
-            Some(entity.flags.try_into().unwrap())
+        let function: MediaEntityFunctions = entity.function.try_into()?;
+        let flags: Option<MediaEntityFlags> = if capabilities.into().has_entity_flags() {
+            Some(entity.flags.try_into()?)
         } else {
             None
         };
-        Self {
+        let name = name(&c_str(&entity.name).to_string_lossy());
+        Ok(Self {
             id,
             name,
             function,
             flags,
-        }
+        })
     }
 
-    pub fn from_desc(version: Version, desc: MediaEntityDesc) -> Self {
+    pub fn from_desc<C>(capabilities: C, desc: MediaEntityDesc) -> Self
+    where
+        C: Into<MediaApiCapabilities>,
+    {
         Self {
             id: desc.id,
-            name: desc.name,
+            name: desc.name.into(),
             function: desc.r#type,
-            flags: if Self::has_flags(version) {
+            flags: if capabilities.into().has_entity_flags() {
                 Some(desc.flags)
             } else {
                 None
@@ -240,3 +497,71 @@ impl MediaEntity {
         }
     }
 }
+
+impl From<MediaEntity> for media::media_v2_entity {
+    fn from(entity: MediaEntity) -> media::media_v2_entity {
+        entity.to_raw()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn functions_are_classified() {
+        use MediaEntityFunctions::*;
+        assert!(CAMSensor.is_sensor() && !Lens.is_sensor());
+        assert_eq!(Lens.category(), MediaEntityCategory::Camera);
+        assert!(IoV4L.is_io() && !ProcVideoScaler.is_io());
+        assert!(ProcVideoScaler.is_processing());
+        assert!(AudioMixer.is_audio());
+        assert!(Tuner.is_tuner() && IFAUDDecoder.is_tuner() && !IFAUDDecoder.is_audio());
+        assert!(!VIDIFBridge.is_connector());
+        assert_eq!(VIDIFBridge.category(), MediaEntityCategory::Video);
+        assert_eq!(V4L2SubdevUnknown.category(), MediaEntityCategory::Unknown);
+    }
+
+    #[cfg(has_linux_media_sys__MEDIA_ENT_F_PROC_VIDEO_ISP)]
+    #[test]
+    fn isp_round_trips() {
+        let function = MediaEntityFunctions::try_from(media::MEDIA_ENT_F_PROC_VIDEO_ISP).unwrap();
+        assert_eq!(function, MediaEntityFunctions::ProcVideoISP);
+        assert_eq!(u32::from(function), media::MEDIA_ENT_F_PROC_VIDEO_ISP);
+        assert!(function.is_processing());
+    }
+
+    #[cfg(all(
+        has_linux_media_sys__MEDIA_ENT_F_CONN_RF,
+        has_linux_media_sys__MEDIA_ENT_F_CONN_SVIDEO,
+        has_linux_media_sys__MEDIA_ENT_F_CONN_COMPOSITE
+    ))]
+    #[test]
+    fn connectors_round_trip() {
+        use MediaEntityFunctions::*;
+        for function in [ConnRF, ConnSVideo, ConnComposite] {
+            assert_eq!(
+                MediaEntityFunctions::try_from(u32::from(function)).unwrap(),
+                function
+            );
+            assert!(function.is_connector());
+        }
+    }
+
+    #[test]
+    fn raw_entities_with_unknown_values_or_unterminated_names() {
+        let mut raw: media::media_v2_entity = unsafe { std::mem::zeroed() };
+        raw.id = 1;
+        raw.function = 0xdead;
+        assert!(matches!(
+            MediaEntity::from_raw(Version::new(6, 1, 0), raw),
+            Err(error::Error::EntityFunctionsParseError { from: 0xdead })
+        ));
+
+        raw.function = media::MEDIA_ENT_F_CAM_SENSOR;
+        raw.name.fill(b'x' as _);
+        let entity = MediaEntity::from_raw(Version::new(6, 1, 0), raw).unwrap();
+        assert_eq!(entity.function, MediaEntityFunctions::CAMSensor);
+        assert!(entity.name.is_empty());
+    }
+}
@@ -1,41 +1,69 @@
 use std::ffi::CStr;
-use std::os::fd::{AsRawFd, BorrowedFd};
+use std::fmt;
+use std::ops::ControlFlow;
 
-use linux_media_sys as media;
 use serde::{Deserialize, Serialize};
 
 use crate::error;
-use crate::ioctl;
-use crate::{EntityId, MediaEntity, MediaEntityFlags, MediaEntityFunctions, Version};
+use crate::media_raw_topology::{c_str, set_c_str};
+use crate::raw as media;
+use crate::{
+    EntityId, MediaApiCapabilities, MediaBackend, MediaEntity, MediaEntityFlags,
+    MediaEntityFunctions, MediaIntfDevnode, Version,
+};
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaEntityDesc {
     /// Entity ID, set by the application. When the ID is or’ed with MEDIA_ENT_ID_FLAG_NEXT, the driver clears the flag and returns the first entity with a larger ID. Do not expect that the ID will always be the same for each instance of the device. In other words, do not hardcode entity IDs in an application.
+    #[serde(rename = "id")]
     pub id: EntityId,
     /// Entity name. This name must be unique within the media topology.
+    #[serde(rename = "name")]
     pub name: String,
     /// Entity type.
+    #[serde(rename = "type")]
     pub r#type: MediaEntityFunctions,
     /// Entity flags.
+    #[serde(rename = "flags")]
     pub flags: MediaEntityFlags,
     /// Number of pads
+    #[serde(rename = "pads")]
     pub pads: usize,
     /// Total number of outbound links.
     /// Inbound links are not counted in this field.
+    #[serde(rename = "links")]
     pub links: usize,
+    /// The device node of the entity, from `dev.major` and `dev.minor`.
+    /// `None` if the entity has no device node, for which the kernel reports both as zero.
+    #[serde(rename = "devnode", default, skip_serializing_if = "Option::is_none")]
+    pub devnode: Option<MediaIntfDevnode>,
+    /// Entity revision, defined by the driver. Zero for most drivers.
+    #[serde(rename = "revision", default, skip_serializing_if = "is_zero")]
+    pub revision: u32,
+    /// Entity group ID, used by older drivers to group entities such as the ALSA and the video devices of a TV card.
+    #[serde(rename = "group_id", default, skip_serializing_if = "is_zero")]
+    pub group_id: u32,
+    /// The bytes of the union following the descriptor, with trailing zero bytes trimmed.
+    /// Out-of-tree drivers may encode data other than the device node here.
+    #[serde(rename = "info", default, skip_serializing_if = "Vec::is_empty")]
+    pub info: Vec<u8>,
+}
+
+/// Fields zero for most drivers are omitted from serialized descriptors.
+fn is_zero(value: &u32) -> bool {
+    *value == 0
 }
 
 impl MediaEntityDesc {
-    pub fn from_fd<F>(fd: F, entity: EntityId) -> error::Result<Self>
+    pub fn from_fd<B>(fd: B, entity: EntityId) -> error::Result<Self>
     where
-        F: AsRawFd,
+        B: MediaBackend,
     {
-        unsafe {
-            let mut desc: media::media_entity_desc = std::mem::zeroed();
-            desc.id = entity.into();
-            ioctl!(fd, media::MEDIA_IOC_ENUM_ENTITIES, &mut desc)?;
-            Ok(desc.into())
-        }
+        let mut desc: media::media_entity_desc = unsafe { std::mem::zeroed() };
+        desc.id = entity.into();
+        fd.enum_entities(&mut desc)?;
+        Ok(desc.into())
     }
 
     pub fn id(&self) -> EntityId {
@@ -47,7 +75,7 @@ impl MediaEntityDesc {
     }
 
     pub fn r#type(&self) -> MediaEntityFunctions {
-        self.r#type.clone()
+        self.r#type
     }
 
     pub fn flags(&self) -> MediaEntityFlags {
@@ -61,79 +89,430 @@ impl MediaEntityDesc {
     pub fn links(&self) -> usize {
         self.links
     }
+
+    /// The device node of the entity such like a video device or a subdevice.
+    ///
+    /// # Details
+    /// Kernels without `MEDIA_IOC_G_TOPOLOGY` report device nodes only here instead of through interfaces.
+    /// The path of the device file is resolved by [`MediaIntfDevnode::dev_path`].
+    pub fn devnode(&self) -> Option<MediaIntfDevnode> {
+        self.devnode
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+
+    pub fn group_id(&self) -> u32 {
+        self.group_id
+    }
+
+    /// The raw bytes of the union of the descriptor, with trailing zero bytes trimmed.
+    pub fn info(&self) -> &[u8] {
+        &self.info
+    }
+}
+
+/// A view of an entity filled by `MEDIA_IOC_ENUM_ENTITIES`, passed to [`MediaEntityDesc::for_each`].
+#[derive(Clone, Copy)]
+pub struct RawEntityDesc<'a> {
+    raw: &'a media::media_entity_desc,
+}
+
+impl fmt::Debug for RawEntityDesc<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RawEntityDesc")
+            .field("id", &self.id())
+            .field("name", &self.name_cstr())
+            .field("type", &self.raw.type_)
+            .field("flags", &self.raw.flags)
+            .field("pads", &self.pads())
+            .field("links", &self.links())
+            .finish()
+    }
+}
+
+impl<'a> RawEntityDesc<'a> {
+    /// The entity as filled by the kernel.
+    pub fn raw(&self) -> &'a media::media_entity_desc {
+        self.raw
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.raw.id.into()
+    }
+
+    /// The name of the entity as filled by the kernel.
+    pub fn name_cstr(&self) -> &'a CStr {
+        c_str(&self.raw.name)
+    }
+
+    /// Whether the entity is named `name`, compared without allocation.
+    pub fn name_eq(&self, name: &str) -> bool {
+        self.name_cstr().to_bytes() == name.as_bytes()
+    }
+
+    pub fn r#type(&self) -> error::Result<MediaEntityFunctions> {
+        self.raw.type_.try_into()
+    }
+
+    pub fn flags(&self) -> error::Result<MediaEntityFlags> {
+        self.raw.flags.try_into()
+    }
+
+    pub fn pads(&self) -> usize {
+        self.raw.pads as usize
+    }
+
+    pub fn links(&self) -> usize {
+        self.raw.links as usize
+    }
+
+    pub fn devnode(&self) -> Option<MediaIntfDevnode> {
+        devnode(self.raw)
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.raw.revision
+    }
+
+    pub fn group_id(&self) -> u32 {
+        self.raw.group_id
+    }
+
+    /// The raw bytes of the union of the descriptor, with trailing zero bytes trimmed.
+    pub fn info(&self) -> &'a [u8] {
+        info(self.raw)
+    }
+
+    /// Convert the entity into an owned [`MediaEntityDesc`].
+    pub fn to_desc(&self) -> MediaEntityDesc {
+        (*self.raw).into()
+    }
+}
+
+impl MediaEntityDesc {
+    /// Call `visit` with every entity in ascending order of id, without allocating anything.
+    ///
+    /// # Details
+    /// Entities are enumerated with `MEDIA_IOC_ENUM_ENTITIES` into a single descriptor on the stack,
+    /// which is passed to `visit` as a [`RawEntityDesc`]. Returning [`ControlFlow::Break`] stops the enumeration.
+    /// The enumeration ends when the kernel answers `EINVAL` for the id after the last entity.
+    ///
+    /// # Returns
+    /// The value `visit` stopped with, or `None` if every entity was visited.
+    ///
+    /// # Errors
+    /// Any error of `MEDIA_IOC_ENUM_ENTITIES` other than `EINVAL`, such like `ENODEV` of an unplugged device,
+    /// so that a failure in the middle is not taken for the end of the entities.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::ops::ControlFlow;
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let sensor = MediaEntityDesc::for_each(media.device_fd(), |desc| {
+    ///         match desc.r#type() {
+    ///             Ok(MediaEntityFunctions::CAMSensor) => ControlFlow::Break(desc.id()),
+    ///             _ => ControlFlow::Continue(()),
+    ///         }
+    ///     })?;
+    ///     println!("{:?}", sensor);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn for_each<B, V, T>(fd: B, mut visit: V) -> error::Result<Option<T>>
+    where
+        B: MediaBackend,
+        V: FnMut(RawEntityDesc<'_>) -> ControlFlow<T>,
+    {
+        let mut desc: media::media_entity_desc = unsafe { std::mem::zeroed() };
+        loop {
+            desc.id |= media::MEDIA_ENT_ID_FLAG_NEXT;
+            match fd.enum_entities(&mut desc) {
+                Err(err) if err.errno() == Some(libc::EINVAL) => return Ok(None),
+                result => result?,
+            }
+            if let ControlFlow::Break(value) = visit(RawEntityDesc { raw: &desc }) {
+                return Ok(Some(value));
+            }
+        }
+    }
 }
 
 impl From<media::media_entity_desc> for MediaEntityDesc {
     fn from(desc: media::media_entity_desc) -> Self {
         Self {
             id: desc.id.into(),
-            name: unsafe {
-                CStr::from_ptr(desc.name.as_ptr())
-                    .to_string_lossy()
-                    .to_string()
-            },
+            name: c_str(&desc.name).to_string_lossy().into_owned(),
             r#type: desc.type_.try_into().unwrap(),
             flags: desc.flags.try_into().unwrap(),
             pads: desc.pads.try_into().unwrap(),
             links: desc.links.try_into().unwrap(),
+            devnode: devnode(&desc),
+            revision: desc.revision,
+            group_id: desc.group_id,
+            info: info(&desc).to_vec(),
+        }
+    }
+}
+
+/// The bytes of the union of `desc` up to the last non-zero byte.
+fn info(desc: &media::media_entity_desc) -> &[u8] {
+    // safety: every member of the union is plain integers
+    let raw = unsafe { &desc.__bindgen_anon_1.raw };
+    let len = raw
+        .iter()
+        .rposition(|byte| *byte != 0)
+        .map_or(0, |last| last + 1);
+    &raw[..len]
+}
+
+/// The `dev` member of the union of `desc`, which is zero if the entity has no device node.
+fn devnode(desc: &media::media_entity_desc) -> Option<MediaIntfDevnode> {
+    // safety: every member of the union is plain integers
+    let dev = unsafe { desc.__bindgen_anon_1.dev };
+    (dev.major != 0 || dev.minor != 0).then_some(MediaIntfDevnode {
+        major: dev.major,
+        minor: dev.minor,
+    })
+}
+
+impl From<MediaEntityDesc> for media::media_entity_desc {
+    fn from(desc: MediaEntityDesc) -> media::media_entity_desc {
+        let mut raw: media::media_entity_desc = unsafe { std::mem::zeroed() };
+        raw.id = desc.id.into();
+        set_c_str(&mut raw.name, &desc.name);
+        raw.type_ = desc.r#type.into();
+        raw.flags = desc.flags.bits();
+        raw.pads = desc.pads as u16;
+        raw.links = desc.links as u16;
+        raw.revision = desc.revision;
+        raw.group_id = desc.group_id;
+        // safety: every member of the union is plain integers
+        let info = unsafe { &mut raw.__bindgen_anon_1.raw };
+        let len = desc.info.len().min(info.len());
+        info[..len].copy_from_slice(&desc.info[..len]);
+        if let Some(devnode) = desc.devnode {
+            raw.__bindgen_anon_1.dev.major = devnode.major;
+            raw.__bindgen_anon_1.dev.minor = devnode.minor;
+        }
+        raw
+    }
+}
+
+/// Iterates over the descriptors of all entities, as filled by `MEDIA_IOC_ENUM_ENTITIES`.
+///
+/// # Details
+/// Unlike [`MediaEntityIter`], the numbers of pads and links of each entity are kept,
+/// e.g. to size the arrays of [`MediaLinksEnum`][crate::MediaLinksEnum] without another ioctl.
+/// Enumerated items are in ascending order of ID.
+/// Each call of [`next`][Iterator::next] issues exactly one `MEDIA_IOC_ENUM_ENTITIES`.
+#[derive(Debug)]
+pub struct MediaEntityDescIter<B> {
+    fd: B,
+    /// The id after which the next entity is looked up, or `None` once the enumeration finished.
+    after: Option<EntityId>,
+}
+
+impl<B> MediaEntityDescIter<B>
+where
+    B: MediaBackend,
+{
+    /// Enumerate the entities with an ID greater than or equal to `id`.
+    pub fn new(fd: B, id: EntityId) -> Self {
+        Self {
+            fd,
+            // entities are looked up with MEDIA_ENT_ID_FLAG_NEXT, which finds the one after the id
+            after: Some(EntityId::from(u32::from(id).saturating_sub(1))),
+        }
+    }
+
+    /// Enumerate every entity of the device from the first one.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     for desc in MediaEntityDescIter::all(media.device_fd()) {
+    ///         println!("{}: {} pads, {} links", desc.name(), desc.pads(), desc.links());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all(fd: B) -> Self {
+        Self {
+            fd,
+            // no entity has the id 0, so the first one is found after it
+            after: Some(EntityId::from(0)),
         }
     }
 }
 
+impl<B> Iterator for MediaEntityDescIter<B>
+where
+    B: MediaBackend,
+{
+    type Item = MediaEntityDesc;
+    fn next(&mut self) -> Option<Self::Item> {
+        let after = self.after?;
+        let desc =
+            MediaEntityDesc::from_fd(&self.fd, after | media::MEDIA_ENT_ID_FLAG_NEXT.into()).ok();
+        self.after = desc.as_ref().map(|desc| desc.id);
+        desc
+    }
+}
+
 /// Iterates over all MediaEntities.
 ///
 /// # Details
 /// Iterates over all MediaEntities with an ID greater than or equal to the stored ID.
 /// Enumerated items are in ascending order of ID.
+/// Each call of [`next`][Iterator::next] issues exactly one `MEDIA_IOC_ENUM_ENTITIES`.
 #[derive(Debug)]
-pub struct MediaEntityIter<'a> {
-    fd: BorrowedFd<'a>,
-    media_version: Version,
-    id: EntityId,
-    // next item descriptor
-    desc: Option<MediaEntityDesc>,
+pub struct MediaEntityIter<B> {
+    descs: MediaEntityDescIter<B>,
+    capabilities: MediaApiCapabilities,
 }
 
-impl<'a> MediaEntityIter<'a> {
-    pub fn new(fd: BorrowedFd<'a>, media_version: Version, id: EntityId) -> Self {
+impl<B> MediaEntityIter<B>
+where
+    B: MediaBackend,
+{
+    pub fn new(fd: B, media_version: Version, id: EntityId) -> Self {
         Self {
-            fd,
-            media_version,
-            id,
-            desc: Self::desc(fd, id),
+            descs: MediaEntityDescIter::new(fd, id),
+            capabilities: media_version.into(),
         }
     }
 
-    fn desc(fd: BorrowedFd<'_>, id: EntityId) -> Option<MediaEntityDesc> {
-        unsafe {
-            let mut desc: media::media_entity_desc = std::mem::zeroed();
-            desc.id = Into::<u32>::into(id);
-            if ioctl!(fd, media::MEDIA_IOC_ENUM_ENTITIES, &mut desc).is_ok() {
-                Some(desc.into())
-            } else {
-                None
-            }
+    /// Enumerate every entity of the device from the first one, without knowing any id in advance.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     for entity in MediaEntityIter::all(media.device_fd(), media.media_version()) {
+    ///         println!("{:?}: {}", entity.id(), entity.name());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all(fd: B, media_version: Version) -> Self {
+        Self {
+            descs: MediaEntityDescIter::all(fd),
+            capabilities: media_version.into(),
         }
     }
+
+    /// Yield the full descriptors of the rest of the entities instead of converting them into [`MediaEntity`].
+    pub fn descs(self) -> MediaEntityDescIter<B> {
+        self.descs
+    }
 }
 
-impl<'a> Iterator for MediaEntityIter<'a> {
+impl<B> Iterator for MediaEntityIter<B>
+where
+    B: MediaBackend,
+{
     type Item = MediaEntity;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.desc.clone() {
-            Some(desc) => {
-                let entity = MediaEntity::from_desc(self.media_version, desc);
-                if let Some(desc) =
-                    Self::desc(self.fd, self.id | media::MEDIA_ENT_ID_FLAG_NEXT.into())
-                {
-                    self.id = desc.id.into();
-                    self.desc = Some(desc);
-                } else {
-                    self.desc = None;
-                }
-                Some(entity)
-            }
-            None => None,
-        }
+        let desc = self.descs.next()?;
+        Some(MediaEntity::from_desc(self.capabilities, desc))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::{MockCall, MockOp};
+
+    #[test]
+    fn iterates_all_entities() {
+        let backend = mock();
+        let names: Vec<String> = MediaEntityIter::all(&backend, Version::new(6, 1, 0))
+            .map(|entity| entity.name().to_string())
+            .collect();
+        assert_eq!(names, ["sensor", "csi", "dma"]);
+        // one ioctl per entity, and one more finding no entity after the last one
+        let next = |id: u32| MockCall::EnumEntities {
+            id: id | media::MEDIA_ENT_ID_FLAG_NEXT,
+        };
+        assert_eq!(backend.calls(), [next(0), next(1), next(3), next(6)]);
+
+        let mut descs = MediaEntityIter::all(&backend, Version::new(6, 1, 0)).descs();
+        let sensor = descs.next().unwrap();
+        assert_eq!((sensor.pads(), sensor.links()), (1, 1));
+        let rest: Vec<EntityId> = descs.map(|desc| desc.id()).collect();
+        assert_eq!(rest, [EntityId::from(3), EntityId::from(6)]);
+    }
+
+    #[test]
+    fn for_each_stops_only_at_the_end() {
+        let backend = mock();
+        let mut names = Vec::new();
+        let found = MediaEntityDesc::for_each(&backend, |desc| {
+            names.push(desc.name_cstr().to_string_lossy().into_owned());
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(found.unwrap(), None);
+        assert_eq!(names, ["sensor", "csi", "dma"]);
+
+        backend.script(MockOp::EnumEntities, [None, Some(libc::EIO)]);
+        let err = MediaEntityDesc::for_each(&backend, |_| ControlFlow::<()>::Continue(()));
+        assert_eq!(err.unwrap_err().errno(), Some(libc::EIO));
+    }
+
+    #[test]
+    fn devnode_from_dev_union() {
+        let mut raw: media::media_entity_desc = unsafe { std::mem::zeroed() };
+        raw.id = 1;
+        raw.type_ = media::MEDIA_ENT_F_IO_V4L;
+        assert_eq!(MediaEntityDesc::from(raw).devnode(), None);
+
+        raw.__bindgen_anon_1.dev.major = 81;
+        raw.__bindgen_anon_1.dev.minor = 3;
+        let desc = MediaEntityDesc::from(raw);
+        let devnode = MediaIntfDevnode {
+            major: 81,
+            minor: 3,
+        };
+        assert_eq!(desc.devnode(), Some(devnode));
+        assert_eq!(RawEntityDesc { raw: &raw }.devnode(), Some(devnode));
+        assert_eq!(desc.info(), [81, 0, 0, 0, 3]);
+        assert_eq!(
+            MediaEntityDesc::from(media::media_entity_desc::from(desc.clone())),
+            desc
+        );
+    }
+
+    #[test]
+    fn revision_group_id_and_info() {
+        let mut raw: media::media_entity_desc = unsafe { std::mem::zeroed() };
+        raw.id = 1;
+        raw.type_ = media::MEDIA_ENT_F_IO_V4L;
+        raw.revision = 2;
+        raw.group_id = 7;
+        unsafe { raw.__bindgen_anon_1.raw[100] = 0xff };
+        let desc = MediaEntityDesc::from(raw);
+        assert_eq!((desc.revision(), desc.group_id()), (2, 7));
+        assert_eq!(desc.info().len(), 101);
+        assert_eq!(desc.devnode(), None);
+
+        let json = serde_json::to_string(&desc).unwrap();
+        assert_eq!(
+            serde_json::from_str::<MediaEntityDesc>(&json).unwrap(),
+            desc
+        );
+        let raw = media::media_entity_desc::from(desc);
+        assert_eq!((raw.revision, raw.group_id), (2, 7));
+        assert_eq!(unsafe { raw.__bindgen_anon_1.raw[100] }, 0xff);
     }
 }
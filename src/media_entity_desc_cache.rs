@@ -0,0 +1,138 @@
+use std::collections::btree_map::{BTreeMap, Entry};
+
+use crate::error::Result;
+use crate::media_backend::MediaBackend;
+use crate::media_entity::EntityId;
+use crate::media_entity_desc::MediaEntityDesc;
+use crate::media_link_enum::MediaLinksEnum;
+use crate::media_topology_counts::MediaTopologyCounts;
+
+/// A cache of [`MediaEntityDesc`] of a device, keyed by [`EntityId`].
+///
+/// # Details
+/// Repeated lookups of the same entity, e.g. resolving the names of both ends of many links,
+/// issue `MEDIA_IOC_ENUM_ENTITIES` only once per entity.
+/// The cache is valid for a single topology version. Call [`sync`][Self::sync] to drop stale entries
+/// after the topology may have changed.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(media) = Media::from_path("/dev/media0") {
+///     let mut cache = EntityDescCache::new();
+///     cache.sync(media.device_fd())?;
+///     for entity in media.new_topology()?.entities_slice() {
+///         let desc = cache.get(media.device_fd(), entity.id())?;
+///         assert_eq!(desc.name(), entity.name());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EntityDescCache {
+    /// The topology version the entries belong to.
+    version: Option<u64>,
+    descs: BTreeMap<EntityId, MediaEntityDesc>,
+}
+
+impl EntityDescCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The topology version the cached entries belong to, if known.
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    /// Drop all the entries if `version` differs from the version of the entries.
+    ///
+    /// # Returns
+    /// `true` if the entries were dropped.
+    pub fn invalidate_if_changed(&mut self, version: u64) -> bool {
+        let changed = self.version.map_or(false, |cached| cached != version);
+        if changed {
+            self.descs.clear();
+        }
+        self.version = Some(version);
+        changed
+    }
+
+    /// Probe the topology version of the device and drop the entries if it changed.
+    ///
+    /// # Returns
+    /// `true` if the entries were dropped.
+    pub fn sync<B>(&mut self, fd: B) -> Result<bool>
+    where
+        B: MediaBackend,
+    {
+        let version = MediaTopologyCounts::from_fd(fd)?.version;
+        Ok(self.invalidate_if_changed(version))
+    }
+
+    /// Drop all the entries.
+    pub fn clear(&mut self) {
+        self.version = None;
+        self.descs.clear();
+    }
+
+    /// Get the descriptor of the entity `id`, issuing `MEDIA_IOC_ENUM_ENTITIES` only if it is not cached.
+    pub fn get<B>(&mut self, fd: B, id: EntityId) -> Result<&MediaEntityDesc>
+    where
+        B: MediaBackend,
+    {
+        match self.descs.entry(id) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(MediaEntityDesc::from_fd(fd, id)?)),
+        }
+    }
+}
+
+impl MediaLinksEnum {
+    /// Enumerate pads and links of the entity, looking up the entity in `cache`.
+    pub fn with_cache<B>(fd: B, entity: EntityId, cache: &mut EntityDescCache) -> Result<Self>
+    where
+        B: MediaBackend,
+    {
+        let desc = cache.get(&fd, entity)?.clone();
+        Self::from_desc(fd, &desc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MediaEntityFlags, MediaEntityFunctions};
+
+    #[test]
+    fn entries_are_dropped_on_version_change() {
+        let mut cache = EntityDescCache::new();
+        assert!(!cache.invalidate_if_changed(1));
+        let id = EntityId::from(1);
+        cache.descs.insert(
+            id,
+            MediaEntityDesc {
+                id,
+                name: "sensor".to_string(),
+                r#type: MediaEntityFunctions::CAMSensor,
+                flags: MediaEntityFlags::empty(),
+                pads: 1,
+                links: 1,
+                devnode: None,
+                revision: 0,
+                group_id: 0,
+                info: vec![],
+            },
+        );
+        // cached entries are returned without an ioctl, even for a file which is not a media device
+        let null = std::fs::File::open("/dev/null").unwrap();
+        assert_eq!(cache.get(&null, id).unwrap().name(), "sensor");
+        assert!(!cache.invalidate_if_changed(1));
+        assert!(cache.get(&null, id).is_ok());
+        assert!(cache.invalidate_if_changed(2));
+        assert!(cache.get(&null, id).is_err());
+        assert_eq!(cache.version(), Some(2));
+    }
+}
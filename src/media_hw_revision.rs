@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{OnceLock, RwLock};
+
+use derive_more::{From, Into};
+use serde::{Deserialize, Serialize};
+
+/// The hardware revision of a media device, whose format is defined by each driver.
+///
+/// # Details
+/// Formatted in hex by `Display`, as `media-ctl` does.
+/// [`decode`][HwRevision::decode] formats it by a decoder registered for the driver instead,
+/// e.g. `uvcvideo` reports `bcdDevice` of the USB device descriptor, which is decoded to `1.00`.
+#[derive(
+    Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, From, Into, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct HwRevision(u32);
+
+/// Formats the hardware revision reported by a driver, or returns `None` if it is not in the format of the driver.
+pub type HwRevisionDecoder = fn(u32) -> Option<String>;
+
+fn decoders() -> &'static RwLock<BTreeMap<String, HwRevisionDecoder>> {
+    static DECODERS: OnceLock<RwLock<BTreeMap<String, HwRevisionDecoder>>> = OnceLock::new();
+    DECODERS.get_or_init(|| {
+        let mut decoders = BTreeMap::new();
+        decoders.insert("uvcvideo".to_string(), decode_bcd as HwRevisionDecoder);
+        RwLock::new(decoders)
+    })
+}
+
+/// Decode a binary-coded decimal version such like `bcdDevice` of USB devices, e.g. `0x0100` to `1.00`.
+fn decode_bcd(revision: u32) -> Option<String> {
+    let decimal = (0..4).all(|nibble| (revision >> (nibble * 4)) & 0xF <= 9);
+    (revision <= 0xFFFF && decimal).then(|| format!("{:x}.{:02x}", revision >> 8, revision & 0xFF))
+}
+
+impl HwRevision {
+    /// Register `decoder` for the hardware revisions of `driver`, replacing the one registered before.
+    ///
+    /// # Details
+    /// `driver` is matched against [`MediaDeviceInfo::driver`][crate::MediaDeviceInfo::driver].
+    /// The decoders are shared by the whole process.
+    pub fn register_decoder(driver: &str, decoder: HwRevisionDecoder) {
+        decoders()
+            .write()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(driver.to_string(), decoder);
+    }
+
+    /// The value reported by the driver.
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+
+    /// Format this revision by the decoder registered for `driver`, or in hex if there is none.
+    pub fn decode(&self, driver: &str) -> String {
+        let decoder = decoders()
+            .read()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(driver)
+            .copied();
+        decoder
+            .and_then(|decode| decode(self.0))
+            .unwrap_or_else(|| self.to_string())
+    }
+}
+
+impl fmt::Display for HwRevision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:x}", self.0)
+    }
+}
+
+impl fmt::UpperHex for HwRevision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn revisions_are_decoded_by_drivers() {
+        let revision = HwRevision::from(0x0100);
+        assert_eq!(revision.to_string(), "0x100");
+        assert_eq!(revision.decode("uvcvideo"), "1.00");
+        assert_eq!(HwRevision::from(0x1234).decode("uvcvideo"), "12.34");
+        assert_eq!(HwRevision::from(0x00ab).decode("uvcvideo"), "0xab");
+        assert_eq!(revision.decode("unicam"), "0x100");
+
+        HwRevision::register_decoder("test-driver", |revision| Some(format!("rev {}", revision)));
+        assert_eq!(revision.decode("test-driver"), "rev 256");
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs::OpenOptions;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use crate::error::{self, Result};
+use crate::media_backend::MediaBackend;
+use crate::media_device_info::MediaDeviceInfo;
+
+/// A media device file together with its device information, which keeps the device open for as long as the information is used.
+///
+/// # Details
+/// Replaces [`MediaDeviceInfo::from_path`] returning the file descriptor and the information as a pair, which callers had to keep together.
+/// Use [`Media`][crate::Media] to access the topology and the links of the device as well.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(handle) = MediaInfoHandle::from_path("/dev/media0") {
+///     println!("{} on {}", handle.info().driver(), handle.path().display());
+///     let topology = MediaTopology::from_fd(handle.info(), handle.backend())?;
+///     println!("{} entities", topology.entities_slice().len());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MediaInfoHandle<B = OwnedFd> {
+    path: PathBuf,
+    backend: B,
+    info: MediaDeviceInfo,
+}
+
+impl MediaInfoHandle {
+    /// Open the device file such like `/dev/media0` and fetch its device information.
+    pub fn from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let fd: OwnedFd = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC)
+            .open(path)
+            .map_err(|err| error::trap_io_error(err, path.to_path_buf()))?
+            .into();
+        Self::new(path, fd)
+    }
+}
+
+impl<B> MediaInfoHandle<B>
+where
+    B: MediaBackend,
+{
+    /// Fetch the device information through `backend`, recording `path` as the path of the device.
+    pub fn new<P>(path: P, backend: B) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let info = MediaDeviceInfo::from_fd(&backend)?;
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            backend,
+            info,
+        })
+    }
+}
+
+impl<B> MediaInfoHandle<B> {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn info(&self) -> &MediaDeviceInfo {
+        &self.info
+    }
+
+    pub fn into_parts(self) -> (B, MediaDeviceInfo) {
+        (self.backend, self.info)
+    }
+}
+
+impl<B> AsFd for MediaInfoHandle<B>
+where
+    B: AsFd,
+{
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.backend.as_fd()
+    }
+}
+
+impl<B> AsRawFd for MediaInfoHandle<B>
+where
+    B: AsFd,
+{
+    fn as_raw_fd(&self) -> RawFd {
+        self.backend.as_fd().as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::MediaTopology;
+
+    #[test]
+    fn keeps_backend_with_info() {
+        let handle = MediaInfoHandle::new("/dev/media-mock", mock()).unwrap();
+        assert_eq!(handle.path(), Path::new("/dev/media-mock"));
+        assert_eq!(handle.info().driver(), "mock");
+        let topology = MediaTopology::from_fd(handle.info(), handle.backend()).unwrap();
+        assert_eq!(topology.entities_slice().len(), 3);
+    }
+}
@@ -1,21 +1,39 @@
 use std::path::PathBuf;
 
 use derive_more::{Display, From, Into};
-use linux_media_sys as media;
 use serde::{Deserialize, Serialize};
 
+use crate::error;
 use crate::media_interface_type::MediaInterfaceType;
 use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::raw as media;
 
 #[derive(
-    Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, From, Into, Display, Serialize, Deserialize,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Hash,
+    From,
+    Into,
+    Display,
+    Serialize,
+    Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct InterfaceId(u32);
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaInterface {
+    #[serde(rename = "id")]
     id: InterfaceId,
+    #[serde(rename = "type")]
     r#type: MediaInterfaceType,
+    #[serde(rename = "devnode")]
     devnode: MediaIntfDevnode,
 }
 
@@ -48,14 +66,40 @@ impl MediaInterface {
     pub fn path(&self) -> PathBuf {
         self.devnode.into()
     }
+
+    /// Convert from the raw struct filled by `MEDIA_IOC_G_TOPOLOGY`.
+    ///
+    /// # Errors
+    /// [`InterfaceTypeParseError`][error::Error::InterfaceTypeParseError] if the type of the interface is not known to this crate,
+    /// as a newer kernel may report.
+    pub fn from_raw(intf: media::media_v2_interface) -> error::Result<Self> {
+        intf.try_into()
+    }
+
+    /// Convert into the raw struct to pass to ioctls not wrapped by this crate.
+    pub fn to_raw(&self) -> media::media_v2_interface {
+        let mut raw: media::media_v2_interface = unsafe { std::mem::zeroed() };
+        raw.id = self.id.into();
+        raw.intf_type = self.r#type.into();
+        raw.__bindgen_anon_1.devnode.major = self.devnode.major;
+        raw.__bindgen_anon_1.devnode.minor = self.devnode.minor;
+        raw
+    }
 }
 
-impl From<media::media_v2_interface> for MediaInterface {
-    fn from(intf: media::media_v2_interface) -> Self {
-        Self {
+impl TryFrom<media::media_v2_interface> for MediaInterface {
+    type Error = error::Error;
+    fn try_from(intf: media::media_v2_interface) -> error::Result<Self> {
+        Ok(Self {
             id: intf.id.into(),
-            r#type: intf.intf_type.try_into().unwrap(),
+            r#type: intf.intf_type.try_into()?,
             devnode: unsafe { intf.__bindgen_anon_1.devnode.into() },
-        }
+        })
+    }
+}
+
+impl From<MediaInterface> for media::media_v2_interface {
+    fn from(intf: MediaInterface) -> media::media_v2_interface {
+        intf.to_raw()
     }
 }
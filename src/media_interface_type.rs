@@ -1,9 +1,15 @@
-use linux_media_sys as media;
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
 
 use crate::error;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::raw as media;
 
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum MediaInterfaceType {
     /// Device node interface for the Digital TV frontend
     /// typically, /dev/dvb/adapter?/frontend?
@@ -64,6 +70,92 @@ pub enum MediaInterfaceType {
     ALSATimer,
 }
 
+impl MediaInterfaceType {
+    /// The conventional path of the device files of this interface type, such like `/dev/video*`.
+    ///
+    /// # Details
+    /// Each `*` stands for any number of characters within a path component.
+    /// The actual name of a device file depends on udev rules, see [`MediaIntfDevnode::dev_path`] for the resolved path.
+    pub fn devnode_pattern(&self) -> &'static str {
+        use MediaInterfaceType::*;
+        match self {
+            DigitalTVFrontEnd => "/dev/dvb/adapter*/frontend*",
+            DigitalTVDemux => "/dev/dvb/adapter*/demux*",
+            DigitalTVDVR => "/dev/dvb/adapter*/dvr*",
+            DigitalTVConditionalAccess => "/dev/dvb/adapter*/ca*",
+            DigitalTVNetworkControl => "/dev/dvb/adapter*/net*",
+            V4LVideo => "/dev/video*",
+            V4LVBI => "/dev/vbi*",
+            V4LRadio => "/dev/radio*",
+            V4LSubdev => "/dev/v4l-subdev*",
+            V4LSoftwareDefinedRadio => "/dev/swradio*",
+            V4LTouchDevice => "/dev/v4l-touch*",
+            ALSAPCMCapture => "/dev/snd/pcmC*D*c",
+            ALSAPCMPlayback => "/dev/snd/pcmC*D*p",
+            ALSAControl => "/dev/snd/controlC*",
+            ALSACompress => "/dev/snd/comprC*D*",
+            ALSARawMIDI => "/dev/snd/midiC*D*",
+            ALSAHardwareDependent => "/dev/snd/hwC*D*",
+            ALSASequencer => "/dev/snd/seq",
+            ALSATimer => "/dev/snd/timer",
+        }
+    }
+
+    /// Guess the path to the device file of `devnode` from the [conventional paths][MediaInterfaceType::devnode_pattern] of this interface type.
+    ///
+    /// # Details
+    /// The character devices matching the pattern are looked up in `/dev` for the one numbered `devnode`.
+    /// This complements [`MediaIntfDevnode::dev_path`] where sysfs is not available, e.g. in containers only bind-mounting `/dev`.
+    pub fn guess_path(&self, devnode: MediaIntfDevnode) -> Option<PathBuf> {
+        glob(self.devnode_pattern()).into_iter().find(|path| {
+            fs::metadata(path).is_ok_and(|metadata| {
+                metadata.file_type().is_char_device()
+                    && MediaIntfDevnode::from_rdev(metadata.rdev()) == devnode
+            })
+        })
+    }
+}
+
+/// List the paths matching `pattern`, where `*` matches any number of characters within a component.
+fn glob(pattern: &str) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::from("/")];
+    for component in Path::new(pattern).iter().skip(1) {
+        let component = component.to_string_lossy();
+        if !component.contains('*') {
+            paths = paths
+                .into_iter()
+                .map(|path| path.join(&*component))
+                .collect();
+            continue;
+        }
+        paths = paths
+            .into_iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| wildcard_match(&component, &entry.file_name().to_string_lossy()))
+            .map(|entry| entry.path())
+            .collect();
+        paths.sort();
+    }
+    paths
+}
+
+/// Whether `name` matches `pattern`, where `*` matches any number of characters.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=name.len())
+                .filter(|&i| name.is_char_boundary(i))
+                .any(|i| wildcard_match(rest, &name[i..]))
+        }
+    }
+}
+
 impl Into<u32> for MediaInterfaceType {
     fn into(self: Self) -> u32 {
         use MediaInterfaceType::*;
@@ -119,3 +211,31 @@ impl TryFrom<u32> for MediaInterfaceType {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wildcards_match_within_a_component() {
+        assert!(wildcard_match("video*", "video0"));
+        assert!(wildcard_match("video*", "video"));
+        assert!(!wildcard_match("video*", "vbi0"));
+        assert!(wildcard_match("pcmC*D*c", "pcmC1D10c"));
+        assert!(!wildcard_match("pcmC*D*c", "pcmC1D10p"));
+        assert!(wildcard_match("seq", "seq"));
+    }
+
+    #[test]
+    fn unknown_devnodes_are_not_guessed() {
+        let devnode = MediaIntfDevnode {
+            major: u32::MAX,
+            minor: u32::MAX,
+        };
+        assert_eq!(MediaInterfaceType::V4LVideo.guess_path(devnode), None);
+        assert_eq!(
+            MediaInterfaceType::V4LSubdev.devnode_pattern(),
+            "/dev/v4l-subdev*"
+        );
+    }
+}
@@ -1,12 +1,19 @@
-use std::path::PathBuf;
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
 
-use linux_media_sys as media;
 use serde::{Deserialize, Serialize};
 
-/// A wrapper type of [`linux_media_sys::media_v2_intf_devnode`]
+use crate::error::{self, Result};
+use crate::raw as media;
+
+/// A wrapper type of [`media_v2_intf_devnode`][crate::raw::media_v2_intf_devnode]
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaIntfDevnode {
+    #[serde(rename = "major")]
     pub major: u32,
+    #[serde(rename = "minor")]
     pub minor: u32,
 }
 
@@ -19,8 +26,82 @@ impl From<media::media_v2_intf_devnode> for MediaIntfDevnode {
     }
 }
 
+impl MediaIntfDevnode {
+    /// Decode the device number of a device file, such as [`MetadataExt::rdev`][std::os::unix::fs::MetadataExt::rdev],
+    /// in the encoding of the Linux kernel as `major(3)` and `minor(3)` of glibc do.
+    pub fn from_rdev(rdev: u64) -> Self {
+        Self {
+            major: (((rdev >> 32) & 0xffff_f000) | ((rdev >> 8) & 0x0000_0fff)) as u32,
+            minor: (((rdev >> 12) & 0xffff_ff00) | (rdev & 0x0000_00ff)) as u32,
+        }
+    }
+
+    /// The device number of the character device file `path`.
+    ///
+    /// # Errors
+    /// `path` is not found, or is not a character device, e.g. the path of a media device opened with a mock backend.
+    pub fn from_char_device<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let metadata =
+            fs::metadata(path).map_err(|err| error::trap_io_error(err, path.to_path_buf()))?;
+        if !metadata.file_type().is_char_device() {
+            return Err(error::Error::Io {
+                source: std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "not a character device",
+                ),
+                path: path.to_path_buf(),
+            });
+        }
+        Ok(Self::from_rdev(metadata.rdev()))
+    }
+
+    /// Resolve the path to the device file such like `/dev/video0`.
+    ///
+    /// # Details
+    /// The path is read from the `DEVNAME` entry of `/sys/dev/char/{major}:{minor}/uevent`.
+    /// Returns `None` if sysfs is not available or the device node is not known to it.
+    pub fn dev_path(&self) -> Option<PathBuf> {
+        let uevent = PathBuf::from(*self).join("uevent");
+        fs::read_to_string(uevent)
+            .ok()?
+            .lines()
+            .find_map(|line| line.strip_prefix("DEVNAME="))
+            .map(|name| Path::new("/dev").join(name))
+    }
+}
+
 impl From<MediaIntfDevnode> for PathBuf {
     fn from(devnode: MediaIntfDevnode) -> Self {
         PathBuf::from(format!("/sys/dev/char/{}:{}", devnode.major, devnode.minor))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rdev_is_decoded_as_linux_does() {
+        let devnode = MediaIntfDevnode::from_rdev(0x5100);
+        assert_eq!(
+            devnode,
+            MediaIntfDevnode {
+                major: 81,
+                minor: 0
+            }
+        );
+        // the major and the minor numbers exceeding 12 and 8 bits respectively
+        let devnode = MediaIntfDevnode::from_rdev(0x0001_2000_6783_459a);
+        assert_eq!(
+            devnode,
+            MediaIntfDevnode {
+                major: 0x12345,
+                minor: 0x6789a
+            }
+        );
+    }
+}
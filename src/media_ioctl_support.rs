@@ -0,0 +1,161 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::raw as media;
+use crate::{
+    EntityId, Media, MediaApiCapabilities, MediaBackend, MediaEntityDesc, MediaLinksEnum,
+    MediaTopologyCounts,
+};
+
+/// Which ioctls of the media controller API a device supports, found by issuing harmless ones.
+///
+/// # Details
+/// Each ioctl is `Some(true)` if it succeeded, `Some(false)` if it failed with `ENOTTY`,
+/// and `None` if it failed for another reason or was not issued, in which case its support is unknown.
+/// `MEDIA_IOC_SETUP_LINK` is never issued as it changes the device.
+///
+/// Serialized for attaching to bug reports, and formatted by `Display` as a table of the ioctls.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(media) = Media::from_path("/dev/media0") {
+///     let support = media.ioctl_support();
+///     if support.g_topology != Some(true) {
+///         println!("falling back to the legacy enumeration");
+///     }
+///     println!("{}", support);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MediaIoctlSupport {
+    #[serde(rename = "device_info")]
+    pub device_info: Option<bool>,
+    #[serde(rename = "g_topology")]
+    pub g_topology: Option<bool>,
+    #[serde(rename = "enum_entities")]
+    pub enum_entities: Option<bool>,
+    /// Issued for the first entity, so unknown on devices without entities.
+    #[serde(rename = "enum_links")]
+    pub enum_links: Option<bool>,
+    /// Probed by allocating a request, which is freed immediately.
+    #[serde(rename = "request_alloc")]
+    pub request_alloc: Option<bool>,
+    /// The numbers of the objects reported by `MEDIA_IOC_G_TOPOLOGY`, if it is supported.
+    #[serde(rename = "counts")]
+    pub counts: Option<MediaTopologyCounts>,
+    /// The fields of the API implied by the media version of the device.
+    #[serde(rename = "capabilities")]
+    pub capabilities: MediaApiCapabilities,
+}
+
+/// Whether the ioctl which resulted in `result` is supported.
+fn support<T>(result: &Result<T>) -> Option<bool> {
+    match result {
+        Ok(_) => Some(true),
+        Err(error::Error::NotSupportedIoctl { .. }) => Some(false),
+        Err(_) => None,
+    }
+}
+
+impl MediaIoctlSupport {
+    /// Probe the ioctls supported by `device`.
+    ///
+    /// # Details
+    /// Issues `MEDIA_IOC_DEVICE_INFO`, `MEDIA_IOC_G_TOPOLOGY` without arrays, `MEDIA_IOC_ENUM_ENTITIES`
+    /// and `MEDIA_IOC_ENUM_LINKS` for the first entity, and `MEDIA_IOC_REQUEST_ALLOC` closing the request at once.
+    pub fn probe<B>(device: &Media<B>) -> Self
+    where
+        B: MediaBackend,
+    {
+        let fd = device.backend();
+        let device_info = fd.device_info();
+        let counts = MediaTopologyCounts::from_fd(fd);
+        let first =
+            MediaEntityDesc::from_fd(fd, EntityId::from(0) | media::MEDIA_ENT_ID_FLAG_NEXT.into());
+        let enum_links = first
+            .as_ref()
+            .ok()
+            .and_then(|desc| support(&MediaLinksEnum::from_desc(fd, desc)));
+        let capabilities = device.info().capabilities().probe(fd);
+        Self {
+            device_info: support(&device_info),
+            g_topology: support(&counts),
+            enum_entities: support(&first),
+            enum_links,
+            request_alloc: capabilities.supports_requests(),
+            counts: counts.ok(),
+            capabilities,
+        }
+    }
+}
+
+impl fmt::Display for MediaIoctlSupport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ioctls = [
+            ("MEDIA_IOC_DEVICE_INFO", self.device_info),
+            ("MEDIA_IOC_G_TOPOLOGY", self.g_topology),
+            ("MEDIA_IOC_ENUM_ENTITIES", self.enum_entities),
+            ("MEDIA_IOC_ENUM_LINKS", self.enum_links),
+            ("MEDIA_IOC_REQUEST_ALLOC", self.request_alloc),
+        ];
+        for (i, (name, support)) in ioctls.into_iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let support = match support {
+                Some(true) => "supported",
+                Some(false) => "not supported",
+                None => "unknown",
+            };
+            write!(f, "{:<25}{}", name, support)?;
+        }
+        Ok(())
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Probe the ioctls supported by the device. See [`MediaIoctlSupport::probe`].
+    pub fn ioctl_support(&self) -> MediaIoctlSupport {
+        MediaIoctlSupport::probe(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::MockOp;
+
+    #[test]
+    fn unsupported_ioctls_are_reported() {
+        let media = Media::with_backend("/dev/media-mock", mock()).unwrap();
+        media
+            .backend()
+            .script(MockOp::GTopology, [Some(libc::ENOTTY)]);
+        let support = media.ioctl_support();
+        assert_eq!(support.device_info, Some(true));
+        assert_eq!(support.g_topology, Some(false));
+        assert_eq!(support.enum_entities, Some(true));
+        assert_eq!(support.enum_links, Some(true));
+        // the mock does not support requests
+        assert_eq!(support.request_alloc, Some(false));
+        assert_eq!(support.counts, None);
+
+        let support = media.ioctl_support();
+        assert_eq!(support.g_topology, Some(true));
+        assert_eq!(support.counts.unwrap().entities, 3);
+        let table = support.to_string();
+        assert!(table.starts_with("MEDIA_IOC_DEVICE_INFO    supported\n"));
+        assert!(table.ends_with("MEDIA_IOC_REQUEST_ALLOC  not supported"));
+    }
+}
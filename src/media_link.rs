@@ -1,19 +1,41 @@
 use std::marker::PhantomData;
 
-use derive_more::{From, Into};
-use linux_media_sys as media;
+use derive_more::{Display, From, Into};
 use serde::{Deserialize, Serialize};
 
 use crate::error;
 use crate::media_entity::EntityId;
 use crate::media_interface::InterfaceId;
 use crate::media_pad::PadId;
+use crate::raw as media;
 
 #[derive(
-    Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, From, Into, Serialize, Deserialize,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Hash,
+    From,
+    Into,
+    Display,
+    Serialize,
+    Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct LinkId(u32);
 
+/// `MEDIA_LNK_FL_ANCILLARY_LINK`, added in Linux 5.17.
+/// Defined here for the headers without it, so that ancillary links are decoded on any build.
+#[cfg(has_linux_media_sys__MEDIA_LNK_FL_ANCILLARY_LINK)]
+pub(crate) const MEDIA_LNK_FL_ANCILLARY_LINK: u32 = media::MEDIA_LNK_FL_ANCILLARY_LINK;
+/// `MEDIA_LNK_FL_ANCILLARY_LINK`, added in Linux 5.17.
+/// Defined here for the headers without it, so that ancillary links are decoded on any build.
+#[cfg(not(has_linux_media_sys__MEDIA_LNK_FL_ANCILLARY_LINK))]
+pub(crate) const MEDIA_LNK_FL_ANCILLARY_LINK: u32 = 2 << 28;
+
 bitflags::bitflags! {
     #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
     pub struct MediaLinkFlags: u32 {
@@ -26,6 +48,9 @@ bitflags::bitflags! {
     }
 }
 
+#[cfg(feature = "schemars")]
+crate::schema::bitflags_schema!(MediaLinkFlags);
+
 impl TryFrom<u32> for MediaLinkFlags {
     type Error = error::Error;
     fn try_from(v: u32) -> error::Result<Self> {
@@ -34,31 +59,53 @@ impl TryFrom<u32> for MediaLinkFlags {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PadIdOr<T>(u32, PhantomData<T>);
 
+impl<T> PadIdOr<T> {
+    /// The raw object id.
+    pub fn raw(&self) -> u32 {
+        self.0
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum LinkType {
     /// MEDIA_LNK_FL_DATA_LINK
     /// On pad to pad links: unique IDs for the source/sink pad.
-    DataLink { source_id: PadId, sink_id: PadId },
+    DataLink {
+        #[serde(rename = "source_id")]
+        source_id: PadId,
+        #[serde(rename = "sink_id")]
+        sink_id: PadId,
+    },
     /// MEDIA_LNK_FL_INTERFACE_LINK
     /// On interface to entity links: unique IDs for the interface/entity.
     InterfaceLink {
+        #[serde(rename = "source_id")]
         source_id: InterfaceId,
+        #[serde(rename = "sink_id")]
         sink_id: EntityId,
     },
     /// MEDIA_LNK_FL_ANCILLARY_LINK for links that represent a physical relationship between two entities. The link may or may not be immutable, so applications must not assume either case.
     AncillaryLink {
+        #[serde(rename = "source_id")]
         source_id: PadIdOr<InterfaceId>,
+        #[serde(rename = "sink_id")]
         sink_id: PadIdOr<EntityId>,
     },
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaLink {
+    #[serde(rename = "id")]
     id: LinkId,
+    #[serde(rename = "type")]
     r#type: LinkType,
+    #[serde(rename = "flags")]
     flags: MediaLinkFlags,
 }
 
@@ -79,30 +126,111 @@ impl MediaLink {
     pub fn flags(&self) -> MediaLinkFlags {
         self.flags
     }
+
+    /// Convert from the raw struct filled by `MEDIA_IOC_G_TOPOLOGY`.
+    ///
+    /// # Errors
+    /// [`LinkTypeParseError`][error::Error::LinkTypeParseError] or [`LinkFlagsParseError`][error::Error::LinkFlagsParseError]
+    /// if the type or the flags of the link are not known to this crate, as a newer kernel may report.
+    pub fn from_raw(link: media::media_v2_link) -> error::Result<Self> {
+        link.try_into()
+    }
+
+    /// Convert into the raw struct to pass to ioctls not wrapped by this crate.
+    pub fn to_raw(&self) -> media::media_v2_link {
+        let mut raw: media::media_v2_link = unsafe { std::mem::zeroed() };
+        raw.id = self.id.into();
+        raw.source_id = self.r#type.source_raw();
+        raw.sink_id = self.r#type.sink_raw();
+        raw.flags = self.raw_flags();
+        raw
+    }
+
+    /// The flags as reported by the kernel, which combine the [flags][MediaLink::flags] and the [type][MediaLink::type] of this link.
+    pub fn raw_flags(&self) -> u32 {
+        self.flags.bits() | self.r#type.type_flag()
+    }
+
+    /// Whether the link is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.flags.contains(MediaLinkFlags::Enabled)
+    }
+
+    /// Whether the link enabled state can't be modified.
+    pub fn is_immutable(&self) -> bool {
+        self.flags.contains(MediaLinkFlags::Immutable)
+    }
+
+    /// Whether the link enabled state can be modified during streaming.
+    pub fn is_dynamic(&self) -> bool {
+        self.flags.contains(MediaLinkFlags::Dynamic)
+    }
 }
 
-impl From<media::media_v2_link> for MediaLink {
-    fn from(link: media::media_v2_link) -> Self {
-        let r#type = match link.flags & media::MEDIA_LNK_FL_LINK_TYPE {
-            media::MEDIA_LNK_FL_DATA_LINK => LinkType::DataLink {
+impl LinkType {
+    /// Decode the type and the end points of a link filled by the kernel.
+    ///
+    /// # Errors
+    /// [`LinkTypeParseError`][error::Error::LinkTypeParseError] if the link type is not known to this crate.
+    pub(crate) fn from_raw(link: &media::media_v2_link) -> error::Result<Self> {
+        match link.flags & media::MEDIA_LNK_FL_LINK_TYPE {
+            media::MEDIA_LNK_FL_DATA_LINK => Ok(LinkType::DataLink {
                 source_id: link.source_id.into(),
                 sink_id: link.sink_id.into(),
-            },
-            media::MEDIA_LNK_FL_INTERFACE_LINK => LinkType::InterfaceLink {
+            }),
+            media::MEDIA_LNK_FL_INTERFACE_LINK => Ok(LinkType::InterfaceLink {
                 source_id: link.source_id.into(),
                 sink_id: link.sink_id.into(),
-            },
-            #[cfg(has_linux_media_sys__MEDIA_LNK_FL_ANCILLARY_LINK)]
-            media::MEDIA_LNK_FL_ANCILLARY_LINK => LinkType::AncillaryLink {
+            }),
+            MEDIA_LNK_FL_ANCILLARY_LINK => Ok(LinkType::AncillaryLink {
                 source_id: PadIdOr(link.source_id, PhantomData),
                 sink_id: PadIdOr(link.sink_id, PhantomData),
-            },
-            other => unreachable!("link type should not be there: {}", other),
-        };
-        Self {
-            id: link.id.into(),
-            r#type,
-            flags: link.flags.try_into().unwrap(),
+            }),
+            _ => Err(error::Error::LinkTypeParseError { from: link.flags }),
+        }
+    }
+
+    /// The raw id of the source end point, which is a pad or an interface depending on the type.
+    pub fn source_raw(&self) -> u32 {
+        match self {
+            LinkType::DataLink { source_id, .. } => (*source_id).into(),
+            LinkType::InterfaceLink { source_id, .. } => (*source_id).into(),
+            LinkType::AncillaryLink { source_id, .. } => source_id.raw(),
+        }
+    }
+
+    /// The raw id of the sink end point, which is a pad or an entity depending on the type.
+    pub fn sink_raw(&self) -> u32 {
+        match self {
+            LinkType::DataLink { sink_id, .. } => (*sink_id).into(),
+            LinkType::InterfaceLink { sink_id, .. } => (*sink_id).into(),
+            LinkType::AncillaryLink { sink_id, .. } => sink_id.raw(),
+        }
+    }
+
+    /// The `MEDIA_LNK_FL_*_LINK` bits of the link flags for this type.
+    fn type_flag(&self) -> u32 {
+        match self {
+            LinkType::DataLink { .. } => media::MEDIA_LNK_FL_DATA_LINK,
+            LinkType::InterfaceLink { .. } => media::MEDIA_LNK_FL_INTERFACE_LINK,
+            LinkType::AncillaryLink { .. } => MEDIA_LNK_FL_ANCILLARY_LINK,
         }
     }
 }
+
+impl TryFrom<media::media_v2_link> for MediaLink {
+    type Error = error::Error;
+    fn try_from(link: media::media_v2_link) -> error::Result<Self> {
+        Ok(Self {
+            id: link.id.into(),
+            r#type: LinkType::from_raw(&link)?,
+            flags: link.flags.try_into()?,
+        })
+    }
+}
+
+impl From<MediaLink> for media::media_v2_link {
+    fn from(link: MediaLink) -> media::media_v2_link {
+        link.to_raw()
+    }
+}
@@ -1,17 +1,20 @@
-use std::os::fd::{AsFd, AsRawFd};
-
-use linux_media_sys as media;
 use serde::{Deserialize, Serialize};
 
 use crate::error;
-use crate::ioctl;
+use crate::media_link::MEDIA_LNK_FL_ANCILLARY_LINK;
+use crate::raw as media;
+use crate::MediaBackend;
 use crate::MediaLinkFlags;
 use crate::MediaPadDesc;
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaLinkDesc {
+    #[serde(rename = "source")]
     source: MediaPadDesc,
+    #[serde(rename = "sink")]
     sink: MediaPadDesc,
+    #[serde(rename = "flags")]
     flags: MediaLinkFlags,
 }
 
@@ -25,13 +28,13 @@ impl MediaLinkDesc {
     }
 
     /// Pad at the origin of this link.
-    pub fn source(&self) -> &MediaPadDesc {
-        &self.source
+    pub fn source(&self) -> MediaPadDesc {
+        self.source
     }
 
     /// Pad at the target of this link.
-    pub fn sink(&self) -> &MediaPadDesc {
-        &self.sink
+    pub fn sink(&self) -> MediaPadDesc {
+        self.sink
     }
 
     /// Link flags
@@ -39,38 +42,33 @@ impl MediaLinkDesc {
         self.flags
     }
 
-    pub fn setup<F>(&mut self, fd: F, flags: MediaLinkFlags) -> error::Result<()>
+    pub fn setup<B>(&mut self, fd: B, flags: MediaLinkFlags) -> error::Result<()>
     where
-        F: AsFd,
+        B: MediaBackend,
     {
-        unsafe {
-            let mut desc: linux_media_sys::media_link_desc = self.clone().into();
-            desc.flags = flags.bits();
-            ioctl!(fd.as_fd(), media::MEDIA_IOC_SETUP_LINK, &mut desc)?;
-            *self = desc.into();
-            Ok(())
-        }
+        let mut desc: media::media_link_desc = (*self).into();
+        desc.flags = flags.bits();
+        crate::logging::debug!(
+            "setting up the link {}:{} -> {}:{} from {:?} to {:?}",
+            u32::from(self.source.id()),
+            self.source.index(),
+            u32::from(self.sink.id()),
+            self.sink.index(),
+            self.flags,
+            flags
+        );
+        fd.setup_link(&mut desc)?;
+        *self = desc.into();
+        Ok(())
     }
 }
 
 impl From<media::media_link_desc> for MediaLinkDesc {
     fn from(desc: media::media_link_desc) -> Self {
-        #[cfg(has_linux_media_sys__MEDIA_LNK_FL_ANCILLARY_LINK)]
-        assert!({
-            let link_type = desc.flags & media::MEDIA_LNK_FL_LINK_TYPE;
-            (link_type == media::MEDIA_LNK_FL_DATA_LINK) ||
-            (link_type == media::MEDIA_LNK_FL_ANCILLARY_LINK)
-          },
-          "The link type of MediaLinkDesc must be either DATA_LINK or ANCILLARY_LINK, but got flags: {:#x}",
-          desc.flags
-        );
-        #[cfg(not(has_linux_media_sys__MEDIA_LNK_FL_ANCILLARY_LINK))]
+        let link_type = desc.flags & media::MEDIA_LNK_FL_LINK_TYPE;
         assert!(
-            {
-                let link_type = desc.flags & media::MEDIA_LNK_FL_LINK_TYPE;
-                link_type == media::MEDIA_LNK_FL_DATA_LINK
-            },
-            "The link type of MediaLinkDesc must be either DATA_LINK, but got flags: {:#x}",
+            link_type == media::MEDIA_LNK_FL_DATA_LINK || link_type == MEDIA_LNK_FL_ANCILLARY_LINK,
+            "The link type of MediaLinkDesc must be either DATA_LINK or ANCILLARY_LINK, but got flags: {:#x}",
             desc.flags
         );
         Self {
@@ -83,7 +81,7 @@ impl From<media::media_link_desc> for MediaLinkDesc {
 
 impl From<MediaLinkDesc> for media::media_link_desc {
     fn from(desc: MediaLinkDesc) -> media::media_link_desc {
-        let mut raw: linux_media_sys::media_link_desc = unsafe { std::mem::zeroed() };
+        let mut raw: media::media_link_desc = unsafe { std::mem::zeroed() };
         raw.source = desc.source.into();
         raw.sink = desc.sink.into();
         raw.flags = desc.flags.bits();
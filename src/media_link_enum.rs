@@ -1,52 +1,120 @@
-use std::os::fd::AsRawFd;
-
 use serde::{Deserialize, Serialize};
 
+use crate::collections::{LinkDescs, PadDescs, RawLinkDescs, RawPadDescs};
 use crate::error;
-use crate::ioctl;
-use crate::{EntityId, MediaEntityDesc, MediaLinkDesc, MediaPadDesc};
-
-use linux_media_sys as media;
+use crate::raw as media;
+use crate::{EntityId, MediaBackend, MediaEntityDesc, MediaLinkDesc, MediaPadDesc};
 
 /// Enumerates MediaPads and/or MediaLinks associated to an Entity specified with id.
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaLinksEnum {
+    #[serde(rename = "entity")]
     entity: EntityId,
-    pads: Vec<MediaPadDesc>,
-    links: Vec<MediaLinkDesc>,
+    #[serde(rename = "pads")]
+    pads: PadDescs,
+    #[serde(rename = "links")]
+    links: LinkDescs,
 }
 
-fn zeros_vec<T>(num: usize) -> Vec<T>
+fn zeros<A>(num: usize) -> A
 where
-    T: Clone,
+    A: Default + Extend<A::Item> + IntoIterator,
+    A::Item: Clone,
 {
-    let mut xs = vec![];
-    xs.resize(num, unsafe { std::mem::zeroed() });
+    let mut xs = A::default();
+    xs.extend(std::iter::repeat(unsafe { std::mem::zeroed() }).take(num));
     xs
 }
 
 impl MediaLinksEnum {
-    pub fn new<F>(fd: F, entity: EntityId) -> error::Result<Self>
+    pub fn new<B>(fd: B, entity: EntityId) -> error::Result<Self>
+    where
+        B: MediaBackend,
+    {
+        let desc = MediaEntityDesc::from_fd(&fd, entity)?;
+        Self::from_desc(fd, &desc)
+    }
+
+    /// Enumerate pads and links of the entity described by `desc`.
+    pub(crate) fn from_desc<B>(fd: B, desc: &MediaEntityDesc) -> error::Result<Self>
+    where
+        B: MediaBackend,
+    {
+        let mut pads: RawPadDescs = zeros(desc.pads);
+        let mut links: RawLinkDescs = zeros(desc.links);
+        Self::fill(fd, desc, &mut pads, &mut links)
+    }
+
+    /// Enumerate pads and links of every entity.
+    ///
+    /// # Details
+    /// The buffers passed to [`MEDIA_IOC_ENUM_LINKS`][media::MEDIA_IOC_ENUM_LINKS] are allocated once,
+    /// sized for the entity having the most pads and links, and reused for all the entities.
+    /// The entities are enumerated until the kernel answers `EINVAL` for the id after the last one.
+    ///
+    /// # Returns
+    /// Pads and links of entities in ascending order of id.
+    ///
+    /// # Errors
+    /// Any error of `MEDIA_IOC_ENUM_ENTITIES` other than `EINVAL` or of `MEDIA_IOC_ENUM_LINKS`,
+    /// rather than the pads and links of the entities enumerated before the failure.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let enums = MediaLinksEnum::all(media.device_fd())?;
+    ///     let topology = media.new_topology()?;
+    ///     assert_eq!(enums.len(), topology.entities_slice().len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all<B>(fd: B) -> error::Result<Vec<Self>>
     where
-        F: AsRawFd,
+        B: MediaBackend,
     {
-        let desc = MediaEntityDesc::from_fd(fd.as_raw_fd(), entity)?;
-        let mut enum_links: media::media_links_enum = unsafe { std::mem::zeroed() };
-        enum_links.entity = entity.into();
-        unsafe {
-            let mut pads: Vec<media::media_pad_desc> = zeros_vec(desc.pads);
-            enum_links.pads = pads.as_mut_ptr();
-
-            let mut links: Vec<media::media_link_desc> = zeros_vec(desc.links);
-            enum_links.links = links.as_mut_ptr();
-
-            ioctl!(fd, media::MEDIA_IOC_ENUM_LINKS, &mut enum_links)?;
-            Ok(Self {
-                entity,
-                pads: pads.into_iter().map(Into::into).collect(),
-                links: links.into_iter().map(Into::into).collect(),
-            })
+        let mut descs = vec![];
+        let mut id = EntityId::from(0);
+        loop {
+            let desc =
+                match MediaEntityDesc::from_fd(&fd, id | media::MEDIA_ENT_ID_FLAG_NEXT.into()) {
+                    Err(err) if err.errno() == Some(libc::EINVAL) => break,
+                    result => result?,
+                };
+            id = desc.id;
+            descs.push(desc);
         }
+        let mut pads: RawPadDescs = zeros(descs.iter().map(|desc| desc.pads).max().unwrap_or(0));
+        let mut links: RawLinkDescs = zeros(descs.iter().map(|desc| desc.links).max().unwrap_or(0));
+        descs
+            .iter()
+            .map(|desc| Self::fill(&fd, desc, &mut pads, &mut links))
+            .collect()
+    }
+
+    /// Enumerate pads and links of the entity `desc` into the buffers, which are large enough for it.
+    fn fill<B>(
+        fd: B,
+        desc: &MediaEntityDesc,
+        pads: &mut [media::media_pad_desc],
+        links: &mut [media::media_link_desc],
+    ) -> error::Result<Self>
+    where
+        B: MediaBackend,
+    {
+        // the buffers are at least as long as the numbers of the pads and the links reported for the entity
+        unsafe { fd.enum_links(desc.id.into(), pads, links)? };
+        Ok(Self {
+            entity: desc.id,
+            pads: pads[..desc.pads].iter().map(|pad| (*pad).into()).collect(),
+            links: links[..desc.links]
+                .iter()
+                .map(|link| (*link).into())
+                .collect(),
+        })
     }
 
     pub fn entity(&self) -> EntityId {
@@ -61,3 +129,22 @@ impl MediaLinksEnum {
         self.links.as_ref()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::MockOp;
+
+    #[test]
+    fn all_fails_unless_entities_run_out() {
+        let backend = mock();
+        let enums = MediaLinksEnum::all(&backend).unwrap();
+        let entities: Vec<EntityId> = enums.iter().map(MediaLinksEnum::entity).collect();
+        assert_eq!(entities, [1, 3, 6].map(EntityId::from));
+
+        backend.script(MockOp::EnumEntities, [None, Some(libc::ENODEV)]);
+        let err = MediaLinksEnum::all(&backend).unwrap_err();
+        assert_eq!(err.errno(), Some(libc::ENODEV));
+    }
+}
@@ -0,0 +1,339 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::media_entity::EntityId;
+use crate::media_link::{LinkType, MediaLinkFlags};
+use crate::media_link_desc::MediaLinkDesc;
+use crate::media_pad::MediaPad;
+use crate::media_topology::MediaTopology;
+
+/// An entity referred by a link description, either by its id or by its name.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum EntitySpec {
+    Id(EntityId),
+    Name(String),
+}
+
+impl fmt::Display for EntitySpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EntitySpec::Id(id) => write!(f, "{}", u32::from(*id)),
+            EntitySpec::Name(name) => write!(f, "\"{}\"", name),
+        }
+    }
+}
+
+/// A pad referred by a link description: `entity:index`.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PadSpec {
+    #[serde(rename = "entity")]
+    pub entity: EntitySpec,
+    #[serde(rename = "index")]
+    pub index: usize,
+}
+
+impl fmt::Display for PadSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.entity, self.index)
+    }
+}
+
+/// A link description in the syntax of `media-ctl --links`.
+///
+/// # Details
+/// A link is described as `source -> sink [flags]`, where a pad is `"entity name":index` or `entity-id:index`,
+/// and flags are a number such as `1` (enabled) or `0` (disabled).
+/// Whitespaces between tokens are ignored.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// let spec: LinkSpec = "\"imx219 10-0010\":0 -> \"unicam-image\":0 [1]".parse().unwrap();
+/// assert_eq!(spec.sink.entity, EntitySpec::Name("unicam-image".to_string()));
+/// assert_eq!(spec.flags, MediaLinkFlags::Enabled);
+/// assert_eq!(spec.to_string(), "\"imx219 10-0010\":0->\"unicam-image\":0[1]");
+/// ```
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LinkSpec {
+    #[serde(rename = "source")]
+    pub source: PadSpec,
+    #[serde(rename = "sink")]
+    pub sink: PadSpec,
+    #[serde(rename = "flags")]
+    pub flags: MediaLinkFlags,
+}
+
+impl fmt::Display for LinkSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}->{}[{}]", self.source, self.sink, self.flags.bits())
+    }
+}
+
+/// A cursor over a link description.
+struct Parser<'a> {
+    spec: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(spec: &'a str) -> Self {
+        Self { spec, pos: 0 }
+    }
+
+    fn error(&self, reason: &'static str) -> error::Error {
+        error::Error::LinkSpecParseError {
+            spec: self.spec.to_string(),
+            position: self.pos,
+            reason,
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.spec[self.pos..]
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn is_end(&mut self) -> bool {
+        self.skip_spaces();
+        self.rest().is_empty()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_spaces();
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str, reason: &'static str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(reason))
+        }
+    }
+
+    /// Parse a decimal number, or a hexadecimal number prefixed by `0x`.
+    fn number(&mut self) -> Result<u32> {
+        self.skip_spaces();
+        let rest = self.rest();
+        let (radix, prefix) = if rest.starts_with("0x") || rest.starts_with("0X") {
+            (16, 2)
+        } else {
+            (10, 0)
+        };
+        let len = rest[prefix..]
+            .find(|c: char| !c.is_digit(radix))
+            .unwrap_or(rest.len() - prefix);
+        let value = u32::from_str_radix(&rest[prefix..prefix + len], radix)
+            .map_err(|_| self.error("expected a number"))?;
+        self.pos += prefix + len;
+        Ok(value)
+    }
+
+    fn entity(&mut self) -> Result<EntitySpec> {
+        if self.eat("\"") {
+            let len = self
+                .rest()
+                .find('"')
+                .ok_or_else(|| self.error("unterminated entity name"))?;
+            let name = self.rest()[..len].to_string();
+            self.pos += len + 1;
+            Ok(EntitySpec::Name(name))
+        } else {
+            Ok(EntitySpec::Id(self.number()?.into()))
+        }
+    }
+
+    fn pad(&mut self) -> Result<PadSpec> {
+        let entity = self.entity()?;
+        self.expect(":", "expected ':' followed by a pad index")?;
+        let index = self.number()? as usize;
+        Ok(PadSpec { entity, index })
+    }
+
+    fn link(&mut self) -> Result<LinkSpec> {
+        let source = self.pad()?;
+        self.expect("->", "expected '->'")?;
+        let sink = self.pad()?;
+        self.expect("[", "expected '[' followed by link flags")?;
+        let at = self.pos;
+        let bits = self.number()?;
+        let flags = MediaLinkFlags::from_bits(bits).ok_or(error::Error::LinkSpecParseError {
+            spec: self.spec.to_string(),
+            position: at,
+            reason: "unknown link flags",
+        })?;
+        self.expect("]", "expected ']'")?;
+        Ok(LinkSpec {
+            source,
+            sink,
+            flags,
+        })
+    }
+}
+
+impl FromStr for LinkSpec {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser::new(s);
+        let link = parser.link()?;
+        if parser.is_end() {
+            Ok(link)
+        } else {
+            Err(parser.error("unexpected trailing characters"))
+        }
+    }
+}
+
+impl LinkSpec {
+    /// Parse comma separated link descriptions as accepted by `media-ctl --links`.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// let specs = LinkSpec::parse_list("\"a\":0->\"b\":0[1], 5:1 -> 7:0 [0]").unwrap();
+    /// assert_eq!(specs.len(), 2);
+    /// assert_eq!(specs[1].source.entity, EntitySpec::Id(5.into()));
+    /// ```
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        let mut parser = Parser::new(s);
+        let mut links = vec![parser.link()?];
+        while parser.eat(",") {
+            links.push(parser.link()?);
+        }
+        if parser.is_end() {
+            Ok(links)
+        } else {
+            Err(parser.error("expected ',' or the end of the links"))
+        }
+    }
+
+    /// Find the pad referred by `spec` in `topology`.
+    fn find_pad<'a>(topology: &'a MediaTopology, spec: &PadSpec) -> Option<&'a MediaPad> {
+        let entity = topology
+            .entities_slice()
+            .iter()
+            .find(|entity| match &spec.entity {
+                EntitySpec::Id(id) => entity.id() == *id,
+                EntitySpec::Name(name) => entity.name() == name,
+            })?;
+        let mut pads = topology
+            .pads_slice()
+            .iter()
+            .filter(|pad| pad.entity_id == entity.id());
+        // pads are listed in the order of their indices if the media version has no pad index
+        if pads.clone().all(|pad| pad.index.is_some()) {
+            pads.find(|pad| pad.index == Some(spec.index))
+        } else {
+            pads.nth(spec.index)
+        }
+    }
+
+    /// Resolve the link described by `self` in `topology`.
+    ///
+    /// # Returns
+    /// The current state of the link, which can be passed to [`MediaLinkDesc::setup`] with [`self.flags`][LinkSpec::flags].
+    pub fn resolve(&self, topology: &MediaTopology) -> Result<MediaLinkDesc> {
+        let not_found = || error::Error::LinkNotFound {
+            spec: self.to_string(),
+        };
+        let source = Self::find_pad(topology, &self.source).ok_or_else(not_found)?;
+        let sink = Self::find_pad(topology, &self.sink).ok_or_else(not_found)?;
+        topology
+            .links_slice()
+            .iter()
+            .find(|link| match link.r#type() {
+                LinkType::DataLink { source_id, sink_id } => {
+                    *source_id == source.id && *sink_id == sink.id
+                }
+                _ => false,
+            })
+            .and_then(|link| topology.link_desc(link))
+            .ok_or_else(not_found)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_errors() {
+        let err = "\"a\":0->\"b\":0".parse::<LinkSpec>().unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::LinkSpecParseError { position: 12, .. }
+        ));
+        let err = "\"a\":0->\"b\":0[8]".parse::<LinkSpec>().unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::LinkSpecParseError {
+                position: 13,
+                reason: "unknown link flags",
+                ..
+            }
+        ));
+        let err = LinkSpec::parse_list("\"a\":0->\"b\":0[1] x").unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::LinkSpecParseError { position: 16, .. }
+        ));
+    }
+
+    #[test]
+    fn resolve_by_name_and_id() {
+        use crate::media_topology::test::{entity, link, pad};
+        use crate::raw as media;
+
+        let topology = MediaTopology::new(
+            None,
+            1,
+            Some(vec![entity(1, "sensor"), entity(3, "receiver")]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(5, 2, 4, 0)]),
+        );
+        let desc = "\"sensor\":0 -> 3:0 [1]"
+            .parse::<LinkSpec>()
+            .unwrap()
+            .resolve(&topology)
+            .unwrap();
+        assert_eq!(desc.source().id(), 1.into());
+        assert_eq!(desc.sink().id(), 3.into());
+        assert_eq!(desc.flags(), MediaLinkFlags::empty());
+
+        let err = "\"sensor\":1 -> 3:0 [1]"
+            .parse::<LinkSpec>()
+            .unwrap()
+            .resolve(&topology)
+            .unwrap_err();
+        assert!(matches!(err, error::Error::LinkNotFound { .. }));
+    }
+
+    #[test]
+    fn parse_hex_flags() {
+        let spec: LinkSpec = "1:0->2:0[0x3]".parse().unwrap();
+        assert_eq!(
+            spec.flags,
+            MediaLinkFlags::Enabled | MediaLinkFlags::Immutable
+        );
+    }
+}
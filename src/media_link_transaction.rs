@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::media::Media;
+use crate::media_backend::MediaBackend;
+use crate::media_link::MediaLinkFlags;
+use crate::media_link_desc::MediaLinkDesc;
+use crate::media_link_spec::LinkSpec;
+use crate::media_topology::MediaTopology;
+
+/// A set of link changes applied all together.
+///
+/// # Details
+/// Only the `Enabled` flag of each link is changed since the others are read-only.
+/// Every link is resolved against the topology when it is added, so that a misspelled link is reported before any link is changed.
+/// On [`commit`][LinkTransaction::commit], links are set up in the order they were added.
+/// If setting up a link fails, the links already changed are restored in the reverse order.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(media) = Media::from_path("/dev/media0") {
+///     let topology = media.new_topology()?;
+///     let mut transaction = LinkTransaction::new();
+///     for spec in LinkSpec::parse_list("\"imx219 10-0010\":0 -> \"unicam-image\":0 [1]")? {
+///         transaction.add(&topology, &spec)?;
+///     }
+///     transaction.commit(media.device_fd())?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkTransaction {
+    /// The current state of links and flags to set.
+    links: Vec<(MediaLinkDesc, MediaLinkFlags)>,
+}
+
+impl LinkTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the link described by `spec`.
+    ///
+    /// * `topology`: The topology in which the link is looked up.
+    /// * `spec`: The link and the flags to set.
+    pub fn add(&mut self, topology: &MediaTopology, spec: &LinkSpec) -> Result<&mut Self> {
+        let desc = spec.resolve(topology)?;
+        self.links.push((desc, spec.flags));
+        Ok(self)
+    }
+
+    /// Add the link `desc` to be set up with `flags`.
+    pub fn add_desc(&mut self, desc: MediaLinkDesc, flags: MediaLinkFlags) -> &mut Self {
+        self.links.push((desc, flags));
+        self
+    }
+
+    /// Number of links to be set up.
+    pub fn len(&self) -> usize {
+        self.links.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// Set up all the links.
+    ///
+    /// # Returns
+    /// The links updated by the driver, in the order they were added.
+    /// On failure, the error of the failed link is returned after the links already changed are restored.
+    /// If some of them could not be restored, [`Error::PartialLinkSetup`][error::Error::PartialLinkSetup] is returned instead.
+    pub fn commit<B>(self, fd: B) -> Result<Vec<MediaLinkDesc>>
+    where
+        B: MediaBackend,
+    {
+        let mut applied: Vec<(MediaLinkDesc, MediaLinkDesc)> = vec![];
+        for (desc, flags) in self.links {
+            // the kernel rejects changes of flags other than `Enabled`
+            let flags = desc.flags().difference(MediaLinkFlags::Enabled)
+                | flags.intersection(MediaLinkFlags::Enabled);
+            let mut updated = desc;
+            if let Err(err) = updated.setup(&fd, flags) {
+                crate::logging::debug!(
+                    "rolling back {} links after a failed setup: {}",
+                    applied.len(),
+                    err
+                );
+                let mut unrestored = vec![];
+                for (original, mut updated) in applied.into_iter().rev() {
+                    if updated.setup(&fd, original.flags()).is_err() {
+                        unrestored.push(updated);
+                    }
+                }
+                return Err(if unrestored.is_empty() {
+                    err
+                } else {
+                    error::Error::PartialLinkSetup {
+                        source: Box::new(err),
+                        unrestored,
+                    }
+                });
+            }
+            applied.push((desc, updated));
+        }
+        Ok(applied.into_iter().map(|(_, updated)| updated).collect())
+    }
+}
+
+/// The result of [`Media::reset_links`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct LinkReset {
+    /// Links set up as disabled, in the state updated by the driver.
+    #[serde(rename = "disabled")]
+    pub disabled: Vec<MediaLinkDesc>,
+    /// Links left as they are because they are immutable.
+    #[serde(rename = "immutable")]
+    pub immutable: Vec<MediaLinkDesc>,
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Set up the links described by `specs` in a [`LinkTransaction`].
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     media.setup_links(&LinkSpec::parse_list("\"imx219 10-0010\":0 -> \"unicam-image\":0 [1]")?)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn setup_links(&self, specs: &[LinkSpec]) -> Result<Vec<MediaLinkDesc>> {
+        let topology = self.new_topology()?;
+        let mut transaction = LinkTransaction::new();
+        for spec in specs {
+            transaction.add(&topology, spec)?;
+        }
+        transaction.commit(self.backend())
+    }
+
+    /// Disable all the data links except immutable ones, as `media-ctl --reset` does.
+    ///
+    /// # Details
+    /// Links already disabled are set up again. The links are changed in a [`LinkTransaction`],
+    /// which restores the links already disabled if disabling one of them fails.
+    /// Restoring is best-effort as the kernel can not change links atomically:
+    /// links which could not be restored either are reported by [`PartialLinkSetup`][crate::error::Error::PartialLinkSetup].
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let reset = media.reset_links()?;
+    ///     assert!(reset.disabled.iter().all(|link| !link.flags().contains(MediaLinkFlags::Enabled)));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reset_links(&self) -> Result<LinkReset> {
+        let topology = self.new_topology()?;
+        let mut transaction = LinkTransaction::new();
+        let mut immutable = vec![];
+        for desc in topology
+            .links_slice()
+            .iter()
+            .filter_map(|link| topology.link_desc(link))
+        {
+            if desc.flags().contains(MediaLinkFlags::Immutable) {
+                immutable.push(desc);
+            } else {
+                transaction.add_desc(desc, MediaLinkFlags::empty());
+            }
+        }
+        Ok(LinkReset {
+            disabled: transaction.commit(self.backend())?,
+            immutable,
+        })
+    }
+}
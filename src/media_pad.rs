@@ -1,44 +1,122 @@
 use derive_more::{Display, From, Into};
-use linux_media_sys as media;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::error;
+use crate::media_api_capabilities::MediaApiCapabilities;
 use crate::media_entity::EntityId;
+use crate::raw as media;
 use crate::version::Version;
 
 #[derive(
-    Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, From, Into, Display, Serialize, Deserialize,
+    Debug,
+    Copy,
+    Clone,
+    PartialEq,
+    PartialOrd,
+    Eq,
+    Ord,
+    Hash,
+    From,
+    Into,
+    Display,
+    Serialize,
+    Deserialize,
 )]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct PadId(u32);
 
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
-pub enum MediaPadFlags {
-    /// Input pad, relative to the entity. Input pads sink data and are targets of links.
-    Sink,
-    /// Output pad, relative to the entity. Output pads source data and are origins of links.
-    Source,
-    /// The pad is an input pad and the pad is linked to any other pad, then at least one of those links must be enabled for the entity to be able to stream. There could be temporary reasons (e.g. device configuration dependent) for the pad to need enabled links even when this flag isn’t set; the absence of the flag doesn’t imply there is none.
-    SinkMustConnect,
-    /// The pad is an output pad and the pad is linked to any other pad, then at least one of those links must be enabled for the entity to be able to stream. There could be temporary reasons (e.g. device configuration dependent) for the pad to need enabled links even when this flag isn’t set; the absence of the flag doesn’t imply there is none.
-    SourceMustConnect,
+/// `MEDIA_PAD_FL_INTERNAL`, added in Linux 6.10.
+const MEDIA_PAD_FL_INTERNAL: u32 = 1 << 3;
+
+bitflags::bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+    pub struct MediaPadFlags: u32 {
+        /// Input pad, relative to the entity. Input pads sink data and are targets of links.
+        const Sink = media::MEDIA_PAD_FL_SINK;
+        /// Output pad, relative to the entity. Output pads source data and are origins of links.
+        const Source = media::MEDIA_PAD_FL_SOURCE;
+        /// If the pad is linked to any other pad, then at least one of those links must be enabled for the entity to be able to stream. There could be temporary reasons (e.g. device configuration dependent) for the pad to need enabled links even when this flag isn’t set; the absence of the flag doesn’t imply there is none.
+        const MustConnect = media::MEDIA_PAD_FL_MUST_CONNECT;
+        /// The internal flag indicates an internal pad that has no external connections. Such a pad shall not be connected with a link. The internal flag may currently be present only in a sink pad.
+        const Internal = MEDIA_PAD_FL_INTERNAL;
+
+        // bits unknown to this crate are kept
+        const _ = !0;
+    }
+}
+
+impl MediaPadFlags {
+    pub fn is_sink(&self) -> bool {
+        self.contains(Self::Sink)
+    }
+
+    pub fn is_source(&self) -> bool {
+        self.contains(Self::Source)
+    }
+
+    pub fn must_connect(&self) -> bool {
+        self.contains(Self::MustConnect)
+    }
+
+    pub fn is_internal(&self) -> bool {
+        self.contains(Self::Internal)
+    }
+
+    /// The name pad flags were serialized as when they were an enum, for the combinations it could represent.
+    fn legacy_name(&self) -> Option<&'static str> {
+        const SINK_MUST_CONNECT: u32 = media::MEDIA_PAD_FL_SINK | media::MEDIA_PAD_FL_MUST_CONNECT;
+        const SOURCE_MUST_CONNECT: u32 =
+            media::MEDIA_PAD_FL_SOURCE | media::MEDIA_PAD_FL_MUST_CONNECT;
+        match self.bits() {
+            media::MEDIA_PAD_FL_SINK => Some("Sink"),
+            media::MEDIA_PAD_FL_SOURCE => Some("Source"),
+            SINK_MUST_CONNECT => Some("SinkMustConnect"),
+            SOURCE_MUST_CONNECT => Some("SourceMustConnect"),
+            _ => None,
+        }
+    }
+}
+
+/// Pad flags are serialized as the variant names of the former enum, such as `"SinkMustConnect"`, if they are one of them,
+/// so that documents written before keep round-tripping. Other combinations are serialized as `"Sink | Internal"`.
+impl Serialize for MediaPadFlags {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.legacy_name() {
+            Some(name) if serializer.is_human_readable() => serializer.serialize_str(name),
+            _ => bitflags::serde::serialize(self, serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MediaPadFlags {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            return bitflags::serde::deserialize(deserializer);
+        }
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "SinkMustConnect" => Ok(Self::Sink | Self::MustConnect),
+            "SourceMustConnect" => Ok(Self::Source | Self::MustConnect),
+            _ => bitflags::parser::from_str(&name).map_err(serde::de::Error::custom),
+        }
+    }
 }
 
+#[cfg(feature = "schemars")]
+crate::schema::bitflags_schema!(MediaPadFlags);
+
 impl TryFrom<u32> for MediaPadFlags {
     type Error = error::Error;
     fn try_from(v: u32) -> error::Result<Self> {
-        use MediaPadFlags::*;
-        if v & media::MEDIA_PAD_FL_SINK != 0 {
-            if v & media::MEDIA_PAD_FL_MUST_CONNECT != 0 {
-                Ok(SinkMustConnect)
-            } else {
-                Ok(Sink)
-            }
-        } else if v & media::MEDIA_PAD_FL_SOURCE != 0 {
-            if v & media::MEDIA_PAD_FL_MUST_CONNECT != 0 {
-                Ok(SourceMustConnect)
-            } else {
-                Ok(Source)
-            }
+        let flags = MediaPadFlags::from_bits_retain(v);
+        if flags.intersects(MediaPadFlags::Sink | MediaPadFlags::Source) {
+            Ok(flags)
         } else {
             Err(error::Error::PadFlagsParseError { from: v })
         }
@@ -47,42 +125,125 @@ impl TryFrom<u32> for MediaPadFlags {
 
 impl From<MediaPadFlags> for u32 {
     fn from(flags: MediaPadFlags) -> u32 {
-        use MediaPadFlags::*;
-        match flags {
-            Sink => media::MEDIA_PAD_FL_SINK,
-            Source => media::MEDIA_PAD_FL_SOURCE,
-            SinkMustConnect => media::MEDIA_PAD_FL_SINK | media::MEDIA_PAD_FL_MUST_CONNECT,
-            SourceMustConnect => media::MEDIA_PAD_FL_SOURCE | media::MEDIA_PAD_FL_MUST_CONNECT,
-        }
+        flags.bits()
     }
 }
 
 #[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaPad {
     /// Unique ID for the pad. Do not expect that the ID will always be the same for each instance of the device. In other words, do not hardcode pad IDs in an application.
+    #[serde(rename = "id")]
     pub id: PadId,
     /// Unique ID for the entity where this pad belongs.
+    #[serde(rename = "entity_id")]
     pub entity_id: EntityId,
+    #[serde(rename = "flags")]
     pub flags: MediaPadFlags,
     /// Pad index, starts at 0. Only valid if [has_index(media_version)][MediaPad::has_index] returns true.
+    #[serde(rename = "index")]
     pub index: Option<usize>,
 }
 
 impl MediaPad {
     pub fn has_index(media_version: Version) -> bool {
-        media::MEDIA_V2_PAD_HAS_INDEX(Into::<u32>::into(media_version).into())
+        MediaApiCapabilities::new(media_version).has_pad_index()
     }
 
-    pub fn from(version: Version, pad: media::media_v2_pad) -> Self {
-        Self {
+    /// Convert from the raw struct filled by `MEDIA_IOC_G_TOPOLOGY`.
+    ///
+    /// # Details
+    /// The index is only read if `capabilities` reports it, as it is not filled by older kernels.
+    ///
+    /// # Errors
+    /// [`PadFlagsParseError`][error::Error::PadFlagsParseError] if the pad is neither a sink nor a source.
+    pub fn from_raw<C>(capabilities: C, pad: media::media_v2_pad) -> error::Result<Self>
+    where
+        C: Into<MediaApiCapabilities>,
+    {
+        Self::try_from(capabilities, pad)
+    }
+
+    /// Convert into the raw struct to pass to ioctls not wrapped by this crate.
+    pub fn to_raw(&self) -> media::media_v2_pad {
+        let mut raw: media::media_v2_pad = unsafe { std::mem::zeroed() };
+        raw.id = self.id.into();
+        raw.entity_id = self.entity_id.into();
+        raw.flags = self.flags.into();
+        raw.index = self.index.unwrap_or(0) as u32;
+        raw
+    }
+
+    /// The same as [`from_raw`][Self::from_raw].
+    pub fn try_from<C>(capabilities: C, pad: media::media_v2_pad) -> error::Result<Self>
+    where
+        C: Into<MediaApiCapabilities>,
+    {
+        Ok(Self {
             id: pad.id.into(),
             entity_id: pad.entity_id.into(),
-            flags: pad.flags.try_into().unwrap(),
-            index: if Self::has_index(version) {
+            flags: pad.flags.try_into()?,
+            index: if capabilities.into().has_pad_index() {
                 Some(pad.index as usize)
             } else {
                 None
             },
-        }
+        })
+    }
+}
+
+impl From<MediaPad> for media::media_v2_pad {
+    fn from(pad: MediaPad) -> media::media_v2_pad {
+        pad.to_raw()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pad_flags_keep_unknown_bits_and_legacy_names() {
+        let flags =
+            MediaPadFlags::try_from(media::MEDIA_PAD_FL_SINK | MEDIA_PAD_FL_INTERNAL | 0x100)
+                .unwrap();
+        assert!(flags.is_sink() && flags.is_internal() && !flags.must_connect());
+        assert_eq!(u32::from(flags), 0x109);
+        assert!(MediaPadFlags::try_from(MEDIA_PAD_FL_INTERNAL).is_err());
+
+        let flags = MediaPadFlags::Source | MediaPadFlags::MustConnect;
+        assert_eq!(
+            serde_json::to_value(flags).unwrap(),
+            serde_json::json!("SourceMustConnect")
+        );
+        assert_eq!(
+            serde_json::from_str::<MediaPadFlags>("\"SourceMustConnect\"").unwrap(),
+            flags
+        );
+        let flags = MediaPadFlags::Sink | MediaPadFlags::Internal;
+        let json = serde_json::to_string(&flags).unwrap();
+        assert_eq!(json, "\"Sink | Internal\"");
+        assert_eq!(serde_json::from_str::<MediaPadFlags>(&json).unwrap(), flags);
+        assert_eq!(
+            serde_json::from_str::<MediaPadFlags>("\"Sink | MustConnect\"").unwrap(),
+            MediaPadFlags::Sink | MediaPadFlags::MustConnect
+        );
+    }
+
+    #[test]
+    fn pads_without_direction_fail_to_convert() {
+        let mut raw: media::media_v2_pad = unsafe { std::mem::zeroed() };
+        raw.id = 2;
+        raw.entity_id = 1;
+        raw.index = 1;
+        assert!(matches!(
+            MediaPad::from_raw(Version::new(6, 1, 0), raw),
+            Err(error::Error::PadFlagsParseError { from: 0 })
+        ));
+
+        raw.flags = media::MEDIA_PAD_FL_SOURCE;
+        let pad = MediaPad::from_raw(Version::new(6, 1, 0), raw).unwrap();
+        assert!(pad.flags.is_source());
+        assert_eq!(pad.index, Some(1));
     }
 }
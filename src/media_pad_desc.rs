@@ -1,13 +1,17 @@
-use linux_media_sys as media;
 use serde::{Deserialize, Serialize};
 
+use crate::raw as media;
 use crate::EntityId;
 use crate::MediaPadFlags;
 
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaPadDesc {
+    #[serde(rename = "entity")]
     entity: EntityId,
+    #[serde(rename = "index")]
     index: usize,
+    #[serde(rename = "flags")]
     flags: MediaPadFlags,
 }
 
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error::Result;
+use crate::media::Media;
+use crate::media_backend::MediaBackend;
+use crate::media_link::MediaLinkFlags;
+use crate::media_link_desc::MediaLinkDesc;
+use crate::media_link_spec::{EntitySpec, LinkSpec, PadSpec};
+use crate::media_pad_desc::MediaPadDesc;
+use crate::media_topology::MediaTopology;
+
+/// A link configuration of a media device, which can be saved and applied later.
+///
+/// # Details
+/// Links are referred by entity names so that the configuration stays valid across boots.
+/// Each link is serialized as a string in the syntax of `media-ctl --links`, which keeps files written in TOML or JSON readable:
+///
+/// ```toml
+/// model = "unicam"
+/// bus_info = "platform:fe801000.csi"
+/// links = [
+///     '"imx219 10-0010":0->"unicam-image":0[1]',
+/// ]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PipelineConfig {
+    /// The model of the device the configuration was captured from.
+    #[serde(rename = "model", default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// The bus info of the device the configuration was captured from.
+    #[serde(rename = "bus_info", default, skip_serializing_if = "Option::is_none")]
+    pub bus_info: Option<String>,
+    /// Links to set up, in this order.
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<String>"))]
+    #[serde(rename = "links", with = "link_specs")]
+    pub links: Vec<LinkSpec>,
+}
+
+/// (De)serialize link specs as strings in the syntax of `media-ctl --links`.
+mod link_specs {
+    use super::*;
+
+    pub fn serialize<S>(links: &[LinkSpec], serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(links.iter().map(|link| link.to_string()))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> std::result::Result<Vec<LinkSpec>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|link| link.parse().map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
+impl PipelineConfig {
+    /// Capture the state of the links which can be changed.
+    ///
+    /// # Details
+    /// Every data link other than immutable ones is captured whether enabled or not,
+    /// so that applying the configuration also disables the links enabled since.
+    /// Disabled links are listed first, since enabling a link may fail while another link to the same sink pad is enabled.
+    pub fn capture(topology: &MediaTopology) -> Self {
+        let name = |desc: MediaPadDesc| {
+            topology
+                .entities_slice()
+                .iter()
+                .find(|entity| entity.id() == desc.id())
+                .map(|entity| EntitySpec::Name(entity.name().to_string()))
+                .unwrap_or(EntitySpec::Id(desc.id()))
+        };
+        let spec = |desc: &MediaLinkDesc| LinkSpec {
+            source: PadSpec {
+                entity: name(desc.source()),
+                index: desc.source().index(),
+            },
+            sink: PadSpec {
+                entity: name(desc.sink()),
+                index: desc.sink().index(),
+            },
+            flags: desc.flags().intersection(MediaLinkFlags::Enabled),
+        };
+        let mut links: Vec<LinkSpec> = topology
+            .links_slice()
+            .iter()
+            .filter_map(|link| topology.link_desc(link))
+            .filter(|desc| !desc.flags().contains(MediaLinkFlags::Immutable))
+            .map(|desc| spec(&desc))
+            .collect();
+        links.sort_by_key(|link| link.flags.contains(MediaLinkFlags::Enabled));
+        Self {
+            model: None,
+            bus_info: None,
+            links,
+        }
+    }
+
+    /// Capture the link configuration of `media` with its model and bus info.
+    pub fn from_media<B>(media: &Media<B>) -> Result<Self>
+    where
+        B: MediaBackend,
+    {
+        Ok(Self {
+            model: Some(media.info().model().to_string()),
+            bus_info: Some(media.info().bus_info().to_string()),
+            ..Self::capture(&media.new_topology()?)
+        })
+    }
+
+    /// Set up the links of `media` in a [`LinkTransaction`][crate::LinkTransaction].
+    pub fn apply<B>(&self, media: &Media<B>) -> Result<Vec<MediaLinkDesc>>
+    where
+        B: MediaBackend,
+    {
+        media.setup_links(&self.links)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::raw as media;
+
+    #[test]
+    fn capture_skips_immutable_links() {
+        let mut csi_source = pad(5, 3, media::MEDIA_PAD_FL_SOURCE);
+        csi_source.index = Some(1);
+        let topology = MediaTopology::new(
+            None,
+            1,
+            Some(vec![
+                entity(1, "sensor"),
+                entity(3, "csi"),
+                entity(6, "dma"),
+            ]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+                csi_source,
+                pad(7, 6, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![
+                link(
+                    8,
+                    2,
+                    4,
+                    media::MEDIA_LNK_FL_ENABLED | media::MEDIA_LNK_FL_IMMUTABLE,
+                ),
+                link(9, 5, 7, media::MEDIA_LNK_FL_ENABLED),
+            ]),
+        );
+        let config = PipelineConfig::capture(&topology);
+        assert_eq!(config.links.len(), 1);
+        assert_eq!(config.links[0].to_string(), "\"csi\":1->\"dma\":0[1]");
+
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["links"][0], "\"csi\":1->\"dma\":0[1]");
+        assert_eq!(
+            serde_json::from_value::<PipelineConfig>(json).unwrap(),
+            config
+        );
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn schema_of_links_is_strings() {
+        let schema = serde_json::to_value(schemars::schema_for!(PipelineConfig)).unwrap();
+        let links = &schema["properties"]["links"];
+        assert_eq!(links["type"], "array");
+        assert_eq!(links["items"]["type"], "string");
+
+        let schema = serde_json::to_value(schemars::schema_for!(MediaTopology)).unwrap();
+        assert_eq!(schema["definitions"]["MediaLinkFlags"]["type"], "string");
+    }
+}
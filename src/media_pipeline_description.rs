@@ -0,0 +1,328 @@
+use std::collections::{BTreeSet, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+use crate::media_entity::{EntityId, MediaEntity, MediaEntityFunctions};
+use crate::media_interface_type::MediaInterfaceType;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::media_link::MediaLinkFlags;
+use crate::media_link_desc::MediaLinkDesc;
+use crate::media_topology::MediaTopology;
+
+/// The part an entity plays in a camera pipeline, as camera stacks such as libcamera classify them.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum PipelineRole {
+    /// A camera sensor, where a pipeline starts.
+    #[serde(rename = "sensor")]
+    Sensor,
+    /// A receiver or a multiplexer of video buses, such as a CSI-2 receiver.
+    #[serde(rename = "bridge")]
+    Bridge,
+    /// An image signal processor, i.e. a block processing the pixels such as a scaler or a statistics engine.
+    #[serde(rename = "isp")]
+    Isp,
+    /// A video device, where frames are captured into memory.
+    #[serde(rename = "capture")]
+    Capture,
+    /// An entity of any other function, such as a lens or a flash controller.
+    #[serde(rename = "other")]
+    Other,
+}
+
+impl From<MediaEntityFunctions> for PipelineRole {
+    fn from(function: MediaEntityFunctions) -> Self {
+        use MediaEntityFunctions::*;
+        match function {
+            CAMSensor => PipelineRole::Sensor,
+            VIDIFBridge | VIDMux => PipelineRole::Bridge,
+            ProcVideoComposer
+            | ProcVideoPixelFormatter
+            | ProcVideoPixelEncConv
+            | ProcVideoLUT
+            | ProcVideoScaler
+            | ProcVideoStatistics => PipelineRole::Isp,
+            #[cfg(has_linux_media_sys__MEDIA_ENT_F_PROC_VIDEO_ISP)]
+            ProcVideoISP => PipelineRole::Isp,
+            IoV4L => PipelineRole::Capture,
+            _ => PipelineRole::Other,
+        }
+    }
+}
+
+/// An entity of a pipeline and the device nodes through which it is driven.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PipelineEntity {
+    #[serde(rename = "name")]
+    name: String,
+    #[serde(rename = "role")]
+    role: PipelineRole,
+    #[serde(rename = "function")]
+    function: MediaEntityFunctions,
+    /// The device node of the V4L2 subdevice interface such like `/dev/v4l-subdev0`.
+    #[serde(rename = "subdev")]
+    subdev: Option<MediaIntfDevnode>,
+    /// The device node of the V4L2 video interface such like `/dev/video0`.
+    #[serde(rename = "video")]
+    video: Option<MediaIntfDevnode>,
+}
+
+impl PipelineEntity {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn role(&self) -> PipelineRole {
+        self.role
+    }
+
+    pub fn function(&self) -> MediaEntityFunctions {
+        self.function
+    }
+
+    pub fn subdev(&self) -> Option<MediaIntfDevnode> {
+        self.subdev
+    }
+
+    pub fn video(&self) -> Option<MediaIntfDevnode> {
+        self.video
+    }
+}
+
+/// A data link between two entities of a pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PipelineLink {
+    /// The name of the source entity and the index of the source pad.
+    #[serde(rename = "source")]
+    source: (String, usize),
+    /// The name of the sink entity and the index of the sink pad.
+    #[serde(rename = "sink")]
+    sink: (String, usize),
+    #[serde(rename = "enabled")]
+    enabled: bool,
+    /// `false` if the link is immutable, i.e. it can be neither enabled nor disabled by the user space.
+    #[serde(rename = "configurable")]
+    configurable: bool,
+}
+
+impl PipelineLink {
+    pub fn source(&self) -> (&str, usize) {
+        (&self.source.0, self.source.1)
+    }
+
+    pub fn sink(&self) -> (&str, usize) {
+        (&self.sink.0, self.sink.1)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn configurable(&self) -> bool {
+        self.configurable
+    }
+}
+
+/// The entities reachable from a camera sensor by data links, e.g. sensor → CSI-2 receiver → ISP → video devices.
+///
+/// # Details
+/// The description carries what a higher-level camera stack needs to tell whether it can drive the hardware:
+/// the role of each entity, the subdevice and video device nodes, and which links are fixed by the driver.
+/// Every link is followed regardless of whether it is enabled, since a stack may set up the links itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PipelineDescription {
+    /// The name of the sensor the pipeline starts from.
+    #[serde(rename = "sensor")]
+    sensor: String,
+    /// The entities from the sensor in the order of the distance from it.
+    #[serde(rename = "entities")]
+    entities: Vec<PipelineEntity>,
+    #[serde(rename = "links")]
+    links: Vec<PipelineLink>,
+}
+
+impl PipelineDescription {
+    pub fn sensor(&self) -> &str {
+        &self.sensor
+    }
+
+    pub fn entities(&self) -> &[PipelineEntity] {
+        &self.entities
+    }
+
+    pub fn links(&self) -> &[PipelineLink] {
+        &self.links
+    }
+
+    /// The video devices capturing the frames of the sensor.
+    pub fn captures(&self) -> impl Iterator<Item = &PipelineEntity> {
+        self.entities
+            .iter()
+            .filter(|entity| entity.role == PipelineRole::Capture)
+    }
+}
+
+impl MediaTopology {
+    fn entity_by_id(&self, id: EntityId) -> Option<&MediaEntity> {
+        self.entities_slice()
+            .iter()
+            .find(|entity| entity.id() == id)
+    }
+
+    fn pipeline_description(&self, sensor: &MediaEntity) -> PipelineDescription {
+        let descs: Vec<MediaLinkDesc> = self
+            .links_slice()
+            .iter()
+            .filter_map(|link| self.link_desc(link))
+            .collect();
+
+        let mut visited = BTreeSet::from([sensor.id()]);
+        let mut queue = VecDeque::from([sensor.id()]);
+        let mut entities = vec![];
+        let mut links = vec![];
+        while let Some(id) = queue.pop_front() {
+            let Some(entity) = self.entity_by_id(id) else {
+                continue;
+            };
+            entities.push(PipelineEntity {
+                name: entity.name().to_string(),
+                role: entity.function().into(),
+                function: entity.function(),
+                subdev: self.interface_devnode(id, MediaInterfaceType::V4LSubdev),
+                video: self.interface_devnode(id, MediaInterfaceType::V4LVideo),
+            });
+            for desc in descs.iter().filter(|desc| desc.source().id() == id) {
+                let sink = desc.sink().id();
+                let Some(sink_entity) = self.entity_by_id(sink) else {
+                    continue;
+                };
+                links.push(PipelineLink {
+                    source: (entity.name().to_string(), desc.source().index()),
+                    sink: (sink_entity.name().to_string(), desc.sink().index()),
+                    enabled: desc.flags().contains(MediaLinkFlags::Enabled),
+                    configurable: !desc.flags().contains(MediaLinkFlags::Immutable),
+                });
+                if visited.insert(sink) {
+                    queue.push_back(sink);
+                }
+            }
+        }
+        PipelineDescription {
+            sensor: sensor.name().to_string(),
+            entities,
+            links,
+        }
+    }
+
+    /// Describe the pipelines starting from each camera sensor for camera stacks such as libcamera.
+    ///
+    /// # Details
+    /// See [`PipelineDescription`] for the contents.
+    /// The pipelines are in the order of the sensors in the topology.
+    pub fn pipeline_descriptions(&self) -> Vec<PipelineDescription> {
+        self.entities_slice()
+            .iter()
+            .filter(|entity| PipelineRole::from(entity.function()) == PipelineRole::Sensor)
+            .map(|sensor| self.pipeline_description(sensor))
+            .collect()
+    }
+
+    /// Serialize [`pipeline_descriptions`][Self::pipeline_descriptions] into JSON.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     println!("{}", media.new_topology()?.pipelines_to_json().unwrap());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pipelines_to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.pipeline_descriptions())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MediaPadFlags, MockTopologyBuilder};
+
+    #[test]
+    fn describes_sensor_to_capture() {
+        let mut builder = MockTopologyBuilder::new();
+        let (sensor, sensor_pads) = builder.entity(
+            "imx219 10-0010",
+            MediaEntityFunctions::CAMSensor,
+            &[MediaPadFlags::Source],
+        );
+        let (csi, csi_pads) = builder.entity(
+            "csi",
+            MediaEntityFunctions::VIDIFBridge,
+            &[MediaPadFlags::Sink, MediaPadFlags::Source],
+        );
+        let (isp, isp_pads) = builder.entity(
+            "isp",
+            MediaEntityFunctions::ProcVideoScaler,
+            &[MediaPadFlags::Sink, MediaPadFlags::Source],
+        );
+        let (video, video_pads) =
+            builder.entity("video", MediaEntityFunctions::IoV4L, &[MediaPadFlags::Sink]);
+        builder.entity("lens", MediaEntityFunctions::Lens, &[]);
+        let fixed = MediaLinkFlags::Enabled | MediaLinkFlags::Immutable;
+        builder.link(sensor_pads[0], csi_pads[0], fixed);
+        builder.link(csi_pads[1], isp_pads[0], MediaLinkFlags::empty());
+        builder.link(isp_pads[1], video_pads[0], fixed);
+        let devnode = |minor| MediaIntfDevnode { major: 81, minor };
+        builder.interface(MediaInterfaceType::V4LSubdev, devnode(1), sensor);
+        builder.interface(MediaInterfaceType::V4LSubdev, devnode(2), csi);
+        builder.interface(MediaInterfaceType::V4LSubdev, devnode(3), isp);
+        builder.interface(MediaInterfaceType::V4LVideo, devnode(0), video);
+        let topology = builder.build();
+
+        let [pipeline] = &topology.pipeline_descriptions()[..] else {
+            panic!("one pipeline is expected");
+        };
+        assert_eq!(pipeline.sensor(), "imx219 10-0010");
+        let roles: Vec<_> = pipeline
+            .entities()
+            .iter()
+            .map(|entity| (entity.name(), entity.role()))
+            .collect();
+        assert_eq!(
+            roles,
+            [
+                ("imx219 10-0010", PipelineRole::Sensor),
+                ("csi", PipelineRole::Bridge),
+                ("isp", PipelineRole::Isp),
+                ("video", PipelineRole::Capture),
+            ]
+        );
+        let captures: Vec<_> = pipeline.captures().collect();
+        assert_eq!(captures.len(), 1);
+        assert_eq!(captures[0].video(), Some(devnode(0)));
+        assert_eq!(captures[0].subdev(), None);
+        let links: Vec<_> = pipeline
+            .links()
+            .iter()
+            .map(|link| (link.source(), link.enabled(), link.configurable()))
+            .collect();
+        assert_eq!(
+            links,
+            [
+                (("imx219 10-0010", 0), true, false),
+                (("csi", 1), false, true),
+                (("isp", 1), true, false),
+            ]
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&topology.pipelines_to_json().unwrap()).unwrap();
+        assert_eq!(json[0]["entities"][2]["role"], "isp");
+        assert_eq!(json[0]["links"][1]["sink"], serde_json::json!(["isp", 0]));
+    }
+}
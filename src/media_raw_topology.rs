@@ -0,0 +1,880 @@
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::ops::ControlFlow;
+
+use crate::collections::{Entities, Interfaces, Links, Pads};
+use crate::error::Result;
+use crate::media::Media;
+use crate::media_api_capabilities::MediaApiCapabilities;
+use crate::media_backend::{MediaBackend, TopologyArrays};
+use crate::media_device_info::MediaDeviceInfo;
+use crate::media_entity::{EntityId, MediaEntity, MediaEntityFlags, MediaEntityFunctions};
+use crate::media_interface::{InterfaceId, MediaInterface};
+use crate::media_interface_type::MediaInterfaceType;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::media_link::{LinkId, LinkType, MediaLink, MediaLinkFlags};
+use crate::media_pad::{MediaPad, MediaPadFlags, PadId};
+use crate::media_topology::MediaTopology;
+use crate::media_topology_builder::MediaTopologyBuilder;
+use crate::name_interner::NameInterner;
+use crate::raw as media;
+use crate::version::Version;
+
+/// A topology holding the arrays filled by [`MEDIA_IOC_G_TOPOLOGY`][media::MEDIA_IOC_G_TOPOLOGY] as they are.
+///
+/// # Details
+/// Unlike [`MediaTopology`], no object is converted when fetched.
+/// Objects are accessed through views such as [`RawEntity`], which convert only the fields asked for.
+/// Functions, flags and types are parsed only when accessed,
+/// and predicates such as [`RawLink::is_enabled`] test the bits without parsing them at all.
+/// This suits monitors fetching a topology frequently and looking at a few fields of it.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(media) = Media::from_path("/dev/media0") {
+///     let raw = media.new_raw_topology()?;
+///     let enabled = raw
+///         .links()
+///         .filter(|link| link.is_enabled())
+///         .count();
+///     assert!(enabled <= raw.links().len());
+///     assert_eq!(raw.to_topology()?, media.new_topology()?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct RawTopology {
+    pub(crate) media_version: Version,
+    pub(crate) capabilities: MediaApiCapabilities,
+    pub(crate) version: u64,
+    /// A single buffer holding all the arrays one after another.
+    arena: Vec<u8>,
+    entities: Option<Section>,
+    interfaces: Option<Section>,
+    pads: Option<Section>,
+    links: Option<Section>,
+}
+
+/// An array in the arena of [`RawTopology`].
+#[derive(Debug, Clone, Copy)]
+struct Section {
+    /// The offset in bytes
+    offset: usize,
+    /// The number of items
+    len: usize,
+}
+
+// The arrays are placed without padding, which is valid only for packed types.
+const _: () = assert!(
+    std::mem::align_of::<media::media_v2_entity>() == 1
+        && std::mem::align_of::<media::media_v2_interface>() == 1
+        && std::mem::align_of::<media::media_v2_pad>() == 1
+        && std::mem::align_of::<media::media_v2_link>() == 1
+);
+
+impl std::fmt::Debug for RawTopology {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let len = |section: Option<Section>| section.map(|section| section.len);
+        f.debug_struct("RawTopology")
+            .field("media_version", &self.media_version)
+            .field("version", &self.version)
+            .field("entities", &len(self.entities))
+            .field("interfaces", &len(self.interfaces))
+            .field("pads", &len(self.pads))
+            .field("links", &len(self.links))
+            .finish()
+    }
+}
+
+impl RawTopology {
+    /// Construct a [`RawTopology`].
+    /// This function is provided solely for use by [`MediaTopologyBuilder`].
+    pub(crate) fn new(
+        media_version: Version,
+        version: u64,
+        entities: Option<Vec<media::media_v2_entity>>,
+        interfaces: Option<Vec<media::media_v2_interface>>,
+        pads: Option<Vec<media::media_v2_pad>>,
+        links: Option<Vec<media::media_v2_link>>,
+    ) -> Self {
+        let mut raw = Self {
+            media_version,
+            capabilities: media_version.into(),
+            version,
+            arena: vec![],
+            entities: None,
+            interfaces: None,
+            pads: None,
+            links: None,
+        };
+        fn len<T>(xs: &Option<Vec<T>>) -> Option<u32> {
+            xs.as_ref().map(|xs| xs.len() as u32)
+        }
+        raw.reserve(len(&entities), len(&interfaces), len(&pads), len(&links));
+        fn copy<T>(arena: &mut [u8], section: Option<Section>, xs: Option<Vec<T>>) {
+            if let (Some(section), Some(xs)) = (section, xs) {
+                let bytes = xs.len() * std::mem::size_of::<T>();
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        xs.as_ptr() as *const u8,
+                        arena[section.offset..section.offset + bytes].as_mut_ptr(),
+                        bytes,
+                    )
+                };
+            }
+        }
+        copy(&mut raw.arena, raw.entities, entities);
+        copy(&mut raw.arena, raw.interfaces, interfaces);
+        copy(&mut raw.arena, raw.pads, pads);
+        copy(&mut raw.arena, raw.links, links);
+        raw
+    }
+
+    /// Lay out zeroed arrays of the numbers of items in the arena, reusing its capacity.
+    /// Arrays given `None` are dropped.
+    ///
+    /// # Returns
+    /// The arrays of entities, interfaces, pads and links to be filled by the backend.
+    pub(crate) fn reserve(
+        &mut self,
+        entities: Option<u32>,
+        interfaces: Option<u32>,
+        pads: Option<u32>,
+        links: Option<u32>,
+    ) -> TopologyArrays<'_> {
+        let mut size = 0;
+        let mut section = |num: Option<u32>, item: usize| {
+            num.map(|num| {
+                let section = Section {
+                    offset: size,
+                    len: num as usize,
+                };
+                size += num as usize * item;
+                section
+            })
+        };
+        self.entities = section(entities, std::mem::size_of::<media::media_v2_entity>());
+        self.interfaces = section(interfaces, std::mem::size_of::<media::media_v2_interface>());
+        self.pads = section(pads, std::mem::size_of::<media::media_v2_pad>());
+        self.links = section(links, std::mem::size_of::<media::media_v2_link>());
+        self.arena.clear();
+        self.arena.resize(size, 0);
+        let base = self.arena.as_mut_ptr();
+        // safety: the sections lie in the arena without overlapping each other,
+        // `T` is packed and any bytes are valid for it
+        unsafe fn slice<'a, T>(base: *mut u8, section: Option<Section>) -> Option<&'a mut [T]> {
+            section.map(|section| {
+                std::slice::from_raw_parts_mut(base.add(section.offset) as *mut T, section.len)
+            })
+        }
+        unsafe {
+            TopologyArrays {
+                entities: slice(base, self.entities),
+                interfaces: slice(base, self.interfaces),
+                pads: slice(base, self.pads),
+                links: slice(base, self.links),
+            }
+        }
+    }
+
+    /// Shorten the arrays to the numbers of items filled by the kernel.
+    pub(crate) fn truncate(&mut self, entities: u32, interfaces: u32, pads: u32, links: u32) {
+        for (section, num) in [
+            (&mut self.entities, entities),
+            (&mut self.interfaces, interfaces),
+            (&mut self.pads, pads),
+            (&mut self.links, links),
+        ] {
+            if let Some(section) = section {
+                section.len = section.len.min(num as usize);
+            }
+        }
+    }
+
+    fn slice<T>(&self, section: Option<Section>) -> Option<&[T]> {
+        // safety: the section lies in the arena, `T` is packed and any bytes are valid for it
+        section.map(|section| unsafe {
+            std::slice::from_raw_parts(
+                self.arena.as_ptr().add(section.offset) as *const T,
+                section.len,
+            )
+        })
+    }
+
+    /// Fetch the whole topology of the device `fd` without converting it.
+    ///
+    /// * `info`: A reference to a [`MediaDeviceInfo`] containing the [`media_version`][crate::MediaDeviceInfo::media_version] used to interpret the topology.
+    /// * `fd`: A file descriptor referring to the media device file from which `info` was obtained, or another [`MediaBackend`][crate::MediaBackend] of the device.
+    pub fn from_fd<B>(info: &MediaDeviceInfo, fd: B) -> Result<Self>
+    where
+        B: MediaBackend,
+    {
+        MediaTopologyBuilder::new()
+            .get_entity()
+            .get_interface()
+            .get_pad()
+            .get_link()
+            .raw_from_fd(info, fd)
+    }
+
+    /// The media API version of the device, which decides the fields available.
+    pub fn media_version(&self) -> Version {
+        self.media_version
+    }
+
+    /// The fields available for the media API version of the device.
+    pub fn capabilities(&self) -> MediaApiCapabilities {
+        self.capabilities
+    }
+
+    /// The topology version, which the kernel increments whenever the topology changes.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Entities in the topology, or nothing if they were not fetched.
+    pub fn entities(&self) -> impl ExactSizeIterator<Item = RawEntity<'_>> {
+        let capabilities = self.capabilities;
+        self.slice::<media::media_v2_entity>(self.entities)
+            .unwrap_or(&[])
+            .iter()
+            .map(move |raw| RawEntity { raw, capabilities })
+    }
+
+    /// Interfaces in the topology, or nothing if they were not fetched.
+    pub fn interfaces(&self) -> impl ExactSizeIterator<Item = RawInterface<'_>> {
+        self.slice::<media::media_v2_interface>(self.interfaces)
+            .unwrap_or(&[])
+            .iter()
+            .map(|raw| RawInterface { raw })
+    }
+
+    /// Pads in the topology, or nothing if they were not fetched.
+    pub fn pads(&self) -> impl ExactSizeIterator<Item = RawPad<'_>> {
+        let capabilities = self.capabilities;
+        self.slice::<media::media_v2_pad>(self.pads)
+            .unwrap_or(&[])
+            .iter()
+            .map(move |raw| RawPad { raw, capabilities })
+    }
+
+    /// Find the entity named `name` without converting any other entity.
+    pub fn entity_by_name(&self, name: &str) -> Option<RawEntity<'_>> {
+        self.entities().find(|entity| entity.name_eq(name))
+    }
+
+    /// Links in the topology, or nothing if they were not fetched.
+    pub fn links(&self) -> impl ExactSizeIterator<Item = RawLink<'_>> {
+        self.slice::<media::media_v2_link>(self.links)
+            .unwrap_or(&[])
+            .iter()
+            .map(|raw| RawLink { raw })
+    }
+
+    /// Convert every object into a [`MediaTopology`].
+    ///
+    /// # Details
+    /// Objects which were not fetched are `None` in the returned topology as well.
+    ///
+    /// # Errors
+    /// An error of parsing the first object holding a value not known to this crate, such like the function of an entity,
+    /// the type of an interface, the flags of a pad, or the type or the flags of a link, see [`MediaLink::from_raw`].
+    /// Newer kernels may report such values.
+    pub fn to_topology(&self) -> Result<MediaTopology> {
+        self.to_topology_with(|entity| entity.to_entity())
+    }
+
+    /// Convert every object into a [`MediaTopology`], sharing the names of entities pooled in `interner`.
+    ///
+    /// # Details
+    /// Topologies converted with the same interner share the storage of the names of their entities,
+    /// which keeps many snapshots of a topology small, e.g. the ones kept by watchers of devices.
+    ///
+    /// # Errors
+    /// The same as [`RawTopology::to_topology`].
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let mut interner = NameInterner::new();
+    ///     let raw = RawTopology::from_fd(media.info(), media.device_fd())?;
+    ///     let first = raw.to_topology_interned(&mut interner)?;
+    ///     let second = raw.to_topology_interned(&mut interner)?;
+    ///     assert_eq!(first, second);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_topology_interned(&self, interner: &mut NameInterner) -> Result<MediaTopology> {
+        self.to_topology_with(|entity| entity.to_entity_interned(interner))
+    }
+
+    fn to_topology_with<F>(&self, mut entity: F) -> Result<MediaTopology>
+    where
+        F: FnMut(RawEntity<'_>) -> Result<MediaEntity>,
+    {
+        let links = self
+            .links
+            .as_ref()
+            .map(|_| {
+                self.links()
+                    .map(|link| link.to_link())
+                    .collect::<Result<Links>>()
+            })
+            .transpose()?;
+        let entities = self
+            .entities
+            .as_ref()
+            .map(|_| {
+                self.entities()
+                    .map(&mut entity)
+                    .collect::<Result<Entities>>()
+            })
+            .transpose()?;
+        let interfaces = self
+            .interfaces
+            .as_ref()
+            .map(|_| {
+                self.interfaces()
+                    .map(|intf| intf.to_interface())
+                    .collect::<Result<Interfaces>>()
+            })
+            .transpose()?;
+        let pads = self
+            .pads
+            .as_ref()
+            .map(|_| {
+                self.pads()
+                    .map(|pad| pad.to_pad())
+                    .collect::<Result<Pads>>()
+            })
+            .transpose()?;
+        Ok(MediaTopology::new(
+            None,
+            self.version,
+            entities,
+            interfaces,
+            pads,
+            links,
+        ))
+    }
+}
+
+/// Callbacks for [`RawTopology::visit`], each of which is called for the objects of its kind.
+///
+/// # Details
+/// Every method defaults to doing nothing. Returning [`ControlFlow::Break`] stops the visit.
+pub trait RawTopologyVisitor {
+    /// The value a visit stops with.
+    type Break;
+
+    fn visit_entity(&mut self, _entity: RawEntity<'_>) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_interface(&mut self, _interface: RawInterface<'_>) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_pad(&mut self, _pad: RawPad<'_>) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+
+    fn visit_link(&mut self, _link: RawLink<'_>) -> ControlFlow<Self::Break> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl RawTopology {
+    /// Walk entities, interfaces, pads and links in this order, without allocating anything.
+    ///
+    /// # Details
+    /// Combined with [`MediaTopologyBuilder::refresh_into`], which reuses the arrays of the topology,
+    /// a monitoring loop runs without allocation once the arrays are large enough.
+    ///
+    /// # Returns
+    /// The value the visitor stopped with, or `None` if every object was visited.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::ops::ControlFlow;
+    /// use linux_media::*;
+    ///
+    /// struct EnabledLinks(usize);
+    ///
+    /// impl RawTopologyVisitor for EnabledLinks {
+    ///     type Break = ();
+    ///     fn visit_link(&mut self, link: RawLink<'_>) -> ControlFlow<()> {
+    ///         if link.is_enabled() {
+    ///             self.0 += 1;
+    ///         }
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// }
+    ///
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let mut enabled = EnabledLinks(0);
+    ///     media.new_raw_topology()?.visit(&mut enabled);
+    ///     println!("{} links enabled", enabled.0);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn visit<V>(&self, visitor: &mut V) -> Option<V::Break>
+    where
+        V: RawTopologyVisitor,
+    {
+        let mut walk = || {
+            self.entities()
+                .try_for_each(|entity| visitor.visit_entity(entity))?;
+            self.interfaces()
+                .try_for_each(|intf| visitor.visit_interface(intf))?;
+            self.pads().try_for_each(|pad| visitor.visit_pad(pad))?;
+            self.links().try_for_each(|link| visitor.visit_link(link))
+        };
+        match walk() {
+            ControlFlow::Continue(()) => None,
+            ControlFlow::Break(value) => Some(value),
+        }
+    }
+}
+
+impl TryFrom<&RawTopology> for MediaTopology {
+    type Error = crate::error::Error;
+    fn try_from(raw: &RawTopology) -> Result<Self> {
+        raw.to_topology()
+    }
+}
+
+/// Borrow a string filled by the kernel in a fixed size array, which is empty if not terminated.
+pub(crate) fn c_str(chars: &[std::os::raw::c_char]) -> &CStr {
+    // safety: `c_char` and `u8` have the same layout
+    let bytes: &[u8] =
+        unsafe { std::slice::from_raw_parts(chars.as_ptr() as *const u8, chars.len()) };
+    CStr::from_bytes_until_nul(bytes).unwrap_or_default()
+}
+
+/// Copy `s` into a fixed size array in the layout filled by the kernel, truncated to keep the terminating nul.
+pub(crate) fn set_c_str(chars: &mut [std::os::raw::c_char], s: &str) {
+    let len = chars.len().saturating_sub(1);
+    chars.fill(0);
+    for (dst, src) in chars[..len].iter_mut().zip(s.bytes()) {
+        *dst = src as std::os::raw::c_char;
+    }
+}
+
+/// A view of an entity in a [`RawTopology`].
+#[derive(Debug, Clone, Copy)]
+pub struct RawEntity<'a> {
+    raw: &'a media::media_v2_entity,
+    capabilities: MediaApiCapabilities,
+}
+
+impl<'a> RawEntity<'a> {
+    /// The entity as filled by the kernel.
+    pub fn raw(&self) -> &'a media::media_v2_entity {
+        self.raw
+    }
+
+    pub fn id(&self) -> EntityId {
+        self.raw.id.into()
+    }
+
+    /// The name of the entity as filled by the kernel.
+    pub fn name_cstr(&self) -> &'a CStr {
+        c_str(&self.raw.name)
+    }
+
+    /// The name of the entity, borrowed from the kernel buffer unless it is not valid UTF-8.
+    pub fn name(&self) -> Cow<'a, str> {
+        self.name_cstr().to_string_lossy()
+    }
+
+    /// Whether the entity is named `name`, compared without allocation.
+    pub fn name_eq(&self, name: &str) -> bool {
+        self.name_cstr().to_bytes() == name.as_bytes()
+    }
+
+    pub fn function(&self) -> Result<MediaEntityFunctions> {
+        self.raw.function.try_into()
+    }
+
+    /// The function as filled by the kernel, one of `MEDIA_ENT_F_*`.
+    pub fn function_bits(&self) -> u32 {
+        self.raw.function
+    }
+
+    /// The entity flags, or `Ok(None)` if the media API version of the device does not report them.
+    pub fn flags(&self) -> Result<Option<MediaEntityFlags>> {
+        if self.capabilities.has_entity_flags() {
+            self.raw.flags.try_into().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Convert the entity into an owned [`MediaEntity`].
+    ///
+    /// # Errors
+    /// An error of parsing [`function`][Self::function] or [`flags`][Self::flags].
+    pub fn to_entity(&self) -> Result<MediaEntity> {
+        MediaEntity::from_raw(self.capabilities, *self.raw)
+    }
+
+    /// Convert the entity into an owned [`MediaEntity`], taking the name from `interner`.
+    ///
+    /// # Errors
+    /// The same as [`to_entity`][Self::to_entity].
+    pub fn to_entity_interned(&self, interner: &mut NameInterner) -> Result<MediaEntity> {
+        MediaEntity::from_raw_interned(self.capabilities, *self.raw, interner)
+    }
+}
+
+/// A view of an interface in a [`RawTopology`].
+#[derive(Clone, Copy)]
+pub struct RawInterface<'a> {
+    raw: &'a media::media_v2_interface,
+}
+
+/// [`media_v2_interface`][media::media_v2_interface] has no `Debug` for the union in it.
+impl std::fmt::Debug for RawInterface<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("RawInterface")
+            .field("id", &self.id())
+            .field("intf_type", &{ self.raw.intf_type })
+            .field("devnode", &self.devnode())
+            .finish()
+    }
+}
+
+impl<'a> RawInterface<'a> {
+    /// The interface as filled by the kernel.
+    pub fn raw(&self) -> &'a media::media_v2_interface {
+        self.raw
+    }
+
+    pub fn id(&self) -> InterfaceId {
+        self.raw.id.into()
+    }
+
+    pub fn r#type(&self) -> Result<MediaInterfaceType> {
+        self.raw.intf_type.try_into()
+    }
+
+    pub fn devnode(&self) -> MediaIntfDevnode {
+        unsafe { self.raw.__bindgen_anon_1.devnode.into() }
+    }
+
+    /// Convert the interface into an owned [`MediaInterface`].
+    ///
+    /// # Errors
+    /// An error of parsing [`type`][Self::type].
+    pub fn to_interface(&self) -> Result<MediaInterface> {
+        MediaInterface::from_raw(*self.raw)
+    }
+}
+
+/// A view of a pad in a [`RawTopology`].
+#[derive(Debug, Clone, Copy)]
+pub struct RawPad<'a> {
+    raw: &'a media::media_v2_pad,
+    capabilities: MediaApiCapabilities,
+}
+
+impl<'a> RawPad<'a> {
+    /// The pad as filled by the kernel.
+    pub fn raw(&self) -> &'a media::media_v2_pad {
+        self.raw
+    }
+
+    pub fn id(&self) -> PadId {
+        self.raw.id.into()
+    }
+
+    pub fn entity_id(&self) -> EntityId {
+        self.raw.entity_id.into()
+    }
+
+    pub fn flags(&self) -> Result<MediaPadFlags> {
+        self.raw.flags.try_into()
+    }
+
+    /// Whether the pad is a source pad, tested without parsing the flags.
+    pub fn is_source(&self) -> bool {
+        self.raw.flags & media::MEDIA_PAD_FL_SOURCE != 0
+    }
+
+    /// Whether the pad is a sink pad, tested without parsing the flags.
+    pub fn is_sink(&self) -> bool {
+        self.raw.flags & media::MEDIA_PAD_FL_SINK != 0
+    }
+
+    /// The pad index, or `None` if the media API version of the device does not report it.
+    pub fn index(&self) -> Option<usize> {
+        self.capabilities
+            .has_pad_index()
+            .then_some(self.raw.index as usize)
+    }
+
+    /// Convert the pad into an owned [`MediaPad`].
+    ///
+    /// # Errors
+    /// An error of parsing [`flags`][Self::flags], e.g. of a pad being neither a sink nor a source.
+    pub fn to_pad(&self) -> Result<MediaPad> {
+        MediaPad::from_raw(self.capabilities, *self.raw)
+    }
+}
+
+/// A view of a link in a [`RawTopology`].
+#[derive(Debug, Clone, Copy)]
+pub struct RawLink<'a> {
+    raw: &'a media::media_v2_link,
+}
+
+impl<'a> RawLink<'a> {
+    /// The link as filled by the kernel.
+    pub fn raw(&self) -> &'a media::media_v2_link {
+        self.raw
+    }
+
+    pub fn id(&self) -> LinkId {
+        self.raw.id.into()
+    }
+
+    /// The id of the source object, whose kind depends on the link type.
+    pub fn source_id(&self) -> u32 {
+        self.raw.source_id
+    }
+
+    /// The id of the sink object, whose kind depends on the link type.
+    pub fn sink_id(&self) -> u32 {
+        self.raw.sink_id
+    }
+
+    pub fn flags(&self) -> Result<MediaLinkFlags> {
+        self.raw.flags.try_into()
+    }
+
+    /// The flags as filled by the kernel, including the link type.
+    pub fn flags_bits(&self) -> u32 {
+        self.raw.flags
+    }
+
+    /// Whether the link is enabled, tested without parsing the flags.
+    pub fn is_enabled(&self) -> bool {
+        self.raw.flags & media::MEDIA_LNK_FL_ENABLED != 0
+    }
+
+    /// Whether the link is immutable, tested without parsing the flags.
+    pub fn is_immutable(&self) -> bool {
+        self.raw.flags & media::MEDIA_LNK_FL_IMMUTABLE != 0
+    }
+
+    /// Whether the link connects pads, tested without parsing the flags.
+    pub fn is_data_link(&self) -> bool {
+        self.raw.flags & media::MEDIA_LNK_FL_LINK_TYPE == media::MEDIA_LNK_FL_DATA_LINK
+    }
+
+    /// The type of the link with the ids of the end points.
+    ///
+    /// # Errors
+    /// [`LinkTypeParseError`][crate::error::Error::LinkTypeParseError] if the link type is not known to this crate.
+    pub fn r#type(&self) -> Result<LinkType> {
+        LinkType::from_raw(self.raw)
+    }
+
+    /// Convert the link into an owned [`MediaLink`].
+    ///
+    /// # Errors
+    /// The same as [`MediaLink::from_raw`].
+    pub fn to_link(&self) -> Result<MediaLink> {
+        (*self.raw).try_into()
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Fetch the topology of the device without converting it.
+    pub fn new_raw_topology(&self) -> Result<RawTopology> {
+        RawTopology::from_fd(self.info(), self.backend())
+    }
+
+    /// Fetch the whole topology of the device into `raw`, reusing its arrays.
+    ///
+    /// # Details
+    /// See [`MediaTopologyBuilder::refresh_into`].
+    pub fn refresh_raw_topology(&self, raw: &mut RawTopology) -> Result<()> {
+        MediaTopologyBuilder::new()
+            .get_entity()
+            .get_interface()
+            .get_pad()
+            .get_link()
+            .refresh_into(self.info(), self.backend(), raw)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn views_convert_on_access() {
+        let mut entity: media::media_v2_entity = unsafe { std::mem::zeroed() };
+        entity.id = 1;
+        entity.function = media::MEDIA_ENT_F_CAM_SENSOR;
+        for (dst, src) in entity.name.iter_mut().zip(b"imx219 10-0010") {
+            *dst = *src as _;
+        }
+        let mut pad: media::media_v2_pad = unsafe { std::mem::zeroed() };
+        pad.id = 2;
+        pad.entity_id = 1;
+        pad.flags = media::MEDIA_PAD_FL_SOURCE;
+        pad.index = 0;
+        let raw = RawTopology::new(
+            Version::new(6, 1, 0),
+            3,
+            Some(vec![entity]),
+            None,
+            Some(vec![pad]),
+            None,
+        );
+
+        let view = raw.entities().next().unwrap();
+        assert!(matches!(view.name(), Cow::Borrowed("imx219 10-0010")));
+        assert!(view.name_eq("imx219 10-0010"));
+        assert!(!view.name_eq("imx219"));
+        assert_eq!(
+            raw.entity_by_name("imx219 10-0010").unwrap().id(),
+            view.id()
+        );
+        assert_eq!(view.function().unwrap(), MediaEntityFunctions::CAMSensor);
+        assert_eq!(raw.pads().next().unwrap().index(), Some(0));
+        assert!(raw.pads().next().unwrap().is_source());
+        assert_eq!(raw.links().len(), 0);
+
+        let topology = raw.to_topology().unwrap();
+        assert_eq!(topology.version(), 3);
+        assert_eq!(topology.entities_slice()[0].name(), "imx219 10-0010");
+        assert_eq!(topology.interfaces(), None);
+        assert_eq!(topology.links(), None);
+    }
+
+    #[test]
+    fn unknown_values_fail_to_convert() {
+        let mut entity: media::media_v2_entity = unsafe { std::mem::zeroed() };
+        entity.id = 1;
+        entity.function = media::MEDIA_ENT_F_CAM_SENSOR;
+        let mut intf: media::media_v2_interface = unsafe { std::mem::zeroed() };
+        intf.id = 2;
+        intf.intf_type = media::MEDIA_INTF_T_V4L_SUBDEV;
+        let mut pad: media::media_v2_pad = unsafe { std::mem::zeroed() };
+        pad.id = 3;
+        pad.entity_id = 1;
+        pad.flags = media::MEDIA_PAD_FL_SINK;
+        let raw = |entity, intf, pad| {
+            RawTopology::new(
+                Version::new(6, 1, 0),
+                1,
+                Some(vec![entity]),
+                Some(vec![intf]),
+                Some(vec![pad]),
+                None,
+            )
+        };
+        assert!(raw(entity, intf, pad).to_topology().is_ok());
+
+        let unknown = media::media_v2_entity {
+            function: 0xdead,
+            ..entity
+        };
+        assert!(raw(unknown, intf, pad)
+            .entities()
+            .next()
+            .unwrap()
+            .to_entity()
+            .is_err());
+        assert!(raw(unknown, intf, pad).to_topology().is_err());
+        let unknown = media::media_v2_interface {
+            intf_type: 0xdead,
+            ..intf
+        };
+        assert!(raw(entity, unknown, pad).to_topology().is_err());
+        // neither a sink nor a source
+        let unknown = media::media_v2_pad { flags: 0, ..pad };
+        assert!(raw(entity, intf, unknown).to_topology().is_err());
+    }
+
+    #[test]
+    fn visit_stops_on_break() {
+        struct FirstSource(usize);
+
+        impl RawTopologyVisitor for FirstSource {
+            type Break = PadId;
+            fn visit_entity(&mut self, _entity: RawEntity<'_>) -> ControlFlow<PadId> {
+                self.0 += 1;
+                ControlFlow::Continue(())
+            }
+            fn visit_pad(&mut self, pad: RawPad<'_>) -> ControlFlow<PadId> {
+                if pad.is_source() {
+                    ControlFlow::Break(pad.id())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            }
+            fn visit_link(&mut self, _link: RawLink<'_>) -> ControlFlow<PadId> {
+                unreachable!("links are visited after pads")
+            }
+        }
+
+        let pad = |id, flags| {
+            let mut pad: media::media_v2_pad = unsafe { std::mem::zeroed() };
+            pad.id = id;
+            pad.entity_id = 1;
+            pad.flags = flags;
+            pad
+        };
+        let mut entity: media::media_v2_entity = unsafe { std::mem::zeroed() };
+        entity.id = 1;
+        let mut link: media::media_v2_link = unsafe { std::mem::zeroed() };
+        link.id = 4;
+        let raw = RawTopology::new(
+            Version::new(6, 1, 0),
+            1,
+            Some(vec![entity]),
+            None,
+            Some(vec![
+                pad(2, media::MEDIA_PAD_FL_SINK),
+                pad(3, media::MEDIA_PAD_FL_SOURCE),
+            ]),
+            Some(vec![link]),
+        );
+
+        let mut visitor = FirstSource(0);
+        assert_eq!(raw.visit(&mut visitor), Some(PadId::from(3)));
+        assert_eq!(visitor.0, 1);
+    }
+
+    #[test]
+    fn unknown_link_types_fail_to_convert() {
+        let mut link: media::media_v2_link = unsafe { std::mem::zeroed() };
+        link.id = 1;
+        link.flags = media::MEDIA_LNK_FL_LINK_TYPE;
+        let raw = RawTopology::new(Version::new(6, 1, 0), 1, None, None, None, Some(vec![link]));
+        assert!(matches!(
+            raw.links().next().unwrap().to_link(),
+            Err(crate::error::Error::LinkTypeParseError { .. })
+        ));
+        assert!(matches!(
+            raw.to_topology(),
+            Err(crate::error::Error::LinkTypeParseError { .. })
+        ));
+    }
+}
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::fs::OpenOptions;
 use std::os::fd::{AsFd, OwnedFd};
 use std::os::unix::fs::OpenOptionsExt;
@@ -5,28 +6,45 @@ use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::collections::{Entities, Interfaces, Links, Pads};
 use crate::error::{self, Result};
+use crate::media_backend::MediaBackend;
 use crate::media_device_info::MediaDeviceInfo;
-use crate::media_entity::MediaEntity;
-use crate::media_interface::MediaInterface;
-use crate::media_link::MediaLink;
-use crate::media_pad::MediaPad;
+use crate::media_entity::{EntityId, MediaEntity};
+use crate::media_interface::{InterfaceId, MediaInterface};
+use crate::media_interface_type::MediaInterfaceType;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::media_link::{LinkType, MediaLink};
+use crate::media_link_desc::MediaLinkDesc;
+use crate::media_pad::{MediaPad, PadId};
+use crate::media_pad_desc::MediaPadDesc;
 use crate::media_topology_builder::MediaTopologyBuilder;
+use crate::name_interner::NameInterner;
 
-/// Rust representation of the [`media_v2_topology`][linux_media_sys::media_v2_topology] type.
+/// Rust representation of the [`media_v2_topology`][crate::raw::media_v2_topology] type.
 ///
 /// # Details
 /// Captures a media device’s topology as defined by the Linux media controller API,
 /// including its version, optional device file path (if built from a path), and collections of entities, interfaces, pads, and links.
-#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+///
+/// The alternate debug format (`{:#?}`) groups pads and links under the entity (or interface) they belong to
+/// instead of listing four flat vectors. Links are placed under the entity owning their source pad.
+#[derive(Clone, PartialEq, PartialOrd, Eq, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct MediaTopology {
     /// If the instance was built with a file path given, the device file path from which topology information was read, otherwise None if it was built from a file descriptor.
+    #[serde(rename = "path")]
     path: Option<PathBuf>,
+    #[serde(rename = "version")]
     version: u64,
-    entities: Option<Vec<MediaEntity>>,
-    interfaces: Option<Vec<MediaInterface>>,
-    pads: Option<Vec<MediaPad>>,
-    links: Option<Vec<MediaLink>>,
+    #[serde(rename = "entities")]
+    entities: Option<Entities>,
+    #[serde(rename = "interfaces")]
+    interfaces: Option<Interfaces>,
+    #[serde(rename = "pads")]
+    pads: Option<Pads>,
+    #[serde(rename = "links")]
+    links: Option<Links>,
 }
 
 impl MediaTopology {
@@ -35,18 +53,18 @@ impl MediaTopology {
     pub(crate) fn new(
         path: Option<PathBuf>,
         version: u64,
-        entities: Option<Vec<MediaEntity>>,
-        interfaces: Option<Vec<MediaInterface>>,
-        pads: Option<Vec<MediaPad>>,
-        links: Option<Vec<MediaLink>>,
+        entities: Option<impl Into<Entities>>,
+        interfaces: Option<impl Into<Interfaces>>,
+        pads: Option<impl Into<Pads>>,
+        links: Option<impl Into<Links>>,
     ) -> Self {
         Self {
             path,
             version,
-            entities,
-            interfaces,
-            pads,
-            links,
+            entities: entities.map(Into::into),
+            interfaces: interfaces.map(Into::into),
+            pads: pads.map(Into::into),
+            links: links.map(Into::into),
         }
     }
 
@@ -60,6 +78,9 @@ impl MediaTopology {
     ///
     /// # Returns
     /// A Result containing the constructed MediaTopology if successful, or an error otherwise.
+    #[deprecated(
+        note = "use `MediaTopologyHandle::from_path`, which keeps the file descriptor with the topology"
+    )]
     pub fn from_path<P>(info: &MediaDeviceInfo, path: P) -> Result<(OwnedFd, Self)>
     where
         P: AsRef<Path>,
@@ -83,26 +104,48 @@ impl MediaTopology {
     /// Constructs a MediaTopology from a file descriptor referencing a device file (e.g., /dev/mediaX).
     ///
     /// * `info`: A reference to a [`MediaDeviceInfo`] containing the [`media_version`][crate::MediaDeviceInfo::media_version] used to build the topology.
-    /// * `fd`: A file descriptor referring to the media device file from which `info` was obtained.
+    /// * `fd`: A file descriptor referring to the media device file from which `info` was obtained, or another [`MediaBackend`][crate::MediaBackend] of the device.
     ///
     /// # Returns
     /// A Result containing the constructed [`MediaTopology`] if successful, or an error otherwise.
-    pub fn from_fd<F>(info: &MediaDeviceInfo, fd: F) -> Result<Self>
+    pub fn from_fd<B>(info: &MediaDeviceInfo, fd: B) -> Result<Self>
     where
-        F: AsFd,
+        B: MediaBackend,
     {
-        MediaTopologyBuilder::new()
-            .get_entity()
-            .get_interface()
-            .get_pad()
-            .get_link()
-            .from_fd(info, fd)
+        MediaTopologyBuilder::all().from_fd(info, fd)
+    }
+
+    /// The topology version, which the kernel increments whenever the topology changes.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// The device file the topology was read from, if it was built with a path given.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub(crate) fn set_path(&mut self, path: PathBuf) {
+        self.path = Some(path);
     }
 
     pub fn entities_slice(&self) -> &[MediaEntity] {
         self.entities.as_deref().unwrap_or(&[])
     }
 
+    /// Share the names of the entities with the ones pooled in `interner`.
+    ///
+    /// # Details
+    /// For topologies not converted with an interner, such as deserialized snapshots.
+    /// See [`NameInterner`].
+    pub fn intern_names(&mut self, interner: &mut NameInterner) {
+        if let Some(entities) = &mut self.entities {
+            for entity in entities.iter_mut() {
+                entity.intern_name(interner);
+            }
+        }
+    }
+
     pub fn interfaces_slice(&self) -> &[MediaInterface] {
         self.interfaces.as_deref().unwrap_or(&[])
     }
@@ -130,4 +173,559 @@ impl MediaTopology {
     pub fn links(&self) -> Option<&[MediaLink]> {
         self.links.as_deref()
     }
+
+    /// The links between pads.
+    pub fn data_links(&self) -> impl Iterator<Item = &MediaLink> {
+        self.links_slice()
+            .iter()
+            .filter(|link| matches!(link.r#type(), LinkType::DataLink { .. }))
+    }
+
+    /// The links from interfaces to entities.
+    pub fn interface_links(&self) -> impl Iterator<Item = &MediaLink> {
+        self.links_slice()
+            .iter()
+            .filter(|link| matches!(link.r#type(), LinkType::InterfaceLink { .. }))
+    }
+
+    /// The links representing physical relationships between entities, such as a lens controller and its camera sensor.
+    pub fn ancillary_links(&self) -> impl Iterator<Item = &MediaLink> {
+        self.links_slice()
+            .iter()
+            .filter(|link| matches!(link.r#type(), LinkType::AncillaryLink { .. }))
+    }
+
+    /// The end points of the ancillary link `link`, the primary entity such as a camera sensor and its ancillary entity such as a lens controller.
+    ///
+    /// # Returns
+    /// `None` if `link` is not an ancillary link, or its entities are not found in this topology.
+    pub fn ancillary_link_entities(
+        &self,
+        link: &MediaLink,
+    ) -> Option<(&MediaEntity, &MediaEntity)> {
+        let LinkType::AncillaryLink { source_id, sink_id } = link.r#type() else {
+            return None;
+        };
+        let entity = |id: u32| {
+            self.entities_slice()
+                .iter()
+                .find(|entity| u32::from(entity.id()) == id)
+        };
+        Some((entity(source_id.raw())?, entity(sink_id.raw())?))
+    }
+
+    /// The ancillary entities of the primary entity `entity`, such as the lens controller of a camera sensor.
+    pub fn ancillary_entities(&self, entity: EntityId) -> impl Iterator<Item = &MediaEntity> {
+        self.ancillary_links()
+            .filter_map(|link| self.ancillary_link_entities(link))
+            .filter(move |(primary, _)| primary.id() == entity)
+            .map(|(_, ancillary)| ancillary)
+    }
+
+    /// The primary entity which the ancillary entity `entity` belongs to.
+    pub fn primary_entity(&self, entity: EntityId) -> Option<&MediaEntity> {
+        self.ancillary_links()
+            .filter_map(|link| self.ancillary_link_entities(link))
+            .find(|(_, ancillary)| ancillary.id() == entity)
+            .map(|(primary, _)| primary)
+    }
+
+    /// The links of any type which are enabled.
+    pub fn enabled_links(&self) -> impl Iterator<Item = &MediaLink> {
+        self.links_slice().iter().filter(|link| link.is_enabled())
+    }
+
+    /// Replace the flags of the link `id`, as a driver does on `MEDIA_IOC_SETUP_LINK`.
+    #[cfg(any(test, feature = "mock"))]
+    pub(crate) fn set_link_flags(&mut self, id: crate::LinkId, flags: crate::MediaLinkFlags) {
+        let links = self.links.iter_mut().flat_map(|links| links.iter_mut());
+        for link in links.filter(|link| link.id() == id) {
+            *link = MediaLink::new(id, link.r#type().clone(), flags);
+        }
+    }
+
+    /// Advance the topology version, as the kernel does on every change of the topology.
+    #[cfg(any(test, feature = "mock"))]
+    pub(crate) fn bump_version(&mut self) -> u64 {
+        self.version += 1;
+        self.version
+    }
+
+    /// Add `link`, as a driver creating a link at runtime.
+    #[cfg(any(test, feature = "mock"))]
+    pub(crate) fn add_link(&mut self, link: MediaLink) {
+        self.links.get_or_insert_with(Default::default).push(link);
+    }
+
+    /// Remove the link `id`, as a driver removing a link at runtime.
+    #[cfg(any(test, feature = "mock"))]
+    pub(crate) fn remove_link(&mut self, id: crate::LinkId) -> Option<MediaLink> {
+        let links = self.links.as_mut()?;
+        let position = links.iter().position(|link| link.id() == id)?;
+        Some(links.remove(position))
+    }
+
+    /// Describe the pad `pad` in the form passed to [`MEDIA_IOC_SETUP_LINK`][crate::raw::MEDIA_IOC_SETUP_LINK].
+    ///
+    /// # Details
+    /// If the media version has no pad index, the index is the position of the pad among the pads of the entity.
+    pub fn pad_desc(&self, pad: &MediaPad) -> MediaPadDesc {
+        let index = pad.index.unwrap_or_else(|| {
+            self.pads_slice()
+                .iter()
+                .filter(|p| p.entity_id == pad.entity_id)
+                .position(|p| p.id == pad.id)
+                .unwrap_or(0)
+        });
+        MediaPadDesc::new(pad.entity_id, index, pad.flags)
+    }
+
+    /// Describe the data link `link` in the form passed to [`MEDIA_IOC_SETUP_LINK`][crate::raw::MEDIA_IOC_SETUP_LINK].
+    ///
+    /// # Returns
+    /// `None` if `link` is not a data link, or its pads are not found in this topology.
+    pub fn link_desc(&self, link: &MediaLink) -> Option<MediaLinkDesc> {
+        let LinkType::DataLink { source_id, sink_id } = link.r#type() else {
+            return None;
+        };
+        let pad = |id: &PadId| self.pads_slice().iter().find(|pad| pad.id == *id);
+        Some(MediaLinkDesc::new(
+            self.pad_desc(pad(source_id)?),
+            self.pad_desc(pad(sink_id)?),
+            link.flags(),
+        ))
+    }
+
+    /// The device node of the interface of `r#type` linked to the entity `entity`.
+    pub(crate) fn interface_devnode(
+        &self,
+        entity: EntityId,
+        r#type: MediaInterfaceType,
+    ) -> Option<MediaIntfDevnode> {
+        self.links_slice()
+            .iter()
+            .find_map(|link| match link.r#type() {
+                LinkType::InterfaceLink { source_id, sink_id } if *sink_id == entity => self
+                    .interfaces_slice()
+                    .iter()
+                    .find(|intf| intf.id() == *source_id && intf.r#type() == r#type)
+                    .map(|intf| intf.devnode()),
+                _ => None,
+            })
+    }
+}
+
+/// FNV-1a, a hash function whose result is stable across platforms and Rust releases.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Fnv1a(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+}
+
+/// An object encoded for [`MediaTopology::fingerprint`] in numbers of fixed width in little endian and strings prefixed with their lengths.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Record(Vec<u8>);
+
+impl Record {
+    const ENTITY: u8 = 0;
+    const PAD: u8 = 1;
+    const DATA_LINK: u8 = 2;
+    const INTERFACE_LINK: u8 = 3;
+    const ANCILLARY_LINK: u8 = 4;
+
+    fn new(kind: u8) -> Self {
+        Record(vec![kind])
+    }
+
+    fn u32(mut self, value: u32) -> Self {
+        self.0.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    fn str(self, s: &str) -> Self {
+        let mut record = self.u32(s.len() as u32);
+        record.0.extend_from_slice(s.as_bytes());
+        record
+    }
+
+    /// The name of the entity, or its id if the entity is missing from the topology.
+    fn entity(self, topology: &MediaTopology, id: EntityId) -> Self {
+        match topology.entities_slice().iter().find(|e| e.id() == id) {
+            Some(entity) => self.u32(1).str(entity.name()),
+            None => self.u32(0).u32(id.into()),
+        }
+    }
+
+    /// The name of the owning entity and the index of the pad, or its id if the pad is missing from the topology.
+    fn pad(self, topology: &MediaTopology, id: PadId) -> Self {
+        let pads = topology.pads_slice();
+        match pads.iter().find(|pad| pad.id == id) {
+            Some(pad) => {
+                let index = pad.index.unwrap_or_else(|| {
+                    pads.iter()
+                        .filter(|p| p.entity_id == pad.entity_id)
+                        .position(|p| p.id == id)
+                        .unwrap_or(0)
+                });
+                self.entity(topology, pad.entity_id).u32(index as u32)
+            }
+            None => self.u32(0).u32(id.into()),
+        }
+    }
+}
+
+impl MediaTopology {
+    /// Compute a stable hash of the graph structure.
+    ///
+    /// # Details
+    /// The hash covers entities (name, function and flags), pads (owning entity name, index and flags),
+    /// data links (endpoints as entity names and pad indices, and flags) and interface links (interface type, entity name and flags).
+    /// Object ids are not hashed, so the fingerprint is the same for identical graphs numbered differently,
+    /// e.g. between boots or across driver reloads. The device file path and the topology version are not hashed either.
+    ///
+    /// Functions, flags and interface types are hashed as the values defined by the kernel, in fixed width and little endian,
+    /// so the value is stable across platforms and releases of this crate,
+    /// which makes it usable as a cache key or to check whether the graph of a board has changed since it was validated.
+    ///
+    /// Sections which were not fetched (see [`MediaTopologyBuilder`]) are hashed as empty.
+    pub fn fingerprint(&self) -> u64 {
+        let mut records = vec![];
+        for entity in self.entities_slice() {
+            let record = Record::new(Record::ENTITY)
+                .str(entity.name())
+                .u32(entity.function().into());
+            records.push(match entity.flags() {
+                Some(flags) => record.u32(1).u32(flags.bits()),
+                None => record.u32(0),
+            });
+        }
+        for pad in self.pads_slice() {
+            records.push(
+                Record::new(Record::PAD)
+                    .pad(self, pad.id)
+                    .u32(pad.flags.bits()),
+            );
+        }
+        for link in self.links_slice() {
+            let record = match link.r#type() {
+                LinkType::DataLink { source_id, sink_id } => Record::new(Record::DATA_LINK)
+                    .pad(self, *source_id)
+                    .pad(self, *sink_id),
+                LinkType::InterfaceLink { source_id, sink_id } => {
+                    let record = Record::new(Record::INTERFACE_LINK);
+                    let record = match self
+                        .interfaces_slice()
+                        .iter()
+                        .find(|i| i.id() == *source_id)
+                    {
+                        Some(intf) => record.u32(1).u32(intf.r#type().into()),
+                        None => record.u32(0).u32((*source_id).into()),
+                    };
+                    record.entity(self, *sink_id)
+                }
+                LinkType::AncillaryLink { source_id, sink_id } => {
+                    Record::new(Record::ANCILLARY_LINK)
+                        .entity(self, source_id.raw().into())
+                        .entity(self, sink_id.raw().into())
+                }
+            };
+            records.push(record.u32(link.flags().bits()));
+        }
+        // records are sorted, which makes the hash independent of the order reported by the kernel
+        records.sort();
+        let mut hasher = Fnv1a::new();
+        for record in records {
+            hasher.write(&(record.0.len() as u32).to_le_bytes());
+            hasher.write(&record.0);
+        }
+        hasher.0
+    }
+
+    /// Find the entity that owns the pad specified with `id`.
+    fn pad_owner(&self, id: PadId) -> Option<EntityId> {
+        self.pads_slice()
+            .iter()
+            .find(|pad| pad.id == id)
+            .map(|pad| pad.entity_id)
+    }
+
+    /// The entity under which `link` is grouped in the alternate debug format.
+    fn link_owner(&self, link: &MediaLink) -> Option<LinkOwner> {
+        match link.r#type() {
+            LinkType::DataLink { source_id, .. } => {
+                self.pad_owner(*source_id).map(LinkOwner::Entity)
+            }
+            LinkType::InterfaceLink { source_id, .. } => Some(LinkOwner::Interface(*source_id)),
+            LinkType::AncillaryLink { source_id, .. } => {
+                Some(LinkOwner::Entity(EntityId::from(source_id.raw())))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LinkOwner {
+    Entity(EntityId),
+    Interface(InterfaceId),
+}
+
+/// An entity together with its pads and outbound links, used by the alternate debug format.
+struct EntityNode<'a> {
+    entity: &'a MediaEntity,
+    pads: Vec<&'a MediaPad>,
+    links: Vec<&'a MediaLink>,
+}
+
+impl fmt::Debug for EntityNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MediaEntity")
+            .field("id", &self.entity.id())
+            .field("name", &self.entity.name())
+            .field("function", &self.entity.function())
+            .field("flags", &self.entity.flags())
+            .field("pads", &self.pads)
+            .field("links", &self.links)
+            .finish()
+    }
+}
+
+/// An interface together with its links, used by the alternate debug format.
+struct InterfaceNode<'a> {
+    interface: &'a MediaInterface,
+    links: Vec<&'a MediaLink>,
+}
+
+impl fmt::Debug for InterfaceNode<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MediaInterface")
+            .field("id", &self.interface.id())
+            .field("type", &self.interface.r#type())
+            .field("devnode", &self.interface.devnode())
+            .field("links", &self.links)
+            .finish()
+    }
+}
+
+impl fmt::Debug for MediaTopology {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !f.alternate() {
+            return f
+                .debug_struct("MediaTopology")
+                .field("path", &self.path)
+                .field("version", &self.version)
+                .field("entities", &self.entities)
+                .field("interfaces", &self.interfaces)
+                .field("pads", &self.pads)
+                .field("links", &self.links)
+                .finish();
+        }
+
+        let entities: Vec<EntityNode> = self
+            .entities_slice()
+            .iter()
+            .map(|entity| EntityNode {
+                entity,
+                pads: self
+                    .pads_slice()
+                    .iter()
+                    .filter(|pad| pad.entity_id == entity.id())
+                    .collect(),
+                links: self
+                    .links_slice()
+                    .iter()
+                    .filter(|link| self.link_owner(link) == Some(LinkOwner::Entity(entity.id())))
+                    .collect(),
+            })
+            .collect();
+        let interfaces: Vec<InterfaceNode> = self
+            .interfaces_slice()
+            .iter()
+            .map(|interface| InterfaceNode {
+                interface,
+                links: self
+                    .links_slice()
+                    .iter()
+                    .filter(|link| {
+                        self.link_owner(link) == Some(LinkOwner::Interface(interface.id()))
+                    })
+                    .collect(),
+            })
+            .collect();
+        // pads and links which could not be attached to any listed entity or interface
+        let orphan_pads: Vec<&MediaPad> = self
+            .pads_slice()
+            .iter()
+            .filter(|pad| {
+                !self
+                    .entities_slice()
+                    .iter()
+                    .any(|e| e.id() == pad.entity_id)
+            })
+            .collect();
+        let orphan_links: Vec<&MediaLink> = self
+            .links_slice()
+            .iter()
+            .filter(|link| match self.link_owner(link) {
+                Some(LinkOwner::Entity(id)) => !self.entities_slice().iter().any(|e| e.id() == id),
+                Some(LinkOwner::Interface(id)) => {
+                    !self.interfaces_slice().iter().any(|i| i.id() == id)
+                }
+                None => true,
+            })
+            .collect();
+
+        let mut s = f.debug_struct("MediaTopology");
+        s.field("path", &self.path)
+            .field("version", &self.version)
+            .field("entities", &entities)
+            .field("interfaces", &interfaces);
+        if !orphan_pads.is_empty() {
+            s.field("unattached_pads", &orphan_pads);
+        }
+        if !orphan_links.is_empty() {
+            s.field("unattached_links", &orphan_links);
+        }
+        s.finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::raw as media;
+
+    pub(crate) fn entity(id: u32, name: &str) -> MediaEntity {
+        let mut raw: media::media_v2_entity = unsafe { std::mem::zeroed() };
+        raw.id = id;
+        for (dst, src) in raw.name.iter_mut().zip(name.bytes()) {
+            *dst = src as libc::c_char;
+        }
+        raw.function = media::MEDIA_ENT_F_CAM_SENSOR;
+        MediaEntity::from_raw(crate::Version::new(6, 1, 0), raw).unwrap()
+    }
+
+    pub(crate) fn pad(id: u32, entity_id: u32, flags: u32) -> MediaPad {
+        let mut raw: media::media_v2_pad = unsafe { std::mem::zeroed() };
+        raw.id = id;
+        raw.entity_id = entity_id;
+        raw.flags = flags;
+        MediaPad::from_raw(crate::Version::new(6, 1, 0), raw).unwrap()
+    }
+
+    pub(crate) fn link(id: u32, source_id: u32, sink_id: u32, flags: u32) -> MediaLink {
+        let mut raw: media::media_v2_link = unsafe { std::mem::zeroed() };
+        raw.id = id;
+        raw.source_id = source_id;
+        raw.sink_id = sink_id;
+        raw.flags = media::MEDIA_LNK_FL_DATA_LINK | flags;
+        raw.try_into().unwrap()
+    }
+
+    fn sensor_to_receiver(base: u32, flags: u32) -> MediaTopology {
+        MediaTopology::new(
+            None,
+            1,
+            Some(vec![
+                entity(base + 1, "sensor"),
+                entity(base + 3, "receiver"),
+            ]),
+            Some(vec![]),
+            Some(vec![
+                pad(base + 2, base + 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(base + 4, base + 3, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(base + 5, base + 2, base + 4, flags)]),
+        )
+    }
+
+    #[test]
+    fn fingerprint_ignores_ids() {
+        let a = sensor_to_receiver(0, media::MEDIA_LNK_FL_ENABLED);
+        let b = sensor_to_receiver(100, media::MEDIA_LNK_FL_ENABLED);
+        let c = sensor_to_receiver(0, 0);
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+        // pinned, as fingerprints are kept by users to detect changes of their boards
+        assert_eq!(a.fingerprint(), 0x41f4_288c_4713_e049);
+    }
+
+    #[test]
+    fn alternate_debug_groups_by_entity() {
+        let topology = sensor_to_receiver(0, media::MEDIA_LNK_FL_ENABLED);
+        let flat = format!("{:?}", topology);
+        assert!(flat.starts_with("MediaTopology { path: None, version: 1, entities: Some(["));
+
+        let tree = format!("{:#?}", topology);
+        let sensor = tree.find("\"sensor\"").unwrap();
+        let receiver = tree.find("\"receiver\"").unwrap();
+        let link = tree.find("LinkId(\n").unwrap();
+        // the link is listed under its source entity
+        assert!(sensor < link && link < receiver);
+        assert!(!tree.contains("unattached"));
+    }
+
+    #[test]
+    fn links_are_filtered_by_type() {
+        let mut topology = sensor_to_receiver(0, media::MEDIA_LNK_FL_ENABLED);
+        let mut raw: media::media_v2_link = unsafe { std::mem::zeroed() };
+        raw.id = 7;
+        raw.source_id = 6;
+        raw.sink_id = 3;
+        raw.flags = media::MEDIA_LNK_FL_INTERFACE_LINK;
+        topology.add_link(raw.try_into().unwrap());
+
+        let ids = |links: Vec<&MediaLink>| -> Vec<u32> {
+            links.into_iter().map(|link| link.id().into()).collect()
+        };
+        assert_eq!(ids(topology.data_links().collect()), [5]);
+        assert_eq!(ids(topology.interface_links().collect()), [7]);
+        assert_eq!(topology.ancillary_links().count(), 0);
+        assert_eq!(ids(topology.enabled_links().collect()), [5]);
+
+        let link = topology.interface_links().next().unwrap();
+        assert_eq!(link.r#type().source_raw(), 6);
+        assert_eq!(link.r#type().sink_raw(), 3);
+    }
+
+    #[test]
+    fn ancillary_links_are_resolved() {
+        let mut topology = sensor_to_receiver(0, media::MEDIA_LNK_FL_ENABLED);
+        let mut raw: media::media_v2_link = unsafe { std::mem::zeroed() };
+        raw.id = 7;
+        raw.source_id = 1;
+        raw.sink_id = 3;
+        raw.flags = crate::media_link::MEDIA_LNK_FL_ANCILLARY_LINK
+            | media::MEDIA_LNK_FL_ENABLED
+            | media::MEDIA_LNK_FL_IMMUTABLE;
+        topology.add_link(raw.try_into().unwrap());
+
+        let link = topology.ancillary_links().next().unwrap();
+        let (primary, ancillary) = topology.ancillary_link_entities(link).unwrap();
+        assert_eq!((primary.name(), ancillary.name()), ("sensor", "receiver"));
+        let names: Vec<_> = topology
+            .ancillary_entities(1.into())
+            .map(|entity| entity.name())
+            .collect();
+        assert_eq!(names, ["receiver"]);
+        assert_eq!(topology.primary_entity(3.into()).unwrap().name(), "sensor");
+        assert!(topology.primary_entity(1.into()).is_none());
+        assert!(topology
+            .ancillary_link_entities(topology.data_links().next().unwrap())
+            .is_none());
+
+        raw.flags = media::MEDIA_LNK_FL_LINK_TYPE;
+        assert!(matches!(
+            LinkType::from_raw(&raw),
+            Err(error::Error::LinkTypeParseError { .. })
+        ));
+    }
 }
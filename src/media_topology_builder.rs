@@ -1,18 +1,19 @@
 use std::fs::OpenOptions;
-use std::os::fd::{AsFd, AsRawFd, OwnedFd};
+use std::os::fd::OwnedFd;
 use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
-use std::ptr::null;
 
 use crate::error::{self, Result};
-use crate::ioctl;
 use crate::Media;
+use crate::MediaBackend;
 use crate::MediaDeviceInfo;
-use crate::MediaEntity;
-use crate::MediaPad;
 use crate::MediaTopology;
+use crate::MediaTopologyCounts;
+use crate::RawTopology;
+use crate::TopologyArrays;
 
-use linux_media_sys as media;
+/// How many times a topology is fetched while it keeps changing between the ioctl counting its objects and the one filling them.
+const TOPOLOGY_ATTEMPTS: usize = 8;
 
 /// A type for constructing [`MediaTopology`] using builder pattern.
 ///
@@ -38,7 +39,7 @@ use linux_media_sys as media;
 /// # }
 /// ```
 ///
-/// Calling full options of builder, constructed topology is equals to the instance constructed with [`MediaTopology::from_path`][crate::MediaTopology::from_path] or [`MediaTopology::from_fd`][crate::MediaTopology::from_fd].
+/// Calling full options of builder, constructed topology is equals to the instance constructed with [`MediaTopologyHandle::from_path`][crate::MediaTopologyHandle::from_path] or [`MediaTopology::from_fd`][crate::MediaTopology::from_fd].
 ///
 /// ```
 /// use linux_media::*;
@@ -60,21 +61,16 @@ use linux_media_sys as media;
 /// # Ok(())
 /// # }
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Eq, Ord)]
 pub struct MediaTopologyBuilder {
     entities: bool,
     interfaces: bool,
     links: bool,
     pads: bool,
-}
-
-fn zeros_vec<T>(num: u32) -> Vec<T>
-where
-    T: Clone,
-{
-    let mut xs = vec![];
-    xs.resize(num as usize, unsafe { std::mem::zeroed() });
-    xs
+    /// Numbers of entities, interfaces, pads and links to allocate for the single ioctl mode.
+    capacity: Option<[u32; 4]>,
+    /// The most entities, interfaces, pads and links to allocate for.
+    limits: [Option<u32>; 4],
 }
 
 impl MediaTopologyBuilder {
@@ -84,7 +80,155 @@ impl MediaTopologyBuilder {
             interfaces: false,
             links: false,
             pads: false,
+            capacity: None,
+            limits: [None; 4],
+        }
+    }
+
+    /// A builder including every item, which builds the same topology as [`MediaTopology::from_fd`].
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = MediaTopologyBuilder::all()
+    ///         .with_links(false)
+    ///         .from_fd(media.info(), media.device_fd())?;
+    ///     assert_eq!(topology.links(), None);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all() -> Self {
+        Self::new()
+            .with_entities(true)
+            .with_interfaces(true)
+            .with_pads(true)
+            .with_links(true)
+    }
+
+    /// Whether to include entities, taking and returning the builder by value for chaining into [`from_fd`][Self::from_fd].
+    pub fn with_entities(mut self, entities: bool) -> Self {
+        self.entities = entities;
+        self
+    }
+
+    /// Whether to include interfaces, taking and returning the builder by value.
+    pub fn with_interfaces(mut self, interfaces: bool) -> Self {
+        self.interfaces = interfaces;
+        self
+    }
+
+    /// Whether to include pads, taking and returning the builder by value.
+    pub fn with_pads(mut self, pads: bool) -> Self {
+        self.pads = pads;
+        self
+    }
+
+    /// Whether to include links, taking and returning the builder by value.
+    pub fn with_links(mut self, links: bool) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Try to fetch the topology with a single ioctl into arrays of the given capacities.
+    ///
+    /// # Details
+    /// By default the topology is fetched with two `MEDIA_IOC_G_TOPOLOGY` calls,
+    /// the first one for the numbers of objects and the second one for the objects.
+    /// With capacities given, the arrays are allocated in advance and the objects are fetched at once.
+    /// If the topology does not fit in them, the kernel fails with `ENOSPC`
+    /// and the builder falls back to the two calls.
+    ///
+    /// Capacities generous enough for the device halve the syscalls in monitoring loops,
+    /// especially combined with [`refresh_into`][Self::refresh_into].
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = MediaTopologyBuilder::new()
+    ///         .get_entity()
+    ///         .get_link()
+    ///         .capacity(64, 64, 128, 128)
+    ///         .from_fd(media.info(), media.device_fd())?;
+    ///     assert!(topology.entities().is_some());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn capacity(&mut self, entities: u32, interfaces: u32, pads: u32, links: u32) -> &mut Self {
+        self.capacity = Some([entities, interfaces, pads, links]);
+        self
+    }
+
+    /// Try to fetch the topology with a single ioctl into arrays sized for `hint`, e.g. the counts of the last fetch.
+    ///
+    /// # Details
+    /// The same as [`capacity`][Self::capacity] taking the builder by value. The version of `hint` is ignored.
+    pub fn with_capacity_hint(mut self, hint: MediaTopologyCounts) -> Self {
+        self.capacity(hint.entities, hint.interfaces, hint.pads, hint.links);
+        self
+    }
+
+    /// Limit the numbers of entities, interfaces, pads and links to allocate for, `None` for no limit.
+    ///
+    /// # Details
+    /// Only the items included are limited, so a topology of thousands of links can be fetched only for the interfaces
+    /// while failing if the interfaces themselves turn out to be too many.
+    ///
+    /// # Errors
+    /// Building fails with [`TopologyTooLarge`][error::Error::TopologyTooLarge] before allocating the arrays
+    /// if an item included outnumbers its limit.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = MediaTopologyBuilder::new()
+    ///         .with_interfaces(true)
+    ///         .with_limits(None, Some(4096), None, None)
+    ///         .from_fd(media.info(), media.device_fd())?;
+    ///     assert!(topology.links().is_none());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_limits(
+        mut self,
+        entities: Option<u32>,
+        interfaces: Option<u32>,
+        pads: Option<u32>,
+        links: Option<u32>,
+    ) -> Self {
+        self.limits = [entities, interfaces, pads, links];
+        self
+    }
+
+    /// Check the numbers of the items included against the limits.
+    fn check_limits(&self, counts: &MediaTopologyCounts) -> Result<()> {
+        let sections = [
+            ("entities", self.entities, counts.entities),
+            ("interfaces", self.interfaces, counts.interfaces),
+            ("pads", self.pads, counts.pads),
+            ("links", self.links, counts.links),
+        ];
+        for ((section, included, count), limit) in sections.into_iter().zip(self.limits) {
+            match limit {
+                Some(limit) if included && count > limit => {
+                    return Err(error::Error::TopologyTooLarge {
+                        section,
+                        count,
+                        limit,
+                    })
+                }
+                _ => {}
+            }
         }
+        Ok(())
     }
 
     /// Enable inclusion of entities in the [`MediaTopology`].
@@ -132,83 +276,188 @@ impl MediaTopologyBuilder {
     /// # Parameters
     ///
     /// * `info`: A reference to a [`MediaDeviceInfo`] containing the [`media_version`][crate::MediaDeviceInfo::media_version] used to build the topology.
-    /// * `fd`: A file descriptor referring to the media device file from which `info` was obtained.
+    /// * `fd`: A file descriptor referring to the media device file from which `info` was obtained, or another [`MediaBackend`][crate::MediaBackend] of the device.
     ///
     /// # Returns
     /// A Result containing the constructed [`MediaTopology`] if successful, or an error otherwise.
-    pub fn from_fd<F>(self, info: &MediaDeviceInfo, fd: F) -> Result<MediaTopology>
+    /// If the kernel does not support `MEDIA_IOC_G_TOPOLOGY`, the topology is synthesized from the legacy enumeration ioctls
+    /// by [`MediaTopology::from_legacy_fd`].
+    pub fn from_fd<B>(self, info: &MediaDeviceInfo, fd: B) -> Result<MediaTopology>
     where
-        F: AsFd,
+        B: MediaBackend,
     {
-        let mut topology: media::media_v2_topology = unsafe {
-            let mut topology: media::media_v2_topology = std::mem::zeroed();
-            ioctl!(fd.as_fd(), media::MEDIA_IOC_G_TOPOLOGY, &mut topology)?;
-            topology
-        };
-        let version = topology.topology_version;
-
-        let entities: Vec<media::media_v2_entity>;
-        if self.entities {
-            entities = zeros_vec(topology.num_entities);
-            topology.ptr_entities = entities.as_ptr() as media::__u64;
-        } else {
-            entities = vec![];
-            topology.ptr_entities = null::<media::media_v2_entity>() as media::__u64;
+        match self.raw_from_fd(info, &fd) {
+            Ok(raw) => raw.to_topology(),
+            Err(error::Error::NotSupportedIoctl { .. }) => {
+                let legacy = MediaTopology::from_legacy_fd(fd)?;
+                Ok(MediaTopology::new(
+                    None,
+                    legacy.version(),
+                    self.entities.then(|| legacy.entities_slice().to_vec()),
+                    self.interfaces.then(|| legacy.interfaces_slice().to_vec()),
+                    self.pads.then(|| legacy.pads_slice().to_vec()),
+                    self.links.then(|| legacy.links_slice().to_vec()),
+                ))
+            }
+            Err(err) => Err(err),
         }
+    }
 
-        let interfaces: Vec<media::media_v2_interface>;
-        if self.interfaces {
-            interfaces = zeros_vec(topology.num_interfaces);
-            topology.ptr_interfaces = interfaces.as_ptr() as media::__u64;
-        } else {
-            interfaces = vec![];
-            topology.ptr_interfaces = null::<media::media_v2_interface>() as media::__u64;
-        }
+    /// Fetch a [`RawTopology`] including items specified with builder methods, without converting them.
+    ///
+    /// # Details
+    /// The arrays filled by the kernel are kept as they are.
+    /// See [`from_fd`][Self::from_fd] for the parameters.
+    pub fn raw_from_fd<B>(self, info: &MediaDeviceInfo, fd: B) -> Result<RawTopology>
+    where
+        B: MediaBackend,
+    {
+        let mut raw = RawTopology::new(info.media_version(), 0, None, None, None, None);
+        self.refresh_into(info, fd, &mut raw)?;
+        Ok(raw)
+    }
 
-        let links: Vec<media::media_v2_link>;
-        if self.links {
-            links = zeros_vec(topology.num_links);
-            topology.ptr_links = links.as_ptr() as media::__u64;
-        } else {
-            links = vec![];
-            topology.ptr_links = null::<media::media_v2_link>() as media::__u64;
+    /// Fetch items specified with builder methods into `raw`, reusing the arrays allocated by previous fetches.
+    ///
+    /// # Details
+    /// Polling loops can keep a [`RawTopology`] and refresh it instead of allocating new arrays on every fetch.
+    /// The arrays share a single buffer, which is reallocated only when the topology grew beyond its capacity.
+    /// Items not specified are dropped from `raw`.
+    /// The topology is fetched again if it changed between the ioctl counting the objects and the one filling them,
+    /// and [`TopologyUnstable`][error::Error::TopologyUnstable] is returned if it kept changing.
+    ///
+    /// # Parameters
+    ///
+    /// * `info`: A reference to a [`MediaDeviceInfo`] containing the [`media_version`][crate::MediaDeviceInfo::media_version] used to interpret the topology.
+    /// * `fd`: A file descriptor referring to the media device file from which `info` was obtained, or another [`MediaBackend`][crate::MediaBackend] of the device.
+    /// * `raw`: The topology to be overwritten.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let mut builder = MediaTopologyBuilder::new();
+    ///     builder.get_link();
+    ///     let mut raw = builder.raw_from_fd(media.info(), media.device_fd())?;
+    ///     for _ in 0..3 {
+    ///         builder.refresh_into(media.info(), media.device_fd(), &mut raw)?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn refresh_into<B>(self, info: &MediaDeviceInfo, fd: B, raw: &mut RawTopology) -> Result<()>
+    where
+        B: MediaBackend,
+    {
+        if let Some(mut capacity) = self.capacity {
+            for (capacity, limit) in capacity.iter_mut().zip(self.limits) {
+                *capacity = limit.map_or(*capacity, |limit| (*capacity).min(limit));
+            }
+            if self.fill_at_once(info, &fd, raw, capacity)? {
+                return Ok(());
+            }
         }
 
-        let pads: Vec<media::media_v2_pad>;
-        if self.pads {
-            pads = zeros_vec(topology.num_pads);
-            topology.ptr_pads = pads.as_ptr() as media::__u64;
-        } else {
-            pads = vec![];
-            topology.ptr_pads = null::<media::media_v2_pad>() as media::__u64;
-        }
+        for _ in 0..TOPOLOGY_ATTEMPTS {
+            let counts = fd.g_topology(TopologyArrays::default())?;
+            self.check_limits(&counts)?;
 
-        unsafe {
-            // Second ioctl call with allocated space to
+            // all the arrays are allocated at once in a single buffer
+            let arrays = raw.reserve(
+                self.entities.then_some(counts.entities),
+                self.interfaces.then_some(counts.interfaces),
+                self.pads.then_some(counts.pads),
+                self.links.then_some(counts.links),
+            );
+
+            // Second call with allocated space to
             // populate the entities/interface/links/pads array.
-            ioctl!(fd.as_fd(), media::MEDIA_IOC_G_TOPOLOGY, &mut topology)?;
-        };
-        assert_eq!(version, { topology.topology_version });
+            let filled = match fd.g_topology(arrays) {
+                Ok(filled) => filled,
+                // the topology grew since counted
+                Err(error::Error::Ioctl { code, .. })
+                    if code.raw_os_error() == Some(libc::ENOSPC) =>
+                {
+                    crate::logging::trace!("the topology grew since counted, fetching it again");
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            if counts.version != filled.version {
+                crate::logging::trace!(
+                    "the topology version changed from {} to {}, fetching it again",
+                    counts.version,
+                    filled.version
+                );
+                continue;
+            }
+
+            raw.media_version = info.media_version();
+            raw.capabilities = info.capabilities();
+            raw.version = filled.version;
+            crate::logging::debug!(
+                "fetched the topology version {}: {} entities, {} interfaces, {} pads, {} links",
+                filled.version,
+                filled.entities,
+                filled.interfaces,
+                filled.pads,
+                filled.links
+            );
+            return Ok(());
+        }
+        Err(error::Error::TopologyUnstable {
+            attempts: TOPOLOGY_ATTEMPTS,
+        })
+    }
 
-        Ok(MediaTopology::new(
-            None,
-            topology.topology_version,
-            self.entities.then_some(
-                entities
-                    .into_iter()
-                    .map(|ent| MediaEntity::from_raw_entity(info.media_version(), ent))
-                    .collect(),
-            ),
-            self.interfaces
-                .then_some(interfaces.into_iter().map(Into::into).collect()),
-            self.pads.then_some(
-                pads.into_iter()
-                    .map(|pad| MediaPad::from(info.media_version(), pad))
-                    .collect(),
-            ),
-            self.links
-                .then_some(links.into_iter().map(Into::into).collect()),
-        ))
+    /// Fetch the topology with a single ioctl into arrays of `capacity`.
+    ///
+    /// # Returns
+    /// `false` if the topology did not fit in the arrays.
+    fn fill_at_once<B>(
+        self,
+        info: &MediaDeviceInfo,
+        fd: B,
+        raw: &mut RawTopology,
+        [entities, interfaces, pads, links]: [u32; 4],
+    ) -> Result<bool>
+    where
+        B: MediaBackend,
+    {
+        let arrays = raw.reserve(
+            self.entities.then_some(entities),
+            self.interfaces.then_some(interfaces),
+            self.pads.then_some(pads),
+            self.links.then_some(links),
+        );
+        let counts = match fd.g_topology(arrays) {
+            Ok(counts) => counts,
+            Err(error::Error::Ioctl { code, .. }) if code.raw_os_error() == Some(libc::ENOSPC) => {
+                crate::logging::trace!("the topology did not fit in the capacity, counting it");
+                return Ok(false);
+            }
+            Err(err) => return Err(err),
+        };
+        raw.truncate(
+            counts.entities,
+            counts.interfaces,
+            counts.pads,
+            counts.links,
+        );
+        raw.media_version = info.media_version();
+        raw.capabilities = info.capabilities();
+        raw.version = counts.version;
+        crate::logging::debug!(
+            "fetched the topology version {} at once: {} entities, {} interfaces, {} pads, {} links",
+            counts.version,
+            counts.entities,
+            counts.interfaces,
+            counts.pads,
+            counts.links
+        );
+        Ok(true)
     }
 
     /// Construct an instance of [`MediaTopology`] from device file.
@@ -248,11 +497,48 @@ impl MediaTopologyBuilder {
     /// # Parameters
     ///
     /// * `media`: A reference to a [`Media`] containing the [`media_info`][crate::Media::info] and
-    /// [`device_fd`][crate::Media::device_fd`].
+    /// [`backend`][crate::Media::backend].
     ///
     /// # Returns
     /// A Result containing the constructed [`MediaTopology`] if successful, or an error otherwise.
-    pub fn from_media(&self, media: &Media) -> Result<MediaTopology> {
-        self.from_fd(media.info(), media.device_fd())
+    pub fn from_media<B>(&self, media: &Media<B>) -> Result<MediaTopology>
+    where
+        B: MediaBackend,
+    {
+        self.from_fd(media.info(), media.backend())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::MockCall;
+
+    #[test]
+    fn limits_and_capacity_hints() {
+        let media = Media::with_backend("/dev/media-mock", mock()).unwrap();
+        let counts = media.topology_counts().unwrap();
+
+        let builder = MediaTopologyBuilder::all().with_limits(None, None, None, Some(1));
+        match builder.from_media(&media) {
+            Err(error::Error::TopologyTooLarge {
+                section: "links",
+                count: 2,
+                limit: 1,
+            }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        let topology = builder.with_links(false).from_media(&media).unwrap();
+        assert_eq!(topology.links(), None);
+        assert_eq!(topology.entities_slice().len(), 3);
+
+        let calls = media.backend().calls().len();
+        let topology = MediaTopologyBuilder::all()
+            .with_capacity_hint(counts)
+            .from_media(&media)
+            .unwrap();
+        assert_eq!(topology, media.backend().topology());
+        assert_eq!(&media.backend().calls()[calls..], [MockCall::GTopology]);
     }
 }
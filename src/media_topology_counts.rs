@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::{Media, MediaBackend, TopologyArrays};
+
+/// Numbers of the objects in a topology, obtained without fetching the objects themselves.
+///
+/// # Details
+/// Fetched with a single `MEDIA_IOC_G_TOPOLOGY` call without any array to be filled,
+/// which is cheaper than building a [`MediaTopology`][crate::MediaTopology] when only the sizes are needed.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MediaTopologyCounts {
+    #[serde(rename = "version")]
+    pub version: u64,
+    #[serde(rename = "entities")]
+    pub entities: u32,
+    #[serde(rename = "interfaces")]
+    pub interfaces: u32,
+    #[serde(rename = "pads")]
+    pub pads: u32,
+    #[serde(rename = "links")]
+    pub links: u32,
+}
+
+impl MediaTopologyCounts {
+    /// Count the objects in the topology of the media device `fd`.
+    pub fn from_fd<B>(fd: B) -> Result<Self>
+    where
+        B: MediaBackend,
+    {
+        fd.g_topology(TopologyArrays::default())
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Count the objects in the topology of the device.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let counts = media.topology_counts()?;
+    ///     let topology = media.new_topology()?;
+    ///     assert_eq!(counts.entities as usize, topology.entities_slice().len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn topology_counts(&self) -> Result<MediaTopologyCounts> {
+        MediaTopologyCounts::from_fd(self.backend())
+    }
+}
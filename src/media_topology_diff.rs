@@ -0,0 +1,318 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::media_entity::EntityId;
+use crate::media_link::LinkType;
+use crate::media_pad::PadId;
+use crate::media_topology::MediaTopology;
+
+/// Kind of the objects compared by [`MediaTopology::diff`].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ObjectKind {
+    Entity,
+    Pad,
+    /// Data links and ancillary links.
+    Link,
+    /// Links between an interface and an entity.
+    InterfaceLink,
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use ObjectKind::*;
+        match self {
+            Entity => write!(f, "entity"),
+            Pad => write!(f, "pad"),
+            Link => write!(f, "link"),
+            InterfaceLink => write!(f, "interface link"),
+        }
+    }
+}
+
+/// How an object differs between two topologies.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// A change of an object between two topologies.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TopologyChange {
+    #[serde(rename = "change")]
+    pub change: ChangeKind,
+    #[serde(rename = "object")]
+    pub object: ObjectKind,
+    /// Identifies the object independently of its id, e.g. `"imx219 10-0010":0 -> "unicam":0` for a data link.
+    #[serde(rename = "key")]
+    pub key: String,
+    /// Function and/or flags of the object before the change. `None` if the object was added.
+    #[serde(rename = "before")]
+    pub before: Option<String>,
+    /// Function and/or flags of the object after the change. `None` if the object was removed.
+    #[serde(rename = "after")]
+    pub after: Option<String>,
+}
+
+/// The differences between two topologies computed by [`MediaTopology::diff`].
+///
+/// # Details
+/// Objects are matched by entity names and pad indices rather than by ids, so that
+/// topologies captured from different boots of the same board can be compared.
+/// [`Display`][fmt::Display] formats the differences in the style of a unified diff.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TopologyDiff {
+    #[serde(rename = "changes")]
+    pub changes: Vec<TopologyChange>,
+}
+
+impl TopologyDiff {
+    /// Whether the compared topologies are the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Iterate over changes of the given kind.
+    pub fn changes_of(&self, change: ChangeKind) -> impl Iterator<Item = &TopologyChange> {
+        self.changes.iter().filter(move |c| c.change == change)
+    }
+
+    /// Format the differences in the style of a unified diff.
+    ///
+    /// * `before`: The label of the old topology printed in the `---` header.
+    /// * `after`: The label of the new topology printed in the `+++` header.
+    pub fn to_unified(&self, before: &str, after: &str) -> String {
+        let mut text = format!("--- {}\n+++ {}\n", before, after);
+        let mut section = None;
+        for change in &self.changes {
+            if section != Some(change.object) {
+                section = Some(change.object);
+                let plural = match change.object {
+                    ObjectKind::Entity => "entities".to_string(),
+                    object => format!("{}s", object),
+                };
+                text.push_str(&format!("@@ {} @@\n", plural));
+            }
+            if let Some(before) = &change.before {
+                text.push_str(&format!("-{} {} {}\n", change.object, change.key, before));
+            }
+            if let Some(after) = &change.after {
+                text.push_str(&format!("+{} {} {}\n", change.object, change.key, after));
+            }
+        }
+        text
+    }
+
+    /// Serialize the list of changes into JSON.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.changes)
+    }
+}
+
+impl fmt::Display for TopologyDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_unified("before", "after"))
+    }
+}
+
+/// Format set flags as `[A | B]`.
+fn flag_names<B>(flags: &B) -> String
+where
+    B: bitflags::Flags,
+    B::Bits: bitflags::parser::WriteHex,
+{
+    let mut names = String::new();
+    bitflags::parser::to_writer(flags, &mut names).expect("writing to a String never fails");
+    format!("[{}]", names)
+}
+
+/// Enumerate the objects of `topology` keyed with their kinds and id-independent names.
+/// Values describe the state of each object.
+pub(crate) fn objects(topology: &MediaTopology) -> BTreeMap<(ObjectKind, String), String> {
+    let entity_name = |id: EntityId| {
+        topology
+            .entities_slice()
+            .iter()
+            .find(|e| e.id() == id)
+            .map(|e| format!("{:?}", e.name()))
+            .unwrap_or_else(|| format!("#{}", u32::from(id)))
+    };
+    let pad_index = |id: PadId| {
+        let pad = topology.pads_slice().iter().find(|pad| pad.id == id)?;
+        Some(pad.index.unwrap_or_else(|| {
+            topology
+                .pads_slice()
+                .iter()
+                .filter(|p| p.entity_id == pad.entity_id)
+                .position(|p| p.id == id)
+                .unwrap_or(0)
+        }))
+        .map(|index| (pad.entity_id, index))
+    };
+    let pad_name = |id: PadId| {
+        pad_index(id)
+            .map(|(entity, index)| format!("{}:{}", entity_name(entity), index))
+            .unwrap_or_else(|| format!("#{}", u32::from(id)))
+    };
+
+    let mut objects = BTreeMap::new();
+    for entity in topology.entities_slice() {
+        let state = match entity.flags() {
+            Some(flags) => format!("{:?} {}", entity.function(), flag_names(&flags)),
+            None => format!("{:?}", entity.function()),
+        };
+        objects.insert((ObjectKind::Entity, entity_name(entity.id())), state);
+    }
+    for pad in topology.pads_slice() {
+        objects.insert(
+            (ObjectKind::Pad, pad_name(pad.id)),
+            format!("[{:?}]", pad.flags),
+        );
+    }
+    for link in topology.links_slice() {
+        let state = flag_names(&link.flags());
+        let key = match link.r#type() {
+            LinkType::DataLink { source_id, sink_id } => (
+                ObjectKind::Link,
+                format!("{} -> {}", pad_name(*source_id), pad_name(*sink_id)),
+            ),
+            LinkType::InterfaceLink { source_id, sink_id } => {
+                let r#type = topology
+                    .interfaces_slice()
+                    .iter()
+                    .find(|intf| intf.id() == *source_id)
+                    .map(|intf| format!("{:?}", intf.r#type()))
+                    .unwrap_or_else(|| format!("#{}", source_id));
+                (
+                    ObjectKind::InterfaceLink,
+                    format!("{} -> {}", r#type, entity_name(*sink_id)),
+                )
+            }
+            LinkType::AncillaryLink { source_id, sink_id } => (
+                ObjectKind::Link,
+                format!(
+                    "{} => {}",
+                    entity_name(source_id.raw().into()),
+                    entity_name(sink_id.raw().into())
+                ),
+            ),
+        };
+        objects.insert(key, state);
+    }
+    objects
+}
+
+impl MediaTopology {
+    /// Compute the differences from `self` to `other`.
+    ///
+    /// # Details
+    /// Entities are matched by name, pads by the name of the owning entity and the pad index,
+    /// and links by their endpoints. Changes are ordered by object kind and then by key.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let before = media.new_topology()?;
+    ///     let after = media.new_topology()?;
+    ///     let diff = before.diff(&after);
+    ///     assert!(diff.is_empty());
+    ///     print!("{}", diff.to_unified("before.json", "after.json"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn diff(&self, other: &MediaTopology) -> TopologyDiff {
+        let before = objects(self);
+        let mut after = objects(other);
+        let mut changes = vec![];
+        for ((object, key), state) in before {
+            match after.remove(&(object, key.clone())) {
+                Some(new) if new == state => {}
+                Some(new) => changes.push(TopologyChange {
+                    change: ChangeKind::Modified,
+                    object,
+                    key,
+                    before: Some(state),
+                    after: Some(new),
+                }),
+                None => changes.push(TopologyChange {
+                    change: ChangeKind::Removed,
+                    object,
+                    key,
+                    before: Some(state),
+                    after: None,
+                }),
+            }
+        }
+        for ((object, key), state) in after {
+            changes.push(TopologyChange {
+                change: ChangeKind::Added,
+                object,
+                key,
+                before: None,
+                after: Some(state),
+            });
+        }
+        changes.sort_by(|a, b| (a.object, &a.key).cmp(&(b.object, &b.key)));
+        TopologyDiff { changes }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::raw as media;
+
+    fn topology(flags: u32, with_lens: bool) -> MediaTopology {
+        let mut entities = vec![entity(1, "sensor"), entity(3, "receiver")];
+        if with_lens {
+            entities.push(entity(6, "lens"));
+        }
+        MediaTopology::new(
+            None,
+            1,
+            Some(entities),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(5, 2, 4, flags)]),
+        )
+    }
+
+    #[test]
+    fn diff_reports_changes() {
+        let before = topology(0, true);
+        let after = topology(media::MEDIA_LNK_FL_ENABLED, false);
+        assert!(before.diff(&before).is_empty());
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.changes.len(), 2);
+        assert_eq!(diff.changes[0].change, ChangeKind::Removed);
+        assert_eq!(diff.changes[0].key, "\"lens\"");
+        assert_eq!(diff.changes[1].change, ChangeKind::Modified);
+        assert_eq!(diff.changes[1].key, "\"sensor\":0 -> \"receiver\":0");
+
+        let text = diff.to_unified("a", "b");
+        assert!(text.contains("-link \"sensor\":0 -> \"receiver\":0 []\n"));
+        assert!(text.contains("+link \"sensor\":0 -> \"receiver\":0 [Enabled]\n"));
+
+        let json: serde_json::Value = serde_json::from_str(&diff.to_json().unwrap()).unwrap();
+        assert_eq!(json[0]["change"], "removed");
+        assert_eq!(json[0]["object"], "entity");
+        assert_eq!(json[1]["after"], "[Enabled]");
+    }
+}
@@ -0,0 +1,434 @@
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::media_entity::{EntityId, MediaEntity, MediaEntityFunctions};
+use crate::media_interface::MediaInterface;
+use crate::media_link::{LinkType, MediaLink, MediaLinkFlags};
+use crate::media_pad::{MediaPad, PadId};
+use crate::media_topology::MediaTopology;
+use crate::media_topology_graph::entity_node;
+
+/// Options controlling the appearance of the graph generated by [`MediaTopology::to_dot`].
+///
+/// # Details
+/// The [`Default`] style resembles the output of `media-ctl --print-dot`:
+/// disabled links are dashed, and interfaces and pipeline clusters are not drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct DotStyle {
+    /// Fill entity nodes with a color chosen by the category of their [function][MediaEntityFunctions].
+    /// If false, sub-devices are filled with green and I/O entities with yellow as `media-ctl` does.
+    #[serde(rename = "color_by_function")]
+    pub color_by_function: bool,
+    /// Draw disabled links with dashed lines. If false, disabled links are omitted.
+    #[serde(rename = "dashed_disabled_links")]
+    pub dashed_disabled_links: bool,
+    /// Include interface nodes and the interface links connecting them to entities.
+    #[serde(rename = "interfaces")]
+    pub interfaces: bool,
+    /// Group entities connected with enabled data links into clusters, one for each pipeline.
+    #[serde(rename = "cluster_pipelines")]
+    pub cluster_pipelines: bool,
+}
+
+impl Default for DotStyle {
+    fn default() -> Self {
+        Self {
+            color_by_function: false,
+            dashed_disabled_links: true,
+            interfaces: false,
+            cluster_pipelines: false,
+        }
+    }
+}
+
+impl DotStyle {
+    /// A style enabling all decorations.
+    pub fn full() -> Self {
+        Self {
+            color_by_function: true,
+            dashed_disabled_links: true,
+            interfaces: true,
+            cluster_pipelines: true,
+        }
+    }
+}
+
+fn interface_node(intf: &MediaInterface) -> String {
+    format!("i{:08x}", u32::from(intf.id()))
+}
+
+/// Escape characters having special meanings in record labels.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '"' | '\\' | '{' | '}' | '|' | '<' | '>') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn function_color(function: MediaEntityFunctions) -> &'static str {
+    use MediaEntityFunctions::*;
+    match function {
+        CAMSensor | Flash | Lens => "lightblue",
+        IoV4L | IoVBI | IoSWRadio | IoDTV => "yellow",
+        ProcVideoComposer
+        | ProcVideoPixelFormatter
+        | ProcVideoPixelEncConv
+        | ProcVideoLUT
+        | ProcVideoScaler
+        | ProcVideoStatistics
+        | ProcVideoEncoder
+        | ProcVideoDecoder => "orange",
+        #[cfg(has_linux_media_sys__MEDIA_ENT_F_PROC_VIDEO_ISP)]
+        ProcVideoISP => "orange",
+        VIDMux | VIDIFBridge | ATVDecoder | DVDecoder | DVEncoder => "palegreen",
+        AudioCapture | AudioPlayback | AudioMixer | IFAUDDecoder => "plum",
+        Tuner | IFVIDDecoder | DTVDemod | TSDemux | DTVCondAccess | DTVNetDecap => "khaki",
+        _ => "lightgrey",
+    }
+}
+
+/// Index of `pad` within its entity.
+/// Falls back to the position in the pads of the entity for kernels not reporting pad indices.
+fn pad_index(pads: &[&MediaPad], pad: &MediaPad) -> usize {
+    pad.index
+        .unwrap_or_else(|| pads.iter().position(|p| p.id == pad.id).unwrap_or(0))
+}
+
+struct Dot<'a> {
+    topology: &'a MediaTopology,
+    style: &'a DotStyle,
+}
+
+impl Dot<'_> {
+    fn pads_of(&self, entity: EntityId) -> Vec<&MediaPad> {
+        self.topology
+            .pads_slice()
+            .iter()
+            .filter(|pad| pad.entity_id == entity)
+            .collect()
+    }
+
+    /// Resolve a pad id into the node name and port of the pad.
+    fn endpoint(&self, id: PadId) -> Option<String> {
+        let pad = self.topology.pads_slice().iter().find(|pad| pad.id == id)?;
+        let pads = self.pads_of(pad.entity_id);
+        Some(format!(
+            "{}:port{}",
+            entity_node(pad.entity_id),
+            pad_index(&pads, pad)
+        ))
+    }
+
+    fn pad_owner(&self, id: PadId) -> Option<EntityId> {
+        self.topology
+            .pads_slice()
+            .iter()
+            .find(|pad| pad.id == id)
+            .map(|pad| pad.entity_id)
+    }
+
+    /// Assign a pipeline number to each entity connected with other entities by enabled data links.
+    fn pipelines(&self) -> BTreeMap<EntityId, usize> {
+        let entities = self.topology.entities_slice();
+        let mut parent: Vec<usize> = (0..entities.len()).collect();
+        fn find(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        let position = |id: EntityId| entities.iter().position(|e| e.id() == id);
+        let mut connected = vec![false; entities.len()];
+        for link in self.topology.links_slice() {
+            if !link.is_enabled() {
+                continue;
+            }
+            if let LinkType::DataLink { source_id, sink_id } = link.r#type() {
+                let ends = self
+                    .pad_owner(*source_id)
+                    .and_then(position)
+                    .zip(self.pad_owner(*sink_id).and_then(position));
+                if let Some((a, b)) = ends {
+                    let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+                    parent[ra] = rb;
+                    connected[a] = true;
+                    connected[b] = true;
+                }
+            }
+        }
+        let mut roots: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut pipelines = BTreeMap::new();
+        for (i, entity) in entities.iter().enumerate() {
+            if connected[i] {
+                let root = find(&mut parent, i);
+                let next = roots.len();
+                let n = *roots.entry(root).or_insert(next);
+                pipelines.insert(entity.id(), n);
+            }
+        }
+        pipelines
+    }
+
+    fn write_entity<W: Write>(&self, w: &mut W, indent: &str, entity: &MediaEntity) -> fmt::Result {
+        let pads = self.pads_of(entity.id());
+        let ports = |sink: bool| -> String {
+            pads.iter()
+                .filter(|pad| pad.flags.is_sink() == sink)
+                .map(|pad| {
+                    let index = pad_index(&pads, pad);
+                    format!("<port{}> {}", index, index)
+                })
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+        let color = if self.style.color_by_function {
+            function_color(entity.function())
+        } else if entity.function().is_io() {
+            "yellow"
+        } else {
+            "green"
+        };
+        if entity.function().is_io() && pads.len() <= 1 {
+            // a record of a single field rather than a box, which has no port for the links to end at
+            let port = pads.first().map_or(String::new(), |pad| {
+                format!("<port{}> ", pad_index(&pads, pad))
+            });
+            writeln!(
+                w,
+                "{}{} [label=\"{}{}\", shape=record, style=filled, fillcolor={}]",
+                indent,
+                entity_node(entity.id()),
+                port,
+                escape(entity.name()),
+                color
+            )
+        } else {
+            writeln!(
+                w,
+                "{}{} [label=\"{{{{{}}} | {} | {{{}}}}}\", shape=Mrecord, style=filled, fillcolor={}]",
+                indent,
+                entity_node(entity.id()),
+                ports(true),
+                escape(entity.name()),
+                ports(false),
+                color
+            )
+        }
+    }
+
+    fn write_link<W: Write>(&self, w: &mut W, link: &MediaLink) -> fmt::Result {
+        let flags = link.flags();
+        let mut attrs = vec![];
+        if flags.contains(MediaLinkFlags::Immutable) {
+            attrs.push("style=bold");
+        } else if !flags.contains(MediaLinkFlags::Enabled) {
+            if !self.style.dashed_disabled_links {
+                return Ok(());
+            }
+            attrs.push("style=dashed");
+        }
+        match link.r#type() {
+            LinkType::DataLink { source_id, sink_id } => {
+                let ends = self.endpoint(*source_id).zip(self.endpoint(*sink_id));
+                if let Some((source, sink)) = ends {
+                    write!(w, "\t{} -> {}", source, sink)?;
+                } else {
+                    return Ok(());
+                }
+            }
+            LinkType::InterfaceLink { source_id, sink_id } => {
+                if !self.style.interfaces {
+                    return Ok(());
+                }
+                write!(
+                    w,
+                    "\ti{:08x} -> {}",
+                    u32::from(*source_id),
+                    entity_node(*sink_id)
+                )?;
+                attrs.push("color=gray");
+                attrs.push("arrowhead=none");
+            }
+            LinkType::AncillaryLink { source_id, sink_id } => {
+                write!(w, "\tn{:08x} -> n{:08x}", source_id.raw(), sink_id.raw())?;
+                attrs.push("style=dotted");
+            }
+        }
+        if attrs.is_empty() {
+            writeln!(w)
+        } else {
+            writeln!(w, " [{}]", attrs.join(", "))
+        }
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> fmt::Result {
+        writeln!(w, "digraph board {{")?;
+        writeln!(w, "\trankdir=TB")?;
+        let pipelines = if self.style.cluster_pipelines {
+            self.pipelines()
+        } else {
+            BTreeMap::new()
+        };
+        let clusters = pipelines.values().max().map_or(0, |n| n + 1);
+        for cluster in 0..clusters {
+            writeln!(w, "\tsubgraph cluster_{} {{", cluster)?;
+            writeln!(w, "\t\tlabel=\"pipeline {}\"", cluster)?;
+            writeln!(w, "\t\tstyle=rounded")?;
+            for entity in self
+                .topology
+                .entities_slice()
+                .iter()
+                .filter(|e| pipelines.get(&e.id()) == Some(&cluster))
+            {
+                self.write_entity(w, "\t\t", entity)?;
+            }
+            writeln!(w, "\t}}")?;
+        }
+        for entity in self
+            .topology
+            .entities_slice()
+            .iter()
+            .filter(|e| !pipelines.contains_key(&e.id()))
+        {
+            self.write_entity(w, "\t", entity)?;
+        }
+        if self.style.interfaces {
+            for intf in self.topology.interfaces_slice() {
+                let devnode = intf.devnode();
+                writeln!(
+                    w,
+                    "\t{} [label=\"{:?}\\n{}:{}\", shape=box, style=\"filled,dashed\", fillcolor=white]",
+                    interface_node(intf),
+                    intf.r#type(),
+                    devnode.major,
+                    devnode.minor
+                )?;
+            }
+        }
+        for link in self.topology.links_slice() {
+            self.write_link(w, link)?;
+        }
+        writeln!(w, "}}")
+    }
+}
+
+impl MediaTopology {
+    /// Write the topology in the Graphviz DOT language.
+    ///
+    /// # Details
+    /// Entities are drawn as records with sink pads on the left and source pads on the right,
+    /// in the same manner as `media-ctl --print-dot`, except that I/O entities of a single pad are records of a single field
+    /// ending the links at the port of the pad. Immutable links are drawn bold.
+    ///
+    /// * `w`: The destination of the output.
+    /// * `style`: Options controlling the appearance of the graph.
+    pub fn write_dot<W>(&self, w: &mut W, style: &DotStyle) -> fmt::Result
+    where
+        W: Write,
+    {
+        Dot {
+            topology: self,
+            style,
+        }
+        .write(w)
+    }
+
+    /// Convert the topology into a graph in the Graphviz DOT language.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = media.new_topology()?;
+    ///     let dot = topology.to_dot(&DotStyle::default());
+    ///     assert!(dot.starts_with("digraph board {"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_dot(&self, style: &DotStyle) -> String {
+        let mut dot = String::new();
+        self.write_dot(&mut dot, style)
+            .expect("writing to a String never fails");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_raw_topology::set_c_str;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::raw as media;
+
+    fn topology(enabled: bool) -> MediaTopology {
+        let flags = if enabled {
+            media::MEDIA_LNK_FL_ENABLED
+        } else {
+            0
+        };
+        MediaTopology::new(
+            None,
+            1,
+            Some(vec![entity(1, "imx219 10-0010"), entity(3, "unicam")]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(5, 2, 4, flags)]),
+        )
+    }
+
+    #[test]
+    fn disabled_links_are_dashed_or_omitted() {
+        let dot = topology(false).to_dot(&DotStyle::default());
+        assert!(dot.contains("n00000001:port0 -> n00000003:port0 [style=dashed]"));
+        let style = DotStyle {
+            dashed_disabled_links: false,
+            ..DotStyle::default()
+        };
+        assert!(!topology(false).to_dot(&style).contains("->"));
+    }
+
+    #[test]
+    fn enabled_links_form_a_pipeline_cluster() {
+        let dot = topology(true).to_dot(&DotStyle::full());
+        assert!(dot.contains("subgraph cluster_0 {"));
+        assert!(dot.contains("\t\tn00000001 [label=\"{{} | imx219 10-0010 | {<port0> 0}}\""));
+        assert!(dot.contains("fillcolor=lightblue"));
+    }
+
+    #[test]
+    fn io_entities_have_ports() {
+        let mut raw: media::media_v2_entity = unsafe { std::mem::zeroed() };
+        raw.id = 5;
+        raw.function = media::MEDIA_ENT_F_IO_V4L;
+        set_c_str(&mut raw.name, "unicam-image");
+        let io = MediaEntity::from_raw(crate::Version::new(6, 1, 0), raw).unwrap();
+        let topology = MediaTopology::new(
+            None,
+            1,
+            Some(vec![entity(1, "imx219 10-0010"), io]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(6, 5, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(7, 2, 6, media::MEDIA_LNK_FL_ENABLED)]),
+        );
+
+        let dot = topology.to_dot(&DotStyle::default());
+        assert!(dot.contains("n00000005 [label=\"<port0> unicam-image\", shape=record"));
+        assert!(dot.contains("n00000001:port0 -> n00000005:port0"));
+    }
+}
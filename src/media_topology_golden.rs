@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::Path;
+
+use crate::error::{self, Result};
+use crate::media_link::LinkType;
+use crate::media_topology::MediaTopology;
+use crate::media_topology_diff::TopologyDiff;
+use crate::{EntityId, InterfaceId, PadId};
+
+/// The environment variable rewriting golden files with the topologies compared, when set to `1`.
+pub const UPDATE_GOLDEN_ENV: &str = "LINUX_MEDIA_UPDATE_GOLDEN";
+
+impl MediaTopology {
+    /// Serialize the topology into pretty JSON ordered independently of the order reported by the kernel.
+    ///
+    /// # Details
+    /// Entities are ordered by name, pads by the names of their entities and indices,
+    /// interfaces by type and device node, and links by the names of their endpoints.
+    /// The path and the topology version are cleared, since they vary between runs.
+    /// The output is deserialized back into a [`MediaTopology`], which is how golden files are read.
+    pub fn to_golden(&self) -> String {
+        let entity_name = |id: EntityId| {
+            self.entities_slice()
+                .iter()
+                .find(|entity| entity.id() == id)
+                .map(|entity| entity.name().to_string())
+        };
+        let interface_name = |id: InterfaceId| {
+            self.interfaces_slice()
+                .iter()
+                .find(|intf| intf.id() == id)
+                .map(|intf| format!("{:?} {:?}", intf.r#type(), intf.devnode()))
+        };
+        let pad_key = |id: PadId| {
+            self.pads_slice()
+                .iter()
+                .find(|pad| pad.id == id)
+                .map(|pad| (entity_name(pad.entity_id), pad.index))
+        };
+
+        let mut entities = self.entities().map(<[_]>::to_vec);
+        if let Some(entities) = &mut entities {
+            entities.sort_by(|a, b| (a.name(), a.id()).cmp(&(b.name(), b.id())));
+        }
+        let mut interfaces = self.interfaces().map(<[_]>::to_vec);
+        if let Some(interfaces) = &mut interfaces {
+            interfaces.sort_by_key(|intf| (intf.r#type(), intf.devnode(), intf.id()));
+        }
+        let mut pads = self.pads().map(<[_]>::to_vec);
+        if let Some(pads) = &mut pads {
+            pads.sort_by_cached_key(|pad| (entity_name(pad.entity_id), pad.index, pad.id));
+        }
+        let mut links = self.links().map(<[_]>::to_vec);
+        if let Some(links) = &mut links {
+            links.sort_by_cached_key(|link| {
+                let endpoints = match link.r#type() {
+                    LinkType::DataLink { source_id, sink_id } => {
+                        (0, pad_key(*source_id), pad_key(*sink_id))
+                    }
+                    LinkType::InterfaceLink { source_id, sink_id } => (
+                        1,
+                        Some((interface_name(*source_id), None)),
+                        Some((entity_name(*sink_id), None)),
+                    ),
+                    LinkType::AncillaryLink { source_id, sink_id } => (
+                        2,
+                        Some((entity_name(source_id.raw().into()), None)),
+                        Some((entity_name(sink_id.raw().into()), None)),
+                    ),
+                };
+                (endpoints, link.id())
+            });
+        }
+        let golden = MediaTopology::new(None, 0, entities, interfaces, pads, links);
+        let mut json =
+            serde_json::to_string_pretty(&golden).expect("serializing a topology never fails");
+        json.push('\n');
+        json
+    }
+}
+
+/// Compare `topology` with the golden file at `path`.
+///
+/// # Details
+/// The topologies are compared by [`MediaTopology::diff`], so ids and the order of objects do not matter.
+/// The golden file is written with [`MediaTopology::to_golden`] if it does not exist,
+/// or if [`UPDATE_GOLDEN_ENV`] is set to `1`.
+///
+/// # Returns
+/// The differences from the golden file to `topology`, which are empty if they match.
+pub fn check_golden<P>(topology: &MediaTopology, path: P) -> Result<TopologyDiff>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let update = std::env::var(UPDATE_GOLDEN_ENV).map_or(false, |value| value.trim() == "1");
+    if update || !path.exists() {
+        fs::write(path, topology.to_golden())
+            .map_err(|err| error::trap_io_error(err, path.to_path_buf()))?;
+        return Ok(TopologyDiff::default());
+    }
+    let golden =
+        fs::read_to_string(path).map_err(|err| error::trap_io_error(err, path.to_path_buf()))?;
+    let golden: MediaTopology = serde_json::from_str(&golden).map_err(|err| error::Error::Io {
+        source: err.into(),
+        path: path.to_path_buf(),
+    })?;
+    Ok(golden.diff(topology))
+}
+
+/// Assert that `topology` matches the golden file at `path`.
+///
+/// # Details
+/// See [`check_golden`] for how they are compared and when the golden file is written.
+///
+/// # Panics
+/// If they differ, with the differences formatted as a unified diff, or if the golden file can not be read.
+///
+/// # Examples
+/// ```no_run
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// let media = Media::from_path("/dev/media0")?;
+/// // run with LINUX_MEDIA_UPDATE_GOLDEN=1 to accept changes
+/// assert_golden(&media.new_topology()?, "tests/golden/media0.json");
+/// # Ok(())
+/// # }
+/// ```
+#[track_caller]
+pub fn assert_golden<P>(topology: &MediaTopology, path: P)
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    match check_golden(topology, path) {
+        Ok(diff) if diff.is_empty() => {}
+        Ok(diff) => panic!(
+            "the topology differs from the golden file, set {}=1 to update it\n{}",
+            UPDATE_GOLDEN_ENV,
+            diff.to_unified(&path.display().to_string(), "actual")
+        ),
+        Err(err) => panic!("failed to check the golden file: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::raw as media;
+    use crate::test_util::TempDir;
+    use crate::MediaLinkFlags;
+
+    fn topology(ids: [u32; 4]) -> MediaTopology {
+        let [sensor, csi, source, sink] = ids;
+        MediaTopology::new(
+            None,
+            7,
+            Some(vec![entity(sensor, "sensor"), entity(csi, "csi")]),
+            Some(vec![]),
+            Some(vec![
+                pad(sink, csi, media::MEDIA_PAD_FL_SINK),
+                pad(source, sensor, media::MEDIA_PAD_FL_SOURCE),
+            ]),
+            Some(vec![link(9, source, sink, 0)]),
+        )
+    }
+
+    #[test]
+    fn golden_ignores_ids_and_order() {
+        let golden = topology([1, 3, 2, 4]).to_golden();
+        assert!(golden.find("\"csi\"") < golden.find("\"sensor\""));
+        let tmp = TempDir::new("golden");
+        let path = tmp.path().join("topology.json");
+        assert!(check_golden(&topology([1, 3, 2, 4]), &path)
+            .unwrap()
+            .is_empty());
+        assert_golden(&topology([11, 13, 12, 14]), &path);
+
+        let mut changed = topology([1, 3, 2, 4]);
+        changed.set_link_flags(9.into(), MediaLinkFlags::Enabled);
+        let diff = check_golden(&changed, &path).unwrap();
+        assert_eq!(diff.changes.len(), 1);
+        assert_eq!(diff.changes[0].key, "\"sensor\":0 -> \"csi\":0");
+    }
+}
@@ -0,0 +1,8 @@
+//! The parts shared by the graph formats of topologies, [DOT][crate::MediaTopology::to_dot] and Mermaid.
+
+use crate::media_entity::EntityId;
+
+/// The name of the node of an entity, which is the same in every format so that graphs are compared easily.
+pub(crate) fn entity_node(id: EntityId) -> String {
+    format!("n{:08x}", u32::from(id))
+}
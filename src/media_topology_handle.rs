@@ -0,0 +1,123 @@
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
+use std::path::Path;
+
+use crate::error::Result;
+use crate::media::Media;
+use crate::media_backend::MediaBackend;
+use crate::media_topology::MediaTopology;
+
+/// A media device together with its topology, which keeps the device open for as long as the topology is used.
+///
+/// # Details
+/// Unlike [`MediaTopology::from_path`] returning the file descriptor and the topology as a pair,
+/// the handle holds both, and the topology is brought up to date by [`refresh`][Self::refresh].
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(mut handle) = MediaTopologyHandle::from_path("/dev/media0") {
+///     println!("{} entities", handle.topology().entities_slice().len());
+///     if handle.refresh()? {
+///         println!("changed to version {}", handle.topology().version());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MediaTopologyHandle<B = OwnedFd> {
+    media: Media<B>,
+    topology: MediaTopology,
+}
+
+impl MediaTopologyHandle {
+    /// Open the device file such like `/dev/media0` and fetch its topology.
+    pub fn from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::new(Media::from_path(path)?)
+    }
+}
+
+impl<B> MediaTopologyHandle<B>
+where
+    B: MediaBackend,
+{
+    /// Fetch the topology of `media` and hold both.
+    ///
+    /// # Details
+    /// The [`path`][MediaTopology::path] of the topology is the path of `media`.
+    pub fn new(media: Media<B>) -> Result<Self> {
+        let mut topology = media.new_topology()?;
+        topology.set_path(media.path().to_path_buf());
+        Ok(Self { media, topology })
+    }
+
+    /// Fetch the topology again if the topology version of the device has changed.
+    ///
+    /// # Returns
+    /// Whether the topology was replaced.
+    pub fn refresh(&mut self) -> Result<bool> {
+        if self.media.topology_counts()?.version == self.topology.version() {
+            return Ok(false);
+        }
+        let mut topology = self.media.new_topology()?;
+        topology.set_path(self.media.path().to_path_buf());
+        self.topology = topology;
+        Ok(true)
+    }
+}
+
+impl<B> MediaTopologyHandle<B> {
+    pub fn media(&self) -> &Media<B> {
+        &self.media
+    }
+
+    /// The topology fetched on construction or by the last [`refresh`][Self::refresh] which replaced it.
+    pub fn topology(&self) -> &MediaTopology {
+        &self.topology
+    }
+
+    pub fn into_parts(self) -> (Media<B>, MediaTopology) {
+        (self.media, self.topology)
+    }
+}
+
+impl<B> AsFd for MediaTopologyHandle<B>
+where
+    B: AsFd,
+{
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.media.as_fd()
+    }
+}
+
+impl<B> AsRawFd for MediaTopologyHandle<B>
+where
+    B: AsFd,
+{
+    fn as_raw_fd(&self) -> RawFd {
+        self.media.as_raw_fd()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+
+    #[test]
+    fn refreshes_on_version_change() {
+        let media = Media::with_backend("/dev/media-mock", mock()).unwrap();
+        let mut handle = MediaTopologyHandle::new(media).unwrap();
+        assert_eq!(handle.topology().path(), Some(Path::new("/dev/media-mock")));
+        assert!(!handle.refresh().unwrap());
+
+        let version = handle.media().backend().bump_version();
+        assert!(handle.refresh().unwrap());
+        assert_eq!(handle.topology().version(), version);
+        assert!(!handle.refresh().unwrap());
+    }
+}
@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+
+use crate::error::Result;
+use crate::media_backend::MediaBackend;
+use crate::media_entity::{EntityId, MediaEntity, MediaEntityCategory, MediaEntityFunctions};
+use crate::media_entity_desc::MediaEntityDescIter;
+use crate::media_interface::MediaInterface;
+use crate::media_interface_type::MediaInterfaceType;
+use crate::media_link::{LinkType, MediaLink, MediaLinkFlags};
+use crate::media_link_enum::MediaLinksEnum;
+use crate::media_pad::{MediaPad, PadId};
+use crate::media_pad_desc::MediaPadDesc;
+use crate::media_topology::MediaTopology;
+
+/// The types of graph objects encoded in the 8 most significant bits of their ids, as `media_gobj_gen_id` does.
+const MEDIA_GRAPH_PAD: u32 = 1;
+const MEDIA_GRAPH_LINK: u32 = 2;
+const MEDIA_GRAPH_INTF_DEVNODE: u32 = 3;
+
+/// Generates ids of objects of a type in the form the kernel assigns.
+struct IdGen {
+    r#type: u32,
+    next: u32,
+}
+
+impl IdGen {
+    fn new(r#type: u32) -> Self {
+        Self { r#type, next: 1 }
+    }
+
+    fn next(&mut self) -> u32 {
+        let id = (self.r#type << 24) | (self.next & 0x00ff_ffff);
+        self.next += 1;
+        id
+    }
+}
+
+/// The type of the interface of an entity having a device node, which is not reported by the legacy API.
+fn interface_type(function: MediaEntityFunctions) -> Option<MediaInterfaceType> {
+    use MediaEntityFunctions::*;
+    match function {
+        IoV4L => Some(MediaInterfaceType::V4LVideo),
+        IoVBI => Some(MediaInterfaceType::V4LVBI),
+        IoSWRadio => Some(MediaInterfaceType::V4LSoftwareDefinedRadio),
+        V4L2SubdevUnknown => Some(MediaInterfaceType::V4LSubdev),
+        _ => match function.category() {
+            MediaEntityCategory::Camera
+            | MediaEntityCategory::Tuner
+            | MediaEntityCategory::Processing
+            | MediaEntityCategory::Video => Some(MediaInterfaceType::V4LSubdev),
+            // the kinds of DVB and ALSA device nodes can not be told from the entities,
+            // nor the ones of entities of unknown functions
+            _ => None,
+        },
+    }
+}
+
+impl MediaTopology {
+    /// Synthesize a topology from `MEDIA_IOC_ENUM_ENTITIES` and `MEDIA_IOC_ENUM_LINKS`, for kernels without `MEDIA_IOC_G_TOPOLOGY`.
+    ///
+    /// # Details
+    /// The legacy API reports neither ids of pads and links nor interfaces, so they are made up:
+    ///
+    /// * Pads and links are numbered in the order of enumeration, with their object types in the most significant bits as the kernel does.
+    /// * An interface is made for each entity with a device node, linked to the entity by an enabled and immutable interface link.
+    ///   Device nodes of entities other than V4L ones, such as DVB and ALSA ones, are skipped as their interface types can not be told.
+    ///
+    /// Ids of entities are the ones reported by the kernel. The version of the topology is always 0.
+    /// [`MediaTopologyBuilder::from_fd`][crate::MediaTopologyBuilder::from_fd] falls back to this
+    /// if `MEDIA_IOC_G_TOPOLOGY` fails with [`NotSupportedIoctl`][crate::error::Error::NotSupportedIoctl].
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = MediaTopology::from_legacy_fd(media.device_fd())?;
+    ///     assert_eq!(topology.version(), 0);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_legacy_fd<B>(fd: B) -> Result<Self>
+    where
+        B: MediaBackend,
+    {
+        let descs = MediaEntityDescIter::all(&fd)
+            .map(|desc| MediaLinksEnum::from_desc(&fd, &desc).map(|enums| (desc, enums)))
+            .collect::<Result<Vec<_>>>()?;
+        let mut pad_ids = IdGen::new(MEDIA_GRAPH_PAD);
+        let mut link_ids = IdGen::new(MEDIA_GRAPH_LINK);
+        let mut intf_ids = IdGen::new(MEDIA_GRAPH_INTF_DEVNODE);
+
+        let mut entities = vec![];
+        let mut interfaces = vec![];
+        let mut pads = vec![];
+        let mut intf_links = vec![];
+        let mut pad_by_index: BTreeMap<(EntityId, usize), PadId> = BTreeMap::new();
+        for (desc, enums) in &descs {
+            entities.push(MediaEntity::new(
+                desc.id(),
+                desc.name(),
+                desc.r#type(),
+                Some(desc.flags()),
+            ));
+            for pad in enums.pads() {
+                let id = PadId::from(pad_ids.next());
+                pad_by_index.insert((pad.id(), pad.index()), id);
+                pads.push(MediaPad {
+                    id,
+                    entity_id: pad.id(),
+                    flags: pad.flags(),
+                    index: Some(pad.index()),
+                });
+            }
+            let devnode = desc.devnode().zip(interface_type(desc.r#type()));
+            if let Some((devnode, r#type)) = devnode {
+                let intf = MediaInterface::new(intf_ids.next().into(), r#type, devnode);
+                intf_links.push(MediaLink::new(
+                    link_ids.next().into(),
+                    LinkType::InterfaceLink {
+                        source_id: intf.id(),
+                        sink_id: desc.id(),
+                    },
+                    MediaLinkFlags::Enabled | MediaLinkFlags::Immutable,
+                ));
+                interfaces.push(intf);
+            }
+        }
+
+        let mut links = vec![];
+        for (_, enums) in &descs {
+            for link in enums.links() {
+                let pad = |desc: MediaPadDesc| pad_by_index.get(&(desc.id(), desc.index()));
+                // links to pads of entities which disappeared during the enumeration
+                let (Some(&source_id), Some(&sink_id)) = (pad(link.source()), pad(link.sink()))
+                else {
+                    continue;
+                };
+                links.push(MediaLink::new(
+                    link_ids.next().into(),
+                    LinkType::DataLink { source_id, sink_id },
+                    link.flags(),
+                ));
+            }
+        }
+        links.extend(intf_links);
+        Ok(MediaTopology::new(
+            None,
+            0,
+            Some(entities),
+            Some(interfaces),
+            Some(pads),
+            Some(links),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::{Media, MediaTopologyBuilder, MockBackend, MockCall, MockOp, Version};
+
+    #[test]
+    fn falls_back_to_legacy_enumeration() {
+        let media = Media::with_backend("/dev/media-mock", mock()).unwrap();
+        media
+            .backend()
+            .script(MockOp::GTopology, [Some(libc::ENOTTY)]);
+        let topology = media.new_topology().unwrap();
+        let expected = media.backend().topology();
+
+        let names = |topology: &MediaTopology| -> Vec<String> {
+            let entities = topology.entities_slice().iter();
+            entities.map(|entity| entity.name().to_string()).collect()
+        };
+        assert_eq!(names(&topology), names(&expected));
+        assert_eq!(topology.version(), 0);
+        assert_eq!(topology.pads_slice().len(), expected.pads_slice().len());
+        assert_eq!(topology.links_slice().len(), 2);
+        for (link, expected_link) in topology.links_slice().iter().zip(expected.links_slice()) {
+            assert!(matches!(link.r#type(), LinkType::DataLink { .. }));
+            assert_eq!(topology.link_desc(link), expected.link_desc(expected_link));
+        }
+
+        media
+            .backend()
+            .script(MockOp::GTopology, [Some(libc::ENOTTY)]);
+        let topology = MediaTopologyBuilder::new()
+            .get_entity()
+            .from_fd(media.info(), media.backend())
+            .unwrap();
+        assert_eq!(topology.entities_slice().len(), 3);
+        assert_eq!(topology.links(), None);
+    }
+
+    #[test]
+    fn cached_topology_falls_back_without_probing_the_version() {
+        let backend = mock();
+        let mut info = backend.info().clone();
+        info.media_version = Version::new(0, 1, 0);
+        let backend = MockBackend::new(info, backend.topology());
+        backend.script(MockOp::GTopology, [Some(libc::ENOTTY)]);
+        let media = Media::with_backend("/dev/media-mock", backend).unwrap();
+        media.backend().clear_calls();
+
+        let topology = media.cached_topology().unwrap();
+        assert_eq!(topology.entities_slice().len(), 3);
+        let probes = media.backend().calls().into_iter();
+        assert_eq!(
+            probes.filter(|call| *call == MockCall::GTopology).count(),
+            1
+        );
+
+        // an odd driver reporting a recent version without MEDIA_IOC_G_TOPOLOGY
+        let media = Media::with_backend("/dev/media-mock", mock()).unwrap();
+        media.backend().fail(MockOp::GTopology, libc::ENOTTY);
+        assert_eq!(media.cached_topology().unwrap().entities_slice().len(), 3);
+    }
+
+    #[test]
+    fn interfaces_are_only_made_for_known_device_nodes() {
+        use MediaEntityFunctions::*;
+        assert_eq!(interface_type(IoV4L), Some(MediaInterfaceType::V4LVideo));
+        assert_eq!(
+            interface_type(CAMSensor),
+            Some(MediaInterfaceType::V4LSubdev)
+        );
+        assert_eq!(
+            interface_type(V4L2SubdevUnknown),
+            Some(MediaInterfaceType::V4LSubdev)
+        );
+        for function in [Unknown, IoDTV, DTVDemod, AudioCapture] {
+            assert_eq!(interface_type(function), None);
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::{self, Write};
+
+use crate::media_entity::EntityId;
+use crate::media_link::{LinkType, MediaLinkFlags};
+use crate::media_pad::PadId;
+use crate::media_topology::MediaTopology;
+use crate::media_topology_graph::entity_node;
+
+/// Escape characters which terminate a quoted label.
+fn escape(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
+
+impl MediaTopology {
+    /// Resolve a pad id into the entity owning the pad and the index of the pad.
+    fn mermaid_endpoint(&self, id: PadId) -> Option<(EntityId, usize)> {
+        let pad = self.pads_slice().iter().find(|pad| pad.id == id)?;
+        let desc = self.pad_desc(pad);
+        Some((desc.id(), desc.index()))
+    }
+
+    /// Write the topology as a [Mermaid](https://mermaid.js.org/) flowchart.
+    ///
+    /// # Details
+    /// Entities are drawn as nodes filled in the same colors as [`to_dot`][MediaTopology::to_dot] does by default,
+    /// and data links as edges labelled with the source and sink pad indices.
+    /// Disabled links are dotted, immutable links are thick, and ancillary links have no arrowhead.
+    ///
+    /// * `w`: The destination of the output.
+    pub fn write_mermaid<W>(&self, w: &mut W) -> fmt::Result
+    where
+        W: Write,
+    {
+        writeln!(w, "flowchart LR")?;
+        writeln!(w, "    classDef subdev fill:#9f9")?;
+        writeln!(w, "    classDef io fill:#ff0")?;
+        for entity in self.entities_slice() {
+            writeln!(
+                w,
+                "    {}[\"{}\"]:::{}",
+                entity_node(entity.id()),
+                escape(entity.name()),
+                if entity.function().is_io() {
+                    "io"
+                } else {
+                    "subdev"
+                }
+            )?;
+        }
+        for link in self.links_slice() {
+            let flags = link.flags();
+            match link.r#type() {
+                LinkType::DataLink { source_id, sink_id } => {
+                    let ends = self
+                        .mermaid_endpoint(*source_id)
+                        .zip(self.mermaid_endpoint(*sink_id));
+                    let Some(((source, source_pad), (sink, sink_pad))) = ends else {
+                        continue;
+                    };
+                    let arrow = if flags.contains(MediaLinkFlags::Immutable) {
+                        "==>"
+                    } else if flags.contains(MediaLinkFlags::Enabled) {
+                        "-->"
+                    } else {
+                        "-.->"
+                    };
+                    writeln!(
+                        w,
+                        "    {} {}|\"{}/{}\"| {}",
+                        entity_node(source),
+                        arrow,
+                        source_pad,
+                        sink_pad,
+                        entity_node(sink)
+                    )?;
+                }
+                LinkType::AncillaryLink { source_id, sink_id } => {
+                    writeln!(w, "    n{:08x} --- n{:08x}", source_id.raw(), sink_id.raw())?
+                }
+                LinkType::InterfaceLink { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Convert the topology into a [Mermaid](https://mermaid.js.org/) flowchart,
+    /// which can be embedded in Markdown documents.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = media.new_topology()?;
+    ///     assert!(topology.to_mermaid().starts_with("flowchart LR"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::new();
+        self.write_mermaid(&mut mermaid)
+            .expect("writing to a String never fails");
+        mermaid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::raw as media;
+
+    #[test]
+    fn disabled_links_are_dotted() {
+        let topology = MediaTopology::new(
+            None,
+            1,
+            Some(vec![entity(1, "imx219 10-0010"), entity(3, "unicam")]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(5, 2, 4, 0)]),
+        );
+        let mermaid = topology.to_mermaid();
+        assert!(mermaid.contains("    n00000001[\"imx219 10-0010\"]:::subdev\n"));
+        assert!(mermaid.contains("    n00000001 -.->|\"0/0\"| n00000003\n"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::media_entity::{EntityId, MediaEntityFunctions};
+use crate::media_interface::InterfaceId;
+use crate::media_interface_type::MediaInterfaceType;
+use crate::media_link::LinkType;
+use crate::media_pad::PadId;
+use crate::media_topology::MediaTopology;
+
+/// Summary statistics of a topology computed by [`MediaTopology::stats`].
+///
+/// # Details
+/// Flags are counted over data links, as interface links are always enabled and immutable.
+///
+/// Orphaned objects refer to objects missing from the topology, which the kernel never reports in a consistent topology.
+/// They appear if the topology changes between fetching its sections, or in topologies edited by hand,
+/// so a nonzero number is worth reporting from long-running services.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MediaTopologyStats {
+    #[serde(rename = "version")]
+    pub version: u64,
+    #[serde(rename = "entities")]
+    pub entities: usize,
+    #[serde(rename = "interfaces")]
+    pub interfaces: usize,
+    #[serde(rename = "pads")]
+    pub pads: usize,
+    #[serde(rename = "links")]
+    pub links: usize,
+    #[serde(rename = "entities_by_function")]
+    pub entities_by_function: BTreeMap<MediaEntityFunctions, usize>,
+    #[serde(rename = "interfaces_by_type")]
+    pub interfaces_by_type: BTreeMap<MediaInterfaceType, usize>,
+    #[serde(rename = "data_links")]
+    pub data_links: usize,
+    #[serde(rename = "interface_links")]
+    pub interface_links: usize,
+    #[serde(rename = "ancillary_links")]
+    pub ancillary_links: usize,
+    #[serde(rename = "enabled_links")]
+    pub enabled_links: usize,
+    #[serde(rename = "immutable_links")]
+    pub immutable_links: usize,
+    #[serde(rename = "dynamic_links")]
+    pub dynamic_links: usize,
+    /// Pads of entities missing from the topology.
+    #[serde(rename = "orphaned_pads")]
+    pub orphaned_pads: usize,
+    /// Links from or to pads, entities or interfaces missing from the topology.
+    #[serde(rename = "orphaned_links")]
+    pub orphaned_links: usize,
+    /// Interfaces not linked to any entity.
+    #[serde(rename = "orphaned_interfaces")]
+    pub orphaned_interfaces: usize,
+}
+
+impl MediaTopology {
+    /// Count the objects of the topology by their kinds.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let stats = media.new_topology()?.stats();
+    ///     println!("{} sensors", stats.entities_by_function.get(&MediaEntityFunctions::CAMSensor).unwrap_or(&0));
+    ///     assert_eq!(stats.orphaned_links, 0);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stats(&self) -> MediaTopologyStats {
+        let entities: BTreeSet<EntityId> = self.entities_slice().iter().map(|e| e.id()).collect();
+        let interfaces: BTreeSet<InterfaceId> =
+            self.interfaces_slice().iter().map(|i| i.id()).collect();
+        let pads: BTreeSet<PadId> = self.pads_slice().iter().map(|pad| pad.id).collect();
+
+        let mut stats = MediaTopologyStats {
+            version: self.version(),
+            entities: entities.len(),
+            interfaces: interfaces.len(),
+            pads: pads.len(),
+            links: self.links_slice().len(),
+            ..Default::default()
+        };
+        for entity in self.entities_slice() {
+            *stats
+                .entities_by_function
+                .entry(entity.function())
+                .or_default() += 1;
+        }
+        for interface in self.interfaces_slice() {
+            *stats
+                .interfaces_by_type
+                .entry(interface.r#type())
+                .or_default() += 1;
+        }
+        stats.orphaned_pads = self
+            .pads_slice()
+            .iter()
+            .filter(|pad| !entities.contains(&pad.entity_id))
+            .count();
+
+        let mut linked_interfaces = BTreeSet::new();
+        for link in self.links_slice() {
+            let orphaned = match link.r#type() {
+                LinkType::DataLink { source_id, sink_id } => {
+                    stats.data_links += 1;
+                    stats.enabled_links += link.is_enabled() as usize;
+                    stats.immutable_links += link.is_immutable() as usize;
+                    stats.dynamic_links += link.is_dynamic() as usize;
+                    !pads.contains(source_id) || !pads.contains(sink_id)
+                }
+                LinkType::InterfaceLink { source_id, sink_id } => {
+                    stats.interface_links += 1;
+                    linked_interfaces.insert(*source_id);
+                    !interfaces.contains(source_id) || !entities.contains(sink_id)
+                }
+                LinkType::AncillaryLink { source_id, sink_id } => {
+                    stats.ancillary_links += 1;
+                    !entities.contains(&EntityId::from(source_id.raw()))
+                        || !entities.contains(&EntityId::from(sink_id.raw()))
+                }
+            };
+            stats.orphaned_links += orphaned as usize;
+        }
+        stats.orphaned_interfaces = interfaces.difference(&linked_interfaces).count();
+        stats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::raw as media;
+
+    #[test]
+    fn counts_links_and_orphans() {
+        let topology = MediaTopology::new(
+            None,
+            3,
+            Some(vec![entity(1, "sensor"), entity(3, "csi")]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+                pad(10, 9, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![
+                link(
+                    5,
+                    2,
+                    4,
+                    media::MEDIA_LNK_FL_ENABLED | media::MEDIA_LNK_FL_IMMUTABLE,
+                ),
+                link(6, 2, 10, 0),
+                link(7, 2, 11, media::MEDIA_LNK_FL_DYNAMIC),
+            ]),
+        );
+        let stats = topology.stats();
+        assert_eq!(stats.version, 3);
+        assert_eq!((stats.entities, stats.pads, stats.links), (2, 3, 3));
+        assert_eq!(
+            stats
+                .entities_by_function
+                .get(&MediaEntityFunctions::CAMSensor),
+            Some(&2)
+        );
+        assert_eq!(stats.data_links, 3);
+        assert_eq!(
+            (
+                stats.enabled_links,
+                stats.immutable_links,
+                stats.dynamic_links
+            ),
+            (1, 1, 1)
+        );
+        // the pad 10 belongs to a missing entity, and the pad 11 is missing
+        assert_eq!(stats.orphaned_pads, 1);
+        assert_eq!(stats.orphaned_links, 1);
+        assert_eq!(stats.orphaned_interfaces, 0);
+    }
+}
@@ -0,0 +1,57 @@
+use layout::backends::svg::SVGWriter;
+use layout::gv::{DotParser, GraphBuilder};
+
+use crate::error::{Error, Result};
+use crate::media_topology::MediaTopology;
+use crate::media_topology_dot::DotStyle;
+
+impl MediaTopology {
+    /// Render the topology graph into an SVG image.
+    ///
+    /// # Details
+    /// The graph generated by [`to_dot`][MediaTopology::to_dot] is laid out and rendered with a pure-Rust implementation,
+    /// so that Graphviz is not required to be installed on the target.
+    /// This function is only available with the `render` feature.
+    ///
+    /// * `style`: Options controlling the appearance of the graph.
+    ///
+    /// # Returns
+    /// A Result containing the SVG document if successful, or [`Error::RenderError`] otherwise.
+    pub fn to_svg(&self, style: &DotStyle) -> Result<String> {
+        let dot = self.to_dot(style);
+        let graph = DotParser::new(&dot)
+            .process()
+            .map_err(|reason| Error::RenderError { reason })?;
+        let mut builder = GraphBuilder::new();
+        builder.visit_graph(&graph);
+        let mut visual = builder.get();
+        let mut svg = SVGWriter::new();
+        visual.do_it(false, false, false, &mut svg);
+        Ok(svg.finalize())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::raw as media;
+
+    #[test]
+    fn render_topology() {
+        let topology = MediaTopology::new(
+            None,
+            1,
+            Some(vec![entity(1, "imx219 10-0010"), entity(3, "unicam")]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(5, 2, 4, media::MEDIA_LNK_FL_ENABLED)]),
+        );
+        let svg = topology.to_svg(&DotStyle::full()).unwrap();
+        assert!(svg.contains("<svg"));
+        assert!(svg.contains("unicam"));
+    }
+}
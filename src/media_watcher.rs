@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::os::fd::OwnedFd;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::media::Media;
+use crate::media_backend::MediaBackend;
+use crate::media_device_enumerator::{MediaDeviceEnumerator, MediaDeviceNode, SYSFS_MEDIA_DEVICES};
+use crate::media_topology::MediaTopology;
+use crate::media_topology_diff::TopologyDiff;
+use crate::name_interner::NameInterner;
+
+/// A change observed by [`MediaWatcher`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum MediaEvent {
+    /// A media device appeared.
+    DeviceAdded {
+        #[serde(rename = "device")]
+        device: MediaDeviceNode,
+    },
+    /// A media device disappeared.
+    DeviceRemoved {
+        #[serde(rename = "device")]
+        device: MediaDeviceNode,
+    },
+    /// The topology version of a media device changed.
+    TopologyChanged {
+        #[serde(rename = "device")]
+        device: MediaDeviceNode,
+        #[serde(rename = "old_version")]
+        old_version: u64,
+        #[serde(rename = "new_version")]
+        new_version: u64,
+        #[serde(rename = "diff")]
+        diff: TopologyDiff,
+    },
+}
+
+/// Where [`MediaWatcher`] looks for media devices.
+///
+/// # Details
+/// Implemented for the paths of directories listing devices as sysfs does,
+/// and for `MockDevices` of the `mock` feature to watch devices without hardware.
+#[cfg_attr(
+    feature = "mock",
+    doc = "",
+    doc = "See [`MockDevices`][crate::MockDevices] for the devices without hardware."
+)]
+pub trait MediaDeviceSource: fmt::Debug {
+    type Backend: MediaBackend + fmt::Debug;
+
+    /// The devices present now.
+    fn devices(&self) -> Result<Vec<MediaDeviceNode>>;
+
+    /// Open the device `node`.
+    fn open(&self, node: &MediaDeviceNode) -> Result<Media<Self::Backend>>;
+}
+
+impl MediaDeviceSource for PathBuf {
+    type Backend = OwnedFd;
+
+    fn devices(&self) -> Result<Vec<MediaDeviceNode>> {
+        let devices = if self == Path::new(SYSFS_MEDIA_DEVICES) {
+            MediaDeviceEnumerator::new()?
+        } else {
+            MediaDeviceEnumerator::with_sysfs(self)?
+        };
+        Ok(devices.collect())
+    }
+
+    fn open(&self, node: &MediaDeviceNode) -> Result<Media> {
+        node.open()
+    }
+}
+
+/// A device being watched.
+#[derive(Debug)]
+struct Watched<B> {
+    node: MediaDeviceNode,
+    /// `None` if the device could not be opened, e.g. for lack of permission.
+    media: Option<(Media<B>, MediaTopology)>,
+}
+
+impl<B: MediaBackend> Watched<B> {
+    fn new<S>(source: &S, node: MediaDeviceNode, interner: &mut NameInterner) -> Self
+    where
+        S: MediaDeviceSource<Backend = B>,
+    {
+        let media = source.open(&node).ok().and_then(|media| {
+            let mut topology = media.new_topology().ok()?;
+            topology.intern_names(interner);
+            Some((media, topology))
+        });
+        Self { node, media }
+    }
+}
+
+/// Watches media devices being added or removed, and topologies being changed.
+///
+/// # Details
+/// The media controller API has no notification mechanism,
+/// so the watcher polls the list of devices in sysfs and the topology version of each device.
+/// Devices are looked for in another [`MediaDeviceSource`] with [`with_source`][MediaWatcher::with_source].
+/// Devices present when the watcher is created are not reported as added.
+/// Devices which can not be opened are reported as added or removed, but their topologies are not watched.
+/// The names of the entities of the watched topologies are shared through a [`NameInterner`].
+///
+/// # Examples
+/// ```no_run
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// for event in MediaWatcher::new()? {
+///     println!("{:?}", event?);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MediaWatcher<S: MediaDeviceSource = PathBuf> {
+    source: S,
+    interval: Duration,
+    devices: BTreeMap<PathBuf, Watched<S::Backend>>,
+    pending: VecDeque<MediaEvent>,
+    interner: NameInterner,
+}
+
+impl MediaWatcher {
+    /// Watch media devices listed in [`/sys/bus/media/devices`][SYSFS_MEDIA_DEVICES].
+    pub fn new() -> Result<Self> {
+        Self::with_sysfs(SYSFS_MEDIA_DEVICES)
+    }
+
+    /// Watch media devices listed in the directory `sysfs`.
+    pub fn with_sysfs<P>(sysfs: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::with_source(sysfs.as_ref().to_path_buf())
+    }
+}
+
+impl<S: MediaDeviceSource> MediaWatcher<S> {
+    /// Watch media devices found in `source`.
+    pub fn with_source(source: S) -> Result<Self> {
+        let mut watcher = Self {
+            source,
+            interval: Duration::from_millis(500),
+            devices: BTreeMap::new(),
+            pending: VecDeque::new(),
+            interner: NameInterner::new(),
+        };
+        watcher.poll()?;
+        watcher.pending.clear();
+        Ok(watcher)
+    }
+
+    /// Set the interval between polls of the blocking iterator. Defaults to 500ms.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The source in which devices are looked for.
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Check the devices once.
+    ///
+    /// # Returns
+    /// The changes since the last poll, or since the watcher was created.
+    pub fn poll(&mut self) -> Result<Vec<MediaEvent>> {
+        let mut present = BTreeMap::new();
+        for node in self.source.devices()? {
+            present.insert(node.path().to_path_buf(), node);
+        }
+        let removed: Vec<PathBuf> = self
+            .devices
+            .keys()
+            .filter(|path| !present.contains_key(*path))
+            .cloned()
+            .collect();
+        for path in removed {
+            if let Some(watched) = self.devices.remove(&path) {
+                self.pending.push_back(MediaEvent::DeviceRemoved {
+                    device: watched.node,
+                });
+            }
+        }
+        for (path, node) in present {
+            match self.devices.get_mut(&path) {
+                None => {
+                    self.pending.push_back(MediaEvent::DeviceAdded {
+                        device: node.clone(),
+                    });
+                    let watched = Watched::new(&self.source, node, &mut self.interner);
+                    self.devices.insert(path, watched);
+                }
+                Some(Watched {
+                    node,
+                    media: Some((media, topology)),
+                }) => {
+                    let Ok(mut current) = media.new_topology() else {
+                        continue;
+                    };
+                    current.intern_names(&mut self.interner);
+                    if current.version() != topology.version() {
+                        self.pending.push_back(MediaEvent::TopologyChanged {
+                            device: node.clone(),
+                            old_version: topology.version(),
+                            new_version: current.version(),
+                            diff: topology.diff(&current),
+                        });
+                        *topology = current;
+                    }
+                }
+                Some(Watched { media: None, .. }) => {}
+            }
+        }
+        self.interner.shrink();
+        Ok(self.pending.drain(..).collect())
+    }
+}
+
+/// Blocks until the next change is observed.
+impl<S: MediaDeviceSource> Iterator for MediaWatcher<S> {
+    type Item = Result<MediaEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            match self.poll() {
+                Ok(events) if events.is_empty() => thread::sleep(self.interval),
+                Ok(events) => self.pending.extend(events),
+                Err(err) => return Some(Err(err)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::fs;
+
+    fn add_device(root: &Path, name: &str) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("uevent"), format!("DEVNAME={}\n", name)).unwrap();
+    }
+
+    #[test]
+    fn reports_added_and_removed_devices() {
+        let tmp = TempDir::new("watch");
+        let root = tmp.path();
+        add_device(root, "media0");
+        let mut watcher = MediaWatcher::with_sysfs(root).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+
+        add_device(root, "media1");
+        fs::remove_dir_all(root.join("media0")).unwrap();
+        let events = watcher.poll().unwrap();
+
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], MediaEvent::DeviceRemoved { device } if device.index() == 0));
+        assert!(matches!(&events[1], MediaEvent::DeviceAdded { device } if device.index() == 1));
+    }
+}
@@ -0,0 +1,360 @@
+//! Snapshots of topology statistics and request counters in the Prometheus text exposition format, with the `metrics` feature.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::media_topology::MediaTopology;
+use crate::media_topology_stats::MediaTopologyStats;
+use crate::{Media, MediaBackend};
+
+/// The operations on requests counted by [`RequestMetrics`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RequestOp {
+    Alloc,
+    Queue,
+    Reinit,
+}
+
+struct RequestCounters {
+    allocated: AtomicU64,
+    queued: AtomicU64,
+    reinitialized: AtomicU64,
+    failed: AtomicU64,
+}
+
+static REQUESTS: RequestCounters = RequestCounters {
+    allocated: AtomicU64::new(0),
+    queued: AtomicU64::new(0),
+    reinitialized: AtomicU64::new(0),
+    failed: AtomicU64::new(0),
+};
+
+/// Count an operation on a request, called by [`Request`][crate::Request].
+pub(crate) fn record_request(op: RequestOp, succeeded: bool) {
+    if !succeeded {
+        REQUESTS.failed.fetch_add(1, Ordering::Relaxed);
+        return;
+    }
+    let counter = match op {
+        RequestOp::Alloc => &REQUESTS.allocated,
+        RequestOp::Queue => &REQUESTS.queued,
+        RequestOp::Reinit => &REQUESTS.reinitialized,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// The numbers of operations on requests made by the process since it started.
+///
+/// # Details
+/// Counted over the requests of all media devices, as they are usually few per process.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RequestMetrics {
+    /// Requests allocated with `MEDIA_IOC_REQUEST_ALLOC`.
+    #[serde(rename = "allocated")]
+    pub allocated: u64,
+    /// Requests queued with `MEDIA_REQUEST_IOC_QUEUE`.
+    #[serde(rename = "queued")]
+    pub queued: u64,
+    /// Requests recycled with `MEDIA_REQUEST_IOC_REINIT`.
+    #[serde(rename = "reinitialized")]
+    pub reinitialized: u64,
+    /// Allocations, queueings and reinitializations failed.
+    #[serde(rename = "failed")]
+    pub failed: u64,
+}
+
+impl RequestMetrics {
+    /// Read the counters of the process.
+    pub fn snapshot() -> Self {
+        Self {
+            allocated: REQUESTS.allocated.load(Ordering::Relaxed),
+            queued: REQUESTS.queued.load(Ordering::Relaxed),
+            reinitialized: REQUESTS.reinitialized.load(Ordering::Relaxed),
+            failed: REQUESTS.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The statistics of the topologies of media devices and the request counters, taken at once for exporting.
+///
+/// # Details
+/// Formatted by [`to_prometheus`][MetricsSnapshot::to_prometheus] or `Display` in the Prometheus text exposition format,
+/// with the metrics of topologies labeled by the paths of their devices, so that it can be served as is from a `/metrics` endpoint.
+/// The metrics are prefixed with `linux_media_`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MetricsSnapshot {
+    /// The statistics of the topologies by the paths of their devices.
+    #[serde(rename = "topologies")]
+    topologies: BTreeMap<String, MediaTopologyStats>,
+    #[serde(rename = "requests")]
+    requests: RequestMetrics,
+}
+
+/// Escape a label value as the text format requires.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Write the `HELP` and `TYPE` lines of a metric.
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP linux_media_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE linux_media_{} {}", name, kind);
+}
+
+/// A gauge of the statistics of a topology: its name, its help text and how its value is taken.
+type Gauge = (&'static str, &'static str, fn(&MediaTopologyStats) -> u64);
+
+impl MetricsSnapshot {
+    /// Take a snapshot of the request counters without any topology.
+    pub fn new() -> Self {
+        Self {
+            topologies: BTreeMap::new(),
+            requests: RequestMetrics::snapshot(),
+        }
+    }
+
+    /// Add the statistics of `topology` of the device `device`, replacing those added for the device before.
+    pub fn add_topology<P>(&mut self, device: P, topology: &MediaTopology)
+    where
+        P: AsRef<Path>,
+    {
+        self.add_stats(device, topology.stats());
+    }
+
+    /// Add the statistics `stats` of the device `device`, replacing those added for the device before.
+    pub fn add_stats<P>(&mut self, device: P, stats: MediaTopologyStats)
+    where
+        P: AsRef<Path>,
+    {
+        let device = device.as_ref().to_string_lossy().into_owned();
+        self.topologies.insert(device, stats);
+    }
+
+    pub fn topologies(&self) -> &BTreeMap<String, MediaTopologyStats> {
+        &self.topologies
+    }
+
+    pub fn requests(&self) -> &RequestMetrics {
+        &self.requests
+    }
+
+    /// Format the snapshot in the Prometheus text exposition format.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// let mut snapshot = MetricsSnapshot::new();
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     snapshot.add_topology(media.path(), &media.new_topology()?);
+    /// }
+    /// let text = snapshot.to_prometheus();
+    /// assert!(text.contains("# TYPE linux_media_requests_queued_total counter"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let gauges: [Gauge; 14] = [
+            (
+                "topology_version",
+                "The version of the topology, bumped by the kernel on every change.",
+                |s| s.version,
+            ),
+            ("entities", "The number of entities.", |s| s.entities as u64),
+            ("interfaces", "The number of interfaces.", |s| {
+                s.interfaces as u64
+            }),
+            ("pads", "The number of pads.", |s| s.pads as u64),
+            ("links", "The number of links of any type.", |s| {
+                s.links as u64
+            }),
+            ("data_links", "The number of links between pads.", |s| {
+                s.data_links as u64
+            }),
+            (
+                "interface_links",
+                "The number of links from interfaces to entities.",
+                |s| s.interface_links as u64,
+            ),
+            (
+                "ancillary_links",
+                "The number of links between entities.",
+                |s| s.ancillary_links as u64,
+            ),
+            ("enabled_links", "The number of enabled data links.", |s| {
+                s.enabled_links as u64
+            }),
+            (
+                "immutable_links",
+                "The number of immutable data links.",
+                |s| s.immutable_links as u64,
+            ),
+            ("dynamic_links", "The number of dynamic data links.", |s| {
+                s.dynamic_links as u64
+            }),
+            (
+                "orphaned_pads",
+                "The number of pads of missing entities.",
+                |s| s.orphaned_pads as u64,
+            ),
+            (
+                "orphaned_links",
+                "The number of links from or to missing objects.",
+                |s| s.orphaned_links as u64,
+            ),
+            (
+                "orphaned_interfaces",
+                "The number of interfaces not linked to any entity.",
+                |s| s.orphaned_interfaces as u64,
+            ),
+        ];
+        if !self.topologies.is_empty() {
+            for (name, help, value) in gauges {
+                header(&mut out, name, "gauge", help);
+                for (device, stats) in &self.topologies {
+                    let _ = writeln!(
+                        out,
+                        "linux_media_{}{{device=\"{}\"}} {}",
+                        name,
+                        escape(device),
+                        value(stats)
+                    );
+                }
+            }
+            header(
+                &mut out,
+                "entities_by_function",
+                "gauge",
+                "The number of entities by their functions.",
+            );
+            for (device, stats) in &self.topologies {
+                for (function, count) in &stats.entities_by_function {
+                    let _ = writeln!(
+                        out,
+                        "linux_media_entities_by_function{{device=\"{}\",function=\"{:?}\"}} {}",
+                        escape(device),
+                        function,
+                        count
+                    );
+                }
+            }
+            header(
+                &mut out,
+                "interfaces_by_type",
+                "gauge",
+                "The number of interfaces by their types.",
+            );
+            for (device, stats) in &self.topologies {
+                for (r#type, count) in &stats.interfaces_by_type {
+                    let _ = writeln!(
+                        out,
+                        "linux_media_interfaces_by_type{{device=\"{}\",type=\"{:?}\"}} {}",
+                        escape(device),
+                        r#type,
+                        count
+                    );
+                }
+            }
+        }
+        let counters = [
+            (
+                "requests_allocated_total",
+                "Requests allocated by the process.",
+                self.requests.allocated,
+            ),
+            (
+                "requests_queued_total",
+                "Requests queued by the process.",
+                self.requests.queued,
+            ),
+            (
+                "requests_reinitialized_total",
+                "Requests reinitialized by the process.",
+                self.requests.reinitialized,
+            ),
+            (
+                "requests_failed_total",
+                "Failed allocations, queueings and reinitializations of requests.",
+                self.requests.failed,
+            ),
+        ];
+        for (name, help, value) in counters {
+            header(&mut out, name, "counter", help);
+            let _ = writeln!(out, "linux_media_{} {}", name, value);
+        }
+        out
+    }
+}
+
+impl fmt::Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_prometheus())
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Take a snapshot of the metrics of the topology of the device and the request counters.
+    ///
+    /// # Errors
+    /// Fetching the topology failed.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     print!("{}", media.metrics()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn metrics(&self) -> Result<MetricsSnapshot> {
+        let mut snapshot = MetricsSnapshot::new();
+        snapshot.add_topology(self.path(), &self.new_topology()?);
+        Ok(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+
+    #[test]
+    fn formats_prometheus_text() {
+        let media = Media::with_backend("/dev/media-mock", mock()).unwrap();
+        let mut snapshot = media.metrics().unwrap();
+        let stats = MediaTopologyStats {
+            orphaned_links: 2,
+            ..Default::default()
+        };
+        snapshot.add_stats("/dev/media\"1", stats);
+        let text = snapshot.to_prometheus();
+
+        assert!(text.contains(
+            "# HELP linux_media_entities The number of entities.\n\
+             # TYPE linux_media_entities gauge\n\
+             linux_media_entities{device=\"/dev/media\\\"1\"} 0\n\
+             linux_media_entities{device=\"/dev/media-mock\"} 3\n"
+        ));
+        assert!(text.contains("linux_media_orphaned_links{device=\"/dev/media\\\"1\"} 2\n"));
+        assert!(text.contains(
+            "linux_media_entities_by_function{device=\"/dev/media-mock\",function=\"CAMSensor\"} 3\n"
+        ));
+        assert!(text.contains("# TYPE linux_media_requests_failed_total counter\n"));
+    }
+}
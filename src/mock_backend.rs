@@ -0,0 +1,545 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::os::fd::OwnedFd;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::error::{Error, Result};
+use crate::media_backend::{MediaBackend, TopologyArrays};
+use crate::media_raw_topology::set_c_str;
+use crate::raw as media;
+use crate::{
+    EntityId, LinkId, LinkType, MediaDeviceInfo, MediaIntfDevnode, MediaLink, MediaLinkDesc,
+    MediaLinkFlags, MediaTopology, MediaTopologyCounts,
+};
+
+/// An operation of [`MediaBackend`], to which errors of [`MockBackend`] are scripted.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum MockOp {
+    DeviceInfo,
+    GTopology,
+    EnumEntities,
+    EnumLinks,
+    SetupLink,
+    RequestAlloc,
+}
+
+impl MockOp {
+    /// The ioctl the operation corresponds to.
+    pub fn api(&self) -> libc::c_ulong {
+        use MockOp::*;
+        match self {
+            DeviceInfo => media::MEDIA_IOC_DEVICE_INFO,
+            GTopology => media::MEDIA_IOC_G_TOPOLOGY,
+            EnumEntities => media::MEDIA_IOC_ENUM_ENTITIES,
+            EnumLinks => media::MEDIA_IOC_ENUM_LINKS,
+            SetupLink => media::MEDIA_IOC_SETUP_LINK,
+            RequestAlloc => media::MEDIA_IOC_REQUEST_ALLOC,
+        }
+    }
+}
+
+/// A call recorded by [`MockBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MockCall {
+    DeviceInfo,
+    GTopology,
+    /// Look up of the entity `id`, which may be or'ed with [`MEDIA_ENT_ID_FLAG_NEXT`][media::MEDIA_ENT_ID_FLAG_NEXT].
+    EnumEntities {
+        id: u32,
+    },
+    EnumLinks {
+        entity: EntityId,
+    },
+    /// Set up of a link as requested.
+    SetupLink {
+        link: MediaLinkDesc,
+    },
+    RequestAlloc,
+}
+
+impl MockCall {
+    pub fn op(&self) -> MockOp {
+        match self {
+            MockCall::DeviceInfo => MockOp::DeviceInfo,
+            MockCall::GTopology => MockOp::GTopology,
+            MockCall::EnumEntities { .. } => MockOp::EnumEntities,
+            MockCall::EnumLinks { .. } => MockOp::EnumLinks,
+            MockCall::SetupLink { .. } => MockOp::SetupLink,
+            MockCall::RequestAlloc => MockOp::RequestAlloc,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    topology: MediaTopology,
+    /// Responses scripted for the next calls of each operation, `None` for serving the call.
+    script: BTreeMap<MockOp, VecDeque<Option<libc::c_int>>>,
+    /// Errors returned by every call of each operation once the script ran out.
+    failures: BTreeMap<MockOp, libc::c_int>,
+    calls: Vec<MockCall>,
+    /// Whether the device is unplugged, failing every call with `ENODEV`.
+    unplugged: bool,
+}
+
+/// A [`MediaBackend`] serving a canned topology, for testing code built on this crate without hardware.
+///
+/// # Details
+/// The device is described by a [`MediaDeviceInfo`] and a [`MediaTopology`],
+/// e.g. one captured from a real board and saved as JSON.
+/// Links are set up on the topology as a driver does, so later calls see the changes.
+/// Requests are not supported and fail with `ENOTTY`.
+///
+/// Errors such as `EBUSY` or `ENOTTY` can be scripted for each operation,
+/// and every call is recorded to be inspected afterwards. Scripted errors report the file descriptor `-1`.
+///
+/// The topology can be changed and the device unplugged at any point of a test,
+/// and [`MockDevices`][crate::MockDevices] serves backends as devices appearing and disappearing.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// # let json = r#"{"path":null,"version":1,"entities":[],"interfaces":[],"pads":[],"links":[]}"#;
+/// let topology: MediaTopology = serde_json::from_str(json).unwrap();
+/// let info = MediaDeviceInfo {
+///     driver: "unicam".to_string(),
+///     model: "unicam".to_string(),
+///     serial: String::new(),
+///     bus_info: "platform:fe801000.csi".to_string(),
+///     media_version: Version::new(6, 1, 0),
+///     hw_revision: 0.into(),
+///     driver_version: Version::new(6, 1, 0),
+/// };
+/// let media = Media::with_backend("/dev/media0", MockBackend::new(info, topology))?;
+/// media.backend().fail(MockOp::SetupLink, libc::EBUSY);
+/// assert_eq!(media.new_topology()?.version(), 1);
+/// assert_eq!(media.backend().calls().last(), Some(&MockCall::GTopology));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MockBackend {
+    info: MediaDeviceInfo,
+    state: Mutex<State>,
+}
+
+impl MockBackend {
+    /// Serve `topology` as the topology of the device described by `info`.
+    ///
+    /// # Details
+    /// Sections of `topology` which were not fetched are served as empty.
+    pub fn new(info: MediaDeviceInfo, topology: MediaTopology) -> Self {
+        Self {
+            info,
+            state: Mutex::new(State {
+                topology,
+                script: BTreeMap::new(),
+                failures: BTreeMap::new(),
+                calls: vec![],
+                unplugged: false,
+            }),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    pub fn info(&self) -> &MediaDeviceInfo {
+        &self.info
+    }
+
+    /// The topology served, including the links set up so far.
+    pub fn topology(&self) -> MediaTopology {
+        self.state().topology.clone()
+    }
+
+    /// Replace the topology served, e.g. to emulate a device whose topology changed.
+    pub fn set_topology(&self, topology: MediaTopology) {
+        self.state().topology = topology;
+    }
+
+    /// Advance the topology version served, as the kernel does when the topology changes.
+    ///
+    /// # Returns
+    /// The new version.
+    pub fn bump_version(&self) -> u64 {
+        self.state().topology.bump_version()
+    }
+
+    /// Add `link` to the topology served and advance the topology version, as a driver creating a link at runtime.
+    pub fn add_link(&self, link: MediaLink) {
+        let mut state = self.state();
+        state.topology.add_link(link);
+        state.topology.bump_version();
+    }
+
+    /// Remove the link `id` from the topology served and advance the topology version.
+    ///
+    /// # Returns
+    /// The link removed, or `None` leaving the topology as is if there is no such link.
+    pub fn remove_link(&self, id: LinkId) -> Option<MediaLink> {
+        let mut state = self.state();
+        let link = state.topology.remove_link(id)?;
+        state.topology.bump_version();
+        Some(link)
+    }
+
+    /// Unplug the device, after which every call fails with `ENODEV` as on a device file of a removed device.
+    pub fn unplug(&self) {
+        self.state().unplugged = true;
+    }
+
+    /// Plug the device unplugged by [`unplug`][Self::unplug] back.
+    pub fn replug(&self) {
+        self.state().unplugged = false;
+    }
+
+    pub fn is_plugged(&self) -> bool {
+        !self.state().unplugged
+    }
+
+    /// Fail every call of `op` with `errno`, once the responses scripted by [`script`][Self::script] ran out.
+    pub fn fail(&self, op: MockOp, errno: libc::c_int) {
+        self.state().failures.insert(op, errno);
+    }
+
+    /// Fail the next call of `op` with `errno`.
+    pub fn fail_once(&self, op: MockOp, errno: libc::c_int) {
+        self.script(op, [Some(errno)]);
+    }
+
+    /// Script the responses to the next calls of `op`, in order.
+    ///
+    /// # Details
+    /// Each response is either the errno the call fails with, or `None` for serving the call.
+    /// Responses are appended to the ones scripted before.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn run(mock: &MockBackend) {
+    /// // the second link set up fails with EBUSY
+    /// mock.script(MockOp::SetupLink, [None, Some(libc::EBUSY)]);
+    /// # }
+    /// ```
+    pub fn script<I>(&self, op: MockOp, responses: I)
+    where
+        I: IntoIterator<Item = Option<libc::c_int>>,
+    {
+        self.state().script.entry(op).or_default().extend(responses);
+    }
+
+    /// Drop all the errors scripted so far.
+    pub fn clear_failures(&self) {
+        let mut state = self.state();
+        state.script.clear();
+        state.failures.clear();
+    }
+
+    /// The calls so far, in order.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.state().calls.clone()
+    }
+
+    /// Forget the calls recorded so far.
+    pub fn clear_calls(&self) {
+        self.state().calls.clear();
+    }
+
+    /// Record `call` and fail it if an error is scripted for it.
+    fn call(&self, call: MockCall) -> Result<MutexGuard<'_, State>> {
+        let op = call.op();
+        let mut state = self.state();
+        state.calls.push(call);
+        if state.unplugged {
+            return Err(self::errno(op, libc::ENODEV));
+        }
+        let scripted = state
+            .script
+            .get_mut(&op)
+            .and_then(|script| script.pop_front());
+        match scripted.unwrap_or_else(|| state.failures.get(&op).copied()) {
+            Some(errno) => Err(self::errno(op, errno)),
+            None => Ok(state),
+        }
+    }
+}
+
+/// Fail `op` with `errno` as the kernel does.
+fn errno(op: MockOp, errno: libc::c_int) -> Error {
+    Error::ioctl_error(-1, errno, op.api())
+}
+
+/// Copy `items` into `array` if requested.
+///
+/// # Errors
+/// `ENOSPC` if the array is too short, as `MEDIA_IOC_G_TOPOLOGY` does.
+fn fill<T, R>(array: Option<&mut [R]>, items: &[T]) -> Result<u32>
+where
+    T: Clone + Into<R>,
+{
+    if let Some(array) = array {
+        if array.len() < items.len() {
+            return Err(errno(MockOp::GTopology, libc::ENOSPC));
+        }
+        for (dst, src) in array.iter_mut().zip(items) {
+            *dst = src.clone().into();
+        }
+    }
+    Ok(items.len() as u32)
+}
+
+impl State {
+    /// The data links going out of `entity`, as enumerated by `MEDIA_IOC_ENUM_LINKS`.
+    fn outbound_links(
+        &self,
+        entity: EntityId,
+    ) -> impl Iterator<Item = (&MediaLink, MediaLinkDesc)> {
+        self.topology.links_slice().iter().filter_map(move |link| {
+            let desc = self.topology.link_desc(link)?;
+            (desc.source().id() == entity).then_some((link, desc))
+        })
+    }
+
+    /// The device node of the first interface linked to `entity`, as reported by `MEDIA_IOC_ENUM_ENTITIES`.
+    fn entity_devnode(&self, entity: EntityId) -> Option<MediaIntfDevnode> {
+        self.topology
+            .links_slice()
+            .iter()
+            .find_map(|link| match link.r#type() {
+                LinkType::InterfaceLink { source_id, sink_id } if *sink_id == entity => self
+                    .topology
+                    .interfaces_slice()
+                    .iter()
+                    .find(|intf| intf.id() == *source_id),
+                _ => None,
+            })
+            .map(|intf| intf.devnode())
+    }
+}
+
+impl MediaBackend for MockBackend {
+    fn device_info(&self) -> Result<media::media_device_info> {
+        let _state = self.call(MockCall::DeviceInfo)?;
+        Ok(self.info.clone().into())
+    }
+
+    fn g_topology(&self, arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts> {
+        let state = self.call(MockCall::GTopology)?;
+        let topology = &state.topology;
+        Ok(MediaTopologyCounts {
+            version: topology.version(),
+            entities: fill(arrays.entities, topology.entities_slice())?,
+            interfaces: fill(arrays.interfaces, topology.interfaces_slice())?,
+            pads: fill(arrays.pads, topology.pads_slice())?,
+            links: fill(arrays.links, topology.links_slice())?,
+        })
+    }
+
+    fn enum_entities(&self, desc: &mut media::media_entity_desc) -> Result<()> {
+        let state = self.call(MockCall::EnumEntities { id: desc.id })?;
+        let mut entities = state.topology.entities_slice().iter();
+        let entity = if desc.id & media::MEDIA_ENT_ID_FLAG_NEXT != 0 {
+            let after = EntityId::from(desc.id & !media::MEDIA_ENT_ID_FLAG_NEXT);
+            entities
+                .filter(|entity| entity.id() > after)
+                .min_by_key(|entity| entity.id())
+        } else {
+            entities.find(|entity| entity.id() == desc.id.into())
+        };
+        let entity = entity.ok_or_else(|| errno(MockOp::EnumEntities, libc::EINVAL))?;
+        *desc = unsafe { std::mem::zeroed() };
+        desc.id = entity.id().into();
+        set_c_str(&mut desc.name, entity.name());
+        desc.type_ = entity.function().into();
+        desc.flags = entity.flags().map_or(0, |flags| flags.bits());
+        desc.pads = state
+            .topology
+            .pads_slice()
+            .iter()
+            .filter(|pad| pad.entity_id == entity.id())
+            .count() as u16;
+        desc.links = state.outbound_links(entity.id()).count() as u16;
+        if let Some(devnode) = state.entity_devnode(entity.id()) {
+            desc.__bindgen_anon_1.dev.major = devnode.major;
+            desc.__bindgen_anon_1.dev.minor = devnode.minor;
+        }
+        Ok(())
+    }
+
+    unsafe fn enum_links(
+        &self,
+        entity: u32,
+        pads: &mut [media::media_pad_desc],
+        links: &mut [media::media_link_desc],
+    ) -> Result<()> {
+        let entity = EntityId::from(entity);
+        let state = self.call(MockCall::EnumLinks { entity })?;
+        let topology = &state.topology;
+        if !topology
+            .entities_slice()
+            .iter()
+            .any(|ent| ent.id() == entity)
+        {
+            return Err(errno(MockOp::EnumLinks, libc::EINVAL));
+        }
+        let entity_pads = topology
+            .pads_slice()
+            .iter()
+            .filter(|pad| pad.entity_id == entity);
+        for (dst, pad) in pads.iter_mut().zip(entity_pads) {
+            *dst = topology.pad_desc(pad).into();
+        }
+        for (dst, (_, desc)) in links.iter_mut().zip(state.outbound_links(entity)) {
+            *dst = desc.into();
+        }
+        Ok(())
+    }
+
+    fn setup_link(&self, link: &mut media::media_link_desc) -> Result<()> {
+        let requested = MediaLinkDesc::from(*link);
+        let mut state = self.call(MockCall::SetupLink { link: requested })?;
+        let found = state.topology.links_slice().iter().find_map(|link| {
+            let desc = state.topology.link_desc(link)?;
+            (desc.source() == requested.source() && desc.sink() == requested.sink())
+                .then(|| (link.id(), desc.flags()))
+        });
+        let Some((id, flags)) = found else {
+            return Err(errno(MockOp::SetupLink, libc::EINVAL));
+        };
+        // only `Enabled` can be changed, and not at all on immutable links
+        let mutable = if flags.contains(MediaLinkFlags::Immutable) {
+            MediaLinkFlags::empty()
+        } else {
+            MediaLinkFlags::Enabled
+        };
+        if flags.difference(mutable) != requested.flags().difference(mutable) {
+            return Err(errno(MockOp::SetupLink, libc::EINVAL));
+        }
+        state.topology.set_link_flags(id, requested.flags());
+        Ok(())
+    }
+
+    fn request_alloc(&self) -> Result<OwnedFd> {
+        let _state = self.call(MockCall::RequestAlloc)?;
+        Err(errno(MockOp::RequestAlloc, libc::ENOTTY))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::{LinkSpec, Media, Version};
+
+    /// A device of a sensor, a CSI-2 receiver and a DMA engine, whose links are disabled.
+    pub(crate) fn mock() -> MockBackend {
+        let mut csi_source = pad(5, 3, media::MEDIA_PAD_FL_SOURCE);
+        csi_source.index = Some(1);
+        let topology = MediaTopology::new(
+            None,
+            1,
+            Some(vec![
+                entity(1, "sensor"),
+                entity(3, "csi"),
+                entity(6, "dma"),
+            ]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+                csi_source,
+                pad(7, 6, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(8, 2, 4, 0), link(9, 5, 7, 0)]),
+        );
+        let info = MediaDeviceInfo {
+            driver: "mock".to_string(),
+            model: "mock".to_string(),
+            serial: String::new(),
+            bus_info: "platform:mock".to_string(),
+            media_version: Version::new(6, 1, 0),
+            hw_revision: 0.into(),
+            driver_version: Version::new(6, 1, 0),
+        };
+        MockBackend::new(info, topology)
+    }
+
+    fn media() -> Media<MockBackend> {
+        Media::with_backend("/dev/media-mock", mock()).unwrap()
+    }
+
+    #[test]
+    fn serves_topology_and_legacy_enumeration() {
+        let media = media();
+        assert_eq!(media.info().driver(), "mock");
+        let topology = media.new_topology().unwrap();
+        assert_eq!(&topology, &media.backend().topology());
+
+        let enums = media.links_enums().unwrap();
+        assert_eq!(enums.len(), 3);
+        assert_eq!(enums[1].entity(), EntityId::from(3));
+        assert_eq!(enums[1].pads().len(), 2);
+        assert_eq!(enums[1].links().len(), 1);
+        assert_eq!(enums[1].links()[0].sink().id(), EntityId::from(6));
+    }
+
+    #[test]
+    fn failed_setup_is_rolled_back() {
+        let media = media();
+        let specs =
+            LinkSpec::parse_list("\"sensor\":0->\"csi\":0[1], \"csi\":1->\"dma\":0[1]").unwrap();
+        media
+            .backend()
+            .script(MockOp::SetupLink, [None, Some(libc::EBUSY)]);
+        media.backend().clear_calls();
+
+        let err = media.setup_links(&specs).unwrap_err();
+        assert!(matches!(err, Error::DeviceIsBusy { .. }));
+        let setups: Vec<_> = media
+            .backend()
+            .calls()
+            .into_iter()
+            .filter_map(|call| match call {
+                MockCall::SetupLink { link } => Some(link.flags()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            setups,
+            [
+                MediaLinkFlags::Enabled,
+                MediaLinkFlags::Enabled,
+                MediaLinkFlags::empty()
+            ]
+        );
+        let topology = media.backend().topology();
+        assert!(topology
+            .links_slice()
+            .iter()
+            .all(|link| link.flags().is_empty()));
+
+        media.setup_links(&specs).unwrap();
+        assert!(media
+            .backend()
+            .topology()
+            .links_slice()
+            .iter()
+            .all(|link| link.flags() == MediaLinkFlags::Enabled));
+    }
+
+    #[test]
+    fn requests_support_is_probed() {
+        let media = media();
+        let capabilities = media.info().capabilities();
+        assert_eq!(capabilities.supports_requests(), None);
+        assert!(capabilities.supports_ancillary_links());
+        assert_eq!(media.capabilities().supports_requests(), Some(false));
+
+        media
+            .backend()
+            .script(MockOp::RequestAlloc, [Some(libc::EMFILE)]);
+        assert_eq!(media.capabilities().supports_requests(), None);
+    }
+}
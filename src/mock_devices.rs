@@ -0,0 +1,143 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::error::{Error, Result};
+use crate::media_watcher::MediaDeviceSource;
+use crate::{Media, MediaDeviceNode, MockBackend};
+
+/// Media devices served by [`MockBackend`]s, which are plugged and unplugged as a test goes.
+///
+/// # Details
+/// The devices are listed as `/dev/mediaN` of the indices they are plugged at,
+/// so [`MediaWatcher::with_source`][crate::MediaWatcher::with_source] reports them added and removed
+/// and their topologies changed as it does for real devices.
+/// Unplugging a device also unplugs its backend, so devices opened before fail with `ENODEV`.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use linux_media::*;
+/// # fn run(mock: MockBackend) -> error::Result<()> {
+/// let devices = Arc::new(MockDevices::new());
+/// let mut watcher = MediaWatcher::with_source(devices.clone())?;
+/// let backend = devices.plug(0, mock);
+/// assert!(matches!(watcher.poll()?[..], [MediaEvent::DeviceAdded { .. }]));
+/// backend.bump_version();
+/// assert!(matches!(watcher.poll()?[..], [MediaEvent::TopologyChanged { .. }]));
+/// devices.unplug(0);
+/// assert!(matches!(watcher.poll()?[..], [MediaEvent::DeviceRemoved { .. }]));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct MockDevices {
+    devices: Mutex<BTreeMap<u32, Arc<MockBackend>>>,
+}
+
+impl MockDevices {
+    /// No device plugged.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<u32, Arc<MockBackend>>> {
+        self.devices.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Plug `backend` as the device `/dev/mediaN` of the index `index`, replacing the device plugged there if any.
+    ///
+    /// # Returns
+    /// The backend shared with the devices opened, through which the topology is changed afterwards.
+    pub fn plug(&self, index: u32, backend: MockBackend) -> Arc<MockBackend> {
+        backend.replug();
+        let backend = Arc::new(backend);
+        if let Some(old) = self.lock().insert(index, backend.clone()) {
+            old.unplug();
+        }
+        backend
+    }
+
+    /// Unplug the device of `index`.
+    ///
+    /// # Returns
+    /// The backend of the device, or `None` if no device is plugged at `index`.
+    pub fn unplug(&self, index: u32) -> Option<Arc<MockBackend>> {
+        let backend = self.lock().remove(&index)?;
+        backend.unplug();
+        Some(backend)
+    }
+
+    /// The backend of the device of `index`.
+    pub fn backend(&self, index: u32) -> Option<Arc<MockBackend>> {
+        self.lock().get(&index).cloned()
+    }
+}
+
+impl MediaDeviceSource for Arc<MockDevices> {
+    type Backend = Arc<MockBackend>;
+
+    fn devices(&self) -> Result<Vec<MediaDeviceNode>> {
+        Ok(self
+            .lock()
+            .iter()
+            .map(|(index, backend)| {
+                MediaDeviceNode::with_index(*index, Some(backend.info().model().to_string()))
+            })
+            .collect())
+    }
+
+    fn open(&self, node: &MediaDeviceNode) -> Result<Media<Arc<MockBackend>>> {
+        let backend = self
+            .backend(node.index())
+            .ok_or_else(|| Error::FileNotFound {
+                path: node.path().to_path_buf(),
+                source: std::io::ErrorKind::NotFound.into(),
+            })?;
+        Media::with_backend(node.path(), backend)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::link;
+    use crate::mock_backend::test::mock;
+    use crate::{ChangeKind, LinkId, MediaEvent, MediaWatcher};
+
+    #[test]
+    fn watches_hotplug_and_topology_changes() {
+        let devices = Arc::new(MockDevices::new());
+        let mut watcher = MediaWatcher::with_source(devices.clone()).unwrap();
+        assert!(watcher.poll().unwrap().is_empty());
+
+        let backend = devices.plug(1, mock());
+        let media = devices.open(&devices.devices().unwrap()[0]).unwrap();
+        let events = watcher.poll().unwrap();
+        assert!(matches!(&events[..], [MediaEvent::DeviceAdded { device }] if device.index() == 1));
+
+        backend.add_link(link(10, 2, 7, 0));
+        assert_eq!(backend.remove_link(LinkId::from(8)).unwrap().id(), 8.into());
+        assert!(backend.remove_link(LinkId::from(8)).is_none());
+        let events = watcher.poll().unwrap();
+        let [MediaEvent::TopologyChanged {
+            old_version,
+            new_version,
+            diff,
+            ..
+        }] = &events[..]
+        else {
+            panic!("unexpected events: {:?}", events);
+        };
+        assert_eq!((*old_version, *new_version), (1, 3));
+        assert_eq!(diff.changes_of(ChangeKind::Added).count(), 1);
+        assert_eq!(diff.changes_of(ChangeKind::Removed).count(), 1);
+
+        devices.unplug(1).unwrap();
+        let err = media.new_topology().unwrap_err();
+        assert_eq!(err.errno(), Some(libc::ENODEV));
+        let events = watcher.poll().unwrap();
+        assert!(
+            matches!(&events[..], [MediaEvent::DeviceRemoved { device }] if device.index() == 1)
+        );
+    }
+}
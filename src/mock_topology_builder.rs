@@ -0,0 +1,221 @@
+use crate::{
+    EntityId, InterfaceId, LinkId, LinkType, MediaEntity, MediaEntityFlags, MediaEntityFunctions,
+    MediaInterface, MediaInterfaceType, MediaIntfDevnode, MediaLink, MediaLinkFlags, MediaPad,
+    MediaPadFlags, MediaTopology, PadId,
+};
+
+/// The types of graph objects, encoded in the upper bits of their ids by the kernel.
+const ENTITY: u32 = 0;
+const PAD: u32 = 1;
+const LINK: u32 = 2;
+const INTF_DEVNODE: u32 = 3;
+
+/// The bits of an id numbering the objects of a device.
+const ID_BITS: u32 = 24;
+
+/// Build the id of a graph object as `media_gobj_gen_id` of the kernel does.
+const fn gobj_id(r#type: u32, local_id: u32) -> u32 {
+    (r#type << ID_BITS) | (local_id & ((1 << ID_BITS) - 1))
+}
+
+/// Builds a [`MediaTopology`] for tests, numbering the objects as the kernel does.
+///
+/// # Details
+/// The kernel numbers the objects of a device in the order registered with a single counter starting from 1,
+/// and puts the type of each object in the upper 8 bits of its id, e.g. `0x01000002` for the pad registered second.
+/// The builder does the same, so the ids are the same on every run and shaped like the ones of real devices.
+/// [`next_id`][Self::next_id] overrides the number of the next object.
+///
+/// Entities are registered along with their pads, and interfaces along with their links to the entities.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+///
+/// let mut builder = MockTopologyBuilder::new();
+/// let (_, sensor) = builder.entity("imx219 10-0010", MediaEntityFunctions::CAMSensor, &[MediaPadFlags::Source]);
+/// let (unicam, image) = builder.entity("unicam-image", MediaEntityFunctions::IoV4L, &[MediaPadFlags::Sink]);
+/// builder.link(sensor[0], image[0], MediaLinkFlags::Enabled | MediaLinkFlags::Immutable);
+/// builder.interface(MediaInterfaceType::V4LVideo, MediaIntfDevnode { major: 81, minor: 0 }, unicam);
+/// let topology = builder.build();
+/// assert_eq!(topology.entities_slice()[0].id(), EntityId::from(1));
+/// assert_eq!(topology.pads_slice()[0].id, PadId::from(0x0100_0002));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockTopologyBuilder {
+    next: u32,
+    version: u64,
+    entities: Vec<MediaEntity>,
+    interfaces: Vec<MediaInterface>,
+    pads: Vec<MediaPad>,
+    links: Vec<MediaLink>,
+}
+
+impl Default for MockTopologyBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockTopologyBuilder {
+    pub fn new() -> Self {
+        Self {
+            next: 1,
+            version: 1,
+            entities: vec![],
+            interfaces: vec![],
+            pads: vec![],
+            links: vec![],
+        }
+    }
+
+    /// Number the next object `id`, and the following ones after it.
+    pub fn next_id(&mut self, id: u32) -> &mut Self {
+        self.next = id;
+        self
+    }
+
+    /// The topology version of the topology built, `1` by default.
+    pub fn version(&mut self, version: u64) -> &mut Self {
+        self.version = version;
+        self
+    }
+
+    fn id(&mut self, r#type: u32) -> u32 {
+        let id = gobj_id(r#type, self.next);
+        self.next += 1;
+        id
+    }
+
+    /// Register an entity and its pads, whose indices are the positions in `pads`.
+    ///
+    /// # Returns
+    /// The ids of the entity and of its pads.
+    pub fn entity(
+        &mut self,
+        name: &str,
+        function: MediaEntityFunctions,
+        pads: &[MediaPadFlags],
+    ) -> (EntityId, Vec<PadId>) {
+        let id = EntityId::from(self.id(ENTITY));
+        self.entities.push(MediaEntity::new(
+            id,
+            name,
+            function,
+            Some(MediaEntityFlags::empty()),
+        ));
+        let pads = pads
+            .iter()
+            .enumerate()
+            .map(|(index, flags)| {
+                let pad = PadId::from(self.id(PAD));
+                self.pads.push(MediaPad {
+                    id: pad,
+                    entity_id: id,
+                    flags: *flags,
+                    index: Some(index),
+                });
+                pad
+            })
+            .collect();
+        (id, pads)
+    }
+
+    /// Replace the flags of the entity `id`.
+    pub fn entity_flags(&mut self, id: EntityId, flags: MediaEntityFlags) -> &mut Self {
+        if let Some(entity) = self.entities.iter_mut().find(|entity| entity.id() == id) {
+            *entity = MediaEntity::new(id, entity.name(), entity.function(), Some(flags));
+        }
+        self
+    }
+
+    /// Register a data link from the pad `source` to the pad `sink`.
+    pub fn link(&mut self, source: PadId, sink: PadId, flags: MediaLinkFlags) -> LinkId {
+        let id = LinkId::from(self.id(LINK));
+        self.links.push(MediaLink::new(
+            id,
+            LinkType::DataLink {
+                source_id: source,
+                sink_id: sink,
+            },
+            flags,
+        ));
+        id
+    }
+
+    /// Register an interface of a device node and its link to `entity`.
+    pub fn interface(
+        &mut self,
+        r#type: MediaInterfaceType,
+        devnode: MediaIntfDevnode,
+        entity: EntityId,
+    ) -> InterfaceId {
+        let id = InterfaceId::from(self.id(INTF_DEVNODE));
+        self.interfaces
+            .push(MediaInterface::new(id, r#type, devnode));
+        let link = LinkId::from(self.id(LINK));
+        self.links.push(MediaLink::new(
+            link,
+            LinkType::InterfaceLink {
+                source_id: id,
+                sink_id: entity,
+            },
+            MediaLinkFlags::Enabled | MediaLinkFlags::Immutable,
+        ));
+        id
+    }
+
+    pub fn build(&self) -> MediaTopology {
+        MediaTopology::new(
+            None,
+            self.version,
+            Some(self.entities.clone()),
+            Some(self.interfaces.clone()),
+            Some(self.pads.clone()),
+            Some(self.links.clone()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ids_follow_kernel_conventions() {
+        let mut builder = MockTopologyBuilder::new();
+        let (sensor, source) = builder.entity(
+            "sensor",
+            MediaEntityFunctions::CAMSensor,
+            &[MediaPadFlags::Source],
+        );
+        builder.next_id(10);
+        let (csi, sink) = builder.entity(
+            "csi",
+            MediaEntityFunctions::VIDIFBridge,
+            &[MediaPadFlags::Sink],
+        );
+        let link = builder.link(source[0], sink[0], MediaLinkFlags::Enabled);
+        let intf = builder.interface(
+            MediaInterfaceType::V4LSubdev,
+            MediaIntfDevnode {
+                major: 81,
+                minor: 1,
+            },
+            csi,
+        );
+
+        assert_eq!(sensor, EntityId::from(1));
+        assert_eq!(source, [PadId::from(0x0100_0002)]);
+        assert_eq!(csi, EntityId::from(10));
+        assert_eq!(sink, [PadId::from(0x0100_000b)]);
+        assert_eq!(link, LinkId::from(0x0200_000c));
+        assert_eq!(intf, InterfaceId::from(0x0300_000d));
+
+        let topology = builder.build();
+        assert_eq!(topology.links_slice().len(), 2);
+        let desc = topology.link_desc(&topology.links_slice()[0]).unwrap();
+        assert_eq!(desc.sink().id(), csi);
+        assert_eq!(builder.build(), topology);
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// A pool of entity names shared by the topologies converted with it.
+///
+/// # Details
+/// Every snapshot of a topology holds the names of its entities, which rarely change between snapshots.
+/// Converting snapshots with the same interner, e.g. by [`RawTopology::to_topology_interned`][crate::RawTopology::to_topology_interned],
+/// makes entities of the same name share a single [`Arc<str>`] instead of allocating a string for each snapshot.
+///
+/// Names stay in the pool until [`shrink`][Self::shrink] drops the ones no longer held by any topology.
+///
+/// # Examples
+/// ```
+/// use std::sync::Arc;
+/// use linux_media::*;
+///
+/// let mut interner = NameInterner::new();
+/// let a = interner.intern("imx219 10-0010");
+/// let b = interner.intern("imx219 10-0010");
+/// assert!(Arc::ptr_eq(&a, &b));
+/// assert_eq!(interner.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NameInterner {
+    names: HashSet<Arc<str>>,
+}
+
+impl NameInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The pooled name equal to `name`, which is added to the pool if it is not there yet.
+    pub fn intern(&mut self, name: &str) -> Arc<str> {
+        if let Some(interned) = self.names.get(name) {
+            return Arc::clone(interned);
+        }
+        let interned: Arc<str> = Arc::from(name);
+        self.names.insert(Arc::clone(&interned));
+        interned
+    }
+
+    /// The number of names in the pool.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Drop the names which are only held by the pool, e.g. after old snapshots are dropped.
+    pub fn shrink(&mut self) {
+        self.names.retain(|name| Arc::strong_count(name) > 1);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock_backend::test::mock;
+    use crate::{Media, RawTopology};
+
+    #[test]
+    fn snapshots_share_names() {
+        let media = Media::with_backend("/dev/media-mock", mock()).unwrap();
+        let raw = RawTopology::from_fd(media.info(), media.backend()).unwrap();
+        let mut interner = NameInterner::new();
+        let first = raw.to_topology_interned(&mut interner).unwrap();
+        let second = raw.to_topology_interned(&mut interner).unwrap();
+        assert_eq!(first, raw.to_topology().unwrap());
+        assert_eq!(first, second);
+        assert_eq!(interner.len(), 3);
+        for (a, b) in first.entities_slice().iter().zip(second.entities_slice()) {
+            assert!(Arc::ptr_eq(a.name_arc(), b.name_arc()));
+        }
+
+        let mut third = media.new_topology().unwrap();
+        third.intern_names(&mut interner);
+        assert!(Arc::ptr_eq(
+            first.entities_slice()[0].name_arc(),
+            third.entities_slice()[0].name_arc()
+        ));
+
+        drop((first, second, third));
+        interner.shrink();
+        assert!(interner.is_empty());
+    }
+}
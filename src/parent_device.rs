@@ -0,0 +1,240 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::media_intf_devnode::MediaIntfDevnode;
+use crate::{BusInfo, Media, MediaBackend, MediaDeviceNode};
+
+/// The physical device a media device belongs to, found by walking sysfs.
+///
+/// # Details
+/// Unlike [`BusInfo`], which drivers format as they like, this is read from the sysfs hierarchy,
+/// so that media devices of the same card or the same USB device compare equal and can be grouped together.
+#[derive(Debug, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ParentDevice {
+    /// A PCI function such like `0000:02:00.0`.
+    Pci {
+        #[serde(rename = "domain")]
+        domain: u16,
+        #[serde(rename = "bus")]
+        bus: u8,
+        #[serde(rename = "device")]
+        device: u8,
+        #[serde(rename = "function")]
+        function: u8,
+    },
+    /// A USB device, numbered as `lsusb` does.
+    Usb {
+        #[serde(rename = "busnum")]
+        busnum: u32,
+        #[serde(rename = "devnum")]
+        devnum: u32,
+        /// The name in sysfs, which is the ports from the root hub such like `1-2.3`.
+        #[serde(rename = "port")]
+        port: String,
+    },
+    /// A platform device such like `fe801000.csi`.
+    Platform {
+        #[serde(rename = "name")]
+        name: String,
+    },
+}
+
+/// The name of the subsystem of the device in the sysfs directory `dir`.
+fn subsystem(dir: &Path) -> Option<String> {
+    let link = fs::read_link(dir.join("subsystem")).ok()?;
+    Some(link.file_name()?.to_string_lossy().into_owned())
+}
+
+fn attribute(dir: &Path, name: &str) -> Option<u32> {
+    fs::read_to_string(dir.join(name)).ok()?.trim().parse().ok()
+}
+
+impl ParentDevice {
+    /// Find the nearest PCI function, USB device or platform device of the device in the sysfs directory `device` or its ancestors.
+    fn read(device: &Path) -> Option<Self> {
+        device.ancestors().find_map(|dir| {
+            let name = dir.file_name()?.to_str()?;
+            match subsystem(dir)?.as_str() {
+                "pci" => match BusInfo::parse(&format!("PCI:{}", name)) {
+                    BusInfo::Pci {
+                        domain,
+                        bus,
+                        device,
+                        function,
+                    } => Some(ParentDevice::Pci {
+                        domain,
+                        bus,
+                        device,
+                        function,
+                    }),
+                    _ => None,
+                },
+                // interfaces of USB devices are in the usb subsystem as well, but have no device numbers
+                "usb" => Some(ParentDevice::Usb {
+                    busnum: attribute(dir, "busnum")?,
+                    devnum: attribute(dir, "devnum")?,
+                    port: name.to_string(),
+                }),
+                "platform" => Some(ParentDevice::Platform {
+                    name: name.to_string(),
+                }),
+                _ => None,
+            }
+        })
+    }
+
+    /// Find the parent of the character device `devnode`, walking `/sys/dev/char`.
+    ///
+    /// # Returns
+    /// `None` if sysfs is not available, the device node is not known to it,
+    /// or the device is not on PCI, USB or the platform bus, e.g. virtual devices such like `vimc`.
+    pub fn from_devnode(devnode: MediaIntfDevnode) -> Option<Self> {
+        Self::read(&fs::canonicalize(PathBuf::from(devnode).join("device")).ok()?)
+    }
+
+    /// The name of the bus, `"pci"`, `"usb"` or `"platform"`.
+    pub fn bus(&self) -> &'static str {
+        match self {
+            ParentDevice::Pci { .. } => "pci",
+            ParentDevice::Usb { .. } => "usb",
+            ParentDevice::Platform { .. } => "platform",
+        }
+    }
+}
+
+impl fmt::Display for ParentDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParentDevice::Pci {
+                domain,
+                bus,
+                device,
+                function,
+            } => write!(
+                f,
+                "pci {:04x}:{:02x}:{:02x}.{:x}",
+                domain, bus, device, function
+            ),
+            ParentDevice::Usb { busnum, devnum, .. } => {
+                write!(f, "usb {:03}:{:03}", busnum, devnum)
+            }
+            ParentDevice::Platform { name } => write!(f, "platform {}", name),
+        }
+    }
+}
+
+impl MediaDeviceNode {
+    /// Find the PCI function, USB device or platform device the media device belongs to, without opening it.
+    ///
+    /// # Examples
+    /// Group media devices by the cards they belong to.
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(devices) = enumerate() {
+    ///     let mut cards: BTreeMap<ParentDevice, Vec<MediaDeviceNode>> = BTreeMap::new();
+    ///     for node in devices {
+    ///         if let Some(parent) = node.parent_device() {
+    ///             cards.entry(parent).or_default().push(node);
+    ///         }
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parent_device(&self) -> Option<ParentDevice> {
+        ParentDevice::read(&fs::canonicalize(self.sysfs_path().join("device")).ok()?)
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Find the PCI function, USB device or platform device the media device belongs to.
+    ///
+    /// # Details
+    /// Complements [`bus_info`][crate::MediaDeviceInfo::bus_info] with data read from sysfs,
+    /// e.g. for grouping the media devices of a capture card exposing one media device per input.
+    ///
+    /// # Returns
+    /// `Ok(None)` if the device is not on PCI, USB or the platform bus.
+    ///
+    /// # Errors
+    /// The [`path`][Media::path] of the device is not a character device, e.g. if the device was opened with a mock backend.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     if let Some(parent) = media.parent_device()? {
+    ///         println!("{} on {}", media.info().driver(), parent);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parent_device(&self) -> Result<Option<ParentDevice>> {
+        let devnode = MediaIntfDevnode::from_char_device(self.path())?;
+        Ok(ParentDevice::from_devnode(devnode))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn finds_nearest_parent_device() {
+        let tmp = TempDir::new("parent");
+        let root = tmp.path();
+        let pci = root.join("devices/pci0000:00/0000:00:14.0");
+        let usb = pci.join("usb1/1-2");
+        let intf = usb.join("1-2:1.0");
+        let capture = root.join("devices/pci0000:00/0000:02:00.0");
+        let csi = root.join("devices/platform/soc/fe801000.csi");
+        for dir in [&intf, &capture, &csi] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        for bus in ["pci", "usb", "platform"] {
+            fs::create_dir_all(root.join("bus").join(bus)).unwrap();
+        }
+        symlink(root.join("bus/pci"), pci.join("subsystem")).unwrap();
+        symlink(root.join("bus/pci"), capture.join("subsystem")).unwrap();
+        symlink(root.join("bus/usb"), usb.join("subsystem")).unwrap();
+        symlink(root.join("bus/usb"), intf.join("subsystem")).unwrap();
+        symlink(root.join("bus/platform"), csi.join("subsystem")).unwrap();
+        fs::write(usb.join("busnum"), "1\n").unwrap();
+        fs::write(usb.join("devnum"), "4\n").unwrap();
+
+        let webcam = ParentDevice::read(&intf.join("media0"));
+        let card = ParentDevice::read(&capture);
+        let platform = ParentDevice::read(&csi);
+
+        assert_eq!(
+            webcam,
+            Some(ParentDevice::Usb {
+                busnum: 1,
+                devnum: 4,
+                port: "1-2".to_string()
+            })
+        );
+        assert_eq!(webcam.unwrap().to_string(), "usb 001:004");
+        assert_eq!(card.unwrap().to_string(), "pci 0000:02:00.0");
+        assert_eq!(
+            platform,
+            Some(ParentDevice::Platform {
+                name: "fe801000.csi".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::fmt;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// The group database read for the names of groups.
+const ETC_GROUP: &str = "/etc/group";
+
+/// Why the process may not access a file, collected when opening it or an ioctl on it fails with `EACCES` or `EPERM`.
+///
+/// # Details
+/// Compares the owner, the group and the mode of the file with the credentials of the process,
+/// so that applications can tell users e.g. to join the `video` group instead of showing a bare "permission denied".
+/// Formatted by `Display` as a sentence ending with such a hint.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct PermissionDiagnostics {
+    /// The uid owning the file.
+    #[serde(rename = "owner")]
+    owner: u32,
+    /// The gid owning the file.
+    #[serde(rename = "group")]
+    group: u32,
+    /// The name of the group owning the file such like `video`, if found in `/etc/group`.
+    #[serde(rename = "group_name")]
+    group_name: Option<String>,
+    /// The permission bits of the file such like `0o660`.
+    #[serde(rename = "mode")]
+    mode: u32,
+    /// The effective uid of the process.
+    #[serde(rename = "uid")]
+    uid: u32,
+    /// The effective gid of the process.
+    #[serde(rename = "gid")]
+    gid: u32,
+    /// The supplementary gids of the process.
+    #[serde(rename = "groups")]
+    groups: Vec<u32>,
+}
+
+/// The supplementary gids of the process.
+fn supplementary_groups() -> Vec<u32> {
+    let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+    if count <= 0 {
+        return vec![];
+    }
+    let mut groups = vec![0; count as usize];
+    let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+    groups.truncate(count.max(0) as usize);
+    groups
+}
+
+/// Find the name of `gid` in the group database `etc_group`.
+fn group_name(etc_group: &str, gid: u32) -> Option<String> {
+    etc_group.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let name = fields.next()?;
+        let id = fields.nth(1)?.parse::<u32>().ok()?;
+        (id == gid).then(|| name.to_string())
+    })
+}
+
+impl PermissionDiagnostics {
+    /// Compare the file `path` with the credentials of the process.
+    ///
+    /// # Returns
+    /// `None` if the file can not be inspected either.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// if let Some(diagnostics) = PermissionDiagnostics::inspect("/dev/media0") {
+    ///     if !diagnostics.is_member() {
+    ///         println!("{}", diagnostics);
+    ///     }
+    /// }
+    /// ```
+    pub fn inspect<P>(path: P) -> Option<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let metadata = fs::metadata(path).ok()?;
+        let group = metadata.gid();
+        Some(Self {
+            owner: metadata.uid(),
+            group,
+            group_name: fs::read_to_string(ETC_GROUP)
+                .ok()
+                .and_then(|etc_group| group_name(&etc_group, group)),
+            mode: metadata.mode() & 0o7777,
+            uid: unsafe { libc::geteuid() },
+            gid: unsafe { libc::getegid() },
+            groups: supplementary_groups(),
+        })
+    }
+
+    pub fn owner(&self) -> u32 {
+        self.owner
+    }
+
+    pub fn group(&self) -> u32 {
+        self.group
+    }
+
+    pub fn group_name(&self) -> Option<&str> {
+        self.group_name.as_deref()
+    }
+
+    pub fn mode(&self) -> u32 {
+        self.mode
+    }
+
+    pub fn uid(&self) -> u32 {
+        self.uid
+    }
+
+    pub fn gid(&self) -> u32 {
+        self.gid
+    }
+
+    pub fn groups(&self) -> &[u32] {
+        &self.groups
+    }
+
+    /// Whether the process belongs to the group owning the file.
+    pub fn is_member(&self) -> bool {
+        self.gid == self.group || self.groups.contains(&self.group)
+    }
+
+    /// What the user can do to access the file, if it is one of the common causes.
+    pub fn hint(&self) -> Option<String> {
+        let group = self
+            .group_name
+            .clone()
+            .unwrap_or_else(|| self.group.to_string());
+        if self.uid == self.owner {
+            None
+        } else if !self.is_member() && self.mode & 0o060 == 0o060 {
+            Some(format!(
+                "add the user to the group {} and log in again",
+                group
+            ))
+        } else if self.is_member() && self.mode & 0o060 != 0o060 {
+            Some(format!(
+                "the group {} is not allowed to read and write the file",
+                group
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for PermissionDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "owned by uid {} and group ", self.owner)?;
+        match &self.group_name {
+            Some(name) => write!(f, "{} ({})", name, self.group)?,
+            None => write!(f, "{}", self.group)?,
+        }
+        write!(
+            f,
+            " with mode {:04o}, while the process runs as uid {} with gid {} and groups {:?}",
+            self.mode, self.uid, self.gid, self.groups
+        )?;
+        if let Some(hint) = self.hint() {
+            write!(f, "; {}", hint)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn diagnostics(mode: u32, gid: u32, groups: Vec<u32>) -> PermissionDiagnostics {
+        PermissionDiagnostics {
+            owner: 0,
+            group: 44,
+            group_name: group_name("root:x:0:\nvideo:x:44:alice\n", 44),
+            mode,
+            uid: 1000,
+            gid,
+            groups,
+        }
+    }
+
+    #[test]
+    fn hints_at_joining_the_group() {
+        let outsider = diagnostics(0o660, 1000, vec![1000, 27]);
+        assert_eq!(outsider.group_name(), Some("video"));
+        assert!(!outsider.is_member());
+        assert_eq!(
+            outsider.to_string(),
+            "owned by uid 0 and group video (44) with mode 0660, \
+             while the process runs as uid 1000 with gid 1000 and groups [1000, 27]; \
+             add the user to the group video and log in again"
+        );
+
+        let member = diagnostics(0o660, 1000, vec![1000, 44]);
+        assert!(member.is_member());
+        assert_eq!(member.hint(), None);
+
+        let read_only = diagnostics(0o640, 44, vec![]);
+        assert_eq!(
+            read_only.hint().as_deref(),
+            Some("the group video is not allowed to read and write the file")
+        );
+    }
+}
@@ -0,0 +1,551 @@
+use std::fmt;
+use std::mem::{self, MaybeUninit};
+
+use crate::raw as media;
+
+/// The raw structs of the media controller API, which are valid for any bytes.
+///
+/// # Safety
+/// Implemented only for `repr(C)` structs consisting of integers, arrays and unions of them without padding bytes,
+/// since [`Raw::as_bytes`] reads every byte of them.
+/// `media_pad_desc` and `media_link_desc` are not, as they have padding following the `__u16` index of a pad.
+pub unsafe trait RawStruct: Copy {}
+
+unsafe impl RawStruct for media::media_device_info {}
+unsafe impl RawStruct for media::media_v2_entity {}
+unsafe impl RawStruct for media::media_v2_interface {}
+unsafe impl RawStruct for media::media_v2_pad {}
+unsafe impl RawStruct for media::media_v2_link {}
+unsafe impl RawStruct for media::media_entity_desc {}
+
+/// A raw struct generated for property tests of the conversions from it.
+///
+/// # Details
+/// The foreign structs are wrapped since [`Arbitrary`][::arbitrary::Arbitrary] can not be implemented for them in this crate.
+/// `Arbitrary` generates any bytes, as a buggy or newer kernel may return,
+/// while the [`strategy`] module provides both any bytes and well formed structs.
+#[derive(Clone, Copy)]
+pub struct Raw<T>(pub T);
+
+impl<T: RawStruct> Raw<T> {
+    /// Reinterpret `bytes`, which must be as long as `T`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        assert_eq!(bytes.len(), mem::size_of::<T>());
+        Self(unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const T) })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(&self.0 as *const T as *const u8, mem::size_of::<T>()) }
+    }
+}
+
+impl<T: RawStruct> fmt::Debug for Raw<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Raw<{}>(", std::any::type_name::<T>())?;
+        for byte in self.as_bytes() {
+            write!(f, "{:02x}", byte)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl<T: RawStruct> PartialEq for Raw<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<T: RawStruct> Eq for Raw<T> {}
+
+impl<'a, T: RawStruct> ::arbitrary::Arbitrary<'a> for Raw<T> {
+    fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+        let mut raw = MaybeUninit::<T>::zeroed();
+        let bytes = unsafe {
+            std::slice::from_raw_parts_mut(raw.as_mut_ptr() as *mut u8, mem::size_of::<T>())
+        };
+        u.fill_buffer(bytes)?;
+        Ok(Self(unsafe { raw.assume_init() }))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (mem::size_of::<T>(), Some(mem::size_of::<T>()))
+    }
+}
+
+/// [proptest] strategies generating raw structs and the types of this crate built from them.
+///
+/// # Details
+/// The strategies of the types of this crate let downstream crates check that their persistence of the types round-trips.
+///
+/// # Examples
+/// ```
+/// use linux_media::raw_arbitrary::strategy;
+/// use linux_media::*;
+/// use proptest::prelude::*;
+///
+/// proptest!(|(config in strategy::pipeline_config())| {
+///     let json = serde_json::to_string(&config).unwrap();
+///     prop_assert_eq!(serde_json::from_str::<PipelineConfig>(&json).unwrap(), config);
+/// });
+/// ```
+pub mod strategy {
+    use std::collections::BTreeSet;
+    use std::os::raw::c_char;
+
+    use crate::raw as media;
+    use proptest::prelude::*;
+    use proptest::sample::{select, Index};
+
+    use super::{Raw, RawStruct};
+    use crate::media_link::MEDIA_LNK_FL_ANCILLARY_LINK;
+    use crate::{
+        EntityId, EntitySpec, InterfaceId, LinkId, LinkSpec, LinkType, MediaEntity,
+        MediaEntityFlags, MediaEntityFunctions, MediaInterface, MediaInterfaceType,
+        MediaIntfDevnode, MediaLink, MediaLinkDesc, MediaLinkFlags, MediaPad, MediaPadDesc,
+        MediaPadFlags, MediaTopology, PadId, PadSpec, PipelineConfig,
+    };
+
+    /// Any bytes as `T`.
+    pub fn raw<T: RawStruct>() -> impl Strategy<Value = Raw<T>> {
+        proptest::collection::vec(any::<u8>(), std::mem::size_of::<T>())
+            .prop_map(|bytes| Raw::from_bytes(&bytes))
+    }
+
+    /// The values in `candidates` which `parse` accepts.
+    fn known<E>(
+        candidates: impl IntoIterator<Item = u32>,
+        parse: fn(u32) -> Result<E, crate::error::Error>,
+    ) -> Vec<u32> {
+        candidates
+            .into_iter()
+            .filter(|v| parse(*v).is_ok())
+            .collect()
+    }
+
+    /// The entity functions known to this crate.
+    pub fn function() -> impl Strategy<Value = u32> {
+        const OLD_BASE: u32 = 0x10000;
+        const OLD_SUBDEV_BASE: u32 = 0x20000;
+        const CONN_BASE: u32 = 0x30000;
+        let candidates = (0..0x8000)
+            .chain(OLD_BASE..OLD_BASE + 0x10)
+            .chain(OLD_SUBDEV_BASE..OLD_SUBDEV_BASE + 0x10)
+            .chain(CONN_BASE..CONN_BASE + 0x10);
+        select(known(candidates, MediaEntityFunctions::try_from))
+    }
+
+    /// The interface types known to this crate.
+    pub fn interface_type() -> impl Strategy<Value = u32> {
+        select(known(0..0x400, MediaInterfaceType::try_from))
+    }
+
+    /// A nul terminated name printable in ASCII, filling `N` bytes.
+    fn name<const N: usize>() -> impl Strategy<Value = [c_char; N]> {
+        proptest::collection::vec(0x20u8..0x7f, 0..N).prop_map(|chars| {
+            let mut name = [0; N];
+            for (dst, src) in name.iter_mut().zip(chars) {
+                *dst = src as c_char;
+            }
+            name
+        })
+    }
+
+    /// A well formed entity.
+    pub fn entity() -> impl Strategy<Value = Raw<media::media_v2_entity>> {
+        (
+            any::<u32>(),
+            name(),
+            function(),
+            0..=media::MEDIA_ENT_FL_DEFAULT | media::MEDIA_ENT_FL_CONNECTOR,
+        )
+            .prop_map(|(id, name, function, flags)| {
+                let mut entity: media::media_v2_entity = unsafe { std::mem::zeroed() };
+                entity.id = id;
+                entity.name = name;
+                entity.function = function;
+                entity.flags = flags;
+                Raw(entity)
+            })
+    }
+
+    /// A well formed interface of a device node.
+    pub fn interface() -> impl Strategy<Value = Raw<media::media_v2_interface>> {
+        (any::<u32>(), interface_type(), any::<u32>(), any::<u32>()).prop_map(
+            |(id, intf_type, major, minor)| {
+                let mut intf: media::media_v2_interface = unsafe { std::mem::zeroed() };
+                intf.id = id;
+                intf.intf_type = intf_type;
+                intf.__bindgen_anon_1.devnode.major = major;
+                intf.__bindgen_anon_1.devnode.minor = minor;
+                Raw(intf)
+            },
+        )
+    }
+
+    /// A well formed pad.
+    pub fn pad() -> impl Strategy<Value = Raw<media::media_v2_pad>> {
+        let flags = select(vec![
+            media::MEDIA_PAD_FL_SINK,
+            media::MEDIA_PAD_FL_SOURCE,
+            media::MEDIA_PAD_FL_SINK | media::MEDIA_PAD_FL_MUST_CONNECT,
+            media::MEDIA_PAD_FL_SOURCE | media::MEDIA_PAD_FL_MUST_CONNECT,
+        ]);
+        (any::<u32>(), any::<u32>(), flags, 0u32..64).prop_map(|(id, entity_id, flags, index)| {
+            let mut pad: media::media_v2_pad = unsafe { std::mem::zeroed() };
+            pad.id = id;
+            pad.entity_id = entity_id;
+            pad.flags = flags;
+            pad.index = index;
+            Raw(pad)
+        })
+    }
+
+    /// A well formed data, interface or ancillary link.
+    pub fn link() -> impl Strategy<Value = Raw<media::media_v2_link>> {
+        let link_type = select(vec![
+            media::MEDIA_LNK_FL_DATA_LINK,
+            media::MEDIA_LNK_FL_INTERFACE_LINK,
+            MEDIA_LNK_FL_ANCILLARY_LINK,
+        ]);
+        let flags = 0..=media::MEDIA_LNK_FL_ENABLED
+            | media::MEDIA_LNK_FL_IMMUTABLE
+            | media::MEDIA_LNK_FL_DYNAMIC;
+        (any::<u32>(), any::<u32>(), any::<u32>(), link_type, flags).prop_map(
+            |(id, source_id, sink_id, link_type, flags)| {
+                let mut link: media::media_v2_link = unsafe { std::mem::zeroed() };
+                link.id = id;
+                link.source_id = source_id;
+                link.sink_id = sink_id;
+                link.flags = link_type | flags;
+                Raw(link)
+            },
+        )
+    }
+
+    /// A name of an entity printable in ASCII, without `"` which can not be quoted in the syntax of `media-ctl`.
+    pub fn entity_name() -> impl Strategy<Value = String> {
+        proptest::collection::vec(prop_oneof![0x20u8..0x22, 0x23u8..0x7f], 1..32)
+            .prop_map(|chars| chars.into_iter().map(char::from).collect())
+    }
+
+    pub fn pad_flags() -> impl Strategy<Value = MediaPadFlags> {
+        let direction = select(vec![MediaPadFlags::Sink, MediaPadFlags::Source]);
+        (direction, any::<bool>()).prop_map(|(direction, must_connect)| {
+            if must_connect {
+                direction | MediaPadFlags::MustConnect
+            } else {
+                direction
+            }
+        })
+    }
+
+    pub fn link_flags() -> impl Strategy<Value = MediaLinkFlags> {
+        (0..=MediaLinkFlags::all().bits()).prop_map(MediaLinkFlags::from_bits_truncate)
+    }
+
+    /// A pad of any entity.
+    pub fn pad_desc() -> impl Strategy<Value = MediaPadDesc> {
+        (any::<u32>(), 0usize..64, pad_flags())
+            .prop_map(|(entity, index, flags)| MediaPadDesc::new(entity.into(), index, flags))
+    }
+
+    /// A link from a source pad to a sink pad.
+    pub fn link_desc() -> impl Strategy<Value = MediaLinkDesc> {
+        let source = pad_desc().prop_filter("a source pad", |pad| pad.flags().is_source());
+        let sink = pad_desc().prop_filter("a sink pad", |pad| !pad.flags().is_source());
+        (source, sink, link_flags())
+            .prop_map(|(source, sink, flags)| MediaLinkDesc::new(source, sink, flags))
+    }
+
+    /// A pad referred by the name or the id of its entity.
+    pub fn pad_spec() -> impl Strategy<Value = PadSpec> {
+        let entity = prop_oneof![
+            any::<u32>().prop_map(|id| EntitySpec::Id(id.into())),
+            entity_name().prop_map(EntitySpec::Name),
+        ];
+        (entity, 0usize..64).prop_map(|(entity, index)| PadSpec { entity, index })
+    }
+
+    pub fn link_spec() -> impl Strategy<Value = LinkSpec> {
+        (pad_spec(), pad_spec(), link_flags()).prop_map(|(source, sink, flags)| LinkSpec {
+            source,
+            sink,
+            flags,
+        })
+    }
+
+    pub fn pipeline_config() -> impl Strategy<Value = PipelineConfig> {
+        (
+            proptest::option::of(entity_name()),
+            proptest::option::of(entity_name()),
+            proptest::collection::vec(link_spec(), 0..8),
+        )
+            .prop_map(|(model, bus_info, links)| PipelineConfig {
+                model,
+                bus_info,
+                links,
+            })
+    }
+
+    /// A topology whose links connect the pads and the interfaces in it.
+    ///
+    /// # Details
+    /// Entities have distinct names, and objects are numbered in the order of the kernel.
+    pub fn topology() -> impl Strategy<Value = MediaTopology> {
+        let entity = (
+            entity_name(),
+            select(known(0..0x8000, MediaEntityFunctions::try_from)),
+            (0..=MediaEntityFlags::all().bits()).prop_map(MediaEntityFlags::from_bits_truncate),
+            proptest::collection::vec(pad_flags(), 0..4),
+        );
+        let interface = (
+            select(known(0..0x400, MediaInterfaceType::try_from)),
+            any::<u32>(),
+            any::<u32>(),
+            any::<Index>(),
+        );
+        (
+            any::<u64>(),
+            proptest::collection::vec(entity, 1..8),
+            proptest::collection::vec(interface, 0..4),
+            proptest::collection::vec((any::<Index>(), any::<Index>(), link_flags()), 0..12),
+        )
+            .prop_map(|(version, entity_seeds, interface_seeds, link_seeds)| {
+                let mut next = 0;
+                let mut gen_id = |r#type: u32| {
+                    next += 1;
+                    (r#type << 24) | next
+                };
+                let mut names = BTreeSet::new();
+                let mut entities = vec![];
+                let mut pads = vec![];
+                for (name, function, flags, pad_flags) in entity_seeds {
+                    let name = if names.contains(&name) {
+                        format!("{} {}", name, entities.len())
+                    } else {
+                        name
+                    };
+                    names.insert(name.clone());
+                    let function = MediaEntityFunctions::try_from(function).unwrap();
+                    let id = EntityId::from(gen_id(0));
+                    entities.push(MediaEntity::new(id, &name, function, Some(flags)));
+                    for (index, flags) in pad_flags.into_iter().enumerate() {
+                        pads.push(MediaPad {
+                            id: PadId::from(gen_id(1)),
+                            entity_id: id,
+                            flags,
+                            index: Some(index),
+                        });
+                    }
+                }
+                let (sources, sinks): (Vec<&MediaPad>, Vec<&MediaPad>) =
+                    pads.iter().partition(|pad| pad.flags.is_source());
+                let mut links = vec![];
+                if !sources.is_empty() && !sinks.is_empty() {
+                    for (source, sink, flags) in link_seeds {
+                        links.push(MediaLink::new(
+                            LinkId::from(gen_id(2)),
+                            LinkType::DataLink {
+                                source_id: source.get(&sources).id,
+                                sink_id: sink.get(&sinks).id,
+                            },
+                            flags,
+                        ));
+                    }
+                }
+                let mut interfaces = vec![];
+                for (r#type, major, minor, entity) in interface_seeds {
+                    let r#type = MediaInterfaceType::try_from(r#type).unwrap();
+                    let id = InterfaceId::from(gen_id(3));
+                    interfaces.push(MediaInterface::new(
+                        id,
+                        r#type,
+                        MediaIntfDevnode { major, minor },
+                    ));
+                    links.push(MediaLink::new(
+                        LinkId::from(gen_id(2)),
+                        LinkType::InterfaceLink {
+                            source_id: id,
+                            sink_id: entity.get(&entities).id(),
+                        },
+                        MediaLinkFlags::Enabled | MediaLinkFlags::Immutable,
+                    ));
+                }
+                MediaTopology::new(
+                    None,
+                    version,
+                    Some(entities),
+                    Some(interfaces),
+                    Some(pads),
+                    Some(links),
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use super::strategy;
+    use super::*;
+    use crate::{
+        MediaApiCapabilities, MediaDeviceInfo, MediaEntity, MediaEntityFlags, MediaEntityFunctions,
+        MediaInterface, MediaInterfaceType, MediaLink, MediaLinkDesc, MediaPad, MediaPadFlags,
+        MediaTopology, PipelineConfig, RawTopology, Version,
+    };
+
+    /// The sum of the sizes of the fields of `$value`, copied out since the structs may be packed.
+    macro_rules! fields_size {
+        ($value:expr; $($field:ident),+) => {
+            0 $(+ mem::size_of_val(&{ $value.$field }))+
+        };
+    }
+
+    #[test]
+    fn raw_structs_have_no_padding() {
+        let info: media::media_device_info = unsafe { mem::zeroed() };
+        assert_eq!(
+            mem::size_of_val(&info),
+            fields_size!(info; driver, model, serial, bus_info, media_version, hw_revision, driver_version, reserved)
+        );
+        let entity: media::media_v2_entity = unsafe { mem::zeroed() };
+        assert_eq!(
+            mem::size_of_val(&entity),
+            fields_size!(entity; id, name, function, flags, reserved)
+        );
+        let intf: media::media_v2_interface = unsafe { mem::zeroed() };
+        assert_eq!(
+            mem::size_of_val(&intf),
+            fields_size!(intf; id, intf_type, flags, reserved, __bindgen_anon_1)
+        );
+        let pad: media::media_v2_pad = unsafe { mem::zeroed() };
+        assert_eq!(
+            mem::size_of_val(&pad),
+            fields_size!(pad; id, entity_id, flags, index, reserved)
+        );
+        let link: media::media_v2_link = unsafe { mem::zeroed() };
+        assert_eq!(
+            mem::size_of_val(&link),
+            fields_size!(link; id, source_id, sink_id, flags, reserved)
+        );
+        let desc: media::media_entity_desc = unsafe { mem::zeroed() };
+        assert_eq!(
+            mem::size_of_val(&desc),
+            fields_size!(desc; id, name, type_, revision, flags, group_id, pads, links, reserved, __bindgen_anon_1)
+        );
+        // the union of the entity description has no padding either, as its raw bytes fill it
+        assert_eq!(
+            mem::size_of_val(&desc.__bindgen_anon_1),
+            mem::size_of_val(unsafe { &desc.__bindgen_anon_1.raw })
+        );
+    }
+
+    fn capabilities() -> MediaApiCapabilities {
+        MediaApiCapabilities::new(Version::new(u8::MAX, u8::MAX, u8::MAX))
+    }
+
+    proptest! {
+        #[test]
+        fn entity_round_trips(raw in strategy::entity()) {
+            let entity = MediaEntity::from_raw(capabilities(), raw.0).unwrap();
+            prop_assert_eq!(Raw(media::media_v2_entity::from(entity)), raw);
+        }
+
+        #[test]
+        fn interface_round_trips(raw in strategy::interface()) {
+            let intf = MediaInterface::from_raw(raw.0).unwrap();
+            prop_assert_eq!(Raw(media::media_v2_interface::from(intf)), raw);
+        }
+
+        #[test]
+        fn pad_round_trips(raw in strategy::pad()) {
+            let pad = MediaPad::from_raw(capabilities(), raw.0).unwrap();
+            prop_assert_eq!(Raw(media::media_v2_pad::from(pad)), raw);
+        }
+
+        #[test]
+        fn link_round_trips(raw in strategy::link()) {
+            let link = MediaLink::try_from(raw.0).unwrap();
+            prop_assert_eq!(Raw(media::media_v2_link::from(link)), raw);
+        }
+
+        #[test]
+        fn raw_conversions_round_trip(
+            entity in strategy::entity(),
+            intf in strategy::interface(),
+            pad in strategy::pad(),
+            link in strategy::link(),
+        ) {
+            prop_assert_eq!(Raw(MediaEntity::from_raw(capabilities(), entity.0).unwrap().to_raw()), entity);
+            prop_assert_eq!(Raw(MediaInterface::from_raw(intf.0).unwrap().to_raw()), intf);
+            prop_assert_eq!(Raw(MediaPad::from_raw(capabilities(), pad.0).unwrap().to_raw()), pad);
+            prop_assert_eq!(Raw(MediaLink::from_raw(link.0).unwrap().to_raw()), link);
+        }
+
+        #[test]
+        fn topology_round_trips(topology in strategy::topology()) {
+            let json = serde_json::to_string(&topology).unwrap();
+            prop_assert_eq!(serde_json::from_str::<MediaTopology>(&json).unwrap(), topology);
+        }
+
+        #[test]
+        fn link_desc_round_trips(desc in strategy::link_desc()) {
+            let raw = media::media_link_desc::from(desc);
+            prop_assert_eq!(MediaLinkDesc::from(raw), desc);
+        }
+
+        #[test]
+        fn pipeline_config_round_trips(config in strategy::pipeline_config()) {
+            let json = serde_json::to_string(&config).unwrap();
+            prop_assert_eq!(serde_json::from_str::<PipelineConfig>(&json).unwrap(), config);
+        }
+
+        #[test]
+        fn raw_conversions_never_panic(
+            entity in strategy::raw::<media::media_v2_entity>(),
+            intf in strategy::raw::<media::media_v2_interface>(),
+            pad in strategy::raw::<media::media_v2_pad>(),
+            info in strategy::raw::<media::media_device_info>(),
+        ) {
+            let known = MediaEntityFunctions::try_from(entity.0.function).is_ok()
+                && MediaEntityFlags::try_from(entity.0.flags).is_ok();
+            prop_assert_eq!(MediaEntity::from_raw(capabilities(), entity.0).is_ok(), known);
+            let known = MediaInterfaceType::try_from(intf.0.intf_type).is_ok();
+            prop_assert_eq!(MediaInterface::from_raw(intf.0).is_ok(), known);
+            let known = MediaPadFlags::try_from(pad.0.flags).is_ok();
+            prop_assert_eq!(MediaPad::from_raw(capabilities(), pad.0).is_ok(), known);
+            let info = MediaDeviceInfo::from_raw(info.0);
+            // strings are read within their arrays, where an invalid byte is replaced by 3 bytes at most
+            prop_assert!(info.driver.len() < 16 * 3 && info.bus_info.len() < 32 * 3);
+        }
+
+        #[test]
+        fn raw_topologies_never_panic(
+            entities in proptest::collection::vec(strategy::raw::<media::media_v2_entity>(), 0..4),
+            interfaces in proptest::collection::vec(strategy::raw::<media::media_v2_interface>(), 0..4),
+            pads in proptest::collection::vec(strategy::raw::<media::media_v2_pad>(), 0..4),
+            links in proptest::collection::vec(strategy::raw::<media::media_v2_link>(), 0..4),
+        ) {
+            let raw = RawTopology::new(
+                Version::new(u8::MAX, u8::MAX, u8::MAX),
+                0,
+                Some(entities.iter().map(|raw| raw.0).collect()),
+                Some(interfaces.iter().map(|raw| raw.0).collect()),
+                Some(pads.iter().map(|raw| raw.0).collect()),
+                Some(links.iter().map(|raw| raw.0).collect()),
+            );
+            let known = raw.entities().all(|entity| entity.to_entity().is_ok())
+                && raw.interfaces().all(|intf| intf.to_interface().is_ok())
+                && raw.pads().all(|pad| pad.to_pad().is_ok())
+                && links.iter().all(|link| MediaLink::from_raw(link.0).is_ok());
+            prop_assert_eq!(raw.to_topology().is_ok(), known);
+        }
+
+        #[test]
+        fn parsing_functions_never_panics(raw in strategy::raw::<media::media_v2_entity>()) {
+            let function = raw.0.function;
+            let _ = MediaEntityFunctions::try_from(function);
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! The structs and constants of `linux/media.h` used by this crate, for targets other than Linux.
+//!
+//! # Details
+//! `linux-media-sys` generates its bindings from the headers of the system, which only Linux has.
+//! This module declares the same items in the same layout and names as the bindings generated by it,
+//! so that topologies serialized on a device can be loaded, compared and rendered by tools developed on other systems.
+//! No ioctl is issued on these targets, and every one of them fails with
+//! [`NotSupportedIoctl`][crate::error::Error::NotSupportedIoctl].
+#![allow(non_camel_case_types)]
+#![allow(non_snake_case)]
+
+use std::mem::size_of;
+use std::os::raw::{c_char, c_int, c_ulong, c_ulonglong};
+
+pub type __u8 = u8;
+pub type __u16 = u16;
+pub type __u32 = u32;
+pub type __u64 = c_ulonglong;
+
+pub const _IOC_NRBITS: u32 = 8;
+pub const _IOC_TYPEBITS: u32 = 8;
+pub const _IOC_SIZEBITS: u32 = 14;
+pub const _IOC_NRSHIFT: u32 = 0;
+pub const _IOC_TYPESHIFT: u32 = _IOC_NRSHIFT + _IOC_NRBITS;
+pub const _IOC_SIZESHIFT: u32 = _IOC_TYPESHIFT + _IOC_TYPEBITS;
+pub const _IOC_DIRSHIFT: u32 = _IOC_SIZESHIFT + _IOC_SIZEBITS;
+pub const _IOC_NONE: u32 = 0;
+pub const _IOC_WRITE: u32 = 1;
+pub const _IOC_READ: u32 = 2;
+
+pub const MEDIA_ENT_F_BASE: u32 = 0x00000000;
+pub const MEDIA_ENT_F_OLD_BASE: u32 = 0x00010000;
+pub const MEDIA_ENT_F_OLD_SUBDEV_BASE: u32 = 0x00020000;
+pub const MEDIA_ENT_F_UNKNOWN: u32 = MEDIA_ENT_F_BASE;
+pub const MEDIA_ENT_F_V4L2_SUBDEV_UNKNOWN: u32 = MEDIA_ENT_F_OLD_SUBDEV_BASE;
+pub const MEDIA_ENT_F_DTV_DEMOD: u32 = MEDIA_ENT_F_BASE + 0x00001;
+pub const MEDIA_ENT_F_TS_DEMUX: u32 = MEDIA_ENT_F_BASE + 0x00002;
+pub const MEDIA_ENT_F_DTV_CA: u32 = MEDIA_ENT_F_BASE + 0x00003;
+pub const MEDIA_ENT_F_DTV_NET_DECAP: u32 = MEDIA_ENT_F_BASE + 0x00004;
+pub const MEDIA_ENT_F_IO_V4L: u32 = MEDIA_ENT_F_OLD_BASE + 1;
+pub const MEDIA_ENT_F_IO_DTV: u32 = MEDIA_ENT_F_BASE + 0x01001;
+pub const MEDIA_ENT_F_IO_VBI: u32 = MEDIA_ENT_F_BASE + 0x01002;
+pub const MEDIA_ENT_F_IO_SWRADIO: u32 = MEDIA_ENT_F_BASE + 0x01003;
+pub const MEDIA_ENT_F_CONN_RF: u32 = MEDIA_ENT_F_BASE + 0x30001;
+pub const MEDIA_ENT_F_CONN_SVIDEO: u32 = MEDIA_ENT_F_BASE + 0x30002;
+pub const MEDIA_ENT_F_CONN_COMPOSITE: u32 = MEDIA_ENT_F_BASE + 0x30003;
+pub const MEDIA_ENT_F_CAM_SENSOR: u32 = MEDIA_ENT_F_OLD_SUBDEV_BASE + 1;
+pub const MEDIA_ENT_F_FLASH: u32 = MEDIA_ENT_F_OLD_SUBDEV_BASE + 2;
+pub const MEDIA_ENT_F_LENS: u32 = MEDIA_ENT_F_OLD_SUBDEV_BASE + 3;
+pub const MEDIA_ENT_F_ATV_DECODER: u32 = MEDIA_ENT_F_OLD_SUBDEV_BASE + 4;
+pub const MEDIA_ENT_F_TUNER: u32 = MEDIA_ENT_F_OLD_SUBDEV_BASE + 5;
+pub const MEDIA_ENT_F_IF_VID_DECODER: u32 = MEDIA_ENT_F_BASE + 0x02001;
+pub const MEDIA_ENT_F_IF_AUD_DECODER: u32 = MEDIA_ENT_F_BASE + 0x02002;
+pub const MEDIA_ENT_F_AUDIO_CAPTURE: u32 = MEDIA_ENT_F_BASE + 0x03001;
+pub const MEDIA_ENT_F_AUDIO_PLAYBACK: u32 = MEDIA_ENT_F_BASE + 0x03002;
+pub const MEDIA_ENT_F_AUDIO_MIXER: u32 = MEDIA_ENT_F_BASE + 0x03003;
+pub const MEDIA_ENT_F_PROC_VIDEO_COMPOSER: u32 = MEDIA_ENT_F_BASE + 0x4001;
+pub const MEDIA_ENT_F_PROC_VIDEO_PIXEL_FORMATTER: u32 = MEDIA_ENT_F_BASE + 0x4002;
+pub const MEDIA_ENT_F_PROC_VIDEO_PIXEL_ENC_CONV: u32 = MEDIA_ENT_F_BASE + 0x4003;
+pub const MEDIA_ENT_F_PROC_VIDEO_LUT: u32 = MEDIA_ENT_F_BASE + 0x4004;
+pub const MEDIA_ENT_F_PROC_VIDEO_SCALER: u32 = MEDIA_ENT_F_BASE + 0x4005;
+pub const MEDIA_ENT_F_PROC_VIDEO_STATISTICS: u32 = MEDIA_ENT_F_BASE + 0x4006;
+pub const MEDIA_ENT_F_PROC_VIDEO_ENCODER: u32 = MEDIA_ENT_F_BASE + 0x4007;
+pub const MEDIA_ENT_F_PROC_VIDEO_DECODER: u32 = MEDIA_ENT_F_BASE + 0x4008;
+pub const MEDIA_ENT_F_PROC_VIDEO_ISP: u32 = MEDIA_ENT_F_BASE + 0x4009;
+pub const MEDIA_ENT_F_VID_MUX: u32 = MEDIA_ENT_F_BASE + 0x5001;
+pub const MEDIA_ENT_F_VID_IF_BRIDGE: u32 = MEDIA_ENT_F_BASE + 0x5002;
+pub const MEDIA_ENT_F_DV_DECODER: u32 = MEDIA_ENT_F_BASE + 0x6001;
+pub const MEDIA_ENT_F_DV_ENCODER: u32 = MEDIA_ENT_F_BASE + 0x6002;
+
+pub const MEDIA_ENT_FL_DEFAULT: u32 = 1 << 0;
+pub const MEDIA_ENT_FL_CONNECTOR: u32 = 1 << 1;
+pub const MEDIA_ENT_ID_FLAG_NEXT: u32 = 1 << 31;
+
+pub const MEDIA_PAD_FL_SINK: u32 = 1 << 0;
+pub const MEDIA_PAD_FL_SOURCE: u32 = 1 << 1;
+pub const MEDIA_PAD_FL_MUST_CONNECT: u32 = 1 << 2;
+
+pub const MEDIA_LNK_FL_ENABLED: u32 = 1 << 0;
+pub const MEDIA_LNK_FL_IMMUTABLE: u32 = 1 << 1;
+pub const MEDIA_LNK_FL_DYNAMIC: u32 = 1 << 2;
+pub const MEDIA_LNK_FL_LINK_TYPE: u32 = 0xf << 28;
+pub const MEDIA_LNK_FL_DATA_LINK: u32 = 0 << 28;
+pub const MEDIA_LNK_FL_INTERFACE_LINK: u32 = 1 << 28;
+pub const MEDIA_LNK_FL_ANCILLARY_LINK: u32 = 2 << 28;
+
+pub const MEDIA_INTF_T_DVB_BASE: u32 = 0x00000100;
+pub const MEDIA_INTF_T_V4L_BASE: u32 = 0x00000200;
+pub const MEDIA_INTF_T_ALSA_BASE: u32 = 0x00000300;
+pub const MEDIA_INTF_T_DVB_FE: u32 = MEDIA_INTF_T_DVB_BASE;
+pub const MEDIA_INTF_T_DVB_DEMUX: u32 = MEDIA_INTF_T_DVB_BASE + 1;
+pub const MEDIA_INTF_T_DVB_DVR: u32 = MEDIA_INTF_T_DVB_BASE + 2;
+pub const MEDIA_INTF_T_DVB_CA: u32 = MEDIA_INTF_T_DVB_BASE + 3;
+pub const MEDIA_INTF_T_DVB_NET: u32 = MEDIA_INTF_T_DVB_BASE + 4;
+pub const MEDIA_INTF_T_V4L_VIDEO: u32 = MEDIA_INTF_T_V4L_BASE;
+pub const MEDIA_INTF_T_V4L_VBI: u32 = MEDIA_INTF_T_V4L_BASE + 1;
+pub const MEDIA_INTF_T_V4L_RADIO: u32 = MEDIA_INTF_T_V4L_BASE + 2;
+pub const MEDIA_INTF_T_V4L_SUBDEV: u32 = MEDIA_INTF_T_V4L_BASE + 3;
+pub const MEDIA_INTF_T_V4L_SWRADIO: u32 = MEDIA_INTF_T_V4L_BASE + 4;
+pub const MEDIA_INTF_T_V4L_TOUCH: u32 = MEDIA_INTF_T_V4L_BASE + 5;
+pub const MEDIA_INTF_T_ALSA_PCM_CAPTURE: u32 = MEDIA_INTF_T_ALSA_BASE;
+pub const MEDIA_INTF_T_ALSA_PCM_PLAYBACK: u32 = MEDIA_INTF_T_ALSA_BASE + 1;
+pub const MEDIA_INTF_T_ALSA_CONTROL: u32 = MEDIA_INTF_T_ALSA_BASE + 2;
+pub const MEDIA_INTF_T_ALSA_COMPRESS: u32 = MEDIA_INTF_T_ALSA_BASE + 3;
+pub const MEDIA_INTF_T_ALSA_RAWMIDI: u32 = MEDIA_INTF_T_ALSA_BASE + 4;
+pub const MEDIA_INTF_T_ALSA_HWDEP: u32 = MEDIA_INTF_T_ALSA_BASE + 5;
+pub const MEDIA_INTF_T_ALSA_SEQUENCER: u32 = MEDIA_INTF_T_ALSA_BASE + 6;
+pub const MEDIA_INTF_T_ALSA_TIMER: u32 = MEDIA_INTF_T_ALSA_BASE + 7;
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_device_info {
+    pub driver: [c_char; 16],
+    pub model: [c_char; 32],
+    pub serial: [c_char; 40],
+    pub bus_info: [c_char; 32],
+    pub media_version: __u32,
+    pub hw_revision: __u32,
+    pub driver_version: __u32,
+    pub reserved: [__u32; 31],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct media_entity_desc {
+    pub id: __u32,
+    pub name: [c_char; 32],
+    pub type_: __u32,
+    pub revision: __u32,
+    pub flags: __u32,
+    pub group_id: __u32,
+    pub pads: __u16,
+    pub links: __u16,
+    pub reserved: [__u32; 4],
+    pub __bindgen_anon_1: media_entity_desc__bindgen_ty_1,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub union media_entity_desc__bindgen_ty_1 {
+    pub dev: media_entity_desc__bindgen_ty_1__bindgen_ty_1,
+    pub alsa: media_entity_desc__bindgen_ty_1__bindgen_ty_2,
+    pub v4l: media_entity_desc__bindgen_ty_1__bindgen_ty_3,
+    pub fb: media_entity_desc__bindgen_ty_1__bindgen_ty_4,
+    pub dvb: c_int,
+    pub raw: [__u8; 184],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_entity_desc__bindgen_ty_1__bindgen_ty_1 {
+    pub major: __u32,
+    pub minor: __u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_entity_desc__bindgen_ty_1__bindgen_ty_2 {
+    pub card: __u32,
+    pub device: __u32,
+    pub subdevice: __u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_entity_desc__bindgen_ty_1__bindgen_ty_3 {
+    pub major: __u32,
+    pub minor: __u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_entity_desc__bindgen_ty_1__bindgen_ty_4 {
+    pub major: __u32,
+    pub minor: __u32,
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_pad_desc {
+    pub entity: __u32,
+    pub index: __u16,
+    pub flags: __u32,
+    pub reserved: [__u32; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_link_desc {
+    pub source: media_pad_desc,
+    pub sink: media_pad_desc,
+    pub flags: __u32,
+    pub reserved: [__u32; 2],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_links_enum {
+    pub entity: __u32,
+    pub pads: *mut media_pad_desc,
+    pub links: *mut media_link_desc,
+    pub reserved: [__u32; 4],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_v2_entity {
+    pub id: __u32,
+    pub name: [c_char; 64],
+    pub function: __u32,
+    pub flags: __u32,
+    pub reserved: [__u32; 5],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_v2_intf_devnode {
+    pub major: __u32,
+    pub minor: __u32,
+}
+
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub struct media_v2_interface {
+    pub id: __u32,
+    pub intf_type: __u32,
+    pub flags: __u32,
+    pub reserved: [__u32; 9],
+    pub __bindgen_anon_1: media_v2_interface__bindgen_ty_1,
+}
+
+#[repr(C, packed)]
+#[derive(Copy, Clone)]
+pub union media_v2_interface__bindgen_ty_1 {
+    pub devnode: media_v2_intf_devnode,
+    pub raw: [__u32; 16],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_v2_pad {
+    pub id: __u32,
+    pub entity_id: __u32,
+    pub flags: __u32,
+    pub index: __u32,
+    pub reserved: [__u32; 4],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_v2_link {
+    pub id: __u32,
+    pub source_id: __u32,
+    pub sink_id: __u32,
+    pub flags: __u32,
+    pub reserved: [__u32; 6],
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone)]
+pub struct media_v2_topology {
+    pub topology_version: __u64,
+    pub num_entities: __u32,
+    pub reserved1: __u32,
+    pub ptr_entities: __u64,
+    pub num_interfaces: __u32,
+    pub reserved2: __u32,
+    pub ptr_interfaces: __u64,
+    pub num_pads: __u32,
+    pub reserved3: __u32,
+    pub ptr_pads: __u64,
+    pub num_links: __u32,
+    pub reserved4: __u32,
+    pub ptr_links: __u64,
+}
+
+const fn _IOC(dir: u32, r#type: u8, nr: c_ulong, size: usize) -> c_ulong {
+    ((dir as c_ulong) << _IOC_DIRSHIFT)
+        | ((r#type as c_ulong) << _IOC_TYPESHIFT)
+        | (nr << _IOC_NRSHIFT)
+        | ((size as c_ulong) << _IOC_SIZESHIFT)
+}
+
+pub const MEDIA_IOC_DEVICE_INFO: c_ulong = _IOC(
+    _IOC_READ | _IOC_WRITE,
+    b'|',
+    0x00,
+    size_of::<media_device_info>(),
+);
+pub const MEDIA_IOC_ENUM_ENTITIES: c_ulong = _IOC(
+    _IOC_READ | _IOC_WRITE,
+    b'|',
+    0x01,
+    size_of::<media_entity_desc>(),
+);
+pub const MEDIA_IOC_ENUM_LINKS: c_ulong = _IOC(
+    _IOC_READ | _IOC_WRITE,
+    b'|',
+    0x02,
+    size_of::<media_links_enum>(),
+);
+pub const MEDIA_IOC_SETUP_LINK: c_ulong = _IOC(
+    _IOC_READ | _IOC_WRITE,
+    b'|',
+    0x03,
+    size_of::<media_link_desc>(),
+);
+pub const MEDIA_IOC_G_TOPOLOGY: c_ulong = _IOC(
+    _IOC_READ | _IOC_WRITE,
+    b'|',
+    0x04,
+    size_of::<media_v2_topology>(),
+);
+pub const MEDIA_IOC_REQUEST_ALLOC: c_ulong = _IOC(_IOC_READ, b'|', 0x05, size_of::<c_int>());
+pub const MEDIA_REQUEST_IOC_QUEUE: c_ulong = _IOC(_IOC_NONE, b'|', 0x80, 0);
+pub const MEDIA_REQUEST_IOC_REINIT: c_ulong = _IOC(_IOC_NONE, b'|', 0x81, 0);
+
+/// Appeared in 4.19.0.
+pub const fn MEDIA_V2_ENTITY_HAS_FLAGS(media_version: u64) -> bool {
+    media_version >= ((4u64 << 16) | (19u64 << 8))
+}
+
+/// Appeared in 4.19.0.
+pub const fn MEDIA_V2_PAD_HAS_INDEX(media_version: u64) -> bool {
+    media_version >= ((4u64 << 16) | (19u64 << 8))
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use std::mem::{align_of, size_of};
+
+    macro_rules! assert_same_layouts {
+        ($($ty:ident),+ $(,)?) => {
+            $(
+                assert_eq!(size_of::<super::$ty>(), size_of::<linux_media_sys::$ty>(), stringify!($ty));
+                assert_eq!(align_of::<super::$ty>(), align_of::<linux_media_sys::$ty>(), stringify!($ty));
+            )+
+        };
+    }
+
+    macro_rules! assert_same_values {
+        ($($name:ident),+ $(,)?) => {
+            $(assert_eq!(super::$name as u64, linux_media_sys::$name as u64, stringify!($name));)+
+        };
+    }
+
+    #[test]
+    fn same_as_linux_media_sys() {
+        assert_same_layouts!(
+            media_device_info,
+            media_entity_desc,
+            media_entity_desc__bindgen_ty_1,
+            media_entity_desc__bindgen_ty_1__bindgen_ty_1,
+            media_entity_desc__bindgen_ty_1__bindgen_ty_2,
+            media_entity_desc__bindgen_ty_1__bindgen_ty_3,
+            media_entity_desc__bindgen_ty_1__bindgen_ty_4,
+            media_pad_desc,
+            media_link_desc,
+            media_links_enum,
+            media_v2_entity,
+            media_v2_intf_devnode,
+            media_v2_interface,
+            media_v2_interface__bindgen_ty_1,
+            media_v2_pad,
+            media_v2_link,
+            media_v2_topology,
+        );
+        assert_same_values!(
+            _IOC_NRBITS,
+            _IOC_TYPEBITS,
+            _IOC_SIZEBITS,
+            _IOC_NRSHIFT,
+            _IOC_TYPESHIFT,
+            _IOC_SIZESHIFT,
+            _IOC_DIRSHIFT,
+            _IOC_NONE,
+            _IOC_WRITE,
+            _IOC_READ,
+            MEDIA_ENT_F_BASE,
+            MEDIA_ENT_F_OLD_BASE,
+            MEDIA_ENT_F_OLD_SUBDEV_BASE,
+            MEDIA_ENT_F_UNKNOWN,
+            MEDIA_ENT_F_V4L2_SUBDEV_UNKNOWN,
+            MEDIA_ENT_F_DTV_DEMOD,
+            MEDIA_ENT_F_TS_DEMUX,
+            MEDIA_ENT_F_DTV_CA,
+            MEDIA_ENT_F_DTV_NET_DECAP,
+            MEDIA_ENT_F_IO_V4L,
+            MEDIA_ENT_F_IO_DTV,
+            MEDIA_ENT_F_IO_VBI,
+            MEDIA_ENT_F_IO_SWRADIO,
+            MEDIA_ENT_F_CONN_RF,
+            MEDIA_ENT_F_CONN_SVIDEO,
+            MEDIA_ENT_F_CONN_COMPOSITE,
+            MEDIA_ENT_F_CAM_SENSOR,
+            MEDIA_ENT_F_FLASH,
+            MEDIA_ENT_F_LENS,
+            MEDIA_ENT_F_ATV_DECODER,
+            MEDIA_ENT_F_TUNER,
+            MEDIA_ENT_F_IF_VID_DECODER,
+            MEDIA_ENT_F_IF_AUD_DECODER,
+            MEDIA_ENT_F_AUDIO_CAPTURE,
+            MEDIA_ENT_F_AUDIO_PLAYBACK,
+            MEDIA_ENT_F_AUDIO_MIXER,
+            MEDIA_ENT_F_PROC_VIDEO_COMPOSER,
+            MEDIA_ENT_F_PROC_VIDEO_PIXEL_FORMATTER,
+            MEDIA_ENT_F_PROC_VIDEO_PIXEL_ENC_CONV,
+            MEDIA_ENT_F_PROC_VIDEO_LUT,
+            MEDIA_ENT_F_PROC_VIDEO_SCALER,
+            MEDIA_ENT_F_PROC_VIDEO_STATISTICS,
+            MEDIA_ENT_F_PROC_VIDEO_ENCODER,
+            MEDIA_ENT_F_PROC_VIDEO_DECODER,
+            MEDIA_ENT_F_PROC_VIDEO_ISP,
+            MEDIA_ENT_F_VID_MUX,
+            MEDIA_ENT_F_VID_IF_BRIDGE,
+            MEDIA_ENT_F_DV_DECODER,
+            MEDIA_ENT_F_DV_ENCODER,
+            MEDIA_ENT_FL_DEFAULT,
+            MEDIA_ENT_FL_CONNECTOR,
+            MEDIA_ENT_ID_FLAG_NEXT,
+            MEDIA_PAD_FL_SINK,
+            MEDIA_PAD_FL_SOURCE,
+            MEDIA_PAD_FL_MUST_CONNECT,
+            MEDIA_LNK_FL_ENABLED,
+            MEDIA_LNK_FL_IMMUTABLE,
+            MEDIA_LNK_FL_DYNAMIC,
+            MEDIA_LNK_FL_LINK_TYPE,
+            MEDIA_LNK_FL_DATA_LINK,
+            MEDIA_LNK_FL_INTERFACE_LINK,
+            MEDIA_LNK_FL_ANCILLARY_LINK,
+            MEDIA_INTF_T_DVB_BASE,
+            MEDIA_INTF_T_V4L_BASE,
+            MEDIA_INTF_T_ALSA_BASE,
+            MEDIA_INTF_T_DVB_FE,
+            MEDIA_INTF_T_DVB_DEMUX,
+            MEDIA_INTF_T_DVB_DVR,
+            MEDIA_INTF_T_DVB_CA,
+            MEDIA_INTF_T_DVB_NET,
+            MEDIA_INTF_T_V4L_VIDEO,
+            MEDIA_INTF_T_V4L_VBI,
+            MEDIA_INTF_T_V4L_RADIO,
+            MEDIA_INTF_T_V4L_SUBDEV,
+            MEDIA_INTF_T_V4L_SWRADIO,
+            MEDIA_INTF_T_V4L_TOUCH,
+            MEDIA_INTF_T_ALSA_PCM_CAPTURE,
+            MEDIA_INTF_T_ALSA_PCM_PLAYBACK,
+            MEDIA_INTF_T_ALSA_CONTROL,
+            MEDIA_INTF_T_ALSA_COMPRESS,
+            MEDIA_INTF_T_ALSA_RAWMIDI,
+            MEDIA_INTF_T_ALSA_HWDEP,
+            MEDIA_INTF_T_ALSA_SEQUENCER,
+            MEDIA_INTF_T_ALSA_TIMER,
+            MEDIA_IOC_DEVICE_INFO,
+            MEDIA_IOC_ENUM_ENTITIES,
+            MEDIA_IOC_ENUM_LINKS,
+            MEDIA_IOC_SETUP_LINK,
+            MEDIA_IOC_G_TOPOLOGY,
+            MEDIA_IOC_REQUEST_ALLOC,
+            MEDIA_REQUEST_IOC_QUEUE,
+            MEDIA_REQUEST_IOC_REINIT,
+        );
+        for version in [(4 << 16) | (18 << 8), (4 << 16) | (19 << 8)] {
+            assert_eq!(
+                super::MEDIA_V2_ENTITY_HAS_FLAGS(version),
+                linux_media_sys::MEDIA_V2_ENTITY_HAS_FLAGS(version)
+            );
+            assert_eq!(
+                super::MEDIA_V2_PAD_HAS_INDEX(version),
+                linux_media_sys::MEDIA_V2_PAD_HAS_INDEX(version)
+            );
+        }
+    }
+}
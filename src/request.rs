@@ -1,37 +1,43 @@
-use std::os::fd::{AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
-
-use linux_media_sys as media;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd, RawFd};
 
 use crate::error;
-use crate::ioctl;
+use crate::ioctls;
+use crate::MediaBackend;
 
 /// A request associated with a media device.
 ///
 /// # Details
 /// This is a wrapper for the media control API's request, which is tied to a specific media device.
+/// The request is allocated through the [`MediaBackend`] of the device, e.g. a [`BorrowedFd`][std::os::fd::BorrowedFd] of it.
 #[derive(Debug)]
-pub struct Request<'a> {
-    /// The file descriptor of the media device from which the request was allocated.
-    media_fd: BorrowedFd<'a>,
+pub struct Request<B> {
+    /// The media device from which the request was allocated.
+    media_fd: B,
     /// The file descriptor corresponding to the request allocated on the media device (referenced by media_fd).
     request_fd: OwnedFd,
 }
 
-impl<'a> Request<'a> {
-    pub fn new(media_fd: BorrowedFd<'a>) -> error::Result<Self> {
-        unsafe {
-            let mut request_fd: libc::c_int = -1;
-            ioctl!(media_fd, media::MEDIA_IOC_REQUEST_ALLOC, &mut request_fd)?;
-            Ok(Self {
-                media_fd,
-                request_fd: OwnedFd::from_raw_fd(request_fd),
-            })
-        }
+impl<B> Request<B>
+where
+    B: MediaBackend,
+{
+    pub fn new(media_fd: B) -> error::Result<Self> {
+        let request_fd = media_fd.request_alloc();
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_request(crate::metrics::RequestOp::Alloc, request_fd.is_ok());
+        let request_fd = request_fd?;
+        Ok(Self {
+            media_fd,
+            request_fd,
+        })
     }
 
     /// Allocate a new request on the same media device
-    pub fn new_request(&self) -> error::Result<Self> {
-        Self::new(self.media_fd)
+    pub fn new_request(&self) -> error::Result<Self>
+    where
+        B: Clone,
+    {
+        Self::new(self.media_fd.clone())
     }
 
     /// Initializes the request for recycling without re-allocating it.
@@ -43,7 +49,10 @@ impl<'a> Request<'a> {
     /// # Errors
     /// If the request is still queued and has not yet completed, this function returns [`error::Error::DeviceIsBusy`]. No other errors are possible.
     pub fn init(&mut self) -> error::Result<()> {
-        unsafe { ioctl!(self.request_fd, media::MEDIA_REQUEST_IOC_REINIT) }
+        let result = ioctls::request_reinit(self.request_fd.as_fd());
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_request(crate::metrics::RequestOp::Reinit, result.is_ok());
+        result
     }
 
     /// Enqueue the request
@@ -55,24 +64,22 @@ impl<'a> Request<'a> {
     /// - `RequestHasInvalidData`    : The request has invalid data.
     /// - `HardwareBadState`         : The hardware is in a bad state. To recover, the application needs to stop streaming to reset the hardware state and then try to restart streaming.
     pub fn queue(&self) -> error::Result<()> {
-        use error::Error::*;
-        let api = media::MEDIA_REQUEST_IOC_QUEUE;
-        unsafe {
-            ioctl!(self.request_fd, api).map_err(|err| {
-                let fd = self.request_fd.as_raw_fd();
-                if let Ioctl { ref code, .. } = err {
-                    match code.raw_os_error() {
-                        Some(code @ libc::EBUSY) => RequestIsAlreadyQueued { fd, code, api },
-                        Some(code @ libc::ENOENT) => RequestNotContainBuffers { fd, code, api },
-                        Some(code @ libc::ENOMEM) => OutOfMemory { fd, code, api },
-                        Some(code @ libc::EINVAL) => RequestHasInvalidData { fd, code, api },
-                        Some(code @ libc::EIO) => HardwareBadState { fd, code, api },
-                        _ => err,
-                    }
-                } else {
-                    err
-                }
-            })
-        }
+        let result = ioctls::request_queue(self.request_fd.as_fd());
+        #[cfg(feature = "metrics")]
+        crate::metrics::record_request(crate::metrics::RequestOp::Queue, result.is_ok());
+        result
+    }
+}
+
+impl<B> AsFd for Request<B> {
+    /// The file descriptor of the request, which is passed to V4L2 ioctls to associate buffers and controls with the request.
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.request_fd.as_fd()
+    }
+}
+
+impl<B> AsRawFd for Request<B> {
+    fn as_raw_fd(&self) -> RawFd {
+        self.request_fd.as_raw_fd()
     }
 }
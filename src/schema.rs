@@ -0,0 +1,30 @@
+//! JSON schemas of types with hand-written serialization, for the `schemars` feature.
+
+/// Implement `JsonSchema` for a bitflags type as the string it is serialized to, such as `"Enabled | Immutable"`.
+macro_rules! bitflags_schema {
+    ($name:ident) => {
+        impl schemars::JsonSchema for $name {
+            fn schema_name() -> String {
+                stringify!($name).to_string()
+            }
+
+            fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+                let names: Vec<&str> = $name::all().iter_names().map(|(name, _)| name).collect();
+                schemars::schema::SchemaObject {
+                    instance_type: Some(schemars::schema::InstanceType::String.into()),
+                    metadata: Some(Box::new(schemars::schema::Metadata {
+                        description: Some(format!(
+                            "Flags joined with ` | `, of {}",
+                            names.join(", ")
+                        )),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }
+                .into()
+            }
+        }
+    };
+}
+
+pub(crate) use bitflags_schema;
@@ -0,0 +1,780 @@
+//! Formats and selections of V4L2 subdevices, configured through the device files such like `/dev/v4l-subdev0`.
+//!
+//! # Details
+//! `linux/v4l2-subdev.h` is not covered by [`linux_media_sys`], so the structs passed to the ioctls are defined here.
+//! The pads are addressed by their indices as in the topology, i.e. [`MediaPadDesc::index`][crate::MediaPadDesc::index].
+
+use std::fs::OpenOptions;
+use std::mem::size_of;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, OwnedFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::ioctl::ioc;
+use crate::ioctl::ioctl;
+use crate::media_interface_type::MediaInterfaceType;
+use crate::media_topology::MediaTopology;
+use crate::raw as media;
+
+/// Mirrors of the structs of `linux/v4l2-subdev.h` and `linux/videodev2.h`.
+pub mod raw {
+    #![allow(non_camel_case_types)]
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct v4l2_mbus_framefmt {
+        pub width: u32,
+        pub height: u32,
+        pub code: u32,
+        pub field: u32,
+        pub colorspace: u32,
+        /// The union of `ycbcr_enc` and `hsv_enc`.
+        pub ycbcr_enc: u16,
+        pub quantization: u16,
+        pub xfer_func: u16,
+        pub flags: u16,
+        pub reserved: [u16; 10],
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct v4l2_subdev_format {
+        pub which: u32,
+        pub pad: u32,
+        pub format: v4l2_mbus_framefmt,
+        pub stream: u32,
+        pub reserved: [u32; 7],
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct v4l2_rect {
+        pub left: i32,
+        pub top: i32,
+        pub width: u32,
+        pub height: u32,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct v4l2_subdev_selection {
+        pub which: u32,
+        pub pad: u32,
+        pub target: u32,
+        pub flags: u32,
+        pub r: v4l2_rect,
+        pub stream: u32,
+        pub reserved: [u32; 7],
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct v4l2_subdev_route {
+        pub sink_pad: u32,
+        pub sink_stream: u32,
+        pub source_pad: u32,
+        pub source_stream: u32,
+        pub flags: u32,
+        pub reserved: [u32; 5],
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct v4l2_subdev_routing {
+        pub which: u32,
+        pub len_routes: u32,
+        /// The pointer to an array of [`v4l2_subdev_route`] of `len_routes` elements.
+        pub routes: u64,
+        pub num_routes: u32,
+        pub reserved: [u32; 11],
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct v4l2_subdev_client_capability {
+        pub capabilities: u64,
+    }
+}
+
+/// `_IOWR('V', nr, size)`
+pub(crate) const fn iowr(nr: u32, size: usize) -> libc::c_ulong {
+    ioc(media::_IOC_READ | media::_IOC_WRITE, b'V', nr, size)
+}
+
+pub const VIDIOC_SUBDEV_G_FMT: libc::c_ulong = iowr(4, size_of::<raw::v4l2_subdev_format>());
+pub const VIDIOC_SUBDEV_S_FMT: libc::c_ulong = iowr(5, size_of::<raw::v4l2_subdev_format>());
+pub const VIDIOC_SUBDEV_G_SELECTION: libc::c_ulong =
+    iowr(61, size_of::<raw::v4l2_subdev_selection>());
+pub const VIDIOC_SUBDEV_S_SELECTION: libc::c_ulong =
+    iowr(62, size_of::<raw::v4l2_subdev_selection>());
+pub const VIDIOC_SUBDEV_G_ROUTING: libc::c_ulong = iowr(38, size_of::<raw::v4l2_subdev_routing>());
+pub const VIDIOC_SUBDEV_S_ROUTING: libc::c_ulong = iowr(39, size_of::<raw::v4l2_subdev_routing>());
+pub const VIDIOC_SUBDEV_S_CLIENT_CAP: libc::c_ulong =
+    iowr(102, size_of::<raw::v4l2_subdev_client_capability>());
+
+/// `V4L2_SUBDEV_ROUTE_FL_ACTIVE`
+const ROUTE_FL_ACTIVE: u32 = 1 << 0;
+/// `V4L2_SUBDEV_CLIENT_CAP_STREAMS`
+const CLIENT_CAP_STREAMS: u64 = 1 << 0;
+
+/// Which configuration of a subdevice is accessed.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SubdevWhich {
+    /// The configuration tried on the file handle, which does not affect the device.
+    Try,
+    /// The configuration applied to the device.
+    Active,
+}
+
+impl From<SubdevWhich> for u32 {
+    fn from(which: SubdevWhich) -> Self {
+        match which {
+            SubdevWhich::Try => 0,
+            SubdevWhich::Active => 1,
+        }
+    }
+}
+
+/// The format of the media bus on a pad, a wrapper type of [`raw::v4l2_mbus_framefmt`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MbusFrameFormat {
+    #[serde(rename = "width")]
+    pub width: u32,
+    #[serde(rename = "height")]
+    pub height: u32,
+    /// The media bus code such like `MEDIA_BUS_FMT_SRGGB10_1X10` (`0x300f`).
+    #[serde(rename = "code")]
+    pub code: u32,
+    /// `enum v4l2_field`.
+    #[serde(rename = "field")]
+    pub field: u32,
+    /// `enum v4l2_colorspace`.
+    #[serde(rename = "colorspace")]
+    pub colorspace: u32,
+    #[serde(rename = "ycbcr_enc")]
+    pub ycbcr_enc: u16,
+    #[serde(rename = "quantization")]
+    pub quantization: u16,
+    #[serde(rename = "xfer_func")]
+    pub xfer_func: u16,
+    #[serde(rename = "flags")]
+    pub flags: u16,
+}
+
+impl From<raw::v4l2_mbus_framefmt> for MbusFrameFormat {
+    fn from(format: raw::v4l2_mbus_framefmt) -> Self {
+        Self {
+            width: format.width,
+            height: format.height,
+            code: format.code,
+            field: format.field,
+            colorspace: format.colorspace,
+            ycbcr_enc: format.ycbcr_enc,
+            quantization: format.quantization,
+            xfer_func: format.xfer_func,
+            flags: format.flags,
+        }
+    }
+}
+
+impl From<MbusFrameFormat> for raw::v4l2_mbus_framefmt {
+    fn from(format: MbusFrameFormat) -> Self {
+        Self {
+            width: format.width,
+            height: format.height,
+            code: format.code,
+            field: format.field,
+            colorspace: format.colorspace,
+            ycbcr_enc: format.ycbcr_enc,
+            quantization: format.quantization,
+            xfer_func: format.xfer_func,
+            flags: format.flags,
+            reserved: [0; 10],
+        }
+    }
+}
+
+/// A rectangle, a wrapper type of [`raw::v4l2_rect`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Rect {
+    #[serde(rename = "left")]
+    pub left: i32,
+    #[serde(rename = "top")]
+    pub top: i32,
+    #[serde(rename = "width")]
+    pub width: u32,
+    #[serde(rename = "height")]
+    pub height: u32,
+}
+
+impl From<raw::v4l2_rect> for Rect {
+    fn from(r: raw::v4l2_rect) -> Self {
+        Self {
+            left: r.left,
+            top: r.top,
+            width: r.width,
+            height: r.height,
+        }
+    }
+}
+
+impl From<Rect> for raw::v4l2_rect {
+    fn from(r: Rect) -> Self {
+        Self {
+            left: r.left,
+            top: r.top,
+            width: r.width,
+            height: r.height,
+        }
+    }
+}
+
+/// The rectangle of a pad accessed by a selection, `V4L2_SEL_TGT_*`.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum SelectionTarget {
+    /// The area cropped from the frame on a sink pad.
+    Crop,
+    /// The default of the crop rectangle.
+    CropDefault,
+    /// The bounds of the crop rectangle.
+    CropBounds,
+    /// The native size of the sensor.
+    NativeSize,
+    /// The area the cropped frame is scaled and composed into.
+    Compose,
+    /// The default of the compose rectangle.
+    ComposeDefault,
+    /// The bounds of the compose rectangle.
+    ComposeBounds,
+    /// The compose rectangle with the padding written by the hardware.
+    ComposePadded,
+}
+
+impl From<SelectionTarget> for u32 {
+    fn from(target: SelectionTarget) -> Self {
+        use SelectionTarget::*;
+        match target {
+            Crop => 0x0000,
+            CropDefault => 0x0001,
+            CropBounds => 0x0002,
+            NativeSize => 0x0003,
+            Compose => 0x0100,
+            ComposeDefault => 0x0101,
+            ComposeBounds => 0x0102,
+            ComposePadded => 0x0103,
+        }
+    }
+}
+
+bitflags::bitflags! {
+    /// How the driver adjusts a selection rectangle, `V4L2_SEL_FLAG_*`.
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+    pub struct SelectionFlags: u32 {
+        /// The rectangle adjusted is not smaller than the one requested.
+        const Ge = 1 << 0;
+        /// The rectangle adjusted is not larger than the one requested.
+        const Le = 1 << 1;
+        /// The configuration of the other rectangles and formats is not propagated.
+        const KeepConfig = 1 << 2;
+    }
+}
+
+#[cfg(feature = "schemars")]
+crate::schema::bitflags_schema!(SelectionFlags);
+
+/// A route of a stream through a subdevice, from a stream on a sink pad to a stream on a source pad.
+///
+/// # Details
+/// A sensor multiplexing image and embedded data on a CSI-2 bus, for example, routes them as the streams `0` and `1` of its source pad.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SubdevRoute {
+    #[serde(rename = "sink_pad")]
+    pub sink_pad: usize,
+    #[serde(rename = "sink_stream")]
+    pub sink_stream: u32,
+    #[serde(rename = "source_pad")]
+    pub source_pad: usize,
+    #[serde(rename = "source_stream")]
+    pub source_stream: u32,
+    /// Whether the stream flows through the route. Inactive routes are reported by drivers having fixed routes.
+    #[serde(rename = "active")]
+    pub active: bool,
+}
+
+impl From<raw::v4l2_subdev_route> for SubdevRoute {
+    fn from(route: raw::v4l2_subdev_route) -> Self {
+        Self {
+            sink_pad: route.sink_pad as usize,
+            sink_stream: route.sink_stream,
+            source_pad: route.source_pad as usize,
+            source_stream: route.source_stream,
+            active: route.flags & ROUTE_FL_ACTIVE != 0,
+        }
+    }
+}
+
+impl From<SubdevRoute> for raw::v4l2_subdev_route {
+    fn from(route: SubdevRoute) -> Self {
+        Self {
+            sink_pad: route.sink_pad as u32,
+            sink_stream: route.sink_stream,
+            source_pad: route.source_pad as u32,
+            source_stream: route.source_stream,
+            flags: if route.active { ROUTE_FL_ACTIVE } else { 0 },
+            reserved: [0; 5],
+        }
+    }
+}
+
+/// Replace the routes of `routes` from the sink stream or to the source stream of `route` with `route`.
+fn merge_route(routes: &[SubdevRoute], route: SubdevRoute) -> Vec<SubdevRoute> {
+    let mut merged: Vec<SubdevRoute> = routes
+        .iter()
+        .filter(|r| {
+            (r.sink_pad, r.sink_stream) != (route.sink_pad, route.sink_stream)
+                && (r.source_pad, r.source_stream) != (route.source_pad, route.source_stream)
+        })
+        .copied()
+        .collect();
+    merged.push(route);
+    merged
+}
+
+/// Get the routes with `g_routing` issuing `VIDIOC_SUBDEV_G_ROUTING`,
+/// growing the array as long as the kernel counts more routes in `num_routes` than the array holds.
+///
+/// # Errors
+/// `ENOSPC` is returned as it is if the array is not too short for the routes counted.
+fn get_routing<F>(which: SubdevWhich, mut g_routing: F) -> Result<Vec<SubdevRoute>>
+where
+    F: FnMut(&mut raw::v4l2_subdev_routing) -> Result<()>,
+{
+    let mut routes = vec![raw::v4l2_subdev_route::default(); 16];
+    loop {
+        let mut routing = raw::v4l2_subdev_routing {
+            which: which.into(),
+            len_routes: routes.len() as u32,
+            routes: routes.as_mut_ptr() as u64,
+            ..Default::default()
+        };
+        match g_routing(&mut routing) {
+            Ok(()) => {
+                routes.truncate(routing.num_routes as usize);
+                return Ok(routes.into_iter().map(SubdevRoute::from).collect());
+            }
+            // the array is too short for the routes counted in num_routes
+            Err(err)
+                if err.errno() == Some(libc::ENOSPC) && routing.num_routes > routing.len_routes =>
+            {
+                routes.resize(routing.num_routes as usize, Default::default());
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Check that the entity `name` has a sink pad of the index `sink` and a source pad of the index `source`.
+fn check_route_pads(
+    topology: &MediaTopology,
+    name: &str,
+    sink: usize,
+    source: usize,
+) -> Result<()> {
+    let invalid = |reason| error::Error::InvalidRoute {
+        entity: name.to_string(),
+        reason,
+    };
+    let entity = topology
+        .entities_slice()
+        .iter()
+        .find(|entity| entity.name() == name)
+        .ok_or(invalid("no such entity"))?;
+    let pad_flags = |index| {
+        topology
+            .pads_slice()
+            .iter()
+            .filter(|pad| pad.entity_id == entity.id())
+            .map(|pad| topology.pad_desc(pad))
+            .find(|desc| desc.index() == index)
+            .map(|desc| desc.flags())
+    };
+    match pad_flags(sink) {
+        Some(flags) if flags.is_sink() => {}
+        Some(_) => return Err(invalid("the sink pad of the route is not a sink pad")),
+        None => return Err(invalid("no such sink pad")),
+    }
+    match pad_flags(source) {
+        Some(flags) if flags.is_source() => Ok(()),
+        Some(_) => Err(invalid("the source pad of the route is not a source pad")),
+        None => Err(invalid("no such source pad")),
+    }
+}
+
+/// A V4L2 subdevice opened through its device file.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(media) = Media::from_path("/dev/media0") {
+///     let topology = media.new_topology()?;
+///     let sensor = Subdev::from_topology(&topology, "imx219 10-0010")?;
+///     let mut format = sensor.format(0, SubdevWhich::Active)?;
+///     format.width = 1640;
+///     format.height = 1232;
+///     let format = sensor.set_format(0, SubdevWhich::Active, &format)?;
+///     // propagate the format adjusted by the sensor to the receiver
+///     let csi = Subdev::from_topology(&topology, "unicam-image")?;
+///     csi.set_format(0, SubdevWhich::Active, &format)?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Subdev {
+    path: PathBuf,
+    fd: OwnedFd,
+    /// The name of the entity, if the subdevice is opened by [`from_topology`][Self::from_topology].
+    entity: Option<String>,
+}
+
+impl Subdev {
+    pub fn from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let fd: OwnedFd = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_CLOEXEC)
+            .open(&path)
+            .map_err(|err| error::trap_io_error(err, path.clone()))?
+            .into();
+        Ok(Self {
+            path,
+            fd,
+            entity: None,
+        })
+    }
+
+    /// Open the subdevice of the entity `name`, whose device file is resolved from the interface linked to it.
+    ///
+    /// # Errors
+    /// [`SubdevNotFound`][error::Error::SubdevNotFound] if there is no such entity, or it has no subdevice interface resolved.
+    pub fn from_topology(topology: &MediaTopology, name: &str) -> Result<Self> {
+        let path = topology
+            .entities_slice()
+            .iter()
+            .find(|entity| entity.name() == name)
+            .and_then(|entity| {
+                topology.interface_devnode(entity.id(), MediaInterfaceType::V4LSubdev)
+            })
+            .and_then(|devnode| devnode.dev_path())
+            .ok_or_else(|| error::Error::SubdevNotFound {
+                entity: name.to_string(),
+            })?;
+        let mut subdev = Self::from_path(path)?;
+        subdev.entity = Some(name.to_string());
+        Ok(subdev)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The name of the entity of the subdevice, if it is opened by [`from_topology`][Self::from_topology].
+    pub fn entity(&self) -> Option<&str> {
+        self.entity.as_deref()
+    }
+
+    /// Get the format on the pad `pad`, as `VIDIOC_SUBDEV_G_FMT`.
+    pub fn format(&self, pad: usize, which: SubdevWhich) -> Result<MbusFrameFormat> {
+        let mut format = raw::v4l2_subdev_format {
+            which: which.into(),
+            pad: pad as u32,
+            ..Default::default()
+        };
+        unsafe { ioctl!(self.fd, VIDIOC_SUBDEV_G_FMT, &mut format)? };
+        Ok(format.format.into())
+    }
+
+    /// Set the format on the pad `pad`, as `VIDIOC_SUBDEV_S_FMT`.
+    ///
+    /// # Returns
+    /// The format adjusted by the driver, which may differ from `format`.
+    pub fn set_format(
+        &self,
+        pad: usize,
+        which: SubdevWhich,
+        format: &MbusFrameFormat,
+    ) -> Result<MbusFrameFormat> {
+        let mut format = raw::v4l2_subdev_format {
+            which: which.into(),
+            pad: pad as u32,
+            format: (*format).into(),
+            ..Default::default()
+        };
+        unsafe { ioctl!(self.fd, VIDIOC_SUBDEV_S_FMT, &mut format)? };
+        Ok(format.format.into())
+    }
+
+    /// Get the rectangle `target` on the pad `pad`, as `VIDIOC_SUBDEV_G_SELECTION`.
+    pub fn selection(
+        &self,
+        pad: usize,
+        which: SubdevWhich,
+        target: SelectionTarget,
+    ) -> Result<Rect> {
+        let mut selection = raw::v4l2_subdev_selection {
+            which: which.into(),
+            pad: pad as u32,
+            target: target.into(),
+            ..Default::default()
+        };
+        unsafe { ioctl!(self.fd, VIDIOC_SUBDEV_G_SELECTION, &mut selection)? };
+        Ok(selection.r.into())
+    }
+
+    /// Set the rectangle `target` on the pad `pad`, as `VIDIOC_SUBDEV_S_SELECTION`.
+    ///
+    /// # Returns
+    /// The rectangle adjusted by the driver following `flags`.
+    pub fn set_selection(
+        &self,
+        pad: usize,
+        which: SubdevWhich,
+        target: SelectionTarget,
+        flags: SelectionFlags,
+        rect: &Rect,
+    ) -> Result<Rect> {
+        let mut selection = raw::v4l2_subdev_selection {
+            which: which.into(),
+            pad: pad as u32,
+            target: target.into(),
+            flags: flags.bits(),
+            r: (*rect).into(),
+            ..Default::default()
+        };
+        unsafe { ioctl!(self.fd, VIDIOC_SUBDEV_S_SELECTION, &mut selection)? };
+        Ok(selection.r.into())
+    }
+}
+
+impl Subdev {
+    /// Declare that this client handles streams, as `VIDIOC_SUBDEV_S_CLIENT_CAP`, without which the routing ioctls are rejected.
+    fn enable_streams(&self) -> Result<()> {
+        let mut cap = raw::v4l2_subdev_client_capability {
+            capabilities: CLIENT_CAP_STREAMS,
+        };
+        unsafe { ioctl!(self.fd, VIDIOC_SUBDEV_S_CLIENT_CAP, &mut cap) }
+    }
+
+    /// Get the routes of the streams through the subdevice, as `VIDIOC_SUBDEV_G_ROUTING`.
+    ///
+    /// # Errors
+    /// [`NotSupportedIoctl`][error::Error::NotSupportedIoctl] if the subdevice or the kernel does not support streams.
+    pub fn routing(&self, which: SubdevWhich) -> Result<Vec<SubdevRoute>> {
+        self.enable_streams()?;
+        get_routing(which, |routing| unsafe {
+            ioctl!(self.fd, VIDIOC_SUBDEV_G_ROUTING, routing)
+        })
+    }
+
+    /// Replace the routes of the streams through the subdevice with `routes`, as `VIDIOC_SUBDEV_S_ROUTING`.
+    ///
+    /// # Details
+    /// Setting the routes resets the formats and selections of the streams to the defaults of the driver.
+    ///
+    /// # Returns
+    /// The routes adjusted by the driver.
+    pub fn set_routing(
+        &self,
+        which: SubdevWhich,
+        routes: &[SubdevRoute],
+    ) -> Result<Vec<SubdevRoute>> {
+        self.enable_streams()?;
+        let mut raw: Vec<raw::v4l2_subdev_route> =
+            routes.iter().map(|route| (*route).into()).collect();
+        let mut routing = raw::v4l2_subdev_routing {
+            which: which.into(),
+            len_routes: raw.len() as u32,
+            routes: raw.as_mut_ptr() as u64,
+            num_routes: raw.len() as u32,
+            ..Default::default()
+        };
+        unsafe { ioctl!(self.fd, VIDIOC_SUBDEV_S_ROUTING, &mut routing)? };
+        raw.truncate(routing.num_routes as usize);
+        Ok(raw.into_iter().map(SubdevRoute::from).collect())
+    }
+
+    /// Route the stream `sink.1` on the sink pad of the index `sink.0` to the stream `source.1` on the source pad of the index `source.0`.
+    ///
+    /// # Details
+    /// The active routes from the same sink stream or to the same source stream are replaced, and the others are kept.
+    /// The pads are checked against `topology` before the routes are changed.
+    ///
+    /// # Errors
+    /// [`InvalidRoute`][error::Error::InvalidRoute] if the subdevice is not opened by [`from_topology`][Self::from_topology],
+    /// or the pads are not a sink pad and a source pad of its entity.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = media.new_topology()?;
+    ///     let csi = Subdev::from_topology(&topology, "csi2")?;
+    ///     // the embedded data multiplexed as the stream 1 of the sink pad 0 to the source pad 2
+    ///     csi.route_stream(&topology, SubdevWhich::Active, (0, 1), (2, 0))?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn route_stream(
+        &self,
+        topology: &MediaTopology,
+        which: SubdevWhich,
+        sink: (usize, u32),
+        source: (usize, u32),
+    ) -> Result<Vec<SubdevRoute>> {
+        let name = self.entity().ok_or_else(|| error::Error::InvalidRoute {
+            entity: self.path.display().to_string(),
+            reason: "the entity of the subdevice is unknown",
+        })?;
+        check_route_pads(topology, name, sink.0, source.0)?;
+        let route = SubdevRoute {
+            sink_pad: sink.0,
+            sink_stream: sink.1,
+            source_pad: source.0,
+            source_stream: source.1,
+            active: true,
+        };
+        let routes = merge_route(&self.routing(which)?, route);
+        self.set_routing(which, &routes)
+    }
+}
+
+impl AsFd for Subdev {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.fd.as_fd()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ioctl_codes_match_kernel_headers() {
+        assert_eq!(size_of::<raw::v4l2_mbus_framefmt>(), 48);
+        assert_eq!(VIDIOC_SUBDEV_G_FMT, 0xc058_5604);
+        assert_eq!(VIDIOC_SUBDEV_S_FMT, 0xc058_5605);
+        assert_eq!(VIDIOC_SUBDEV_G_SELECTION, 0xc040_563d);
+        assert_eq!(VIDIOC_SUBDEV_S_SELECTION, 0xc040_563e);
+    }
+
+    #[test]
+    fn routing_ioctl_codes_match_kernel_headers() {
+        assert_eq!(size_of::<raw::v4l2_subdev_route>(), 40);
+        assert_eq!(VIDIOC_SUBDEV_G_ROUTING, 0xc040_5626);
+        assert_eq!(VIDIOC_SUBDEV_S_ROUTING, 0xc040_5627);
+        assert_eq!(VIDIOC_SUBDEV_S_CLIENT_CAP, 0xc008_5666);
+    }
+
+    #[test]
+    fn merges_routes_and_checks_pads() {
+        let route = |sink_stream, source_pad, source_stream| SubdevRoute {
+            sink_pad: 0,
+            sink_stream,
+            source_pad,
+            source_stream,
+            active: true,
+        };
+        let routes = [route(0, 1, 0), route(1, 1, 1)];
+        assert_eq!(
+            merge_route(&routes, route(1, 2, 0)),
+            [route(0, 1, 0), route(1, 2, 0)]
+        );
+        assert_eq!(
+            merge_route(&routes, route(2, 1, 0)),
+            [route(1, 1, 1), route(2, 1, 0)]
+        );
+
+        let topology = crate::mock_backend::test::mock().topology();
+        check_route_pads(&topology, "csi", 0, 1).unwrap();
+        let err = check_route_pads(&topology, "csi", 1, 0).unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::InvalidRoute {
+                reason: "the sink pad of the route is not a sink pad",
+                ..
+            }
+        ));
+        let err = check_route_pads(&topology, "csi", 0, 2).unwrap_err();
+        assert!(matches!(
+            err,
+            error::Error::InvalidRoute {
+                reason: "no such source pad",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn not_found_without_subdev_interface() {
+        let topology = crate::mock_backend::test::mock().topology();
+        let err = Subdev::from_topology(&topology, "csi").unwrap_err();
+        assert!(matches!(err, error::Error::SubdevNotFound { entity } if entity == "csi"));
+    }
+
+    #[test]
+    fn routing_grows_only_for_more_routes() {
+        let route = |sink_stream| raw::v4l2_subdev_route {
+            sink_stream,
+            source_pad: 1,
+            flags: ROUTE_FL_ACTIVE,
+            ..Default::default()
+        };
+        let enospc = || error::Error::ioctl_error(-1, libc::ENOSPC, VIDIOC_SUBDEV_G_ROUTING);
+
+        // a subdevice having more routes than the first array holds, as the kernel fills it
+        let kernel: Vec<_> = (0..20).map(route).collect();
+        let mut lens = vec![];
+        let routes = get_routing(SubdevWhich::Active, |routing| {
+            lens.push(routing.len_routes);
+            routing.num_routes = kernel.len() as u32;
+            if routing.len_routes < routing.num_routes {
+                return Err(enospc());
+            }
+            let routes = routing.routes as *mut raw::v4l2_subdev_route;
+            for (i, route) in kernel.iter().enumerate() {
+                unsafe { routes.add(i).write(*route) };
+            }
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(lens, [16, 20]);
+        assert_eq!(routes.len(), 20);
+        assert_eq!(routes[19], SubdevRoute::from(route(19)));
+
+        // ENOSPC for another reason is not retried forever
+        let mut calls = 0;
+        let err = get_routing(SubdevWhich::Active, |routing| {
+            calls += 1;
+            routing.num_routes = 1;
+            Err(enospc())
+        })
+        .unwrap_err();
+        assert_eq!(err.errno(), Some(libc::ENOSPC));
+        assert_eq!(calls, 1);
+    }
+}
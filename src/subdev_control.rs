@@ -0,0 +1,224 @@
+//! V4L2 controls of subdevices, such as the exposure and the gain of a sensor.
+//!
+//! # Details
+//! Controls are read and written with the extended control ioctls,
+//! which also queue control writes into a [`Request`] to take effect on a particular frame.
+
+use std::mem::size_of;
+use std::os::fd::{AsFd, AsRawFd};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::ioctl::ioctl;
+use crate::request::Request;
+use crate::subdev::{iowr, Subdev};
+
+/// Mirrors of the structs of the extended controls of `linux/videodev2.h`.
+pub mod raw {
+    #![allow(non_camel_case_types)]
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    pub union v4l2_ext_control_value {
+        pub value: i32,
+        pub value64: i64,
+        pub ptr: *mut libc::c_void,
+    }
+
+    #[repr(C, packed)]
+    #[derive(Clone, Copy)]
+    pub struct v4l2_ext_control {
+        pub id: u32,
+        pub size: u32,
+        pub reserved2: [u32; 1],
+        pub value: v4l2_ext_control_value,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy)]
+    pub struct v4l2_ext_controls {
+        /// The union of `ctrl_class` and `which`.
+        pub which: u32,
+        pub count: u32,
+        pub error_idx: u32,
+        pub request_fd: i32,
+        pub reserved: [u32; 1],
+        pub controls: *mut v4l2_ext_control,
+    }
+}
+
+pub const VIDIOC_G_EXT_CTRLS: libc::c_ulong = iowr(71, size_of::<raw::v4l2_ext_controls>());
+pub const VIDIOC_S_EXT_CTRLS: libc::c_ulong = iowr(72, size_of::<raw::v4l2_ext_controls>());
+
+/// `V4L2_CTRL_WHICH_CUR_VAL`
+const WHICH_CUR_VAL: u32 = 0;
+/// `V4L2_CTRL_WHICH_REQUEST_VAL`
+const WHICH_REQUEST_VAL: u32 = 0x0f01_0000;
+
+/// The ids of controls commonly implemented by camera sensors, `V4L2_CID_*`.
+pub mod cid {
+    /// The exposure time in lines, `V4L2_CID_EXPOSURE`.
+    pub const EXPOSURE: u32 = 0x0098_0911;
+    pub const HFLIP: u32 = 0x0098_0914;
+    pub const VFLIP: u32 = 0x0098_0915;
+    /// The vertical blanking in lines, which sets the frame rate, `V4L2_CID_VBLANK`.
+    pub const VBLANK: u32 = 0x009e_0901;
+    pub const HBLANK: u32 = 0x009e_0902;
+    pub const ANALOGUE_GAIN: u32 = 0x009e_0903;
+    /// The link frequency of the bus, a menu of 64-bit integers, `V4L2_CID_LINK_FREQ`.
+    pub const LINK_FREQ: u32 = 0x009f_0901;
+    /// The pixel rate, a read-only 64-bit integer, `V4L2_CID_PIXEL_RATE`.
+    pub const PIXEL_RATE: u32 = 0x009f_0902;
+    pub const TEST_PATTERN: u32 = 0x009f_0903;
+    pub const DIGITAL_GAIN: u32 = 0x009f_0905;
+}
+
+/// The value of a control, of the size of the type of the control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum ControlValue {
+    /// The value of an integer, boolean, menu or button control.
+    #[serde(rename = "int")]
+    Int(i32),
+    /// The value of a 64-bit integer control.
+    #[serde(rename = "int64")]
+    Int64(i64),
+}
+
+/// A control and its value written to a subdevice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SubdevControl {
+    /// The id of the control, e.g. [`cid::EXPOSURE`].
+    #[serde(rename = "id")]
+    pub id: u32,
+    #[serde(rename = "value")]
+    pub value: ControlValue,
+}
+
+impl From<SubdevControl> for raw::v4l2_ext_control {
+    fn from(control: SubdevControl) -> Self {
+        let value = match control.value {
+            ControlValue::Int(value) => raw::v4l2_ext_control_value { value },
+            ControlValue::Int64(value64) => raw::v4l2_ext_control_value { value64 },
+        };
+        Self {
+            id: control.id,
+            size: 0,
+            reserved2: [0],
+            value,
+        }
+    }
+}
+
+fn ext_controls(
+    which: u32,
+    request_fd: i32,
+    controls: &mut [raw::v4l2_ext_control],
+) -> raw::v4l2_ext_controls {
+    raw::v4l2_ext_controls {
+        which,
+        count: controls.len() as u32,
+        error_idx: 0,
+        request_fd,
+        reserved: [0],
+        controls: controls.as_mut_ptr(),
+    }
+}
+
+impl Subdev {
+    fn get_control(&self, id: u32) -> Result<raw::v4l2_ext_control_value> {
+        let value = ControlValue::Int64(0);
+        let mut controls = [raw::v4l2_ext_control::from(SubdevControl { id, value })];
+        let mut ext = ext_controls(WHICH_CUR_VAL, 0, &mut controls);
+        let fd = self.as_fd();
+        unsafe { ioctl!(fd, VIDIOC_G_EXT_CTRLS, &mut ext)? };
+        Ok(controls[0].value)
+    }
+
+    /// Read the current value of the control `id` of a 32-bit type, such as [`cid::EXPOSURE`].
+    pub fn control(&self, id: u32) -> Result<i32> {
+        Ok(unsafe { self.get_control(id)?.value })
+    }
+
+    /// Read the current value of the control `id` of a 64-bit type, such as [`cid::PIXEL_RATE`].
+    pub fn control64(&self, id: u32) -> Result<i64> {
+        Ok(unsafe { self.get_control(id)?.value64 })
+    }
+
+    /// Write `controls` at once, as `VIDIOC_S_EXT_CTRLS`.
+    ///
+    /// # Details
+    /// The driver validates all the values before writing any of them, so no control is changed if a value is invalid.
+    /// Writing to the hardware may still fail after some of the controls are changed,
+    /// which V4L2 reports by `error_idx` equal to the number of the controls,
+    /// so read the controls back on failure to know their values.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let topology = media.new_topology()?;
+    ///     let sensor = topology
+    ///         .entities_slice()
+    ///         .iter()
+    ///         .find(|entity| entity.function() == MediaEntityFunctions::CAMSensor);
+    ///     if let Some(sensor) = sensor {
+    ///         let sensor = Subdev::from_topology(&topology, sensor.name())?;
+    ///         sensor.set_controls(&[
+    ///             SubdevControl { id: cid::EXPOSURE, value: ControlValue::Int(1000) },
+    ///             SubdevControl { id: cid::ANALOGUE_GAIN, value: ControlValue::Int(256) },
+    ///         ])?;
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_controls(&self, controls: &[SubdevControl]) -> Result<()> {
+        let mut controls: Vec<raw::v4l2_ext_control> =
+            controls.iter().map(|control| (*control).into()).collect();
+        let mut ext = ext_controls(WHICH_CUR_VAL, 0, &mut controls);
+        let fd = self.as_fd();
+        unsafe { ioctl!(fd, VIDIOC_S_EXT_CTRLS, &mut ext) }
+    }
+
+    /// Store `controls` in `request`, so that they are applied when the request is processed instead of immediately.
+    ///
+    /// # Details
+    /// The request must be allocated on the media device the subdevice belongs to, and not be queued yet.
+    pub fn set_controls_in_request<B>(
+        &self,
+        request: &Request<B>,
+        controls: &[SubdevControl],
+    ) -> Result<()> {
+        let mut controls: Vec<raw::v4l2_ext_control> =
+            controls.iter().map(|control| (*control).into()).collect();
+        let request_fd = request.as_fd().as_raw_fd();
+        let mut ext = ext_controls(WHICH_REQUEST_VAL, request_fd, &mut controls);
+        let fd = self.as_fd();
+        unsafe { ioctl!(fd, VIDIOC_S_EXT_CTRLS, &mut ext) }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ext_controls_match_kernel_headers() {
+        assert_eq!(size_of::<raw::v4l2_ext_control>(), 20);
+        #[cfg(target_pointer_width = "64")]
+        {
+            assert_eq!(VIDIOC_G_EXT_CTRLS, 0xc020_5647);
+            assert_eq!(VIDIOC_S_EXT_CTRLS, 0xc020_5648);
+        }
+        let control = raw::v4l2_ext_control::from(SubdevControl {
+            id: cid::EXPOSURE,
+            value: ControlValue::Int64(-2),
+        });
+        assert_eq!({ control.id }, 0x0098_0911);
+        assert_eq!(unsafe { control.value.value64 }, -2);
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::raw as media;
+
+/// The parts of the crate listed by [`required_ioctls`] and [`required_syscalls`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum Subsystem {
+    /// Opening media devices, reading their topologies and setting up links with [`Media`][crate::Media].
+    #[serde(rename = "media")]
+    Media,
+    /// Allocating, queueing and recycling [`Request`][crate::Request]s.
+    #[serde(rename = "request")]
+    Request,
+    /// Formats, selections, routes and controls of V4L2 subdevices, with the `subdev` feature.
+    #[serde(rename = "subdev")]
+    Subdev,
+    /// Capabilities of DVB frontends, with the `dvb` feature.
+    #[serde(rename = "dvb")]
+    Dvb,
+    /// Enumerating devices and finding their drivers, firmware nodes and parent devices in sysfs.
+    #[serde(rename = "sysfs")]
+    Sysfs,
+    /// Polling devices with [`MediaWatcher`][crate::MediaWatcher], which lists them in sysfs, opens them and reads their topologies.
+    #[serde(rename = "watcher")]
+    Watcher,
+    /// Diagnosing permission errors with [`PermissionDiagnostics`][crate::PermissionDiagnostics].
+    #[serde(rename = "permission")]
+    Permission,
+}
+
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Subsystem::Media => "media",
+            Subsystem::Request => "request",
+            Subsystem::Subdev => "subdev",
+            Subsystem::Dvb => "dvb",
+            Subsystem::Sysfs => "sysfs",
+            Subsystem::Watcher => "watcher",
+            Subsystem::Permission => "permission",
+        };
+        f.write_str(name)
+    }
+}
+
+/// An ioctl issued by a subsystem of the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RequiredIoctl {
+    #[serde(rename = "subsystem")]
+    pub subsystem: Subsystem,
+    /// The name of the request in the kernel headers such like `MEDIA_IOC_G_TOPOLOGY`.
+    #[serde(rename = "name")]
+    pub name: &'static str,
+    /// The request number, compared with the second argument of `ioctl` by seccomp filters.
+    #[serde(rename = "request")]
+    pub request: u64,
+}
+
+/// A system call issued by a subsystem of the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct RequiredSyscall {
+    #[serde(rename = "subsystem")]
+    pub subsystem: Subsystem,
+    /// The name of the system call such like `openat`, as known to libseccomp.
+    #[serde(rename = "name")]
+    pub name: &'static str,
+}
+
+// `c_ulong` is `u32` on 32-bit targets
+#[allow(clippy::useless_conversion)]
+fn ioctl(subsystem: Subsystem, name: &'static str, request: libc::c_ulong) -> RequiredIoctl {
+    RequiredIoctl {
+        subsystem,
+        name,
+        request: u64::from(request),
+    }
+}
+
+/// List the ioctls issued by the crate, for generating the allow-lists of seccomp filters.
+///
+/// # Details
+/// The ioctls of the subsystems behind disabled features are left out.
+/// The same request may be issued by several subsystems, in which case it is listed for each of them.
+///
+/// # Examples
+/// Allow only the ioctls reading topologies.
+/// ```
+/// use linux_media::*;
+/// let allowed: Vec<u64> = required_ioctls()
+///     .into_iter()
+///     .filter(|ioctl| ioctl.subsystem == Subsystem::Media && ioctl.name != "MEDIA_IOC_SETUP_LINK")
+///     .map(|ioctl| ioctl.request)
+///     .collect();
+/// assert!(allowed.contains(&(raw::MEDIA_IOC_G_TOPOLOGY as u64)));
+/// ```
+pub fn required_ioctls() -> Vec<RequiredIoctl> {
+    use Subsystem::*;
+    #[allow(unused_mut)]
+    let mut ioctls = vec![
+        ioctl(Media, "MEDIA_IOC_DEVICE_INFO", media::MEDIA_IOC_DEVICE_INFO),
+        ioctl(Media, "MEDIA_IOC_G_TOPOLOGY", media::MEDIA_IOC_G_TOPOLOGY),
+        ioctl(
+            Media,
+            "MEDIA_IOC_ENUM_ENTITIES",
+            media::MEDIA_IOC_ENUM_ENTITIES,
+        ),
+        ioctl(Media, "MEDIA_IOC_ENUM_LINKS", media::MEDIA_IOC_ENUM_LINKS),
+        ioctl(Media, "MEDIA_IOC_SETUP_LINK", media::MEDIA_IOC_SETUP_LINK),
+        ioctl(
+            Watcher,
+            "MEDIA_IOC_DEVICE_INFO",
+            media::MEDIA_IOC_DEVICE_INFO,
+        ),
+        ioctl(Watcher, "MEDIA_IOC_G_TOPOLOGY", media::MEDIA_IOC_G_TOPOLOGY),
+        ioctl(
+            Request,
+            "MEDIA_IOC_REQUEST_ALLOC",
+            media::MEDIA_IOC_REQUEST_ALLOC,
+        ),
+        ioctl(
+            Request,
+            "MEDIA_REQUEST_IOC_QUEUE",
+            media::MEDIA_REQUEST_IOC_QUEUE,
+        ),
+        ioctl(
+            Request,
+            "MEDIA_REQUEST_IOC_REINIT",
+            media::MEDIA_REQUEST_IOC_REINIT,
+        ),
+    ];
+    #[cfg(feature = "subdev")]
+    {
+        use crate::subdev::{
+            VIDIOC_SUBDEV_G_FMT, VIDIOC_SUBDEV_G_ROUTING, VIDIOC_SUBDEV_G_SELECTION,
+            VIDIOC_SUBDEV_S_CLIENT_CAP, VIDIOC_SUBDEV_S_FMT, VIDIOC_SUBDEV_S_ROUTING,
+            VIDIOC_SUBDEV_S_SELECTION,
+        };
+        use crate::subdev_control::{VIDIOC_G_EXT_CTRLS, VIDIOC_S_EXT_CTRLS};
+        ioctls.extend([
+            ioctl(Subdev, "VIDIOC_SUBDEV_G_FMT", VIDIOC_SUBDEV_G_FMT),
+            ioctl(Subdev, "VIDIOC_SUBDEV_S_FMT", VIDIOC_SUBDEV_S_FMT),
+            ioctl(
+                Subdev,
+                "VIDIOC_SUBDEV_G_SELECTION",
+                VIDIOC_SUBDEV_G_SELECTION,
+            ),
+            ioctl(
+                Subdev,
+                "VIDIOC_SUBDEV_S_SELECTION",
+                VIDIOC_SUBDEV_S_SELECTION,
+            ),
+            ioctl(Subdev, "VIDIOC_SUBDEV_G_ROUTING", VIDIOC_SUBDEV_G_ROUTING),
+            ioctl(Subdev, "VIDIOC_SUBDEV_S_ROUTING", VIDIOC_SUBDEV_S_ROUTING),
+            ioctl(
+                Subdev,
+                "VIDIOC_SUBDEV_S_CLIENT_CAP",
+                VIDIOC_SUBDEV_S_CLIENT_CAP,
+            ),
+            ioctl(Subdev, "VIDIOC_G_EXT_CTRLS", VIDIOC_G_EXT_CTRLS),
+            ioctl(Subdev, "VIDIOC_S_EXT_CTRLS", VIDIOC_S_EXT_CTRLS),
+        ]);
+    }
+    #[cfg(feature = "dvb")]
+    ioctls.push(ioctl(Dvb, "FE_GET_INFO", crate::dvb::FE_GET_INFO));
+    ioctls
+}
+
+/// List the system calls issued by the crate, for generating the allow-lists of seccomp filters.
+///
+/// # Details
+/// The calls are named as the C library issues them on current glibc and musl,
+/// e.g. `openat` instead of `open`, while `stat` and `readlink` are listed in every form such like `statx` and `newfstatat`, as they depend on the architecture and the library,
+/// so the list is meant to be merged with the allow-list of the runtime of the application rather than used alone.
+/// Calls issued by the Rust standard library for every program, such as allocating memory, are not listed.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// let mut names: Vec<&str> = required_syscalls().iter().map(|syscall| syscall.name).collect();
+/// names.sort();
+/// names.dedup();
+/// assert!(names.contains(&"ioctl"));
+/// ```
+pub fn required_syscalls() -> Vec<RequiredSyscall> {
+    use Subsystem::*;
+    let table: &[(Subsystem, &[&'static str])] = &[
+        (
+            Media,
+            &["openat", "ioctl", "close", "readlink", "readlinkat"],
+        ),
+        (Request, &["ioctl", "close"]),
+        (Subdev, &["openat", "ioctl", "close"]),
+        (Dvb, &["openat", "ioctl", "close"]),
+        (
+            Sysfs,
+            &[
+                "openat",
+                "read",
+                "close",
+                "getdents64",
+                "readlink",
+                "readlinkat",
+                "statx",
+                "newfstatat",
+            ],
+        ),
+        (
+            Watcher,
+            &[
+                "openat",
+                "ioctl",
+                "close",
+                "read",
+                "getdents64",
+                "readlink",
+                "readlinkat",
+                "statx",
+                "newfstatat",
+                "nanosleep",
+                "clock_nanosleep",
+            ],
+        ),
+        (
+            Permission,
+            &[
+                "statx",
+                "newfstatat",
+                "geteuid",
+                "getegid",
+                "getgroups",
+                "openat",
+                "read",
+                "close",
+            ],
+        ),
+    ];
+    table
+        .iter()
+        .filter(|(subsystem, _)| {
+            (*subsystem != Subdev || cfg!(feature = "subdev"))
+                && (*subsystem != Dvb || cfg!(feature = "dvb"))
+        })
+        .flat_map(|(subsystem, names)| {
+            names.iter().map(|&name| RequiredSyscall {
+                subsystem: *subsystem,
+                name,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The names of the requests passed to [`ioctl!`][crate::ioctl::ioctl!] in `source`.
+    fn issued_requests(source: &str) -> Vec<&str> {
+        source
+            .split("ioctl!(")
+            .skip(1)
+            .filter_map(|call| call.split(')').next()?.split(',').nth(1))
+            .map(|request| request.trim().trim_start_matches("media::"))
+            .collect()
+    }
+
+    #[test]
+    fn lists_every_issued_ioctl() {
+        let mut sources = vec![include_str!("ioctls.rs")];
+        if cfg!(feature = "subdev") {
+            sources.extend([include_str!("subdev.rs"), include_str!("subdev_control.rs")]);
+        }
+        if cfg!(feature = "dvb") {
+            sources.push(include_str!("dvb.rs"));
+        }
+        let ioctls = required_ioctls();
+        let requests: Vec<&str> = sources.into_iter().flat_map(issued_requests).collect();
+        assert!(requests.contains(&"MEDIA_IOC_G_TOPOLOGY"), "{:?}", requests);
+        for request in requests {
+            assert!(
+                ioctls.iter().any(|ioctl| ioctl.name == request),
+                "{} is not listed",
+                request
+            );
+        }
+    }
+
+    #[test]
+    fn lists_ioctls_of_enabled_subsystems() {
+        let ioctls = required_ioctls();
+        assert!(ioctls.contains(&ioctl(
+            Subsystem::Request,
+            "MEDIA_REQUEST_IOC_QUEUE",
+            media::MEDIA_REQUEST_IOC_QUEUE
+        )));
+        assert_eq!(
+            ioctls
+                .iter()
+                .any(|ioctl| ioctl.subsystem == Subsystem::Subdev),
+            cfg!(feature = "subdev")
+        );
+        // every subsystem issuing ioctls is allowed to
+        for ioctl in &ioctls {
+            assert!(required_syscalls()
+                .iter()
+                .any(|syscall| syscall.subsystem == ioctl.subsystem && syscall.name == "ioctl"));
+        }
+    }
+}
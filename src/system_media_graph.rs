@@ -0,0 +1,458 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::firmware_node::FirmwareNode;
+use crate::media::Media;
+use crate::media_backend::MediaBackend;
+use crate::media_device_enumerator::enumerate;
+use crate::media_device_info::MediaDeviceInfo;
+use crate::media_entity::{EntityId, MediaEntity};
+use crate::media_link::MediaLinkFlags;
+use crate::media_topology::MediaTopology;
+
+/// An entity of one of the devices of a [`SystemMediaGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SystemEntity {
+    /// The index of the device in [`SystemMediaGraph::devices`].
+    #[serde(rename = "device")]
+    pub device: usize,
+    #[serde(rename = "entity")]
+    pub entity: EntityId,
+}
+
+/// Why two entities of different devices are linked in a [`SystemMediaGraph`].
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum SystemLinkKind {
+    /// Linked by [`connect`][SystemMediaGraph::connect], e.g. a DMA engine writing frames the ISP of another device reads.
+    Connected,
+    /// Sensors of the same name, which are the same sensor exposed by both devices.
+    SameSensor,
+    /// Entities described by the same firmware node, which are the same hardware exposed by both devices.
+    SameFirmwareNode,
+}
+
+/// A link between entities of different devices of a [`SystemMediaGraph`].
+///
+/// # Details
+/// Links of [`SystemLinkKind::Connected`] lead from `source` to `sink` as data links do.
+/// The others tell the entities are the same hardware, and are followed in both directions.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SystemLink {
+    #[serde(rename = "source")]
+    pub source: SystemEntity,
+    #[serde(rename = "sink")]
+    pub sink: SystemEntity,
+    #[serde(rename = "kind")]
+    pub kind: SystemLinkKind,
+}
+
+/// A media device of a [`SystemMediaGraph`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SystemMediaDevice {
+    #[serde(rename = "path")]
+    path: PathBuf,
+    #[serde(rename = "info")]
+    info: MediaDeviceInfo,
+    #[serde(rename = "topology")]
+    topology: MediaTopology,
+    /// The firmware nodes of the entities found in sysfs or given by [`SystemMediaGraph::annotate`].
+    #[serde(rename = "firmware_nodes")]
+    firmware_nodes: BTreeMap<EntityId, FirmwareNode>,
+}
+
+impl SystemMediaDevice {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn info(&self) -> &MediaDeviceInfo {
+        &self.info
+    }
+
+    pub fn topology(&self) -> &MediaTopology {
+        &self.topology
+    }
+
+    pub fn firmware_nodes(&self) -> &BTreeMap<EntityId, FirmwareNode> {
+        &self.firmware_nodes
+    }
+}
+
+/// The topologies of all the media devices of a system joined into a single graph.
+///
+/// # Details
+/// Some platforms split a camera pipeline across media devices, e.g. a CSI-2 receiver and an ISP registered as separate devices.
+/// Each topology is kept as it is, and entities of different devices are joined by [`SystemLink`]s,
+/// found by the names of sensors or by firmware nodes, or given by users.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// if let Ok(mut graph) = SystemMediaGraph::load() {
+///     graph.join_by_sensor_name();
+///     graph.join_by_firmware_node();
+///     if graph.connect_by_name("rp1-cfe-fe_image0", "pispbe-input").is_ok() {
+///         for entity in graph.find("imx219 10-0010").collect::<Vec<_>>() {
+///             for reached in graph.reachable(entity) {
+///                 println!("{}", graph.entity(reached).unwrap().name());
+///             }
+///         }
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SystemMediaGraph {
+    #[serde(rename = "devices")]
+    devices: Vec<SystemMediaDevice>,
+    #[serde(rename = "links")]
+    links: Vec<SystemLink>,
+}
+
+impl SystemMediaGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the topologies of all the media devices registered in sysfs.
+    ///
+    /// # Details
+    /// Devices which can not be opened, e.g. for lack of permission, are skipped.
+    /// No link between devices is made until asked for.
+    pub fn load() -> Result<Self> {
+        let mut graph = Self::new();
+        for node in enumerate()? {
+            if let Ok(media) = node.open() {
+                let _ = graph.add(&media);
+            }
+        }
+        Ok(graph)
+    }
+
+    /// Add the topology of `media`, with the firmware nodes of its entities found in sysfs.
+    ///
+    /// # Returns
+    /// The index of the added device.
+    pub fn add<B>(&mut self, media: &Media<B>) -> Result<usize>
+    where
+        B: MediaBackend,
+    {
+        let topology = media.new_topology()?;
+        self.devices.push(SystemMediaDevice {
+            path: media.path().to_path_buf(),
+            info: media.info().clone(),
+            firmware_nodes: topology.firmware_nodes(),
+            topology,
+        });
+        Ok(self.devices.len() - 1)
+    }
+
+    pub fn devices(&self) -> &[SystemMediaDevice] {
+        &self.devices
+    }
+
+    /// The links between entities of different devices.
+    pub fn links(&self) -> &[SystemLink] {
+        &self.links
+    }
+
+    pub fn entity(&self, entity: SystemEntity) -> Option<&MediaEntity> {
+        let topology = &self.devices.get(entity.device)?.topology;
+        topology
+            .entities_slice()
+            .iter()
+            .find(|e| e.id() == entity.entity)
+    }
+
+    /// All the entities of every device.
+    pub fn entities(&self) -> impl Iterator<Item = (SystemEntity, &MediaEntity)> {
+        self.devices.iter().enumerate().flat_map(|(device, dev)| {
+            dev.topology.entities_slice().iter().map(move |e| {
+                let entity = SystemEntity {
+                    device,
+                    entity: e.id(),
+                };
+                (entity, e)
+            })
+        })
+    }
+
+    /// The entities named `name` in any device.
+    pub fn find<'a>(&'a self, name: &'a str) -> impl Iterator<Item = SystemEntity> + 'a {
+        self.entities()
+            .filter(move |(_, e)| e.name() == name)
+            .map(|(entity, _)| entity)
+    }
+
+    fn push(&mut self, source: SystemEntity, sink: SystemEntity, kind: SystemLinkKind) -> bool {
+        let link = SystemLink { source, sink, kind };
+        if self.links.contains(&link) {
+            return false;
+        }
+        self.links.push(link);
+        true
+    }
+
+    /// Link `source` to `sink`, e.g. the DMA engine of a CSI-2 receiver to the input of an ISP of another device.
+    pub fn connect(&mut self, source: SystemEntity, sink: SystemEntity) {
+        self.push(source, sink, SystemLinkKind::Connected);
+    }
+
+    /// Link the entity named `source` to the one named `sink`, as [`connect`][Self::connect] does.
+    ///
+    /// # Errors
+    /// No entity of either name is found in any device.
+    pub fn connect_by_name(&mut self, source: &str, sink: &str) -> Result<()> {
+        let find = |name: &str| {
+            self.find(name)
+                .next()
+                .ok_or_else(|| error::Error::EntityNotFound {
+                    entity: name.to_string(),
+                })
+        };
+        let (source, sink) = (find(source)?, find(sink)?);
+        self.connect(source, sink);
+        Ok(())
+    }
+
+    /// Set the firmware node of `entity`, e.g. if its device node is not found in sysfs.
+    pub fn annotate(&mut self, entity: SystemEntity, node: FirmwareNode) {
+        if let Some(device) = self.devices.get_mut(entity.device) {
+            device.firmware_nodes.insert(entity.entity, node);
+        }
+    }
+
+    /// Join the pairs of entities of different devices which `key` maps to the same value.
+    fn join_by<K, F>(&mut self, kind: SystemLinkKind, key: F) -> usize
+    where
+        K: Ord,
+        F: Fn(&SystemMediaDevice, &MediaEntity) -> Option<K>,
+    {
+        let mut groups: BTreeMap<K, Vec<SystemEntity>> = BTreeMap::new();
+        for (device, dev) in self.devices.iter().enumerate() {
+            for e in dev.topology.entities_slice() {
+                if let Some(key) = key(dev, e) {
+                    let entity = SystemEntity {
+                        device,
+                        entity: e.id(),
+                    };
+                    groups.entry(key).or_default().push(entity);
+                }
+            }
+        }
+        let mut joined = 0;
+        for entities in groups.into_values() {
+            for (i, &source) in entities.iter().enumerate() {
+                for &sink in &entities[i + 1..] {
+                    if source.device != sink.device && self.push(source, sink, kind) {
+                        joined += 1;
+                    }
+                }
+            }
+        }
+        joined
+    }
+
+    /// Join the sensors of the same name on different devices.
+    ///
+    /// # Returns
+    /// The number of links made.
+    pub fn join_by_sensor_name(&mut self) -> usize {
+        self.join_by(SystemLinkKind::SameSensor, |_, entity| {
+            entity
+                .function()
+                .is_sensor()
+                .then(|| entity.name().to_string())
+        })
+    }
+
+    /// Join the entities of different devices described by the same firmware node.
+    ///
+    /// # Returns
+    /// The number of links made.
+    pub fn join_by_firmware_node(&mut self) -> usize {
+        self.join_by(SystemLinkKind::SameFirmwareNode, |device, entity| {
+            device.firmware_nodes.get(&entity.id()).cloned()
+        })
+    }
+
+    /// The entities `entity` leads to directly, through enabled data links of its device and links to other devices.
+    ///
+    /// # Details
+    /// Entities joined with `entity` as the same hardware are included as well.
+    pub fn neighbors(&self, entity: SystemEntity) -> Vec<SystemEntity> {
+        let mut neighbors = vec![];
+        if let Some(device) = self.devices.get(entity.device) {
+            let topology = &device.topology;
+            neighbors.extend(
+                topology
+                    .links_slice()
+                    .iter()
+                    .filter(|link| link.flags().contains(MediaLinkFlags::Enabled))
+                    .filter_map(|link| topology.link_desc(link))
+                    .filter(|desc| desc.source().id() == entity.entity)
+                    .map(|desc| SystemEntity {
+                        device: entity.device,
+                        entity: desc.sink().id(),
+                    }),
+            );
+        }
+        for link in &self.links {
+            if link.source == entity {
+                neighbors.push(link.sink);
+            } else if link.sink == entity && link.kind != SystemLinkKind::Connected {
+                neighbors.push(link.source);
+            }
+        }
+        neighbors
+    }
+
+    /// The entities reached from `entity` by following [`neighbors`][Self::neighbors], from the nearest ones.
+    pub fn reachable(&self, entity: SystemEntity) -> Vec<SystemEntity> {
+        let mut visited = BTreeSet::from([entity]);
+        let mut queue = VecDeque::from([entity]);
+        let mut reached = vec![];
+        while let Some(current) = queue.pop_front() {
+            for next in self.neighbors(current) {
+                if visited.insert(next) {
+                    queue.push_back(next);
+                    reached.push(next);
+                }
+            }
+        }
+        reached
+    }
+}
+
+impl fmt::Display for SystemMediaGraph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = |entity: SystemEntity| {
+            let device = &self.devices[entity.device];
+            let name = self.entity(entity).map_or("?", |e| e.name());
+            format!("{}:\"{}\"", device.path.display(), name)
+        };
+        for device in &self.devices {
+            writeln!(
+                f,
+                "{} ({}, {} entities)",
+                device.path.display(),
+                device.info.driver(),
+                device.topology.entities_slice().len()
+            )?;
+        }
+        for link in &self.links {
+            let arrow = match link.kind {
+                SystemLinkKind::Connected => "->",
+                SystemLinkKind::SameSensor | SystemLinkKind::SameFirmwareNode => "==",
+            };
+            writeln!(f, "{} {} {}", name(link.source), arrow, name(link.sink))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MediaEntityFunctions, MediaPadFlags, MockTopologyBuilder};
+
+    fn device(path: &str, topology: MediaTopology) -> SystemMediaDevice {
+        let media = Media::with_backend(path, crate::mock_backend::test::mock()).unwrap();
+        SystemMediaDevice {
+            path: PathBuf::from(path),
+            info: media.info().clone(),
+            topology,
+            firmware_nodes: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn follows_pipelines_across_devices() {
+        let mut builder = MockTopologyBuilder::new();
+        let (sensor, sensor_pads) = builder.entity(
+            "imx219 10-0010",
+            MediaEntityFunctions::CAMSensor,
+            &[MediaPadFlags::Source],
+        );
+        let (_, csi_pads) = builder.entity(
+            "csi",
+            MediaEntityFunctions::VIDIFBridge,
+            &[MediaPadFlags::Sink, MediaPadFlags::Source],
+        );
+        let (_, dma_pads) =
+            builder.entity("dma", MediaEntityFunctions::IoV4L, &[MediaPadFlags::Sink]);
+        builder.link(sensor_pads[0], csi_pads[0], MediaLinkFlags::Enabled);
+        builder.link(csi_pads[1], dma_pads[0], MediaLinkFlags::Enabled);
+        let receiver = builder.build();
+
+        let mut builder = MockTopologyBuilder::new();
+        let (_, input_pads) = builder.entity(
+            "input",
+            MediaEntityFunctions::IoV4L,
+            &[MediaPadFlags::Source],
+        );
+        let (isp, isp_pads) = builder.entity(
+            "isp",
+            MediaEntityFunctions::ProcVideoPixelFormatter,
+            &[MediaPadFlags::Sink],
+        );
+        builder.link(input_pads[0], isp_pads[0], MediaLinkFlags::Enabled);
+        builder.entity(
+            "imx219 10-0010",
+            MediaEntityFunctions::CAMSensor,
+            &[MediaPadFlags::Source],
+        );
+        let backend = builder.build();
+
+        let mut graph = SystemMediaGraph::new();
+        graph.devices.push(device("/dev/media0", receiver));
+        graph.devices.push(device("/dev/media1", backend));
+        let sensor = SystemEntity {
+            device: 0,
+            entity: sensor,
+        };
+        let isp = SystemEntity {
+            device: 1,
+            entity: isp,
+        };
+        assert!(!graph.reachable(sensor).contains(&isp));
+
+        graph.connect_by_name("dma", "input").unwrap();
+        assert!(graph.connect_by_name("dma", "nowhere").is_err());
+        let names: Vec<&str> = graph
+            .reachable(sensor)
+            .into_iter()
+            .map(|entity| graph.entity(entity).unwrap().name())
+            .collect();
+        assert_eq!(names, ["csi", "dma", "input", "isp"]);
+
+        assert_eq!(graph.join_by_sensor_name(), 1);
+        assert_eq!(graph.join_by_sensor_name(), 0);
+        let other = graph.find("imx219 10-0010").nth(1).unwrap();
+        assert!(graph.reachable(other).contains(&isp));
+
+        let node = FirmwareNode::DeviceTree {
+            path: "/soc/csi@7e801000".to_string(),
+        };
+        let csi = graph.find("csi").next().unwrap();
+        graph.annotate(csi, node.clone());
+        graph.annotate(isp, node);
+        assert_eq!(graph.join_by_firmware_node(), 1);
+        assert_eq!(graph.links().len(), 3);
+        assert!(graph
+            .to_string()
+            .contains("/dev/media0:\"dma\" -> /dev/media1:\"input\""));
+    }
+}
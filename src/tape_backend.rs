@@ -0,0 +1,601 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::os::fd::OwnedFd;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{trap_io_error, Error, Result};
+use crate::media_backend::{MediaBackend, TopologyArrays};
+use crate::raw as media;
+use crate::{
+    MediaApiCapabilities, MediaDeviceInfo, MediaEntity, MediaEntityDesc, MediaInterface, MediaLink,
+    MediaLinkDesc, MediaPad, MediaPadDesc, MediaTopologyCounts, Version,
+};
+
+/// An ioctl issued to a [`MediaBackend`], as recorded on a tape.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TapeRequest {
+    #[serde(rename = "device_info")]
+    DeviceInfo,
+    /// `MEDIA_IOC_G_TOPOLOGY` with the lengths of the arrays requested.
+    #[serde(rename = "g_topology")]
+    GTopology {
+        #[serde(rename = "entities")]
+        entities: Option<usize>,
+        #[serde(rename = "interfaces")]
+        interfaces: Option<usize>,
+        #[serde(rename = "pads")]
+        pads: Option<usize>,
+        #[serde(rename = "links")]
+        links: Option<usize>,
+    },
+    #[serde(rename = "enum_entities")]
+    EnumEntities {
+        #[serde(rename = "id")]
+        id: u32,
+    },
+    /// `MEDIA_IOC_ENUM_LINKS` with the lengths of the arrays passed.
+    #[serde(rename = "enum_links")]
+    EnumLinks {
+        #[serde(rename = "entity")]
+        entity: u32,
+        #[serde(rename = "pads")]
+        pads: usize,
+        #[serde(rename = "links")]
+        links: usize,
+    },
+    #[serde(rename = "setup_link")]
+    SetupLink {
+        #[serde(rename = "link")]
+        link: MediaLinkDesc,
+    },
+    #[serde(rename = "request_alloc")]
+    RequestAlloc,
+}
+
+impl TapeRequest {
+    /// The ioctl the request corresponds to.
+    pub fn api(&self) -> libc::c_ulong {
+        use TapeRequest::*;
+        match self {
+            DeviceInfo => media::MEDIA_IOC_DEVICE_INFO,
+            GTopology { .. } => media::MEDIA_IOC_G_TOPOLOGY,
+            EnumEntities { .. } => media::MEDIA_IOC_ENUM_ENTITIES,
+            EnumLinks { .. } => media::MEDIA_IOC_ENUM_LINKS,
+            SetupLink { .. } => media::MEDIA_IOC_SETUP_LINK,
+            RequestAlloc => media::MEDIA_IOC_REQUEST_ALLOC,
+        }
+    }
+}
+
+/// What a successful ioctl returned, as recorded on a tape.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub enum TapeResponse {
+    #[serde(rename = "device_info")]
+    DeviceInfo {
+        #[serde(rename = "info")]
+        info: MediaDeviceInfo,
+    },
+    /// The counts and the objects filled into the arrays requested.
+    #[serde(rename = "g_topology")]
+    GTopology {
+        #[serde(rename = "counts")]
+        counts: MediaTopologyCounts,
+        #[serde(rename = "entities")]
+        entities: Option<Vec<MediaEntity>>,
+        #[serde(rename = "interfaces")]
+        interfaces: Option<Vec<MediaInterface>>,
+        #[serde(rename = "pads")]
+        pads: Option<Vec<MediaPad>>,
+        #[serde(rename = "links")]
+        links: Option<Vec<MediaLink>>,
+    },
+    #[serde(rename = "enum_entities")]
+    EnumEntities {
+        #[serde(rename = "desc")]
+        desc: MediaEntityDesc,
+    },
+    #[serde(rename = "enum_links")]
+    EnumLinks {
+        #[serde(rename = "pads")]
+        pads: Vec<MediaPadDesc>,
+        #[serde(rename = "links")]
+        links: Vec<MediaLinkDesc>,
+    },
+    /// The link as updated by the driver.
+    #[serde(rename = "setup_link")]
+    SetupLink {
+        #[serde(rename = "link")]
+        link: MediaLinkDesc,
+    },
+    #[serde(rename = "request_alloc")]
+    RequestAlloc,
+}
+
+/// A line of a tape: an ioctl and either its response or the errno it failed with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct TapeEntry {
+    #[serde(rename = "request")]
+    pub request: TapeRequest,
+    #[serde(rename = "result")]
+    pub result: std::result::Result<TapeResponse, libc::c_int>,
+}
+
+/// The capabilities of the newest API, under which the objects are converted without losing fields.
+fn capabilities() -> MediaApiCapabilities {
+    MediaApiCapabilities::new(Version::new(u8::MAX, u8::MAX, u8::MAX))
+}
+
+/// Convert the first `count` objects of `array` if it was requested.
+fn record<R: Copy, T>(array: Option<&mut [R]>, count: u32, f: impl Fn(R) -> T) -> Option<Vec<T>> {
+    array.map(|array| array.iter().take(count as usize).copied().map(f).collect())
+}
+
+/// Copy the objects recorded into `array` if it was requested.
+fn replay<T: Clone + Into<R>, R>(array: Option<&mut [R]>, items: Option<&[T]>) {
+    if let (Some(array), Some(items)) = (array, items) {
+        for (dst, src) in array.iter_mut().zip(items) {
+            *dst = src.clone().into();
+        }
+    }
+}
+
+/// A [`MediaBackend`] forwarding every ioctl to another backend and recording it on a tape.
+///
+/// # Details
+/// The tape is a file in JSON Lines, a [`TapeEntry`] a line, written as each ioctl returns.
+/// A session captured on a board is served back by [`ReplayBackend`],
+/// e.g. for reproducing a bug of the board in CI.
+/// Errors not caused by an ioctl are returned without being recorded.
+///
+/// # Examples
+/// ```no_run
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// let device = std::fs::File::open("/dev/media0").unwrap();
+/// let backend = RecordingBackend::create("media0.tape", device)?;
+/// let media = Media::with_backend("/dev/media0", backend)?;
+/// let _topology = media.new_topology()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct RecordingBackend<B> {
+    backend: B,
+    path: PathBuf,
+    tape: Mutex<File>,
+}
+
+impl<B> RecordingBackend<B> {
+    /// Record the ioctls issued to `backend` on the tape created at `path`.
+    pub fn create<P>(path: P, backend: B) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref().to_path_buf();
+        let tape = File::create(&path).map_err(|err| trap_io_error(err, path.clone()))?;
+        Ok(Self {
+            backend,
+            path,
+            tape: Mutex::new(tape),
+        })
+    }
+
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn into_inner(self) -> B {
+        self.backend
+    }
+
+    /// Issue `request` by `f`, which also returns the response to be recorded.
+    ///
+    /// # Details
+    /// The tape is locked during the call, so that the ioctls are recorded in the order issued.
+    fn call<T>(
+        &self,
+        request: TapeRequest,
+        f: impl FnOnce(&B) -> Result<(T, TapeResponse)>,
+    ) -> Result<T> {
+        let mut tape = self.tape.lock().unwrap_or_else(|err| err.into_inner());
+        let (result, recorded) = match f(&self.backend) {
+            Ok((value, response)) => (Ok(value), Ok(response)),
+            Err(err) => match err.errno() {
+                Some(errno) => (Err(err), Err(errno)),
+                None => return Err(err),
+            },
+        };
+        let entry = TapeEntry {
+            request,
+            result: recorded,
+        };
+        let mut line = serde_json::to_string(&entry).map_err(|err| Error::Io {
+            source: err.into(),
+            path: self.path.clone(),
+        })?;
+        line.push('\n');
+        tape.write_all(line.as_bytes())
+            .map_err(|err| trap_io_error(err, self.path.clone()))?;
+        result
+    }
+}
+
+impl<B: MediaBackend> MediaBackend for RecordingBackend<B> {
+    fn device_info(&self) -> Result<media::media_device_info> {
+        self.call(TapeRequest::DeviceInfo, |backend| {
+            let info = backend.device_info()?;
+            let response = TapeResponse::DeviceInfo { info: info.into() };
+            Ok((info, response))
+        })
+    }
+
+    fn g_topology(&self, mut arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts> {
+        let request = TapeRequest::GTopology {
+            entities: arrays.entities.as_ref().map(|xs| xs.len()),
+            interfaces: arrays.interfaces.as_ref().map(|xs| xs.len()),
+            pads: arrays.pads.as_ref().map(|xs| xs.len()),
+            links: arrays.links.as_ref().map(|xs| xs.len()),
+        };
+        self.call(request, |backend| {
+            let counts = backend.g_topology(TopologyArrays {
+                entities: arrays.entities.as_deref_mut(),
+                interfaces: arrays.interfaces.as_deref_mut(),
+                pads: arrays.pads.as_deref_mut(),
+                links: arrays.links.as_deref_mut(),
+            })?;
+            let response = TapeResponse::GTopology {
+                counts,
+                entities: record(arrays.entities, counts.entities, |entity| {
+                    MediaEntity::from_raw(capabilities(), entity)
+                })
+                .map(|entities| entities.into_iter().collect::<Result<_>>())
+                .transpose()?,
+                interfaces: record(
+                    arrays.interfaces,
+                    counts.interfaces,
+                    MediaInterface::from_raw,
+                )
+                .map(|intfs| intfs.into_iter().collect::<Result<_>>())
+                .transpose()?,
+                pads: record(arrays.pads, counts.pads, |pad| {
+                    MediaPad::from_raw(capabilities(), pad)
+                })
+                .map(|pads| pads.into_iter().collect::<Result<_>>())
+                .transpose()?,
+                links: record(arrays.links, counts.links, MediaLink::try_from)
+                    .map(|links| links.into_iter().collect::<Result<_>>())
+                    .transpose()?,
+            };
+            Ok((counts, response))
+        })
+    }
+
+    fn enum_entities(&self, desc: &mut media::media_entity_desc) -> Result<()> {
+        self.call(TapeRequest::EnumEntities { id: desc.id }, |backend| {
+            backend.enum_entities(desc)?;
+            let response = TapeResponse::EnumEntities {
+                desc: (*desc).into(),
+            };
+            Ok(((), response))
+        })
+    }
+
+    unsafe fn enum_links(
+        &self,
+        entity: u32,
+        pads: &mut [media::media_pad_desc],
+        links: &mut [media::media_link_desc],
+    ) -> Result<()> {
+        let request = TapeRequest::EnumLinks {
+            entity,
+            pads: pads.len(),
+            links: links.len(),
+        };
+        self.call(request, |backend| {
+            backend.enum_links(entity, pads, links)?;
+            let response = TapeResponse::EnumLinks {
+                pads: pads.iter().copied().map(MediaPadDesc::from).collect(),
+                links: links.iter().copied().map(MediaLinkDesc::from).collect(),
+            };
+            Ok(((), response))
+        })
+    }
+
+    fn setup_link(&self, link: &mut media::media_link_desc) -> Result<()> {
+        let request = TapeRequest::SetupLink {
+            link: (*link).into(),
+        };
+        self.call(request, |backend| {
+            backend.setup_link(link)?;
+            let response = TapeResponse::SetupLink {
+                link: (*link).into(),
+            };
+            Ok(((), response))
+        })
+    }
+
+    fn request_alloc(&self) -> Result<OwnedFd> {
+        self.call(TapeRequest::RequestAlloc, |backend| {
+            Ok((backend.request_alloc()?, TapeResponse::RequestAlloc))
+        })
+    }
+}
+
+#[derive(Debug)]
+struct Replay {
+    /// The number of the ioctls replayed
+    position: usize,
+    entries: VecDeque<TapeEntry>,
+}
+
+/// A [`MediaBackend`] serving the ioctls recorded on a tape by [`RecordingBackend`].
+///
+/// # Details
+/// The ioctls must be issued in the order recorded, each with the same request.
+/// Otherwise the ioctl fails with [`Error::TapeMismatch`] and the tape is not advanced.
+/// Errors recorded are returned as [`Error::ioctl_error`] constructs them, reporting the file descriptor `-1`.
+/// A request allocated successfully is served as a file descriptor of `/dev/null`.
+///
+/// # Examples
+/// ```no_run
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// let media = Media::with_backend("/dev/media0", ReplayBackend::from_path("media0.tape")?)?;
+/// let _topology = media.new_topology()?;
+/// assert_eq!(media.backend().remaining(), 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ReplayBackend {
+    state: Mutex<Replay>,
+}
+
+impl ReplayBackend {
+    pub fn new<I>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = TapeEntry>,
+    {
+        Self {
+            state: Mutex::new(Replay {
+                position: 0,
+                entries: entries.into_iter().collect(),
+            }),
+        }
+    }
+
+    /// Load the tape at `path`.
+    ///
+    /// # Errors
+    /// [`Error::TapeParseError`] if a line is not a [`TapeEntry`]. Blank lines are skipped.
+    pub fn from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|err| trap_io_error(err, path.to_path_buf()))?;
+        let mut entries = vec![];
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|err| trap_io_error(err, path.to_path_buf()))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).map_err(|err| Error::TapeParseError {
+                path: path.to_path_buf(),
+                line: index + 1,
+                reason: err.to_string(),
+            })?;
+            entries.push(entry);
+        }
+        Ok(Self::new(entries))
+    }
+
+    /// The number of the ioctls left on the tape.
+    pub fn remaining(&self) -> usize {
+        self.state().entries.len()
+    }
+
+    fn state(&self) -> MutexGuard<'_, Replay> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Serve `request` with the next entry of the tape, whose response is converted by `f`.
+    fn replay<T>(
+        &self,
+        request: TapeRequest,
+        f: impl FnOnce(&TapeResponse) -> Option<T>,
+    ) -> Result<T> {
+        let mut state = self.state();
+        let position = state.position;
+        let mismatch = |expected: String| Error::TapeMismatch {
+            position,
+            expected,
+            found: format!("{:?}", request),
+        };
+        let entry = match state.entries.front() {
+            None => return Err(mismatch("the end of the tape".to_string())),
+            Some(entry) if entry.request != request => {
+                return Err(mismatch(format!("{:?}", entry.request)))
+            }
+            Some(entry) => entry,
+        };
+        let result = match &entry.result {
+            Ok(response) => Ok(f(response).ok_or_else(|| mismatch(format!("{:?}", response)))?),
+            Err(errno) => Err(Error::ioctl_error(-1, *errno, request.api())),
+        };
+        state.entries.pop_front();
+        state.position += 1;
+        result
+    }
+}
+
+impl MediaBackend for ReplayBackend {
+    fn device_info(&self) -> Result<media::media_device_info> {
+        self.replay(TapeRequest::DeviceInfo, |response| match response {
+            TapeResponse::DeviceInfo { info } => Some(info.clone().into()),
+            _ => None,
+        })
+    }
+
+    fn g_topology(&self, arrays: TopologyArrays<'_>) -> Result<MediaTopologyCounts> {
+        let request = TapeRequest::GTopology {
+            entities: arrays.entities.as_ref().map(|xs| xs.len()),
+            interfaces: arrays.interfaces.as_ref().map(|xs| xs.len()),
+            pads: arrays.pads.as_ref().map(|xs| xs.len()),
+            links: arrays.links.as_ref().map(|xs| xs.len()),
+        };
+        self.replay(request, |response| match response {
+            TapeResponse::GTopology {
+                counts,
+                entities,
+                interfaces,
+                pads,
+                links,
+            } => {
+                replay(arrays.entities, entities.as_deref());
+                replay(arrays.interfaces, interfaces.as_deref());
+                replay(arrays.pads, pads.as_deref());
+                replay(arrays.links, links.as_deref());
+                Some(*counts)
+            }
+            _ => None,
+        })
+    }
+
+    fn enum_entities(&self, desc: &mut media::media_entity_desc) -> Result<()> {
+        self.replay(
+            TapeRequest::EnumEntities { id: desc.id },
+            |response| match response {
+                TapeResponse::EnumEntities { desc: recorded } => {
+                    *desc = recorded.clone().into();
+                    Some(())
+                }
+                _ => None,
+            },
+        )
+    }
+
+    unsafe fn enum_links(
+        &self,
+        entity: u32,
+        pads: &mut [media::media_pad_desc],
+        links: &mut [media::media_link_desc],
+    ) -> Result<()> {
+        let request = TapeRequest::EnumLinks {
+            entity,
+            pads: pads.len(),
+            links: links.len(),
+        };
+        self.replay(request, |response| match response {
+            TapeResponse::EnumLinks {
+                pads: recorded_pads,
+                links: recorded_links,
+            } => {
+                replay(Some(pads), Some(recorded_pads));
+                replay(Some(links), Some(recorded_links));
+                Some(())
+            }
+            _ => None,
+        })
+    }
+
+    fn setup_link(&self, link: &mut media::media_link_desc) -> Result<()> {
+        let request = TapeRequest::SetupLink {
+            link: (*link).into(),
+        };
+        self.replay(request, |response| match response {
+            TapeResponse::SetupLink { link: recorded } => {
+                *link = (*recorded).into();
+                Some(())
+            }
+            _ => None,
+        })?;
+        Ok(())
+    }
+
+    fn request_alloc(&self) -> Result<OwnedFd> {
+        self.replay(TapeRequest::RequestAlloc, |response| {
+            matches!(response, TapeResponse::RequestAlloc).then_some(())
+        })?;
+        let path = Path::new("/dev/null");
+        let file = File::open(path).map_err(|err| trap_io_error(err, path.to_path_buf()))?;
+        Ok(file.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::media_topology::test::{entity, link, pad};
+    use crate::test_util::TempDir;
+    use crate::{LinkSpec, Media, MediaTopology, MockBackend, MockOp};
+
+    fn mock() -> MockBackend {
+        let topology = MediaTopology::new(
+            None,
+            1,
+            Some(vec![entity(1, "sensor"), entity(3, "csi")]),
+            Some(vec![]),
+            Some(vec![
+                pad(2, 1, media::MEDIA_PAD_FL_SOURCE),
+                pad(4, 3, media::MEDIA_PAD_FL_SINK),
+            ]),
+            Some(vec![link(5, 2, 4, 0)]),
+        );
+        let info = MediaDeviceInfo {
+            driver: "mock".to_string(),
+            model: "mock".to_string(),
+            serial: String::new(),
+            bus_info: "platform:mock".to_string(),
+            media_version: Version::new(6, 1, 0),
+            hw_revision: 0.into(),
+            driver_version: Version::new(6, 1, 0),
+        };
+        MockBackend::new(info, topology)
+    }
+
+    #[test]
+    fn replays_recorded_session() {
+        let tmp = TempDir::new("tape");
+        let path = tmp.path().join("session.tape");
+        let specs = LinkSpec::parse_list("\"sensor\":0->\"csi\":0[1]").unwrap();
+
+        let backend = RecordingBackend::create(&path, mock()).unwrap();
+        backend
+            .backend()
+            .script(MockOp::SetupLink, [Some(libc::EBUSY)]);
+        let media = Media::with_backend("/dev/media-mock", backend).unwrap();
+        let topology = media.new_topology().unwrap();
+        let enums = media.links_enums().unwrap();
+        assert!(matches!(
+            media.setup_links(&specs),
+            Err(Error::DeviceIsBusy { .. })
+        ));
+        media.setup_links(&specs).unwrap();
+        drop(media);
+
+        let media =
+            Media::with_backend("/dev/media-mock", ReplayBackend::from_path(&path).unwrap())
+                .unwrap();
+        assert_eq!(media.info().driver(), "mock");
+        assert_eq!(media.new_topology().unwrap(), topology);
+        assert_eq!(media.links_enums().unwrap(), enums);
+        assert!(matches!(
+            media.setup_links(&specs),
+            Err(Error::DeviceIsBusy { .. })
+        ));
+        media.setup_links(&specs).unwrap();
+        assert_eq!(media.backend().remaining(), 0);
+        assert!(matches!(
+            media.topology_counts(),
+            Err(Error::TapeMismatch { position, .. }) if position > 0
+        ));
+    }
+}
@@ -0,0 +1,198 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::{Media, MediaDeviceEnumerator, MediaDeviceFilter, MediaDeviceNode, PipelineConfig};
+
+/// The environment variable permitting [`TestHarness`] to load kernel modules, when set to `1`.
+pub const LOAD_MODULES_ENV: &str = "LINUX_MEDIA_LOAD_MODULES";
+
+/// How long to wait for the media devices of a module just loaded.
+const DEVICE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serializes the tests using the devices, since cargo runs tests in parallel.
+static DEVICES: Mutex<()> = Mutex::new(());
+
+/// A virtual media driver of the kernel, whose devices are available on machines without hardware.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub enum VirtualDriver {
+    /// The virtual media controller driver, emulating a camera pipeline of sensors, debayers and a scaler
+    Vimc,
+    /// The virtual video test driver, emulating capture and output devices
+    Vivid,
+}
+
+impl VirtualDriver {
+    /// The name of the kernel module.
+    pub fn module(&self) -> &'static str {
+        match self {
+            VirtualDriver::Vimc => "vimc",
+            VirtualDriver::Vivid => "vivid",
+        }
+    }
+
+    /// The driver name reported by `MEDIA_IOC_DEVICE_INFO`.
+    pub fn driver(&self) -> &'static str {
+        self.module()
+    }
+
+    /// Whether the module is loaded, or built in the kernel.
+    pub fn is_loaded(&self) -> bool {
+        Path::new("/sys/module").join(self.module()).exists()
+    }
+}
+
+/// Whether the value of [`LOAD_MODULES_ENV`] permits loading modules.
+fn permits_loading(value: Option<&str>) -> bool {
+    value.map(str::trim) == Some("1")
+}
+
+/// Run `modprobe` with `args`.
+fn modprobe(args: &[&str]) -> Result<()> {
+    let modprobe = PathBuf::from("modprobe");
+    let status = Command::new(&modprobe)
+        .args(args)
+        .status()
+        .map_err(|err| crate::error::trap_io_error(err, modprobe.clone()))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Io {
+            source: io::Error::new(
+                io::ErrorKind::Other,
+                format!("modprobe {} failed: {}", args.join(" "), status),
+            ),
+            path: modprobe,
+        })
+    }
+}
+
+/// Guarded access to the media devices of a virtual driver, for integration tests issuing real ioctls.
+///
+/// # Details
+/// [`setup`][TestHarness::setup] locates the devices of the driver,
+/// loading its module first if permitted by setting [`LOAD_MODULES_ENV`] to `1`.
+/// When dropped, the links of the devices are restored to the state at the setup,
+/// and the module is unloaded if it was loaded by the harness.
+///
+/// Only a harness exists at a time in a process, so that tests changing links do not interfere each other.
+///
+/// # Examples
+/// ```
+/// use linux_media::*;
+/// # fn main () -> error::Result<()> {
+/// let Some(harness) = TestHarness::setup(VirtualDriver::Vimc)? else {
+///     // vimc is not available: skip the test
+///     return Ok(());
+/// };
+/// let media = harness.open()?;
+/// assert_eq!(media.info().driver(), "vimc");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TestHarness {
+    driver: VirtualDriver,
+    /// Whether the module was loaded by the harness
+    loaded: bool,
+    devices: Vec<MediaDeviceNode>,
+    /// The link configurations to be restored
+    saved: Vec<(MediaDeviceNode, PipelineConfig)>,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestHarness {
+    /// Locate the devices of `driver`, waiting for any other harness to be dropped.
+    ///
+    /// # Returns
+    /// `None` if the driver has no device and loading its module is not permitted,
+    /// with which the test is expected to be skipped.
+    ///
+    /// # Errors
+    /// Loading the module or capturing the links of the devices failed.
+    pub fn setup(driver: VirtualDriver) -> Result<Option<Self>> {
+        let lock = DEVICES.lock().unwrap_or_else(|err| err.into_inner());
+        let mut harness = Self {
+            driver,
+            loaded: false,
+            devices: vec![],
+            saved: vec![],
+            _lock: lock,
+        };
+        if !driver.is_loaded() {
+            if !permits_loading(std::env::var(LOAD_MODULES_ENV).ok().as_deref()) {
+                return Ok(None);
+            }
+            modprobe(&[driver.module()])?;
+            harness.loaded = true;
+        }
+        let deadline = Instant::now() + DEVICE_TIMEOUT;
+        harness.devices = loop {
+            let devices = harness.find_devices()?;
+            if !devices.is_empty() || !harness.loaded || Instant::now() >= deadline {
+                break devices;
+            }
+            thread::sleep(Duration::from_millis(100));
+        };
+        if harness.devices.is_empty() {
+            return Ok(None);
+        }
+        for node in &harness.devices {
+            let config = PipelineConfig::from_media(&node.open()?)?;
+            harness.saved.push((node.clone(), config));
+        }
+        Ok(Some(harness))
+    }
+
+    fn find_devices(&self) -> Result<Vec<MediaDeviceNode>> {
+        let mut filter = MediaDeviceFilter::new();
+        filter.driver(self.driver.driver());
+        Ok(MediaDeviceEnumerator::new()?.filter_by(filter).collect())
+    }
+
+    pub fn driver(&self) -> VirtualDriver {
+        self.driver
+    }
+
+    /// The devices of the driver, in ascending order of their indices.
+    pub fn devices(&self) -> &[MediaDeviceNode] {
+        &self.devices
+    }
+
+    /// Open the first device of the driver.
+    pub fn open(&self) -> Result<Media> {
+        self.devices[0].open()
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        // errors are ignored since the devices may have gone with the module
+        for (node, config) in &self.saved {
+            if let Ok(media) = node.open() {
+                let _ = config.apply(&media);
+            }
+        }
+        if self.loaded {
+            let _ = modprobe(&["-r", self.driver.module()]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn loading_is_opt_in() {
+        assert!(permits_loading(Some("1")));
+        assert!(permits_loading(Some("1\n")));
+        assert!(!permits_loading(Some("0")));
+        assert!(!permits_loading(Some("")));
+        assert!(!permits_loading(None));
+    }
+}
@@ -0,0 +1,258 @@
+//! Utilities for tests of code built on this crate.
+
+/// Assertions on topologies, whose failure messages refer to entities by name instead of dumping ids.
+///
+/// # Details
+/// Pads are given as pairs of the name of the entity and the index of the pad, `("csi", 1)` for `"csi":1`.
+/// Every assertion panics at the caller, listing the objects related to the failure in the syntax of `media-ctl`.
+///
+/// # Examples
+/// ```
+/// use linux_media::test_util::assert::*;
+/// use linux_media::*;
+///
+/// # fn check(topology: &MediaTopology) {
+/// assert_entity_exists(topology, "unicam-image");
+/// assert_link_enabled(topology, ("imx219 10-0010", 0), ("unicam-image", 0));
+/// assert_pipeline(topology, &["imx219 10-0010", "unicam-image"]);
+/// # }
+/// ```
+pub mod assert {
+    use crate::{
+        EntityId, EntitySpec, LinkSpec, MediaEntity, MediaLinkDesc, MediaLinkFlags, MediaPadDesc,
+        MediaTopology, PadSpec,
+    };
+
+    fn find_entity<'a>(topology: &'a MediaTopology, name: &str) -> Option<&'a MediaEntity> {
+        topology
+            .entities_slice()
+            .iter()
+            .find(|entity| entity.name() == name)
+    }
+
+    fn pad_spec(topology: &MediaTopology, id: EntityId, index: usize) -> PadSpec {
+        let entity = topology
+            .entities_slice()
+            .iter()
+            .find(|entity| entity.id() == id)
+            .map_or(EntitySpec::Id(id), |entity| {
+                EntitySpec::Name(entity.name().to_string())
+            });
+        PadSpec { entity, index }
+    }
+
+    /// Describe `desc` as `"source":0->"sink":0[1]`.
+    fn describe(topology: &MediaTopology, desc: &MediaLinkDesc) -> String {
+        let pad = |pad: MediaPadDesc| pad_spec(topology, pad.id(), pad.index());
+        LinkSpec {
+            source: pad(desc.source()),
+            sink: pad(desc.sink()),
+            flags: desc.flags(),
+        }
+        .to_string()
+    }
+
+    /// The data links from or to `entity`, described one per line.
+    fn links_of(topology: &MediaTopology, entity: EntityId) -> String {
+        let links: Vec<String> = topology
+            .links_slice()
+            .iter()
+            .filter_map(|link| topology.link_desc(link))
+            .filter(|desc| desc.source().id() == entity || desc.sink().id() == entity)
+            .map(|desc| format!("\n    {}", describe(topology, &desc)))
+            .collect();
+        if links.is_empty() {
+            " none".to_string()
+        } else {
+            links.concat()
+        }
+    }
+
+    #[track_caller]
+    fn entity<'a>(topology: &'a MediaTopology, name: &str) -> &'a MediaEntity {
+        find_entity(topology, name).unwrap_or_else(|| {
+            let names: Vec<&str> = topology
+                .entities_slice()
+                .iter()
+                .map(|entity| entity.name())
+                .collect();
+            panic!("no entity named {:?}, the entities are {:?}", name, names)
+        })
+    }
+
+    /// Find the data link from `source` to `sink`.
+    #[track_caller]
+    fn link(topology: &MediaTopology, source: (&str, usize), sink: (&str, usize)) -> MediaLinkDesc {
+        let from = entity(topology, source.0);
+        entity(topology, sink.0);
+        let spec = LinkSpec {
+            source: PadSpec {
+                entity: EntitySpec::Name(source.0.to_string()),
+                index: source.1,
+            },
+            sink: PadSpec {
+                entity: EntitySpec::Name(sink.0.to_string()),
+                index: sink.1,
+            },
+            flags: MediaLinkFlags::empty(),
+        };
+        spec.resolve(topology).unwrap_or_else(|_| {
+            panic!(
+                "no link {}->{}, the links of {:?} are:{}",
+                spec.source,
+                spec.sink,
+                source.0,
+                links_of(topology, from.id())
+            )
+        })
+    }
+
+    /// Assert that `topology` has an entity named `name`.
+    #[track_caller]
+    pub fn assert_entity_exists(topology: &MediaTopology, name: &str) {
+        entity(topology, name);
+    }
+
+    /// Assert that `topology` has no entity named `name`.
+    #[track_caller]
+    pub fn assert_entity_absent(topology: &MediaTopology, name: &str) {
+        if let Some(entity) = find_entity(topology, name) {
+            panic!(
+                "unexpected entity {:?} of the id {}",
+                name,
+                u32::from(entity.id())
+            );
+        }
+    }
+
+    /// Assert that the data link from `source` to `sink` exists and is enabled.
+    #[track_caller]
+    pub fn assert_link_enabled(
+        topology: &MediaTopology,
+        source: (&str, usize),
+        sink: (&str, usize),
+    ) {
+        let desc = link(topology, source, sink);
+        if !desc.flags().contains(MediaLinkFlags::Enabled) {
+            panic!("the link {} is disabled", describe(topology, &desc));
+        }
+    }
+
+    /// Assert that the data link from `source` to `sink` exists and is disabled.
+    #[track_caller]
+    pub fn assert_link_disabled(
+        topology: &MediaTopology,
+        source: (&str, usize),
+        sink: (&str, usize),
+    ) {
+        let desc = link(topology, source, sink);
+        if desc.flags().contains(MediaLinkFlags::Enabled) {
+            panic!("the link {} is enabled", describe(topology, &desc));
+        }
+    }
+
+    /// Assert that `entities` are connected in the order by enabled data links, e.g. a sensor to a DMA engine.
+    ///
+    /// # Details
+    /// Any pads may be connected, and links to other entities are not checked.
+    #[track_caller]
+    pub fn assert_pipeline(topology: &MediaTopology, entities: &[&str]) {
+        for pair in entities.windows(2) {
+            let source = entity(topology, pair[0]);
+            let sink = entity(topology, pair[1]);
+            let connected = topology
+                .links_slice()
+                .iter()
+                .filter_map(|link| topology.link_desc(link))
+                .any(|desc| {
+                    desc.source().id() == source.id()
+                        && desc.sink().id() == sink.id()
+                        && desc.flags().contains(MediaLinkFlags::Enabled)
+                });
+            if !connected {
+                panic!(
+                    "the pipeline {:?} is broken: no enabled link from {:?} to {:?}, the links of {:?} are:{}",
+                    entities,
+                    pair[0],
+                    pair[1],
+                    pair[0],
+                    links_of(topology, source.id())
+                );
+            }
+        }
+    }
+}
+
+/// A directory under the temporary directory of the system, removed with its contents when dropped,
+/// so that the files of a test are cleaned up even if it panics.
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct TempDir(std::path::PathBuf);
+
+#[cfg(test)]
+impl TempDir {
+    /// Create an empty directory named after `name`, unique to the process and the call, such like `linux-media-enum-1234-0`.
+    pub(crate) fn new(name: &str) -> Self {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "linux-media-{}-{}-{}",
+            name,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        // left by a process of the same id which did not finish
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    pub(crate) fn path(&self) -> &std::path::Path {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::assert::*;
+    use crate::mock_backend::test::mock;
+    use crate::MediaLinkFlags;
+
+    fn message(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+        let err = std::panic::catch_unwind(f).unwrap_err();
+        err.downcast::<String>().map(|s| *s).unwrap_or_default()
+    }
+
+    #[test]
+    fn failures_name_entities() {
+        let mut topology = mock().topology();
+        topology.set_link_flags(8.into(), MediaLinkFlags::Enabled);
+        assert_entity_exists(&topology, "csi");
+        assert_entity_absent(&topology, "isp");
+        assert_link_enabled(&topology, ("sensor", 0), ("csi", 0));
+        assert_link_disabled(&topology, ("csi", 1), ("dma", 0));
+        assert_pipeline(&topology, &["sensor", "csi"]);
+
+        let msg = message(|| assert_entity_exists(&topology, "isp"));
+        assert_eq!(
+            msg,
+            "no entity named \"isp\", the entities are [\"sensor\", \"csi\", \"dma\"]"
+        );
+        let msg = message(|| assert_link_enabled(&topology, ("csi", 1), ("dma", 0)));
+        assert_eq!(msg, "the link \"csi\":1->\"dma\":0[0] is disabled");
+        let msg = message(|| assert_link_enabled(&topology, ("csi", 0), ("dma", 0)));
+        assert_eq!(
+            msg,
+            "no link \"csi\":0->\"dma\":0, the links of \"csi\" are:\n    \"sensor\":0->\"csi\":0[1]\n    \"csi\":1->\"dma\":0[0]"
+        );
+        let msg = message(|| assert_pipeline(&topology, &["sensor", "csi", "dma"]));
+        assert!(msg.starts_with("the pipeline [\"sensor\", \"csi\", \"dma\"] is broken: no enabled link from \"csi\" to \"dma\""), "{}", msg);
+    }
+}
@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{self, Result};
+use crate::{Media, MediaBackend, MediaIntfDevnode};
+
+/// The directory where udev keeps the properties of devices.
+pub const UDEV_DATA: &str = "/run/udev/data";
+
+/// The sysfs directory listing character devices by their device numbers.
+const SYSFS_DEV_CHAR: &str = "/sys/dev/char";
+
+/// A device seen by udev, with the properties udev recorded for it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct UdevDevice {
+    /// The subsystem such like `media`, `usb` or `pci`.
+    #[serde(rename = "subsystem")]
+    subsystem: String,
+    /// The sysfs directory of the device.
+    #[serde(rename = "sysfs_path")]
+    sysfs_path: PathBuf,
+    /// The properties such like `ID_PATH`, empty if udev has not recorded the device.
+    #[serde(rename = "properties")]
+    properties: BTreeMap<String, String>,
+    /// The sysfs attributes identifying the device, such like `idVendor` of USB devices or `vendor` of PCI functions.
+    #[serde(rename = "attributes")]
+    attributes: BTreeMap<String, String>,
+}
+
+/// The sysfs attributes read for the parents of media devices.
+const USB_ATTRIBUTES: &[&str] = &[
+    "idVendor",
+    "idProduct",
+    "manufacturer",
+    "product",
+    "serial",
+    "busnum",
+    "devpath",
+];
+const PCI_ATTRIBUTES: &[&str] = &["vendor", "device", "subsystem_vendor", "subsystem_device"];
+
+impl UdevDevice {
+    /// Read the device of the sysfs directory `sysfs_path`,
+    /// with its properties from the udev database in the directory `udev_data`.
+    fn read(sysfs_path: &Path, udev_data: &Path, attributes: &[&str]) -> Self {
+        let subsystem = fs::read_link(sysfs_path.join("subsystem"))
+            .ok()
+            .and_then(|link| Some(link.file_name()?.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+        let properties = db_name(sysfs_path, &subsystem)
+            .and_then(|name| fs::read_to_string(udev_data.join(name)).ok())
+            .map(|db| parse_db(&db))
+            .unwrap_or_default();
+        let attributes = attributes
+            .iter()
+            .filter_map(|name| {
+                let value = fs::read_to_string(sysfs_path.join(name)).ok()?;
+                Some((name.to_string(), value.trim_end().to_string()))
+            })
+            .collect();
+        Self {
+            subsystem,
+            sysfs_path: sysfs_path.to_path_buf(),
+            properties,
+            attributes,
+        }
+    }
+
+    pub fn subsystem(&self) -> &str {
+        &self.subsystem
+    }
+
+    pub fn sysfs_path(&self) -> &Path {
+        &self.sysfs_path
+    }
+
+    pub fn properties(&self) -> &BTreeMap<String, String> {
+        &self.properties
+    }
+
+    /// The property `key`.
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    pub fn attributes(&self) -> &BTreeMap<String, String> {
+        &self.attributes
+    }
+
+    /// The sysfs attribute `name`.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).map(String::as_str)
+    }
+}
+
+/// The name of the file of the device in the udev database,
+/// `c<major>:<minor>` for character devices or `+<subsystem>:<sysname>` for the others.
+fn db_name(sysfs_path: &Path, subsystem: &str) -> Option<String> {
+    match fs::read_to_string(sysfs_path.join("dev")) {
+        Ok(dev) => Some(format!("c{}", dev.trim())),
+        Err(_) => Some(format!(
+            "+{}:{}",
+            subsystem,
+            sysfs_path.file_name()?.to_string_lossy()
+        )),
+    }
+}
+
+/// Parse the properties `E:KEY=VALUE` of a file of the udev database.
+fn parse_db(db: &str) -> BTreeMap<String, String> {
+    db.lines()
+        .filter_map(|line| line.strip_prefix("E:")?.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// The properties of a media device recorded by udev, and of the USB device and the PCI function it belongs to.
+///
+/// # Details
+/// Identical devices, such as two capture cards of the same model, report the same [`MediaDeviceInfo`][crate::MediaDeviceInfo]
+/// except for the bus info. udev tells them apart by the port they are plugged into (`ID_PATH`) and their serial numbers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct UdevProperties {
+    #[serde(rename = "device")]
+    device: UdevDevice,
+    #[serde(rename = "usb")]
+    usb: Option<UdevDevice>,
+    #[serde(rename = "pci")]
+    pci: Option<UdevDevice>,
+}
+
+impl UdevProperties {
+    /// Read the properties of the character device `major:minor`,
+    /// walking the sysfs directory `dev_char` and the udev database `udev_data`.
+    fn read(dev_char: &Path, udev_data: &Path, major: u32, minor: u32) -> Result<Self> {
+        let link = dev_char.join(format!("{}:{}", major, minor));
+        let sysfs_path = fs::canonicalize(&link).map_err(|err| error::trap_io_error(err, link))?;
+        let mut properties = Self {
+            device: UdevDevice::read(&sysfs_path, udev_data, &[]),
+            usb: None,
+            pci: None,
+        };
+        for parent in sysfs_path.ancestors().skip(1) {
+            let Ok(uevent) = fs::read_to_string(parent.join("uevent")) else {
+                continue;
+            };
+            let devtype = uevent
+                .lines()
+                .find_map(|line| line.strip_prefix("DEVTYPE="));
+            if properties.usb.is_none() && devtype == Some("usb_device") {
+                properties.usb = Some(UdevDevice::read(parent, udev_data, USB_ATTRIBUTES));
+            } else if properties.pci.is_none()
+                && uevent
+                    .lines()
+                    .any(|line| line.starts_with("PCI_SLOT_NAME="))
+            {
+                properties.pci = Some(UdevDevice::read(parent, udev_data, PCI_ATTRIBUTES));
+            }
+        }
+        Ok(properties)
+    }
+
+    /// The media device itself.
+    pub fn device(&self) -> &UdevDevice {
+        &self.device
+    }
+
+    /// The USB device the media device belongs to, if any.
+    pub fn usb(&self) -> Option<&UdevDevice> {
+        self.usb.as_ref()
+    }
+
+    /// The PCI function the media device belongs to, if any.
+    ///
+    /// # Details
+    /// USB devices belong to the PCI function of their host controller.
+    pub fn pci(&self) -> Option<&UdevDevice> {
+        self.pci.as_ref()
+    }
+
+    /// The property `key` of the media device.
+    pub fn property(&self, key: &str) -> Option<&str> {
+        self.device.property(key)
+    }
+
+    /// `ID_PATH`, which identifies the port the device is connected to.
+    pub fn id_path(&self) -> Option<&str> {
+        self.property("ID_PATH")
+    }
+
+    /// `ID_V4L_PRODUCT`, the product name reported by the V4L driver.
+    pub fn v4l_product(&self) -> Option<&str> {
+        self.property("ID_V4L_PRODUCT")
+    }
+
+    /// `ID_SERIAL`, which is the vendor, the model and the serial number for USB devices.
+    pub fn serial(&self) -> Option<&str> {
+        self.property("ID_SERIAL")
+            .or_else(|| self.usb.as_ref()?.property("ID_SERIAL"))
+    }
+}
+
+impl<B> Media<B>
+where
+    B: MediaBackend,
+{
+    /// Read the properties udev recorded for the device file of the media device.
+    ///
+    /// # Details
+    /// The properties are read from the udev database in [`/run/udev/data`][UDEV_DATA] without linking libudev,
+    /// and are empty if udev is not running.
+    ///
+    /// # Errors
+    /// The [`path`][Media::path] of the device is not a character device, e.g. if the device was opened with a mock backend.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::*;
+    /// # fn main () -> error::Result<()> {
+    /// if let Ok(media) = Media::from_path("/dev/media0") {
+    ///     let udev = media.udev_properties()?;
+    ///     println!("{:?} {:?}", udev.id_path(), udev.serial());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn udev_properties(&self) -> Result<UdevProperties> {
+        let devnode = MediaIntfDevnode::from_char_device(self.path())?;
+        UdevProperties::read(
+            Path::new(SYSFS_DEV_CHAR),
+            Path::new(UDEV_DATA),
+            devnode.major,
+            devnode.minor,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn reads_device_and_usb_parent() {
+        let tmp = TempDir::new("udev");
+        let root = tmp.path();
+        let pci = root.join("devices/pci0000:00/0000:00:14.0");
+        let usb = pci.join("usb1/1-2");
+        let media = usb.join("1-2:1.0/media/media0");
+        fs::create_dir_all(&media).unwrap();
+        fs::create_dir_all(root.join("bus/media")).unwrap();
+        fs::create_dir_all(root.join("bus/usb")).unwrap();
+        fs::create_dir_all(root.join("bus/pci")).unwrap();
+        fs::create_dir_all(root.join("dev/char")).unwrap();
+        fs::create_dir_all(root.join("data")).unwrap();
+        symlink(root.join("bus/media"), media.join("subsystem")).unwrap();
+        symlink(root.join("bus/usb"), usb.join("subsystem")).unwrap();
+        symlink(root.join("bus/pci"), pci.join("subsystem")).unwrap();
+        symlink(&media, root.join("dev/char/239:0")).unwrap();
+        fs::write(media.join("uevent"), "MAJOR=239\nMINOR=0\nDEVNAME=media0\n").unwrap();
+        fs::write(media.join("dev"), "239:0\n").unwrap();
+        fs::write(usb.join("uevent"), "DEVTYPE=usb_device\n").unwrap();
+        fs::write(usb.join("dev"), "189:1\n").unwrap();
+        fs::write(usb.join("idVendor"), "046d\n").unwrap();
+        fs::write(usb.join("serial"), "A1B2C3\n").unwrap();
+        fs::write(pci.join("uevent"), "PCI_SLOT_NAME=0000:00:14.0\n").unwrap();
+        fs::write(pci.join("vendor"), "0x8086\n").unwrap();
+        fs::write(
+            root.join("data/c239:0"),
+            "S:media-by-path\nE:ID_PATH=pci-0000:00:14.0-usb-0:2:1.0\nE:ID_V4L_PRODUCT=HD Pro Webcam C920\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("data/c189:1"),
+            "E:ID_SERIAL=046d_HD_Pro_Webcam_C920_A1B2C3\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("data/+pci:0000:00:14.0"),
+            "E:ID_PCI_CLASS_FROM_DATABASE=Serial bus controller\n",
+        )
+        .unwrap();
+
+        let udev =
+            UdevProperties::read(&root.join("dev/char"), &root.join("data"), 239, 0).unwrap();
+
+        assert_eq!(udev.device().subsystem(), "media");
+        assert_eq!(udev.id_path(), Some("pci-0000:00:14.0-usb-0:2:1.0"));
+        assert_eq!(udev.v4l_product(), Some("HD Pro Webcam C920"));
+        assert_eq!(udev.serial(), Some("046d_HD_Pro_Webcam_C920_A1B2C3"));
+        let usb = udev.usb().unwrap();
+        assert_eq!(usb.subsystem(), "usb");
+        assert_eq!(usb.attribute("idVendor"), Some("046d"));
+        assert_eq!(usb.attribute("serial"), Some("A1B2C3"));
+        let pci = udev.pci().unwrap();
+        assert_eq!(pci.attribute("vendor"), Some("0x8086"));
+        assert_eq!(
+            pci.property("ID_PCI_CLASS_FROM_DATABASE"),
+            Some("Serial bus controller")
+        );
+    }
+}
@@ -1,12 +1,24 @@
 use std::fmt;
+use std::str::FromStr;
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::error;
 
 /// Version information wrapper formatted with `KERNEL_VERSION` macro.
-#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize, Deserialize)]
+///
+/// # Details
+/// Serialized as `{ "major": 6, "minor": 6, "patch": 20 }`.
+/// The string form `"6.6.20"` is also accepted by deserializers of human readable formats,
+/// and fields can be serialized in the string form with [`Version::serialize_str`].
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Hash, Serialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Version {
+    #[serde(rename = "major")]
     pub major: u8,
+    #[serde(rename = "minor")]
     pub minor: u8,
+    #[serde(rename = "patch")]
     pub patch: u8,
 }
 
@@ -15,25 +27,13 @@ impl From<u32> for Version {
     ///
     /// 17..24th bits, 9..16th bits, 0..8th bits represents major, minor and patch version respectively.
     fn from(ver: u32) -> Self {
-        let major = ((ver & (0xFF << 16)) >> 16) as u8;
-        let minor = ((ver & (0xFF << 8)) >> 8) as u8;
-        let patch = ((ver & (0xFF << 0)) >> 0) as u8;
-        Self {
-            major,
-            minor,
-            patch,
-        }
+        Self::from_kernel(ver)
     }
 }
 
 impl Into<u32> for Version {
     fn into(self: Version) -> u32 {
-        let Self {
-            major,
-            minor,
-            patch,
-        } = self;
-        ((major as u32) << 16) | ((minor as u32) << 8) | (patch as u32)
+        self.to_kernel()
     }
 }
 
@@ -45,12 +45,143 @@ impl fmt::Display for Version {
     }
 }
 
+impl FromStr for Version {
+    type Err = error::Error;
+
+    /// Parse a version formatted as `"{major}.{minor}.{patch}"`, such like `"6.6.20"`.
+    fn from_str(s: &str) -> error::Result<Self> {
+        let mut components = s.split('.').map(|component| component.parse::<u8>());
+        match (
+            components.next(),
+            components.next(),
+            components.next(),
+            components.next(),
+        ) {
+            (Some(Ok(major)), Some(Ok(minor)), Some(Ok(patch)), None) => {
+                Ok(Self::new(major, minor, patch))
+            }
+            _ => Err(error::Error::VersionParseError {
+                from: s.to_string(),
+            }),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename = "Version")]
+        struct Fields {
+            #[serde(rename = "major")]
+            major: u8,
+            #[serde(rename = "minor")]
+            minor: u8,
+            #[serde(rename = "patch")]
+            patch: u8,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Fields(Fields),
+            String(String),
+        }
+
+        let Fields {
+            major,
+            minor,
+            patch,
+        } = if deserializer.is_human_readable() {
+            match Repr::deserialize(deserializer)? {
+                Repr::Fields(fields) => fields,
+                Repr::String(s) => return s.parse().map_err(serde::de::Error::custom),
+            }
+        } else {
+            Fields::deserialize(deserializer)?
+        };
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
 impl Version {
-    pub fn new(major: u8, minor: u8, patch: u8) -> Self {
+    pub const fn new(major: u8, minor: u8, patch: u8) -> Self {
         Self {
             major,
             minor,
             patch,
         }
     }
+
+    /// Decode a version encoded with the `KERNEL_VERSION` macro, such like `media_version` of `media_device_info`.
+    pub const fn from_kernel(ver: u32) -> Self {
+        Self::new((ver >> 16) as u8, (ver >> 8) as u8, ver as u8)
+    }
+
+    /// Encode this version as the `KERNEL_VERSION` macro does.
+    pub const fn to_kernel(&self) -> u32 {
+        ((self.major as u32) << 16) | ((self.minor as u32) << 8) | (self.patch as u32)
+    }
+
+    /// Whether this version is `major.minor.patch` or later.
+    ///
+    /// # Examples
+    /// ```
+    /// use linux_media::Version;
+    ///
+    /// assert!(Version::new(6, 6, 20).at_least(6, 1, 0));
+    /// assert!(!Version::new(4, 19, 0).at_least(5, 0, 0));
+    /// ```
+    pub const fn at_least(&self, major: u8, minor: u8, patch: u8) -> bool {
+        self.to_kernel() >= Self::new(major, minor, patch).to_kernel()
+    }
+
+    /// Whether this version is earlier than `major.minor.patch`.
+    pub const fn older_than(&self, major: u8, minor: u8, patch: u8) -> bool {
+        !self.at_least(major, minor, patch)
+    }
+
+    /// Serialize in the string form such like `"6.6.20"`, with `#[serde(serialize_with = "Version::serialize_str")]`.
+    pub fn serialize_str<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn versions_parse_from_both_forms() {
+        let version = Version::new(6, 6, 20);
+        assert_eq!(Version::from_kernel(version.to_kernel()), version);
+        assert_eq!("6.6.20".parse::<Version>().unwrap(), version);
+        assert!("6.6".parse::<Version>().is_err());
+        assert!("6.6.256".parse::<Version>().is_err());
+
+        let json = serde_json::to_string(&version).unwrap();
+        assert_eq!(json, r#"{"major":6,"minor":6,"patch":20}"#);
+        assert_eq!(serde_json::from_str::<Version>(&json).unwrap(), version);
+        assert_eq!(
+            serde_json::from_str::<Version>("\"6.6.20\"").unwrap(),
+            version
+        );
+
+        #[derive(Serialize, Deserialize)]
+        struct Kernel {
+            #[serde(serialize_with = "Version::serialize_str")]
+            version: Version,
+        }
+        let json = serde_json::to_string(&Kernel { version }).unwrap();
+        assert_eq!(json, r#"{"version":"6.6.20"}"#);
+        assert_eq!(
+            serde_json::from_str::<Kernel>(&json).unwrap().version,
+            version
+        );
+    }
 }
@@ -0,0 +1,112 @@
+//! The serialized field names are a public contract.
+//! These documents must keep round-tripping unchanged; do not edit them to make a rename pass.
+
+use linux_media::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+fn round_trip<T>(doc: Value)
+where
+    T: Serialize + DeserializeOwned,
+{
+    let value: T = serde_json::from_value(doc.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&value).unwrap(), doc);
+}
+
+#[test]
+fn media_device_info() {
+    round_trip::<MediaDeviceInfo>(json!({
+        "driver": "unicam",
+        "model": "unicam",
+        "serial": "",
+        "bus_info": "platform:fe801000.csi",
+        "media_version": { "major": 6, "minor": 6, "patch": 20 },
+        "hw_revision": 0,
+        "driver_version": { "major": 6, "minor": 6, "patch": 20 }
+    }));
+}
+
+#[test]
+fn media_topology() {
+    round_trip::<MediaTopology>(json!({
+        "path": "/dev/media0",
+        "version": 3,
+        "entities": [
+            { "id": 1, "name": "imx219 10-0010", "function": "CAMSensor", "flags": "" },
+            { "id": 3, "name": "unicam-image", "function": "IoV4L", "flags": "Default" }
+        ],
+        "interfaces": [
+            { "id": 5, "type": "V4LVideo", "devnode": { "major": 81, "minor": 0 } }
+        ],
+        "pads": [
+            { "id": 2, "entity_id": 1, "flags": "Source", "index": 0 },
+            { "id": 4, "entity_id": 3, "flags": "SinkMustConnect", "index": 0 }
+        ],
+        "links": [
+            {
+                "id": 6,
+                "type": { "DataLink": { "source_id": 2, "sink_id": 4 } },
+                "flags": "Enabled | Immutable"
+            },
+            {
+                "id": 7,
+                "type": { "InterfaceLink": { "source_id": 5, "sink_id": 3 } },
+                "flags": "Enabled"
+            }
+        ]
+    }));
+}
+
+#[test]
+fn media_entity_desc() {
+    round_trip::<MediaEntityDesc>(json!({
+        "id": 1,
+        "name": "imx219 10-0010",
+        "type": "CAMSensor",
+        "flags": "",
+        "pads": 1,
+        "links": 1
+    }));
+}
+
+#[test]
+fn media_links_enum() {
+    round_trip::<MediaLinksEnum>(json!({
+        "entity": 1,
+        "pads": [ { "entity": 1, "index": 0, "flags": "Source" } ],
+        "links": [
+            {
+                "source": { "entity": 1, "index": 0, "flags": "Source" },
+                "sink": { "entity": 3, "index": 0, "flags": "Sink" },
+                "flags": "Enabled"
+            }
+        ]
+    }));
+}
+
+#[test]
+fn topology_diff() {
+    round_trip::<TopologyDiff>(json!({
+        "changes": [
+            {
+                "change": "modified",
+                "object": "link",
+                "key": "\"imx219 10-0010\":0 -> \"unicam-image\":0",
+                "before": "[]",
+                "after": "[Enabled]"
+            }
+        ]
+    }));
+}
+
+#[test]
+fn ids_as_map_keys() {
+    use std::collections::HashMap;
+    round_trip::<HashMap<EntityId, String>>(json!({ "1": "imx219 10-0010", "3": "unicam-image" }));
+    round_trip::<HashMap<InterfaceId, u32>>(json!({ "5": 81 }));
+    round_trip::<HashMap<PadId, EntityId>>(json!({ "2": 1, "4": 3 }));
+    round_trip::<HashMap<LinkId, String>>(json!({ "6": "Enabled | Immutable" }));
+    assert_eq!(EntityId::from(1).to_string(), "1");
+    assert_eq!(LinkId::from(6).to_string(), "6");
+}
@@ -0,0 +1,34 @@
+//! Integration tests issuing real ioctls to the devices of `vimc`.
+//! Skipped unless vimc is loaded, or `LINUX_MEDIA_LOAD_MODULES=1` permits loading it.
+#![cfg(feature = "test-harness")]
+
+use linux_media::*;
+
+#[test]
+fn switch_scaler_input() {
+    let Some(harness) = TestHarness::setup(VirtualDriver::Vimc).unwrap() else {
+        return;
+    };
+    let media = harness.open().unwrap();
+    let specs =
+        LinkSpec::parse_list("\"Debayer A\":1->\"Scaler\":0[0], \"Debayer B\":1->\"Scaler\":0[1]")
+            .unwrap();
+    media.setup_links(&specs).unwrap();
+
+    let topology = media.new_topology().unwrap();
+    let enabled: Vec<_> = topology
+        .links_slice()
+        .iter()
+        .filter_map(|link| topology.link_desc(link))
+        .filter(|desc| desc.flags().contains(MediaLinkFlags::Enabled))
+        .filter_map(|desc| {
+            topology
+                .entities_slice()
+                .iter()
+                .find(|entity| entity.id() == desc.source().id())
+        })
+        .map(|entity| entity.name().to_string())
+        .collect();
+    assert!(enabled.iter().any(|name| name == "Debayer B"));
+    assert!(!enabled.iter().any(|name| name == "Debayer A"));
+}